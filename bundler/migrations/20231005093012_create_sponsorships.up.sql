@@ -0,0 +1,13 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS sponsorships
+(
+    id             SERIAL PRIMARY KEY,
+    transaction_id VARCHAR                                            NOT NULL,
+    user_address   VARCHAR(42)                                        NOT NULL,
+    chain          VARCHAR                                            NOT NULL,
+    gas_cost       NUMERIC                                            NOT NULL,
+    created_at     TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    updated_at     TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+CREATE INDEX IF NOT EXISTS sponsorships_user_address_chain_created_at_idx
+    ON sponsorships (user_address, chain, created_at);
@@ -0,0 +1,10 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS paymaster_clients
+(
+    id           SERIAL PRIMARY KEY,
+    name         VARCHAR UNIQUE                                     NOT NULL,
+    api_key_hash VARCHAR UNIQUE                                     NOT NULL,
+    is_active    BOOLEAN                  DEFAULT true              NOT NULL,
+    created_at   TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    updated_at   TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
@@ -0,0 +1,12 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS balance_topups
+(
+    id               SERIAL PRIMARY KEY,
+    entity           VARCHAR                                            NOT NULL,
+    address          VARCHAR(42)                                        NOT NULL,
+    amount           NUMERIC                                            NOT NULL,
+    currency         VARCHAR                                            NOT NULL,
+    transaction_hash VARCHAR                                            NOT NULL,
+    created_at       TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    updated_at       TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
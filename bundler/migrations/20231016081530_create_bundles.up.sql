@@ -0,0 +1,16 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS bundles
+(
+    id               SERIAL PRIMARY KEY,
+    transaction_hash VARCHAR                                            NOT NULL UNIQUE,
+    chain            VARCHAR                                            NOT NULL,
+    beneficiary      VARCHAR(42)                                        NOT NULL,
+    relayer          VARCHAR(42)                                        NOT NULL,
+    user_op_count    INTEGER                                            NOT NULL,
+    gas_cost         NUMERIC                                            NOT NULL,
+    refund           NUMERIC                                            NOT NULL,
+    created_at       TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    updated_at       TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS bundles_chain_created_at_idx ON bundles (chain, created_at);
@@ -0,0 +1,2 @@
+-- Add down migration script here
+ALTER TABLE IF EXISTS users DROP COLUMN IF EXISTS salt_version;
@@ -0,0 +1,3 @@
+-- Add up migration script here
+-- existing salts stay as stored, they were derived with the v0 (DefaultHasher) scheme
+ALTER TABLE IF EXISTS users ADD COLUMN IF NOT EXISTS salt_version INTEGER NOT NULL DEFAULT 0;
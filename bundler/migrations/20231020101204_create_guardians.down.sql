@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS recovery_approvals;
+DROP TABLE IF EXISTS recovery_requests;
+DROP TABLE IF EXISTS guardians;
@@ -0,0 +1,35 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS guardians
+(
+    id               SERIAL PRIMARY KEY,
+    wallet_address   VARCHAR(42)                                        NOT NULL,
+    guardian_address VARCHAR(42)                                        NOT NULL,
+    created_at       TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    updated_at       TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    UNIQUE (wallet_address, guardian_address)
+);
+
+CREATE TABLE IF NOT EXISTS recovery_requests
+(
+    id             SERIAL PRIMARY KEY,
+    request_id     VARCHAR                                            NOT NULL UNIQUE,
+    wallet_address VARCHAR(42)                                        NOT NULL,
+    new_owner      VARCHAR(42)                                        NOT NULL,
+    status         VARCHAR                                            NOT NULL,
+    execute_after  TIMESTAMP WITH TIME ZONE,
+    transaction_id VARCHAR,
+    created_at     TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    updated_at     TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS recovery_requests_status_idx ON recovery_requests (status, execute_after);
+
+CREATE TABLE IF NOT EXISTS recovery_approvals
+(
+    id               SERIAL PRIMARY KEY,
+    request_id       VARCHAR                                            NOT NULL,
+    guardian_address VARCHAR(42)                                        NOT NULL,
+    signature        VARCHAR                                            NOT NULL,
+    created_at       TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    UNIQUE (request_id, guardian_address)
+);
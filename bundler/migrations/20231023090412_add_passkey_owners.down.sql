@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS passkey_credentials;
+ALTER TABLE IF EXISTS users DROP COLUMN IF EXISTS owner_type;
+ALTER TABLE IF EXISTS users ALTER COLUMN owner_address TYPE VARCHAR(42);
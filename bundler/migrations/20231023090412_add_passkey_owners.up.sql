@@ -0,0 +1,14 @@
+-- Add up migration script here
+-- passkey owners store their uncompressed P-256 public key (0x04 || x || y) as the owner
+ALTER TABLE IF EXISTS users ALTER COLUMN owner_address TYPE VARCHAR;
+ALTER TABLE IF EXISTS users ADD COLUMN IF NOT EXISTS owner_type VARCHAR NOT NULL DEFAULT 'eoa';
+
+CREATE TABLE IF NOT EXISTS passkey_credentials
+(
+    id             SERIAL PRIMARY KEY,
+    wallet_address VARCHAR(42)                                        NOT NULL,
+    credential_id  VARCHAR                                            NOT NULL UNIQUE,
+    public_key     VARCHAR                                            NOT NULL,
+    created_at     TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    updated_at     TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS session_key_spends;
+DROP TABLE IF EXISTS session_keys;
@@ -0,0 +1,33 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS session_keys
+(
+    id              SERIAL PRIMARY KEY,
+    session_id      VARCHAR                                            NOT NULL UNIQUE,
+    wallet_address  VARCHAR(42)                                        NOT NULL,
+    session_key     VARCHAR(42)                                        NOT NULL,
+    valid_after     TIMESTAMP WITH TIME ZONE                           NOT NULL,
+    valid_until     TIMESTAMP WITH TIME ZONE                           NOT NULL,
+    allowed_targets VARCHAR[]                                          NOT NULL,
+    currencies      VARCHAR[]                                          NOT NULL,
+    tokens          VARCHAR[]                                          NOT NULL,
+    spend_limits    NUMERIC[]                                          NOT NULL,
+    permission_hash VARCHAR                                            NOT NULL,
+    owner_signature VARCHAR,
+    status          VARCHAR                                            NOT NULL,
+    created_at      TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    updated_at      TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+CREATE INDEX IF NOT EXISTS session_keys_wallet_address_idx ON session_keys (wallet_address);
+
+-- transfers signed with a session key, released (amount 0) when the submission fails
+CREATE TABLE IF NOT EXISTS session_key_spends
+(
+    id             SERIAL PRIMARY KEY,
+    transaction_id VARCHAR                                            NOT NULL UNIQUE,
+    session_id     VARCHAR                                            NOT NULL,
+    currency       VARCHAR                                            NOT NULL,
+    amount         NUMERIC                                            NOT NULL,
+    created_at     TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    updated_at     TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+CREATE INDEX IF NOT EXISTS session_key_spends_session_id_idx ON session_key_spends (session_id, currency);
@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS admin_audit_log;
+DROP FUNCTION IF EXISTS admin_audit_log_append_only();
@@ -0,0 +1,30 @@
+-- Add up migration script here
+-- one row per admin action, outcome is success or failure
+CREATE TABLE IF NOT EXISTS admin_audit_log
+(
+    id               SERIAL PRIMARY KEY,
+    actor            VARCHAR                                            NOT NULL,
+    action           VARCHAR                                            NOT NULL,
+    parameters       JSONB                                              NOT NULL,
+    transaction_hash VARCHAR,
+    outcome          VARCHAR                                            NOT NULL,
+    error            VARCHAR,
+    created_at       TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+CREATE INDEX IF NOT EXISTS admin_audit_log_actor_idx ON admin_audit_log (actor);
+CREATE INDEX IF NOT EXISTS admin_audit_log_action_idx ON admin_audit_log (action);
+CREATE INDEX IF NOT EXISTS admin_audit_log_created_at_idx ON admin_audit_log (created_at);
+
+-- the log is append only
+CREATE OR REPLACE FUNCTION admin_audit_log_append_only() RETURNS TRIGGER AS
+$$
+BEGIN
+    RAISE EXCEPTION 'admin_audit_log is append only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER admin_audit_log_append_only
+    BEFORE UPDATE OR DELETE OR TRUNCATE
+    ON admin_audit_log
+    FOR EACH STATEMENT
+EXECUTE FUNCTION admin_audit_log_append_only();
@@ -0,0 +1,6 @@
+-- Add down migration script here
+ALTER TABLE IF EXISTS sponsorships DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE IF EXISTS user_operations DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE IF EXISTS user_transactions DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE IF EXISTS users DROP COLUMN IF EXISTS tenant_id;
+DROP TABLE IF EXISTS tenants;
@@ -0,0 +1,38 @@
+-- Add up migration script here
+-- partner apps hosted on the bundler, unset settings fall back to the global config. auth and
+-- sponsorship_policy hold the config's auth and sponsorship_policy sections as json, an empty
+-- supported_tokens list supports every token of the chain
+CREATE TABLE IF NOT EXISTS tenants
+(
+    id                    SERIAL PRIMARY KEY,
+    name                  VARCHAR UNIQUE                                     NOT NULL,
+    api_key_hash          VARCHAR UNIQUE,
+    auth                  JSONB,
+    transaction_id_prefix VARCHAR,
+    sponsorship_policy    JSONB,
+    supported_tokens      VARCHAR[]                DEFAULT '{}'              NOT NULL,
+    webhook_url           VARCHAR,
+    is_active             BOOLEAN                  DEFAULT true              NOT NULL,
+    created_at            TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    updated_at            TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+-- serves requests without an api key and owns everything created before tenants
+INSERT INTO tenants (id, name) VALUES (1, 'default') ON CONFLICT DO NOTHING;
+SELECT setval('tenants_id_seq', (SELECT MAX(id) FROM tenants));
+
+ALTER TABLE IF EXISTS users ADD COLUMN IF NOT EXISTS tenant_id INTEGER NOT NULL DEFAULT 1 REFERENCES tenants (id);
+ALTER TABLE IF EXISTS users ALTER COLUMN tenant_id DROP DEFAULT;
+CREATE INDEX IF NOT EXISTS users_tenant_id_external_user_id_idx ON users (tenant_id, external_user_id);
+
+ALTER TABLE IF EXISTS user_transactions ADD COLUMN IF NOT EXISTS tenant_id INTEGER NOT NULL DEFAULT 1 REFERENCES tenants (id);
+ALTER TABLE IF EXISTS user_transactions ALTER COLUMN tenant_id DROP DEFAULT;
+CREATE INDEX IF NOT EXISTS user_transactions_tenant_id_user_address_idx ON user_transactions (tenant_id, user_address);
+
+ALTER TABLE IF EXISTS user_operations ADD COLUMN IF NOT EXISTS tenant_id INTEGER NOT NULL DEFAULT 1 REFERENCES tenants (id);
+ALTER TABLE IF EXISTS user_operations ALTER COLUMN tenant_id DROP DEFAULT;
+
+-- budgets are per tenant
+ALTER TABLE IF EXISTS sponsorships ADD COLUMN IF NOT EXISTS tenant_id INTEGER NOT NULL DEFAULT 1 REFERENCES tenants (id);
+ALTER TABLE IF EXISTS sponsorships ALTER COLUMN tenant_id DROP DEFAULT;
+CREATE INDEX IF NOT EXISTS sponsorships_tenant_id_chain_idx ON sponsorships (tenant_id, chain);
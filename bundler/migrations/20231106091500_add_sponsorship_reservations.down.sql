@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS sponsorships_transaction_id_idx;
+ALTER TABLE IF EXISTS sponsorships DROP COLUMN IF EXISTS reserved_until;
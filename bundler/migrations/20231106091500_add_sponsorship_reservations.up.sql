@@ -0,0 +1,5 @@
+-- Add up migration script here
+-- a sponsorship is reserved until the op is submitted, an expired reservation no longer counts
+-- against the budgets. Settled sponsorships have no reserved_until
+ALTER TABLE IF EXISTS sponsorships ADD COLUMN IF NOT EXISTS reserved_until TIMESTAMP WITH TIME ZONE;
+CREATE INDEX IF NOT EXISTS sponsorships_transaction_id_idx ON sponsorships (transaction_id);
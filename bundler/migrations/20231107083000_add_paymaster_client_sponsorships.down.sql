@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS sponsorships_unreconciled_idx;
+ALTER TABLE IF EXISTS sponsorships DROP COLUMN IF EXISTS reconciled_at;
+ALTER TABLE IF EXISTS sponsorships DROP COLUMN IF EXISTS paymaster_client_id;
+ALTER TABLE IF EXISTS paymaster_clients DROP COLUMN IF EXISTS sponsorship_policy;
@@ -0,0 +1,10 @@
+-- Add up migration script here
+-- a paymaster client's ops are budgeted on their own, an unset policy falls back to the config's
+ALTER TABLE IF EXISTS paymaster_clients ADD COLUMN IF NOT EXISTS sponsorship_policy JSONB;
+
+-- client ops are submitted elsewhere, reconciled_at is set once the gas cost is settled from
+-- the op's receipt or released because the op expired unmined
+ALTER TABLE IF EXISTS sponsorships ADD COLUMN IF NOT EXISTS paymaster_client_id INTEGER REFERENCES paymaster_clients (id);
+ALTER TABLE IF EXISTS sponsorships ADD COLUMN IF NOT EXISTS reconciled_at TIMESTAMP WITH TIME ZONE;
+CREATE INDEX IF NOT EXISTS sponsorships_unreconciled_idx ON sponsorships (chain, paymaster_client_id)
+    WHERE paymaster_client_id IS NOT NULL AND reconciled_at IS NULL;
@@ -0,0 +1,2 @@
+-- Add down migration script here
+ALTER TABLE IF EXISTS recovery_requests DROP COLUMN IF EXISTS recovery_hash;
@@ -0,0 +1,7 @@
+-- Add up migration script here
+-- guardians sign the account's getRecoveryHash, which the account checks again in recoverOwner
+ALTER TABLE IF EXISTS recovery_requests ADD COLUMN IF NOT EXISTS recovery_hash VARCHAR(66);
+
+-- approvals of open requests were signed over the old message, the account can't verify them
+UPDATE recovery_requests SET status = 'cancelled', updated_at = now()
+WHERE status IN ('pending', 'approved');
@@ -0,0 +1,2 @@
+-- Add down migration script here
+ALTER TABLE IF EXISTS session_key_spends DROP COLUMN IF EXISTS reserved_until;
@@ -0,0 +1,3 @@
+-- Add up migration script here
+-- a spend only counts against the session limit until reserved_until unless the transfer is held
+ALTER TABLE IF EXISTS session_key_spends ADD COLUMN IF NOT EXISTS reserved_until TIMESTAMP WITH TIME ZONE;
@@ -0,0 +1,9 @@
+-- Add up migration script here
+-- nonces of signed admin requests, kept while their timestamp is accepted so a request can't be replayed
+CREATE TABLE IF NOT EXISTS admin_request_nonces
+(
+    key_id     VARCHAR                                            NOT NULL,
+    nonce      VARCHAR                                            NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    PRIMARY KEY (key_id, nonce)
+);
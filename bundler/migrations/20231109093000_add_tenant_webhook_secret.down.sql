@@ -0,0 +1,2 @@
+-- Add down migration script here
+ALTER TABLE IF EXISTS tenants DROP COLUMN IF EXISTS webhook_secret;
@@ -0,0 +1,6 @@
+-- Add up migration script here
+-- signs the webhooks sent to the tenant, see WebhookService
+ALTER TABLE IF EXISTS tenants ADD COLUMN IF NOT EXISTS webhook_secret VARCHAR;
+UPDATE tenants SET webhook_secret = replace(gen_random_uuid()::TEXT, '-', '') || replace(gen_random_uuid()::TEXT, '-', '')
+    WHERE webhook_secret IS NULL;
+ALTER TABLE IF EXISTS tenants ALTER COLUMN webhook_secret SET NOT NULL;
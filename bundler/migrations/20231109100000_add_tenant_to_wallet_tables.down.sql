@@ -0,0 +1,11 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS session_keys_tenant_id_wallet_address_idx;
+CREATE INDEX IF NOT EXISTS session_keys_wallet_address_idx ON session_keys (wallet_address);
+ALTER TABLE IF EXISTS session_keys DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE IF EXISTS passkey_credentials DROP COLUMN IF EXISTS tenant_id;
+DROP INDEX IF EXISTS recovery_requests_tenant_id_wallet_address_idx;
+ALTER TABLE IF EXISTS recovery_requests DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE IF EXISTS guardians DROP CONSTRAINT IF EXISTS guardians_tenant_id_wallet_address_guardian_address_key;
+ALTER TABLE IF EXISTS guardians DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE IF EXISTS guardians ADD CONSTRAINT guardians_wallet_address_guardian_address_key
+    UNIQUE (wallet_address, guardian_address);
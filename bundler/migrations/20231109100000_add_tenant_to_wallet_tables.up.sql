@@ -0,0 +1,23 @@
+-- Add up migration script here
+-- rows keyed by a wallet belong to the wallet's tenant
+ALTER TABLE IF EXISTS guardians ADD COLUMN IF NOT EXISTS tenant_id INTEGER NOT NULL DEFAULT 1 REFERENCES tenants (id);
+UPDATE guardians g SET tenant_id = u.tenant_id FROM users u WHERE u.wallet_address = g.wallet_address;
+ALTER TABLE IF EXISTS guardians ALTER COLUMN tenant_id DROP DEFAULT;
+ALTER TABLE IF EXISTS guardians DROP CONSTRAINT IF EXISTS guardians_wallet_address_guardian_address_key;
+ALTER TABLE IF EXISTS guardians ADD CONSTRAINT guardians_tenant_id_wallet_address_guardian_address_key
+    UNIQUE (tenant_id, wallet_address, guardian_address);
+
+ALTER TABLE IF EXISTS recovery_requests ADD COLUMN IF NOT EXISTS tenant_id INTEGER NOT NULL DEFAULT 1 REFERENCES tenants (id);
+UPDATE recovery_requests r SET tenant_id = u.tenant_id FROM users u WHERE u.wallet_address = r.wallet_address;
+ALTER TABLE IF EXISTS recovery_requests ALTER COLUMN tenant_id DROP DEFAULT;
+CREATE INDEX IF NOT EXISTS recovery_requests_tenant_id_wallet_address_idx ON recovery_requests (tenant_id, wallet_address);
+
+ALTER TABLE IF EXISTS passkey_credentials ADD COLUMN IF NOT EXISTS tenant_id INTEGER NOT NULL DEFAULT 1 REFERENCES tenants (id);
+UPDATE passkey_credentials p SET tenant_id = u.tenant_id FROM users u WHERE u.wallet_address = p.wallet_address;
+ALTER TABLE IF EXISTS passkey_credentials ALTER COLUMN tenant_id DROP DEFAULT;
+
+ALTER TABLE IF EXISTS session_keys ADD COLUMN IF NOT EXISTS tenant_id INTEGER NOT NULL DEFAULT 1 REFERENCES tenants (id);
+UPDATE session_keys s SET tenant_id = u.tenant_id FROM users u WHERE u.wallet_address = s.wallet_address;
+ALTER TABLE IF EXISTS session_keys ALTER COLUMN tenant_id DROP DEFAULT;
+DROP INDEX IF EXISTS session_keys_wallet_address_idx;
+CREATE INDEX IF NOT EXISTS session_keys_tenant_id_wallet_address_idx ON session_keys (tenant_id, wallet_address);
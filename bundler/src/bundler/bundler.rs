@@ -1,9 +1,13 @@
-use ethers::types::Address;
+use ethers::types::{Address, Bytes, U256};
 
+use crate::contracts::aggregator_provider::AggregatorProvider;
 use crate::contracts::entrypoint_provider::EntryPointProvider;
+use crate::contracts::entrypoint_v7_provider::EntryPointV7Provider;
 use crate::errors::ProviderError;
 use crate::models::contract_interaction;
-use crate::provider::web3_provider::Web3Provider;
+use crate::models::contract_interaction::{
+    EntryPointVersion, PackedUserOperation, UserOpsPerAggregator,
+};
 use crate::provider::Web3Client;
 use crate::CONFIG;
 
@@ -16,15 +20,198 @@ impl Bundler {
         user_op: contract_interaction::UserOperation,
         beneficiary: Address,
     ) -> Result<String, ProviderError> {
-        let call_data = EntryPointProvider::handle_ops(provider, user_op, beneficiary).await?;
-        Web3Provider::execute(
-            provider.get_bundler_signer(),
-            CONFIG.get_chain().entrypoint_address,
-            String::from("0"),
-            call_data,
-            provider.get_entrypoint_provider().abi(),
-        )
-        .await
-        .map_err(|err| ProviderError(err))
+        match CONFIG.get_chain().get_entrypoint_version() {
+            EntryPointVersion::V06 => {
+                Self::submit_bundle(provider, vec![user_op], beneficiary).await
+            }
+            EntryPointVersion::V07 => {
+                Self::submit_packed_bundle(
+                    provider,
+                    vec![PackedUserOperation::from(user_op)],
+                    beneficiary,
+                )
+                .await
+            }
+        }
+    }
+
+    pub async fn get_nonce(provider: &Web3Client, sender: Address) -> Result<U256, ProviderError> {
+        match CONFIG.get_chain().get_entrypoint_version() {
+            EntryPointVersion::V06 => EntryPointProvider::get_nonce(provider, sender).await,
+            EntryPointVersion::V07 => EntryPointV7Provider::get_nonce(provider, sender).await,
+        }
+    }
+
+    pub fn get_entrypoint_address() -> Result<Address, ProviderError> {
+        match CONFIG.get_chain().get_entrypoint_version() {
+            EntryPointVersion::V06 => Ok(CONFIG.get_chain().entrypoint_address),
+            EntryPointVersion::V07 => EntryPointV7Provider::get_address(),
+        }
+    }
+
+    pub fn get_user_op_hash(
+        user_op: &contract_interaction::UserOperation,
+    ) -> Result<[u8; 32], ProviderError> {
+        Ok(user_op.hash_for_version(
+            CONFIG.get_chain().get_entrypoint_version(),
+            Self::get_entrypoint_address()?,
+            CONFIG.get_chain().chain_id,
+        ))
+    }
+
+    pub async fn submit_bundle(
+        provider: &Web3Client,
+        user_ops: Vec<contract_interaction::UserOperation>,
+        beneficiary: Address,
+    ) -> Result<String, ProviderError> {
+        let ops_per_aggregator = Self::group_by_aggregator(provider, user_ops).await?;
+        let call_data = if ops_per_aggregator
+            .iter()
+            .all(|ops| ops.aggregator.is_zero())
+        {
+            let user_ops = ops_per_aggregator
+                .into_iter()
+                .flat_map(|ops| ops.user_ops)
+                .collect();
+            EntryPointProvider::handle_ops(provider, user_ops, beneficiary).await?
+        } else {
+            EntryPointProvider::handle_aggregated_ops(provider, ops_per_aggregator, beneficiary)
+                .await?
+        };
+        provider
+            .execute_from_pool(
+                CONFIG.get_chain().entrypoint_address,
+                String::from("0"),
+                call_data,
+                provider.get_entrypoint_provider().abi(),
+            )
+            .await
+            .map_err(ProviderError)
+    }
+
+    pub async fn submit_packed_bundle(
+        provider: &Web3Client,
+        user_ops: Vec<PackedUserOperation>,
+        beneficiary: Address,
+    ) -> Result<String, ProviderError> {
+        let entry_point = EntryPointV7Provider::get_address()?;
+        let call_data = EntryPointV7Provider::handle_ops(provider, user_ops, beneficiary).await?;
+        provider
+            .execute_from_pool(
+                entry_point,
+                String::from("0"),
+                call_data,
+                provider.get_entrypoint_v7_provider(entry_point).abi(),
+            )
+            .await
+            .map_err(ProviderError)
+    }
+
+    // ops whose account does not use an aggregator are grouped under the zero address
+    async fn group_by_aggregator(
+        provider: &Web3Client,
+        user_ops: Vec<contract_interaction::UserOperation>,
+    ) -> Result<Vec<UserOpsPerAggregator>, ProviderError> {
+        let mut aggregated_ops = vec![];
+        for user_op in user_ops {
+            let aggregator = EntryPointProvider::get_aggregator(provider, user_op.clone())
+                .await?
+                .unwrap_or_default();
+            aggregated_ops.push((user_op, aggregator));
+        }
+        let mut ops_per_aggregator = Self::group_ops(aggregated_ops);
+
+        for ops in ops_per_aggregator.iter_mut() {
+            if ops.aggregator.is_zero() {
+                continue;
+            }
+            ops.signature = AggregatorProvider::aggregate_signatures(
+                provider,
+                ops.aggregator,
+                ops.user_ops.clone(),
+            )
+            .await?;
+            for user_op in ops.user_ops.iter_mut() {
+                let signature = AggregatorProvider::validate_user_op_signature(
+                    provider,
+                    ops.aggregator,
+                    user_op.clone(),
+                )
+                .await?;
+                user_op.signature(signature);
+            }
+        }
+        Ok(ops_per_aggregator)
+    }
+
+    // groups keep the order their first op was submitted in
+    fn group_ops(
+        user_ops: Vec<(contract_interaction::UserOperation, Address)>,
+    ) -> Vec<UserOpsPerAggregator> {
+        let mut ops_per_aggregator: Vec<UserOpsPerAggregator> = vec![];
+        for (user_op, aggregator) in user_ops {
+            match ops_per_aggregator
+                .iter_mut()
+                .find(|ops| ops.aggregator == aggregator)
+            {
+                Some(ops) => ops.user_ops.push(user_op),
+                None => ops_per_aggregator.push(UserOpsPerAggregator {
+                    user_ops: vec![user_op],
+                    aggregator,
+                    signature: Bytes::default(),
+                }),
+            }
+        }
+        ops_per_aggregator
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::Address;
+
+    use super::Bundler;
+    use crate::models::contract_interaction::UserOperation;
+
+    fn user_op(nonce: u64) -> UserOperation {
+        UserOperation {
+            nonce,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn groups_ops_by_aggregator_in_submission_order() {
+        let aggregator = Address::repeat_byte(1);
+        let groups = Bundler::group_ops(vec![
+            (user_op(0), aggregator),
+            (user_op(1), Address::zero()),
+            (user_op(2), aggregator),
+        ]);
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].aggregator, aggregator);
+        assert_eq!(
+            groups[0]
+                .user_ops
+                .iter()
+                .map(|op| op.nonce)
+                .collect::<Vec<_>>(),
+            vec![0, 2]
+        );
+        assert!(groups[1].aggregator.is_zero());
+        assert_eq!(groups[1].user_ops[0].nonce, 1);
+        assert!(groups.iter().all(|ops| ops.signature.is_empty()));
+    }
+
+    #[test]
+    fn keeps_unaggregated_ops_in_one_group() {
+        let groups = Bundler::group_ops(vec![
+            (user_op(0), Address::zero()),
+            (user_op(1), Address::zero()),
+        ]);
+
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].user_ops.len(), 2);
     }
 }
@@ -5,6 +5,28 @@ impl Constants {
     pub const PAYMASTER: &'static str = "paymaster";
     pub const RELAYER: &'static str = "relayer";
     pub const VERIFYING_PAYMASTER: &'static str = "verifying";
+    pub const TOKEN_PAYMASTER: &'static str = "token";
+
+    // ERC-7677 paymaster methods
+    pub const PM_GET_PAYMASTER_STUB_DATA: &'static str = "pm_getPaymasterStubData";
+    pub const PM_GET_PAYMASTER_DATA: &'static str = "pm_getPaymasterData";
+
+    // users.owner_type
+    pub const EOA_OWNER: &'static str = "eoa";
+    pub const PASSKEY_OWNER: &'static str = "passkey";
+
+    // account salt derivation, see helpers::get_account_salt
+    pub const ACCOUNT_SALT_VERSION: i32 = 1;
+
+    // tenants.id of the tenant serving requests without an api key
+    pub const DEFAULT_TENANT_ID: i32 = 1;
+
+    // carries the api key of tenants and paymaster clients
+    pub const API_KEY_HEADER: &'static str = "x-api-key";
+
+    // admin_audit_log.outcome
+    pub const AUDIT_SUCCESS: &'static str = "success";
+    pub const AUDIT_FAILURE: &'static str = "failure";
 
     // Currency
     pub const NATIVE: &'static str = "native";
@@ -0,0 +1,78 @@
+use ethers::contract::abigen;
+use ethers::providers::{Http, Provider};
+use ethers::types::{Address, Bytes, U256};
+use std::sync::Arc;
+
+use crate::errors::ProviderError;
+use crate::models::contract_interaction;
+use crate::provider::Web3Client;
+
+abigen!(Aggregator, "abi/Aggregator.json");
+
+#[derive(Clone)]
+pub struct AggregatorProvider;
+
+impl AggregatorProvider {
+    pub fn init_abi(address: Address, client: Arc<Provider<Http>>) -> Aggregator<Provider<Http>> {
+        Aggregator::new(address, client)
+    }
+
+    pub async fn validate_user_op_signature(
+        client: &Web3Client,
+        aggregator: Address,
+        user_op: contract_interaction::UserOperation,
+    ) -> Result<Bytes, ProviderError> {
+        let response = client
+            .get_aggregator_provider(aggregator)
+            .validate_user_op_signature(Self::get_aggregator_user_operation_payload(user_op))
+            .await;
+        match response {
+            Ok(signature) => Ok(signature),
+            Err(err) => Err(ProviderError(format!(
+                "Aggregator: Validate signature: {:?}",
+                err
+            ))),
+        }
+    }
+
+    pub async fn aggregate_signatures(
+        client: &Web3Client,
+        aggregator: Address,
+        user_ops: Vec<contract_interaction::UserOperation>,
+    ) -> Result<Bytes, ProviderError> {
+        let response = client
+            .get_aggregator_provider(aggregator)
+            .aggregate_signatures(
+                user_ops
+                    .into_iter()
+                    .map(Self::get_aggregator_user_operation_payload)
+                    .collect(),
+            )
+            .await;
+        match response {
+            Ok(signature) => Ok(signature),
+            Err(err) => Err(ProviderError(format!(
+                "Aggregator: Aggregate signatures: {:?}",
+                err
+            ))),
+        }
+    }
+
+    fn get_aggregator_user_operation_payload(
+        user_op: contract_interaction::UserOperation,
+    ) -> UserOperation {
+        UserOperation {
+            sender: user_op.sender,
+            nonce: U256::from(user_op.nonce),
+            init_code: user_op.init_code,
+            call_data: user_op.calldata,
+            call_gas_limit: U256::from(user_op.call_gas_limit),
+            verification_gas_limit: U256::from(user_op.verification_gas_limit),
+            pre_verification_gas: U256::from(user_op.pre_verification_gas),
+            max_fee_per_gas: U256::from(user_op.max_fee_per_gas),
+            max_priority_fee_per_gas: U256::from(user_op.max_priority_fee_per_gas),
+            signature: user_op.signature,
+            paymaster_and_data: user_op.paymaster_and_data,
+        }
+    }
+}
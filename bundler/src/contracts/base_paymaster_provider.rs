@@ -0,0 +1,87 @@
+use ethers::contract::abigen;
+use ethers::providers::{Http, Provider};
+use ethers::types::{Address, Bytes, U256};
+use std::sync::Arc;
+
+use crate::errors::ProviderError;
+use crate::provider::Web3Client;
+
+// stake management shared by every paymaster extending BasePaymaster
+abigen!(BasePaymaster, "abi/BasePaymaster.json");
+
+#[derive(Clone)]
+pub struct BasePaymasterProvider;
+
+impl BasePaymasterProvider {
+    pub fn init_abi(
+        address: Address,
+        client: Arc<Provider<Http>>,
+    ) -> BasePaymaster<Provider<Http>> {
+        BasePaymaster::new(address, client)
+    }
+
+    pub fn add_stake(
+        client: &Web3Client,
+        paymaster: Address,
+        unstake_delay_sec: u32,
+    ) -> Result<Bytes, ProviderError> {
+        let data = client
+            .get_base_paymaster_provider(paymaster)
+            .add_stake(unstake_delay_sec)
+            .calldata();
+        match data {
+            Some(call_data) => Ok(call_data),
+            None => Err(ProviderError(String::from(
+                "Paymaster: Add stake data failed",
+            ))),
+        }
+    }
+
+    pub fn unlock_stake(client: &Web3Client, paymaster: Address) -> Result<Bytes, ProviderError> {
+        let data = client
+            .get_base_paymaster_provider(paymaster)
+            .unlock_stake()
+            .calldata();
+        match data {
+            Some(call_data) => Ok(call_data),
+            None => Err(ProviderError(String::from(
+                "Paymaster: Unlock stake data failed",
+            ))),
+        }
+    }
+
+    pub fn withdraw_stake(
+        client: &Web3Client,
+        paymaster: Address,
+        withdraw_address: Address,
+    ) -> Result<Bytes, ProviderError> {
+        let data = client
+            .get_base_paymaster_provider(paymaster)
+            .withdraw_stake(withdraw_address)
+            .calldata();
+        match data {
+            Some(call_data) => Ok(call_data),
+            None => Err(ProviderError(String::from(
+                "Paymaster: Withdraw stake data failed",
+            ))),
+        }
+    }
+
+    pub fn withdraw_to(
+        client: &Web3Client,
+        paymaster: Address,
+        withdraw_address: Address,
+        amount: U256,
+    ) -> Result<Bytes, ProviderError> {
+        let data = client
+            .get_base_paymaster_provider(paymaster)
+            .withdraw_to(withdraw_address, amount)
+            .calldata();
+        match data {
+            Some(call_data) => Ok(call_data),
+            None => Err(ProviderError(String::from(
+                "Paymaster: Withdraw deposit data failed",
+            ))),
+        }
+    }
+}
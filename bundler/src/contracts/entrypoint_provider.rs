@@ -43,15 +43,33 @@ impl EntryPointProvider {
         }
     }
 
+    // getDepositInfo is the same in v0.6 and v0.7, so entry_point can be either
+    pub async fn get_deposit_info(
+        client: &Web3Client,
+        entry_point: Address,
+        address: Address,
+    ) -> Result<DepositInfo, ProviderError> {
+        let response = Self::init_abi(entry_point, client.client.clone())
+            .get_deposit_info(address)
+            .await;
+        match response {
+            Ok(deposit_info) => Ok(deposit_info),
+            Err(err) => Err(ProviderError(format!("EP: Deposit info: {:?}", err))),
+        }
+    }
+
     pub async fn handle_ops(
         client: &Web3Client,
-        user_op: contract_interaction::UserOperation,
+        user_ops: Vec<contract_interaction::UserOperation>,
         beneficiary: Address,
     ) -> Result<Bytes, ProviderError> {
         let data = client
             .get_entrypoint_provider()
             .handle_ops(
-                vec![Self::get_entry_point_user_operation_payload(user_op)],
+                user_ops
+                    .into_iter()
+                    .map(Self::get_entry_point_user_operation_payload)
+                    .collect(),
                 beneficiary,
             )
             .calldata();
@@ -61,6 +79,74 @@ impl EntryPointProvider {
         }
     }
 
+    pub async fn handle_aggregated_ops(
+        client: &Web3Client,
+        ops_per_aggregator: Vec<contract_interaction::UserOpsPerAggregator>,
+        beneficiary: Address,
+    ) -> Result<Bytes, ProviderError> {
+        let data = client
+            .get_entrypoint_provider()
+            .handle_aggregated_ops(
+                ops_per_aggregator
+                    .into_iter()
+                    .map(|ops| UserOpsPerAggregator {
+                        user_ops: ops
+                            .user_ops
+                            .into_iter()
+                            .map(Self::get_entry_point_user_operation_payload)
+                            .collect(),
+                        aggregator: ops.aggregator,
+                        signature: ops.signature,
+                    })
+                    .collect(),
+                beneficiary,
+            )
+            .calldata();
+        match data {
+            Some(call_data) => Ok(call_data),
+            None => Err(ProviderError(String::from(
+                "handle aggregated ops data failed",
+            ))),
+        }
+    }
+
+    // simulateValidation always reverts, the revert data tells us whether the account
+    // returned an aggregator as part of its validation data
+    pub async fn get_aggregator(
+        client: &Web3Client,
+        user_op: contract_interaction::UserOperation,
+    ) -> Result<Option<Address>, ProviderError> {
+        let result = client
+            .get_entrypoint_provider()
+            .simulate_validation(Self::get_entry_point_user_operation_payload(user_op))
+            .call()
+            .await;
+        let err = match result {
+            Ok(_) => {
+                return Err(ProviderError(String::from(
+                    "EP: simulate validation did not revert",
+                )))
+            }
+            Err(err) => err,
+        };
+        if let Some(validation) = err.decode_revert::<ValidationResultWithAggregation>() {
+            return Ok(Some(validation.aggregator_info.0));
+        }
+        if err.decode_revert::<ValidationResult>().is_some() {
+            return Ok(None);
+        }
+        match err.decode_revert::<FailedOp>() {
+            Some(failed_op) => Err(ProviderError(format!(
+                "EP: simulate validation failed: {}",
+                failed_op.reason
+            ))),
+            None => Err(ProviderError(format!(
+                "EP: simulate validation failed: {:?}",
+                err
+            ))),
+        }
+    }
+
     fn get_entry_point_user_operation_payload(
         user_op: contract_interaction::UserOperation,
     ) -> UserOperation {
@@ -0,0 +1,78 @@
+use ethers::contract::abigen;
+use ethers::providers::{Http, Provider};
+use ethers::types::{Address, Bytes, U256};
+use std::sync::Arc;
+
+use crate::errors::ProviderError;
+use crate::models::contract_interaction;
+use crate::models::contract_interaction::EntryPointVersion;
+use crate::provider::Web3Client;
+use crate::CONFIG;
+
+abigen!(EntryPointV7, "abi/EntryPointV7.json");
+
+#[derive(Clone)]
+pub struct EntryPointV7Provider;
+
+impl EntryPointV7Provider {
+    pub fn init_abi(address: Address, client: Arc<Provider<Http>>) -> EntryPointV7<Provider<Http>> {
+        EntryPointV7::new(address, client)
+    }
+
+    pub fn get_address() -> Result<Address, ProviderError> {
+        CONFIG
+            .get_chain()
+            .get_entrypoint_address(EntryPointVersion::V07)
+            .ok_or(ProviderError(String::from(
+                "EP v0.7 is not configured for the current chain",
+            )))
+    }
+
+    pub async fn get_nonce(client: &Web3Client, sender: Address) -> Result<U256, ProviderError> {
+        let result = client
+            .get_entrypoint_v7_provider(Self::get_address()?)
+            .get_nonce(sender, U256::zero())
+            .await;
+        match result {
+            Ok(nonce) => Ok(nonce),
+            Err(err) => Err(ProviderError(format!("Failed to get Nonce: {:?}", err))),
+        }
+    }
+
+    pub async fn handle_ops(
+        client: &Web3Client,
+        user_ops: Vec<contract_interaction::PackedUserOperation>,
+        beneficiary: Address,
+    ) -> Result<Bytes, ProviderError> {
+        let data = client
+            .get_entrypoint_v7_provider(Self::get_address()?)
+            .handle_ops(
+                user_ops
+                    .into_iter()
+                    .map(Self::get_entry_point_user_operation_payload)
+                    .collect(),
+                beneficiary,
+            )
+            .calldata();
+        match data {
+            Some(call_data) => Ok(call_data),
+            None => Err(ProviderError(String::from("handle ops data failed"))),
+        }
+    }
+
+    fn get_entry_point_user_operation_payload(
+        user_op: contract_interaction::PackedUserOperation,
+    ) -> PackedUserOperation {
+        PackedUserOperation {
+            sender: user_op.sender,
+            nonce: user_op.nonce,
+            init_code: user_op.init_code,
+            call_data: user_op.calldata,
+            account_gas_limits: user_op.account_gas_limits.into(),
+            pre_verification_gas: user_op.pre_verification_gas,
+            gas_fees: user_op.gas_fees.into(),
+            paymaster_and_data: user_op.paymaster_and_data,
+            signature: user_op.signature,
+        }
+    }
+}
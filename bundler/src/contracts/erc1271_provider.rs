@@ -0,0 +1,43 @@
+use ethers::contract::abigen;
+use ethers::providers::{Http, Provider};
+use ethers::types::{Address, Bytes, H256};
+use std::sync::Arc;
+
+use crate::errors::ProviderError;
+use crate::provider::Web3Client;
+
+abigen!(ERC1271, "abi/ERC1271.json");
+
+// bytes4(keccak256("isValidSignature(bytes32,bytes)"))
+const ERC1271_MAGIC_VALUE: [u8; 4] = [0x16, 0x26, 0xba, 0x7e];
+
+#[derive(Clone)]
+pub struct Erc1271Provider;
+
+impl Erc1271Provider {
+    pub fn init_abi(address: Address, client: Arc<Provider<Http>>) -> ERC1271<Provider<Http>> {
+        ERC1271::new(address, client)
+    }
+
+    // a revert means the contract does not accept the signature (or does not implement 1271)
+    pub async fn is_valid_signature(
+        client: &Web3Client,
+        signer: Address,
+        hash: H256,
+        signature: Bytes,
+    ) -> Result<bool, ProviderError> {
+        let result = client
+            .get_erc1271_provider(signer)
+            .is_valid_signature(hash.0, signature)
+            .call()
+            .await;
+        match result {
+            Ok(magic_value) => Ok(magic_value == ERC1271_MAGIC_VALUE),
+            Err(err) if err.is_revert() => Ok(false),
+            Err(err) => Err(ProviderError(format!(
+                "Failed to check signature with {:?}: {:?}",
+                signer, err
+            ))),
+        }
+    }
+}
@@ -1,5 +1,13 @@
+pub mod aggregator_provider;
+pub mod base_paymaster_provider;
 pub mod entrypoint_provider;
+pub mod entrypoint_v7_provider;
+pub mod erc1271_provider;
+pub mod passkey_account_factory_provider;
+pub mod rotatable_account_provider;
+pub mod session_key_validator_provider;
 pub mod simple_account_factory_provider;
 pub mod simple_account_provider;
+pub mod token_paymaster_provider;
 pub mod usdc_provider;
 pub mod verifying_paymaster_provider;
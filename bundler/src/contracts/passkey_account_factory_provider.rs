@@ -0,0 +1,66 @@
+use ethers::contract::abigen;
+use ethers::providers::{Http, Provider};
+use ethers::types::{Address, Bytes, U256};
+use std::sync::Arc;
+
+use crate::errors::ProviderError;
+use crate::provider::Web3Client;
+use crate::CONFIG;
+
+// contracts/src/PasskeyAccountFactory.sol, deploys accounts owned by a P-256 public key, the
+// account verifies WebAuthn assertions encoded by provider::webauthn::encode_signature with the
+// RIP-7212 P-256 precompile
+abigen!(PasskeyAccountFactory, "abi/PasskeyAccountFactory.json");
+
+#[derive(Clone)]
+pub struct PasskeyAccountFactoryProvider;
+
+impl PasskeyAccountFactoryProvider {
+    pub fn init_abi(
+        address: Address,
+        client: Arc<Provider<Http>>,
+    ) -> PasskeyAccountFactory<Provider<Http>> {
+        PasskeyAccountFactory::new(address, client)
+    }
+
+    pub fn get_factory_address() -> Result<Address, ProviderError> {
+        match CONFIG.get_chain().passkey_account_factory_address {
+            Some(address) => Ok(address),
+            None => Err(ProviderError(String::from(
+                "Passkey account factory is not configured for the current chain",
+            ))),
+        }
+    }
+
+    pub fn create_account(
+        client: &Web3Client,
+        x: U256,
+        y: U256,
+        salt: U256,
+    ) -> Result<Bytes, ProviderError> {
+        let data = client
+            .get_passkey_factory_provider(Self::get_factory_address()?)
+            .create_account(x, y, salt)
+            .calldata();
+        match data {
+            Some(call_data) => Ok(call_data),
+            None => Err(ProviderError(String::from("create data failed"))),
+        }
+    }
+
+    pub async fn get_address(
+        client: &Web3Client,
+        x: U256,
+        y: U256,
+        salt: U256,
+    ) -> Result<Address, ProviderError> {
+        let result = client
+            .get_passkey_factory_provider(Self::get_factory_address()?)
+            .get_address(x, y, salt)
+            .await;
+        match result {
+            Ok(address) => Ok(address),
+            Err(err) => Err(ProviderError(format!("Failed to get address: {:?}", err))),
+        }
+    }
+}
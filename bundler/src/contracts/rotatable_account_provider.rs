@@ -0,0 +1,119 @@
+use ethers::contract::abigen;
+use ethers::providers::{Http, Provider};
+use ethers::types::{Address, Bytes};
+use std::sync::Arc;
+
+use crate::errors::ProviderError;
+use crate::provider::Web3Client;
+
+// contracts/src/RotatableAccount.sol, the SimpleAccount implementation that lets the account
+// change its own owner, accounts are upgraded to it over UUPS the first time their owner is
+// rotated. It also keeps the guardians set by the owner and changes the owner in recoverOwner once
+// a majority of them signed getRecoveryHash, which commits to the account, the chain, the new
+// owner and recoveryNonce. The account accepts a user operation that only calls recoverOwner
+// without an owner signature, session key signatures go to the SessionKeyValidator the
+// implementation was deployed with. isValidSignature (ERC-1271) accepts the owner's signature over
+// AccountMessage(bytes32 hash) in the account's EIP-712 domain, see MessageService
+abigen!(RotatableAccount, "abi/RotatableAccount.json");
+
+#[derive(Clone)]
+pub struct RotatableAccountProvider;
+
+impl RotatableAccountProvider {
+    pub fn init_abi(
+        address: Address,
+        client: Arc<Provider<Http>>,
+    ) -> RotatableAccount<Provider<Http>> {
+        RotatableAccount::new(address, client)
+    }
+
+    pub fn change_owner(client: &Web3Client, new_owner: Address) -> Result<Bytes, ProviderError> {
+        let data = client
+            .get_rotatable_account_provider(Address::zero())
+            .change_owner(new_owner)
+            .calldata();
+        match data {
+            Some(call_data) => Ok(call_data),
+            None => Err(ProviderError(String::from("change owner data failed"))),
+        }
+    }
+
+    pub fn set_guardians(
+        client: &Web3Client,
+        guardians: Vec<Address>,
+    ) -> Result<Bytes, ProviderError> {
+        let data = client
+            .get_rotatable_account_provider(Address::zero())
+            .set_guardians(guardians)
+            .calldata();
+        match data {
+            Some(call_data) => Ok(call_data),
+            None => Err(ProviderError(String::from("set guardians data failed"))),
+        }
+    }
+
+    pub fn recover_owner(
+        client: &Web3Client,
+        new_owner: Address,
+        signatures: Bytes,
+    ) -> Result<Bytes, ProviderError> {
+        let data = client
+            .get_rotatable_account_provider(Address::zero())
+            .recover_owner(new_owner, signatures)
+            .calldata();
+        match data {
+            Some(call_data) => Ok(call_data),
+            None => Err(ProviderError(String::from("recover owner data failed"))),
+        }
+    }
+
+    // guardians sign this hash as an EIP-191 message, it changes once a recovery went through
+    pub async fn get_recovery_hash(
+        client: &Web3Client,
+        account: Address,
+        new_owner: Address,
+    ) -> Result<[u8; 32], ProviderError> {
+        let result = client
+            .get_rotatable_account_provider(account)
+            .get_recovery_hash(new_owner)
+            .call()
+            .await;
+        match result {
+            Ok(hash) => Ok(hash),
+            Err(err) => Err(ProviderError(format!(
+                "Failed to get recovery hash: {:?}",
+                err
+            ))),
+        }
+    }
+
+    // recoverOwner takes the 65 byte signatures concatenated in ascending guardian order, so a
+    // guardian can't be counted twice
+    pub fn pack_guardian_signatures(mut signatures: Vec<(Address, Bytes)>) -> Bytes {
+        signatures.sort_by_key(|(guardian, _)| *guardian);
+        Bytes::from(
+            signatures
+                .into_iter()
+                .flat_map(|(_, signature)| signature.to_vec())
+                .collect::<Vec<u8>>(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packs_guardian_signatures_in_ascending_guardian_order() {
+        let low = Address::from_low_u64_be(1);
+        let high = Address::from_low_u64_be(2);
+        let packed = RotatableAccountProvider::pack_guardian_signatures(vec![
+            (high, Bytes::from(vec![2u8; 65])),
+            (low, Bytes::from(vec![1u8; 65])),
+        ]);
+        assert_eq!(packed.len(), 130);
+        assert!(packed[..65].iter().all(|byte| *byte == 1));
+        assert!(packed[65..].iter().all(|byte| *byte == 2));
+    }
+}
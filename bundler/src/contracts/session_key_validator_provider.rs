@@ -0,0 +1,219 @@
+use ethers::abi::{encode, Token};
+use ethers::contract::abigen;
+use ethers::providers::{Http, Provider};
+use ethers::types::{Address, Bytes, U256};
+use ethers::utils::keccak256;
+use std::sync::Arc;
+
+use crate::errors::ProviderError;
+use crate::provider::Web3Client;
+use crate::CONFIG;
+
+// contracts/src/SessionKeyValidator.sol, validator module of the account for user operations
+// signed by a session key, it checks the owner authorized the permission, the session key signed
+// the op and the calls stay in scope
+abigen!(SessionKeyValidator, "abi/SessionKeyValidator.json");
+
+// SessionPermission as the validator decodes it, native currency is the zero address in tokens
+#[derive(Clone, Debug)]
+pub struct SessionPermission {
+    pub session_key: Address,
+    pub valid_after: u64,
+    pub valid_until: u64,
+    pub allowed_targets: Vec<Address>,
+    pub tokens: Vec<Address>,
+    pub spend_limits: Vec<U256>,
+    pub salt: [u8; 32],
+}
+
+impl SessionPermission {
+    fn to_token(&self) -> Token {
+        Token::Tuple(vec![
+            Token::Address(self.session_key),
+            Token::Uint(U256::from(self.valid_after)),
+            Token::Uint(U256::from(self.valid_until)),
+            Token::Array(
+                self.allowed_targets
+                    .iter()
+                    .map(|target| Token::Address(*target))
+                    .collect(),
+            ),
+            Token::Array(
+                self.tokens
+                    .iter()
+                    .map(|token| Token::Address(*token))
+                    .collect(),
+            ),
+            Token::Array(
+                self.spend_limits
+                    .iter()
+                    .map(|limit| Token::Uint(*limit))
+                    .collect(),
+            ),
+            Token::FixedBytes(self.salt.to_vec()),
+        ])
+    }
+}
+
+#[derive(Clone)]
+pub struct SessionKeyValidatorProvider;
+
+impl SessionKeyValidatorProvider {
+    pub fn init_abi(
+        address: Address,
+        client: Arc<Provider<Http>>,
+    ) -> SessionKeyValidator<Provider<Http>> {
+        SessionKeyValidator::new(address, client)
+    }
+
+    pub fn get_validator_address() -> Result<Address, ProviderError> {
+        match CONFIG.get_chain().session_key_validator_address {
+            Some(address) => Ok(address),
+            None => Err(ProviderError(String::from(
+                "Session key validator is not configured for the current chain",
+            ))),
+        }
+    }
+
+    // keccak256(abi.encode(chainId, account, validator, permission)), signed by the owner as an
+    // EIP-191 message
+    pub fn get_permission_hash(
+        account: Address,
+        permission: &SessionPermission,
+    ) -> Result<[u8; 32], ProviderError> {
+        Ok(Self::hash_permission(
+            CONFIG.get_chain().chain_id,
+            account,
+            Self::get_validator_address()?,
+            permission,
+        ))
+    }
+
+    // abi.encode(validator, abi.encode(permission, ownerSignature, sessionSignature)), the
+    // account hands signatures that are not a plain 65 byte owner signature to the validator
+    pub fn encode_signature(
+        permission: &SessionPermission,
+        owner_signature: Bytes,
+        session_signature: Bytes,
+    ) -> Result<Bytes, ProviderError> {
+        Ok(Self::encode_validator_signature(
+            Self::get_validator_address()?,
+            permission,
+            owner_signature,
+            session_signature,
+        ))
+    }
+
+    fn hash_permission(
+        chain_id: u64,
+        account: Address,
+        validator: Address,
+        permission: &SessionPermission,
+    ) -> [u8; 32] {
+        keccak256(encode(&[
+            Token::Uint(U256::from(chain_id)),
+            Token::Address(account),
+            Token::Address(validator),
+            permission.to_token(),
+        ]))
+    }
+
+    fn encode_validator_signature(
+        validator: Address,
+        permission: &SessionPermission,
+        owner_signature: Bytes,
+        session_signature: Bytes,
+    ) -> Bytes {
+        let data = encode(&[
+            permission.to_token(),
+            Token::Bytes(owner_signature.to_vec()),
+            Token::Bytes(session_signature.to_vec()),
+        ]);
+        Bytes::from(encode(&[Token::Address(validator), Token::Bytes(data)]))
+    }
+
+    pub fn revoke_session(
+        client: &Web3Client,
+        permission_hash: [u8; 32],
+    ) -> Result<Bytes, ProviderError> {
+        let data = client
+            .get_session_key_validator_provider(Self::get_validator_address()?)
+            .revoke_session(permission_hash)
+            .calldata();
+        match data {
+            Some(call_data) => Ok(call_data),
+            None => Err(ProviderError(String::from("revoke session data failed"))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::H256;
+    use ethers::utils::hash_message;
+    use ethers_signers::{LocalWallet, Signer};
+
+    // the same fixture is validated by contracts/test/SessionKeyValidator.t.sol
+    const CHAIN_ID: u64 = 84532;
+    const USER_OP_HASH: [u8; 32] = [0x22; 32];
+
+    fn wallet(key: u8) -> LocalWallet {
+        LocalWallet::from_bytes(&[key; 32]).unwrap()
+    }
+
+    fn permission() -> SessionPermission {
+        SessionPermission {
+            session_key: wallet(0x33).address(),
+            valid_after: 1700000000,
+            valid_until: 1800000000,
+            allowed_targets: vec![Address::repeat_byte(0x55)],
+            tokens: vec![Address::zero(), Address::repeat_byte(0x66)],
+            spend_limits: vec![U256::exp10(18), U256::from(100_000_000)],
+            salt: keccak256("session_test"),
+        }
+    }
+
+    #[test]
+    fn hashes_the_fixed_permission() {
+        let hash = SessionKeyValidatorProvider::hash_permission(
+            CHAIN_ID,
+            Address::repeat_byte(0x11),
+            Address::repeat_byte(0x22),
+            &permission(),
+        );
+        assert_eq!(
+            H256::from(hash),
+            "0x1222d0619d990a36145f041c0633c2f546ad74f784d9db4e4f6ec625e11d9c3f"
+                .parse::<H256>()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn encodes_the_fixed_signature() {
+        let permission = permission();
+        let permission_hash = SessionKeyValidatorProvider::hash_permission(
+            CHAIN_ID,
+            Address::repeat_byte(0x11),
+            Address::repeat_byte(0x22),
+            &permission,
+        );
+        let owner_signature = wallet(0x44)
+            .sign_hash(hash_message(permission_hash))
+            .unwrap();
+        let session_signature = wallet(0x33).sign_hash(hash_message(USER_OP_HASH)).unwrap();
+        let encoded = SessionKeyValidatorProvider::encode_validator_signature(
+            Address::repeat_byte(0x22),
+            &permission,
+            Bytes::from(owner_signature.to_vec()),
+            Bytes::from(session_signature.to_vec()),
+        );
+        assert_eq!(
+            H256::from(keccak256(&encoded)),
+            "0xa3a41a4f703b06478ed25fac7d10588794e8ea1430bef26c5e94a7816e260c60"
+                .parse::<H256>()
+                .unwrap()
+        );
+    }
+}
@@ -1,11 +1,15 @@
-use ethers::abi::Address;
+use ethers::abi::{encode, AbiEncode, Address, Token};
 use ethers::contract::abigen;
 use ethers::providers::{Http, Provider};
 use ethers::types::{Bytes, U256};
+use ethers::utils::get_create2_address;
 use std::sync::Arc;
 
+use crate::contracts::simple_account_provider::InitializeCall;
 use crate::errors::ProviderError;
+use crate::models::contract_interaction::EntryPointVersion;
 use crate::provider::Web3Client;
+use crate::CONFIG;
 
 abigen!(SimpleAccountFactory, "abi/SimpleAccountFactory.json");
 
@@ -41,18 +45,46 @@ impl SimpleAccountFactoryProvider {
         client.get_factory_provider().address()
     }
 
+    // computed offline when a v0.6 chain configures the account implementation and proxy creation
+    // code, otherwise the factory is asked over rpc
     pub async fn get_address(
         client: &Web3Client,
         owner: Address,
-        salt: u64,
+        salt: U256,
     ) -> Result<Address, ProviderError> {
-        let result = client
-            .get_factory_provider()
-            .get_address(owner, U256::from(salt))
-            .await;
+        if let Some(address) = Self::compute_address(owner, salt) {
+            return Ok(address);
+        }
+        let result = client.get_factory_provider().get_address(owner, salt).await;
         match result {
             Ok(address) => Ok(address),
             Err(err) => Err(ProviderError(format!("Failed to get address: {:?}", err))),
         }
     }
+
+    // mirrors SimpleAccountFactory.getAddress: CREATE2 from the factory with the salt as bytes32
+    // and an ERC1967Proxy pointing at the implementation, initialized with the owner
+    pub fn compute_address(owner: Address, salt: U256) -> Option<Address> {
+        let chain = CONFIG.get_chain();
+        // the implementation is the v0.6 account, v0.7 factories are asked over rpc
+        if chain.get_entrypoint_version() != EntryPointVersion::V06 {
+            return None;
+        }
+        let implementation = chain.simple_account_implementation?;
+        let creation_code = chain.account_proxy_creation_code.as_ref()?;
+
+        let initialize = InitializeCall { an_owner: owner }.encode();
+        let init_code = [
+            creation_code.to_vec(),
+            encode(&[Token::Address(implementation), Token::Bytes(initialize)]),
+        ]
+        .concat();
+        let mut salt_bytes = [0u8; 32];
+        salt.to_big_endian(&mut salt_bytes);
+        Some(get_create2_address(
+            chain.simple_account_factory_address,
+            salt_bytes,
+            init_code,
+        ))
+    }
 }
@@ -1,14 +1,19 @@
 use ethers::abi::Address;
 use ethers::contract::abigen;
-use ethers::providers::{Http, Provider};
-use ethers::types::{Bytes, U256};
+use ethers::providers::{Http, Middleware, Provider};
+use ethers::types::{Bytes, H256, U256};
 use std::sync::Arc;
 
 use crate::errors::ProviderError;
 use crate::provider::Web3Client;
+use crate::PROVIDER;
 
 abigen!(SimpleAccount, "abi/SimpleAccount.json");
 
+// EIP-1967 implementation slot, keccak256("eip1967.proxy.implementation") - 1
+const IMPLEMENTATION_SLOT: &str =
+    "0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc";
+
 #[derive(Clone)]
 pub struct SimpleAccountProvider;
 
@@ -37,6 +42,21 @@ impl SimpleAccountProvider {
         }
     }
 
+    pub fn execute_batch(
+        client: &Web3Client,
+        to: Vec<Address>,
+        data: Vec<Bytes>,
+    ) -> Result<Bytes, ProviderError> {
+        let data = client
+            .get_scw_provider_by_address(Address::zero())
+            .execute_batch(to, data)
+            .calldata();
+        match data {
+            Some(call_data) => Ok(call_data),
+            None => Err(ProviderError(String::from("execute batch data failed"))),
+        }
+    }
+
     pub async fn get_deployer(
         client: &Web3Client,
         contract_address: Address,
@@ -51,4 +71,31 @@ impl SimpleAccountProvider {
             Err(err) => Err(ProviderError(format!("Failed to get deployer: {}", err))),
         }
     }
+
+    pub fn upgrade_to_and_call(
+        client: &Web3Client,
+        implementation: Address,
+        data: Bytes,
+    ) -> Result<Bytes, ProviderError> {
+        let data = client
+            .get_scw_provider_by_address(Address::zero())
+            .upgrade_to_and_call(implementation, data)
+            .calldata();
+        match data {
+            Some(call_data) => Ok(call_data),
+            None => Err(ProviderError(String::from("upgrade data failed"))),
+        }
+    }
+
+    pub async fn get_implementation(account: Address) -> Result<Address, ProviderError> {
+        let slot: H256 = IMPLEMENTATION_SLOT.parse().unwrap();
+        let result = PROVIDER.get_storage_at(account, slot, None).await;
+        match result {
+            Ok(value) => Ok(Address::from(value)),
+            Err(err) => Err(ProviderError(format!(
+                "Failed to get implementation: {:?}",
+                err
+            ))),
+        }
+    }
 }
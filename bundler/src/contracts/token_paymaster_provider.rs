@@ -0,0 +1,70 @@
+use ethers::contract::abigen;
+use ethers::providers::{Http, Provider};
+use ethers::types::{Address, U256};
+use std::sync::Arc;
+
+use crate::errors::ProviderError;
+use crate::models::config::settings;
+use crate::models::contract_interaction;
+use crate::provider::Web3Client;
+use crate::CONFIG;
+
+abigen!(TokenPaymaster, "abi/TokenPaymaster.json");
+
+// all TokenPaymaster prices are multiplied by this value to avoid rounding
+const PRICE_DENOMINATOR: u128 = 100_000_000_000_000_000_000_000_000;
+
+#[derive(Clone)]
+pub struct TokenPaymasterProvider;
+
+impl TokenPaymasterProvider {
+    pub fn init_abi(
+        address: Address,
+        client: Arc<Provider<Http>>,
+    ) -> TokenPaymaster<Provider<Http>> {
+        TokenPaymaster::new(address, client)
+    }
+
+    pub fn get_config() -> Result<&'static settings::TokenPaymaster, ProviderError> {
+        match &CONFIG.get_chain().token_paymaster {
+            Some(token_paymaster) => Ok(token_paymaster),
+            None => Err(ProviderError(String::from(
+                "Token paymaster is not configured for the current chain",
+            ))),
+        }
+    }
+
+    pub fn get_address() -> Result<Address, ProviderError> {
+        Ok(Self::get_config()?.address)
+    }
+
+    // cached oracle price (ether-per-token) with the configured markup applied, in the same
+    // format the paymaster accepts as a client supplied price in paymasterAndData
+    pub async fn get_price_with_markup(client: &Web3Client) -> Result<U256, ProviderError> {
+        let price_markup = Self::get_config()?.get_price_markup();
+        let response = client
+            .get_token_paymaster_provider(Self::get_address()?)
+            .cached_price()
+            .await;
+        match response {
+            Ok(price) => Ok(price * U256::from(PRICE_DENOMINATOR) / price_markup),
+            Err(err) => Err(ProviderError(format!("Token paymaster: Price: {:?}", err))),
+        }
+    }
+
+    // mirrors the pre charge computed in TokenPaymaster._validatePaymasterUserOp
+    pub fn get_token_cost(
+        user_op: &contract_interaction::UserOperation,
+        price_with_markup: U256,
+    ) -> Result<U256, ProviderError> {
+        if price_with_markup.is_zero() {
+            return Err(ProviderError(String::from(
+                "Token paymaster: price not set",
+            )));
+        }
+        let refund_post_op_cost = Self::get_config()?.refund_postop_cost;
+        let pre_charge_native = user_op.get_paymaster_prefund()
+            + U256::from(refund_post_op_cost) * U256::from(user_op.max_fee_per_gas);
+        Ok(pre_charge_native * U256::from(PRICE_DENOMINATOR) / price_with_markup)
+    }
+}
@@ -34,6 +34,36 @@ impl USDCProvider {
         }
     }
 
+    pub fn approve(
+        client: &Web3Client,
+        spender: Address,
+        value: U256,
+    ) -> Result<Bytes, ProviderError> {
+        let data = client
+            .get_usdc_provider()
+            .approve(spender, value)
+            .calldata();
+        match data {
+            Some(call_data) => Ok(call_data),
+            None => Err(ProviderError(String::from("approve data failed"))),
+        }
+    }
+
+    pub async fn allowance(
+        client: &Web3Client,
+        owner: Address,
+        spender: Address,
+    ) -> Result<U256, ProviderError> {
+        let result = client.get_usdc_provider().allowance(owner, spender).await;
+        match result {
+            Ok(allowance) => Ok(allowance),
+            Err(err) => {
+                error!("Failed to get allowance: {}", err);
+                Err(ProviderError(String::from("Failed to get allowance")))
+            }
+        }
+    }
+
     pub fn mint(client: &Web3Client, to: Address, value: String) -> Result<Bytes, String> {
         let data = client
             .get_usdc_provider()
@@ -1,19 +1,28 @@
+use ethers::abi::{decode, encode, ParamType, Token, Tokenizable};
 use ethers::contract::abigen;
 use ethers::providers::{Http, Provider};
-use ethers::types::Address;
 use ethers::types::U256;
-use ethers::utils::format_ether;
+use ethers::types::{Address, Bytes};
+use ethers::utils::{format_ether, keccak256};
+use ethers_signers::Signer;
+use std::ops::Deref;
 use std::sync::Arc;
 
 use crate::errors::ProviderError;
 use crate::models::contract_interaction;
+use crate::models::contract_interaction::{EntryPointVersion, PackedUserOperation};
 use crate::provider::Web3Client;
+use crate::signer::ToadSigner;
+use crate::CONFIG;
 
 abigen!(VerifyingPaymaster, "abi/VerifyingPaymaster.json");
 
 pub struct VerifyingPaymasterProvider;
 
 impl VerifyingPaymasterProvider {
+    pub const VALID_UNTIL: u64 = 3735928559;
+    pub const VALID_AFTER: u64 = 4660;
+
     pub fn init_abi(
         address: Address,
         client: Arc<Provider<Http>>,
@@ -22,9 +31,20 @@ impl VerifyingPaymasterProvider {
         contract
     }
 
+    pub fn get_address(version: EntryPointVersion) -> Result<Address, ProviderError> {
+        CONFIG
+            .get_chain()
+            .get_verifying_paymaster_address(version)
+            .ok_or(ProviderError(format!(
+                "Paymaster: no verifying paymaster configured for {:?}",
+                version
+            )))
+    }
+
     pub async fn get_deposit(client: &Web3Client) -> Result<String, ProviderError> {
+        let address = Self::get_address(CONFIG.get_chain().get_entrypoint_version())?;
         let response = client
-            .get_verifying_paymaster_provider()
+            .get_verifying_paymaster_provider(address)
             .get_deposit()
             .await;
         match response {
@@ -43,7 +63,7 @@ impl VerifyingPaymasterProvider {
         valid_after: u64,
     ) -> Result<[u8; 32], ProviderError> {
         let response = client
-            .get_verifying_paymaster_provider()
+            .get_verifying_paymaster_provider(Self::get_address(EntryPointVersion::V06)?)
             .get_hash(user_operation, valid_until, valid_after)
             .await;
         match response {
@@ -52,6 +72,140 @@ impl VerifyingPaymasterProvider {
         }
     }
 
+    // mirrors getHash of the v0.7 VerifyingPaymaster, which takes a PackedUserOperation the
+    // v0.6 abi can't encode. The paymaster gas limits are covered, its data and signature are not
+    pub fn get_hash_v7(
+        user_op: &PackedUserOperation,
+        chain_id: u64,
+        paymaster: Address,
+        valid_until: u64,
+        valid_after: u64,
+    ) -> [u8; 32] {
+        let mut paymaster_gas_limits = [0u8; 32];
+        if let Some(gas_limits) = user_op.paymaster_and_data.get(20..52) {
+            paymaster_gas_limits.copy_from_slice(gas_limits);
+        }
+        keccak256(encode(&[
+            Token::Address(user_op.sender),
+            Token::Uint(user_op.nonce),
+            Token::FixedBytes(keccak256(user_op.init_code.deref()).to_vec()),
+            Token::FixedBytes(keccak256(user_op.calldata.deref()).to_vec()),
+            Token::FixedBytes(user_op.account_gas_limits.as_bytes().to_vec()),
+            Token::Uint(U256::from_big_endian(&paymaster_gas_limits)),
+            Token::Uint(user_op.pre_verification_gas),
+            Token::FixedBytes(user_op.gas_fees.as_bytes().to_vec()),
+            Token::Uint(U256::from(chain_id)),
+            Token::Address(paymaster),
+            valid_until.into_token(),
+            valid_after.into_token(),
+        ]))
+    }
+
+    // the hash the paymaster of the given version checks the signature against
+    pub async fn get_paymaster_hash(
+        client: &Web3Client,
+        version: EntryPointVersion,
+        user_op: contract_interaction::UserOperation,
+        valid_until: u64,
+        valid_after: u64,
+    ) -> Result<[u8; 32], ProviderError> {
+        match version {
+            EntryPointVersion::V06 => {
+                Self::get_hash(
+                    client,
+                    Self::get_verifying_paymaster_user_operation_payload(user_op),
+                    valid_until,
+                    valid_after,
+                )
+                .await
+            }
+            EntryPointVersion::V07 => Ok(Self::get_hash_v7(
+                &PackedUserOperation::from(user_op),
+                CONFIG.get_chain().chain_id,
+                Self::get_address(version)?,
+                valid_until,
+                valid_after,
+            )),
+        }
+    }
+
+    pub async fn get_verifying_signer(
+        client: &Web3Client,
+        version: EntryPointVersion,
+    ) -> Result<Address, ProviderError> {
+        let response = client
+            .get_verifying_paymaster_provider(Self::get_address(version)?)
+            .verifying_signer()
+            .await;
+        match response {
+            Ok(signer) => Ok(signer),
+            Err(err) => Err(ProviderError(format!("Paymaster: Signer: {:?}", err))),
+        }
+    }
+
+    // the configured key the paymaster currently trusts on chain
+    pub async fn get_signing_wallet(
+        client: &Web3Client,
+        version: EntryPointVersion,
+    ) -> Result<ToadSigner, ProviderError> {
+        let signer = Self::get_verifying_signer(client, version).await?;
+        Web3Client::get_verifying_paymaster_wallets()
+            .into_iter()
+            .find(|wallet| wallet.address() == signer)
+            .ok_or(ProviderError(format!(
+                "Paymaster: no key configured for verifying signer {:?}",
+                signer
+            )))
+    }
+
+    // paymasterAndData is paymaster (20 bytes), abi encoded (validUntil, validAfter) and signature
+    pub fn parse_paymaster_and_data(
+        paymaster_and_data: &Bytes,
+    ) -> Result<(Address, u64, u64, Bytes), ProviderError> {
+        if paymaster_and_data.len() < 20 + 64 {
+            return Err(ProviderError(String::from(
+                "Paymaster: Invalid paymasterAndData",
+            )));
+        }
+        let tokens = decode(
+            &[ParamType::Uint(48), ParamType::Uint(48)],
+            &paymaster_and_data[20..84],
+        )
+        .map_err(|err| ProviderError(format!("Paymaster: Invalid validity: {:?}", err)))?;
+        let valid_until = tokens[0].clone().into_uint().unwrap_or_default().low_u64();
+        let valid_after = tokens[1].clone().into_uint().unwrap_or_default().low_u64();
+        Ok((
+            Address::from_slice(&paymaster_and_data[..20]),
+            valid_until,
+            valid_after,
+            Bytes::from(paymaster_and_data[84..].to_vec()),
+        ))
+    }
+
+    // abi encoded (validUntil, validAfter), the part of paymasterAndData covered by the signature
+    pub fn get_validity_data(valid_until: u64, valid_after: u64) -> Vec<u8> {
+        encode(&[valid_until.into_token(), valid_after.into_token()])
+    }
+
+    pub async fn get_signed_hash(
+        client: &Web3Client,
+        version: EntryPointVersion,
+        user_op: contract_interaction::UserOperation,
+        valid_until: u64,
+        valid_after: u64,
+    ) -> Result<Vec<u8>, ProviderError> {
+        let hash =
+            Self::get_paymaster_hash(client, version, user_op, valid_until, valid_after).await?;
+        Self::sign_hash(&Self::get_signing_wallet(client, version).await?, hash).await
+    }
+
+    pub async fn sign_hash(wallet: &ToadSigner, hash: [u8; 32]) -> Result<Vec<u8>, ProviderError> {
+        match wallet.sign_message(hash).await {
+            Ok(signature) => Ok(signature.to_vec()),
+            Err(err) => Err(ProviderError(format!("Signing failed: {:?}", err))),
+        }
+    }
+
     pub fn get_verifying_paymaster_user_operation_payload(
         user_op: contract_interaction::UserOperation,
     ) -> UserOperation {
@@ -70,3 +224,86 @@ impl VerifyingPaymasterProvider {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::H256;
+
+    fn packed_user_op() -> PackedUserOperation {
+        let mut user_op = PackedUserOperation {
+            sender: Address::repeat_byte(0x11),
+            nonce: U256::from(5),
+            init_code: Bytes::from(vec![0xde, 0xad]),
+            calldata: Bytes::from(vec![0xbe, 0xef]),
+            pre_verification_gas: U256::from(50000),
+            ..Default::default()
+        };
+        user_op
+            .gas_limits(100000, 200000)
+            .gas_fees(1000000000, 2000000000)
+            .paymaster_and_data(
+                Address::repeat_byte(0x22),
+                100000,
+                50000,
+                VerifyingPaymasterProvider::get_validity_data(
+                    VerifyingPaymasterProvider::VALID_UNTIL,
+                    VerifyingPaymasterProvider::VALID_AFTER,
+                ),
+            );
+        user_op
+    }
+
+    fn hash(user_op: &PackedUserOperation) -> [u8; 32] {
+        VerifyingPaymasterProvider::get_hash_v7(
+            user_op,
+            84532,
+            Address::repeat_byte(0x22),
+            VerifyingPaymasterProvider::VALID_UNTIL,
+            VerifyingPaymasterProvider::VALID_AFTER,
+        )
+    }
+
+    #[test]
+    fn hashes_v7_ops_like_the_paymaster() {
+        assert_eq!(
+            H256::from(hash(&packed_user_op())),
+            "0x9616bf9165d943832dd42f8a7164152e4f9a62158798a96f04835da0efd522af"
+                .parse::<H256>()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn v7_hash_covers_paymaster_gas_limits_but_not_its_signature() {
+        let user_op = packed_user_op();
+
+        let mut signed = user_op.clone();
+        signed.paymaster_and_data =
+            Bytes::from([user_op.paymaster_and_data.to_vec(), vec![0x1b; 65]].concat());
+        assert_eq!(hash(&signed), hash(&user_op));
+
+        let mut regassed = user_op.clone();
+        regassed.paymaster_and_data(
+            Address::repeat_byte(0x22),
+            100001,
+            50000,
+            VerifyingPaymasterProvider::get_validity_data(
+                VerifyingPaymasterProvider::VALID_UNTIL,
+                VerifyingPaymasterProvider::VALID_AFTER,
+            ),
+        );
+        assert_ne!(hash(&regassed), hash(&user_op));
+    }
+
+    #[actix_web::test]
+    async fn signs_the_paymaster_hash_as_a_personal_message() {
+        let wallet = ToadSigner::mock(1);
+        let hash = hash(&packed_user_op());
+        let signature = VerifyingPaymasterProvider::sign_hash(&wallet, hash)
+            .await
+            .unwrap();
+        let signature = ethers::types::Signature::try_from(signature.as_slice()).unwrap();
+        assert_eq!(signature.recover(hash.to_vec()).unwrap(), wallet.address());
+    }
+}
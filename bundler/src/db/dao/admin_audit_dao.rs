@@ -0,0 +1,93 @@
+use chrono::{DateTime, Utc};
+use sqlx::types::JsonValue;
+use sqlx::{query, query_as, Executor, Pool, Postgres};
+
+use crate::errors::DatabaseError;
+
+#[derive(Clone)]
+pub struct AdminAuditDao;
+
+impl AdminAuditDao {
+    // takes an executor so the entry can commit in the action's own transaction
+    pub async fn create_entry<'a, E>(
+        executor: E,
+        entry: AdminAuditEntry,
+    ) -> Result<(), DatabaseError>
+    where
+        E: Executor<'a, Database = Postgres>,
+    {
+        let query = query!(
+            "INSERT INTO admin_audit_log (actor, action, parameters, transaction_hash, outcome, \
+            error) VALUES ($1, $2, $3, $4, $5, $6)",
+            entry.actor,
+            entry.action,
+            entry.parameters,
+            entry.transaction_hash,
+            entry.outcome,
+            entry.error
+        );
+        let result = query.execute(executor).await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to create admin audit entry: {}, err: {:?}",
+                entry.action, err
+            ))),
+        }
+    }
+
+    // newest first, id is the last id of the previous page
+    pub async fn get_entries(
+        pool: &Pool<Postgres>,
+        filter: AdminAuditFilter,
+        id: i32,
+        page_size: i64,
+    ) -> Result<Vec<AdminAuditEntry>, DatabaseError> {
+        let query = query_as!(
+            AdminAuditEntry,
+            "SELECT * FROM admin_audit_log WHERE id < $1 \
+            AND ($2::VARCHAR IS NULL OR actor = $2) \
+            AND ($3::VARCHAR IS NULL OR action = $3) \
+            AND ($4::VARCHAR IS NULL OR outcome = $4) \
+            AND ($5::TIMESTAMPTZ IS NULL OR created_at >= $5) \
+            AND ($6::TIMESTAMPTZ IS NULL OR created_at < $6) \
+            ORDER BY id DESC LIMIT $7",
+            id,
+            filter.actor,
+            filter.action,
+            filter.outcome,
+            filter.from,
+            filter.to,
+            page_size
+        );
+        let result = query.fetch_all(pool).await;
+        match result {
+            Ok(rows) => Ok(rows),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to fetch admin audit entries: {:?}",
+                err
+            ))),
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct AdminAuditEntry {
+    pub id: i32,
+    pub actor: String,
+    pub action: String,
+    pub parameters: JsonValue,
+    pub transaction_hash: Option<String>,
+    pub outcome: String,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Default)]
+pub struct AdminAuditFilter {
+    pub actor: Option<String>,
+    pub action: Option<String>,
+    pub outcome: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,73 @@
+use sqlx::{query, Pool, Postgres};
+
+use crate::errors::DatabaseError;
+
+#[derive(Clone)]
+pub struct AdminNonceDao;
+
+impl AdminNonceDao {
+    // false when the key already used the nonce. Nonces older than max_age_secs are dropped first,
+    // requests that old are rejected by their timestamp anyway
+    pub async fn use_nonce(
+        pool: &Pool<Postgres>,
+        key_id: String,
+        nonce: String,
+        max_age_secs: u64,
+    ) -> Result<bool, DatabaseError> {
+        query!(
+            "DELETE FROM admin_request_nonces WHERE created_at < NOW() - make_interval(secs => $1)",
+            max_age_secs as f64
+        )
+        .execute(pool)
+        .await
+        .map_err(|err| {
+            DatabaseError::ServerError(format!("Failed to delete admin nonces, err: {:?}", err))
+        })?;
+        let query = query!(
+            "INSERT INTO admin_request_nonces (key_id, nonce) VALUES ($1, $2) \
+            ON CONFLICT DO NOTHING",
+            key_id,
+            nonce
+        );
+        let result = query.execute(pool).await;
+        match result {
+            Ok(result) => Ok(result.rows_affected() == 1),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to store admin nonce for: {}, err: {:?}",
+                key_id, err
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[sqlx::test]
+    async fn a_nonce_is_only_accepted_once_per_key(pool: Pool<Postgres>) {
+        let use_nonce = |key_id: &str, nonce: &str| {
+            AdminNonceDao::use_nonce(&pool, String::from(key_id), String::from(nonce), 600)
+        };
+        assert!(use_nonce("ops", "a").await.unwrap());
+        assert!(!use_nonce("ops", "a").await.unwrap());
+        assert!(use_nonce("ops", "b").await.unwrap());
+        assert!(use_nonce("treasury", "a").await.unwrap());
+    }
+
+    #[sqlx::test]
+    async fn expired_nonces_are_dropped(pool: Pool<Postgres>) {
+        AdminNonceDao::use_nonce(&pool, String::from("ops"), String::from("a"), 600)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE admin_request_nonces SET created_at = NOW() - INTERVAL '1 hour'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert!(
+            AdminNonceDao::use_nonce(&pool, String::from("ops"), String::from("a"), 600)
+                .await
+                .unwrap()
+        );
+    }
+}
@@ -0,0 +1,36 @@
+use bigdecimal::BigDecimal;
+use sqlx::{query, Pool, Postgres};
+
+use crate::errors::DatabaseError;
+
+#[derive(Clone)]
+pub struct BalanceTopupDao;
+
+impl BalanceTopupDao {
+    pub async fn create_topup(
+        pool: &Pool<Postgres>,
+        entity: String,
+        address: String,
+        amount: BigDecimal,
+        currency: String,
+        transaction_hash: String,
+    ) -> Result<(), DatabaseError> {
+        let query = query!(
+            "INSERT INTO balance_topups (entity, address, amount, currency, transaction_hash) \
+            VALUES ($1, $2, $3, $4, $5)",
+            entity,
+            address,
+            amount,
+            currency,
+            transaction_hash
+        );
+        let result = query.execute(pool).await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to create balance topup: {}, err: {:?}",
+                transaction_hash, err
+            ))),
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use bigdecimal::BigDecimal;
+use sqlx::{query, query_as, Pool, Postgres};
+
+use crate::errors::DatabaseError;
+
+#[derive(Clone)]
+pub struct BundleDao;
+
+impl BundleDao {
+    pub async fn create_bundle(pool: &Pool<Postgres>, bundle: Bundle) -> Result<(), DatabaseError> {
+        let query = query!(
+            "INSERT INTO bundles (transaction_hash, chain, beneficiary, relayer, user_op_count, \
+            gas_cost, refund) VALUES ($1, $2, $3, $4, $5, $6, $7) \
+            ON CONFLICT (transaction_hash) DO NOTHING",
+            bundle.transaction_hash,
+            bundle.chain,
+            bundle.beneficiary,
+            bundle.relayer,
+            bundle.user_op_count,
+            bundle.gas_cost,
+            bundle.refund
+        );
+        let result = query.execute(pool).await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to create bundle: {}, err: {:?}",
+                bundle.transaction_hash, err
+            ))),
+        }
+    }
+
+    // period is a postgres date_trunc field, eg: day, month. None covers every bundle
+    pub async fn get_profit_and_loss(
+        pool: &Pool<Postgres>,
+        chain: String,
+        period: Option<String>,
+    ) -> Result<BundleProfitAndLoss, DatabaseError> {
+        let query = query_as!(
+            BundleProfitAndLoss,
+            "SELECT COUNT(*) as \"bundles!\", \
+            COALESCE(SUM(user_op_count), 0) as \"user_ops!\", \
+            COALESCE(SUM(gas_cost), 0) as \"gas_cost!\", \
+            COALESCE(SUM(refund), 0) as \"refund!\" FROM bundles \
+            WHERE chain = $1 AND ($2::TEXT IS NULL OR created_at >= date_trunc($2, now()))",
+            chain,
+            period
+        );
+        let result = query.fetch_one(pool).await;
+        match result {
+            Ok(pnl) => Ok(pnl),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to get bundle profit and loss: {}, err: {:?}",
+                chain, err
+            ))),
+        }
+    }
+}
+
+// gas_cost is what the relayer paid for the handleOps transaction, refund is the sum of
+// actualGasCost the EntryPoint paid to the beneficiary. Both are in wei
+#[derive(Clone)]
+pub struct Bundle {
+    pub transaction_hash: String,
+    pub chain: String,
+    pub beneficiary: String,
+    pub relayer: String,
+    pub user_op_count: i32,
+    pub gas_cost: BigDecimal,
+    pub refund: BigDecimal,
+}
+
+#[derive(Clone)]
+pub struct BundleProfitAndLoss {
+    pub bundles: i64,
+    pub user_ops: i64,
+    pub gas_cost: BigDecimal,
+    pub refund: BigDecimal,
+}
@@ -0,0 +1,60 @@
+use sqlx::{query, Pool, Postgres};
+
+use crate::errors::DatabaseError;
+
+#[derive(Clone)]
+pub struct GuardianDao;
+
+impl GuardianDao {
+    // mirrors the guardians set on chain, see the GuardiansChanged listener
+    pub async fn set_guardians(
+        pool: &Pool<Postgres>,
+        tenant_id: i32,
+        wallet_address: String,
+        guardian_addresses: Vec<String>,
+    ) -> Result<(), DatabaseError> {
+        let guardian_addresses: Vec<String> = guardian_addresses
+            .into_iter()
+            .map(|guardian| guardian.to_lowercase())
+            .collect();
+        let query = query!(
+            "WITH removed AS (DELETE FROM guardians \
+                WHERE tenant_id = $1 AND wallet_address = $2 AND guardian_address <> ALL($3)) \
+            INSERT INTO guardians (tenant_id, wallet_address, guardian_address) \
+            SELECT $1, $2, guardian FROM unnest($3::VARCHAR[]) guardian \
+            ON CONFLICT (tenant_id, wallet_address, guardian_address) DO NOTHING",
+            tenant_id,
+            wallet_address.to_lowercase(),
+            &guardian_addresses
+        );
+        let result = query.execute(pool).await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to set guardians for wallet: {}, err: {:?}",
+                wallet_address, err
+            ))),
+        }
+    }
+
+    pub async fn get_guardians(
+        pool: &Pool<Postgres>,
+        tenant_id: i32,
+        wallet_address: String,
+    ) -> Result<Vec<String>, DatabaseError> {
+        let query = query!(
+            "SELECT guardian_address FROM guardians \
+            WHERE tenant_id = $1 AND wallet_address = $2 ORDER BY id",
+            tenant_id,
+            wallet_address.to_lowercase()
+        );
+        let result = query.fetch_all(pool).await;
+        match result {
+            Ok(rows) => Ok(rows.into_iter().map(|row| row.guardian_address).collect()),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to get guardians for wallet: {}, err: {:?}",
+                wallet_address, err
+            ))),
+        }
+    }
+}
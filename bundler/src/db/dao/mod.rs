@@ -1,8 +1,30 @@
+pub mod admin_audit_dao;
+pub mod admin_nonce_dao;
+pub mod balance_topup_dao;
+pub mod bundle_dao;
+pub mod guardian_dao;
+pub mod passkey_dao;
+pub mod paymaster_client_dao;
+pub mod recovery_dao;
+pub mod session_key_dao;
+pub mod sponsorship_dao;
+pub mod tenant_dao;
 pub mod token_metadata_dao;
 pub mod transaction_dao;
 pub mod user_operation_dao;
 pub mod wallet_dao;
 
+pub use admin_audit_dao::*;
+pub use admin_nonce_dao::*;
+pub use balance_topup_dao::*;
+pub use bundle_dao::*;
+pub use guardian_dao::*;
+pub use passkey_dao::*;
+pub use paymaster_client_dao::*;
+pub use recovery_dao::*;
+pub use session_key_dao::*;
+pub use sponsorship_dao::*;
+pub use tenant_dao::*;
 pub use token_metadata_dao::*;
 pub use transaction_dao::*;
 pub use user_operation_dao::*;
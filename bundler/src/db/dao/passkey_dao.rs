@@ -0,0 +1,60 @@
+use sqlx::{query, Executor, Pool, Postgres};
+
+use crate::errors::DatabaseError;
+
+#[derive(Clone)]
+pub struct PasskeyDao;
+
+impl PasskeyDao {
+    pub async fn create_credential<'a, E>(
+        executor: E,
+        tenant_id: i32,
+        wallet_address: String,
+        credential_id: String,
+        public_key: String,
+    ) -> Result<(), DatabaseError>
+    where
+        E: Executor<'a, Database = Postgres>,
+    {
+        let query = query!(
+            "INSERT INTO passkey_credentials (tenant_id, wallet_address, credential_id, public_key) \
+            VALUES ($1, $2, $3, $4)",
+            tenant_id,
+            wallet_address.to_lowercase(),
+            credential_id,
+            public_key.to_lowercase()
+        );
+        let result = query.execute(executor).await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to create passkey credential for wallet: {}, err: {:?}",
+                wallet_address, err
+            ))),
+        }
+    }
+
+    pub async fn get_public_key(
+        pool: &Pool<Postgres>,
+        tenant_id: i32,
+        wallet_address: String,
+        credential_id: String,
+    ) -> Result<String, DatabaseError> {
+        let query = query!(
+            "SELECT public_key FROM passkey_credentials \
+            WHERE tenant_id = $1 AND wallet_address = $2 AND credential_id = $3",
+            tenant_id,
+            wallet_address.to_lowercase(),
+            credential_id
+        );
+        let result = query.fetch_optional(pool).await;
+        match result {
+            Ok(Some(row)) => Ok(row.public_key),
+            Ok(None) => Err(DatabaseError::NotFound),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to get passkey credential for wallet: {}, err: {:?}",
+                wallet_address, err
+            ))),
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use sqlx::{query, query_as, Executor, Pool, Postgres};
+
+use crate::errors::DatabaseError;
+use crate::models::config::settings::SponsorshipPolicy;
+use crate::CONFIG;
+
+#[derive(Clone)]
+pub struct PaymasterClientDao;
+
+impl PaymasterClientDao {
+    pub async fn create_client<'a, E>(
+        executor: E,
+        name: String,
+        api_key_hash: String,
+        sponsorship_policy: Option<Value>,
+    ) -> Result<(), DatabaseError>
+    where
+        E: Executor<'a, Database = Postgres>,
+    {
+        let query = query!(
+            "INSERT INTO paymaster_clients (name, api_key_hash, sponsorship_policy) \
+            VALUES ($1, $2, $3)",
+            name,
+            api_key_hash,
+            sponsorship_policy
+        );
+        let result = query.execute(executor).await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to create paymaster client: {}, err: {:?}",
+                name, err
+            ))),
+        }
+    }
+
+    pub async fn get_active_client(
+        pool: &Pool<Postgres>,
+        api_key_hash: String,
+    ) -> Result<Option<PaymasterClient>, DatabaseError> {
+        let query = query_as!(
+            PaymasterClient,
+            "SELECT * FROM paymaster_clients WHERE api_key_hash = $1 AND is_active = true",
+            api_key_hash
+        );
+        let result = query.fetch_optional(pool).await;
+        match result {
+            Ok(client) => Ok(client),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to get paymaster client, err: {:?}",
+                err
+            ))),
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct PaymasterClient {
+    pub id: i32,
+    pub name: String,
+    pub api_key_hash: String,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub sponsorship_policy: Option<Value>,
+}
+
+impl PaymasterClient {
+    pub fn get_sponsorship_policy(&self) -> Result<SponsorshipPolicy, String> {
+        match &self.sponsorship_policy {
+            Some(policy) => serde_json::from_value(policy.clone()).map_err(|err| {
+                format!(
+                    "Invalid sponsorship policy for paymaster client {}: {:?}",
+                    self.id, err
+                )
+            }),
+            None => Ok(CONFIG.get_chain().sponsorship_policy.clone()),
+        }
+    }
+}
@@ -0,0 +1,352 @@
+use chrono::{DateTime, Utc};
+use sqlx::{query, query_as, Pool, Postgres};
+
+use crate::errors::DatabaseError;
+
+#[derive(Clone)]
+pub struct RecoveryDao;
+
+impl RecoveryDao {
+    pub async fn create_request(
+        pool: &Pool<Postgres>,
+        tenant_id: i32,
+        request_id: String,
+        wallet_address: String,
+        new_owner: String,
+        recovery_hash: String,
+        status: String,
+    ) -> Result<(), DatabaseError> {
+        let query = query!(
+            "INSERT INTO recovery_requests \
+                (request_id, wallet_address, new_owner, recovery_hash, status, tenant_id) \
+            VALUES ($1, $2, $3, $4, $5, $6)",
+            request_id,
+            wallet_address.to_lowercase(),
+            new_owner.to_lowercase(),
+            recovery_hash,
+            status,
+            tenant_id
+        );
+        let result = query.execute(pool).await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to create recovery request: {}, err: {:?}",
+                request_id, err
+            ))),
+        }
+    }
+
+    pub async fn get_request(
+        pool: &Pool<Postgres>,
+        tenant_id: i32,
+        request_id: String,
+    ) -> Result<RecoveryRequest, DatabaseError> {
+        let query = query_as!(
+            RecoveryRequest,
+            "SELECT * FROM recovery_requests WHERE tenant_id = $1 AND request_id = $2",
+            tenant_id,
+            request_id
+        );
+        let result = query.fetch_optional(pool).await;
+        match result {
+            Ok(Some(request)) => Ok(request),
+            Ok(None) => Err(DatabaseError::NotFound),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to get recovery request: {}, err: {:?}",
+                request_id, err
+            ))),
+        }
+    }
+
+    // requests still waiting on guardians or on the delay
+    pub async fn get_open_request(
+        pool: &Pool<Postgres>,
+        tenant_id: i32,
+        wallet_address: String,
+        statuses: Vec<String>,
+    ) -> Result<Option<RecoveryRequest>, DatabaseError> {
+        let query = query_as!(
+            RecoveryRequest,
+            "SELECT * FROM recovery_requests \
+            WHERE tenant_id = $1 AND wallet_address = $2 AND status = ANY($3)",
+            tenant_id,
+            wallet_address.to_lowercase(),
+            &statuses
+        );
+        let result = query.fetch_optional(pool).await;
+        match result {
+            Ok(request) => Ok(request),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to get open recovery request: {}, err: {:?}",
+                wallet_address, err
+            ))),
+        }
+    }
+
+    pub async fn get_due_requests(
+        pool: &Pool<Postgres>,
+        status: String,
+    ) -> Result<Vec<RecoveryRequest>, DatabaseError> {
+        let query = query_as!(
+            RecoveryRequest,
+            "SELECT * FROM recovery_requests WHERE status = $1 AND execute_after <= now()",
+            status
+        );
+        let result = query.fetch_all(pool).await;
+        match result {
+            Ok(requests) => Ok(requests),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to get due recovery requests, err: {:?}",
+                err
+            ))),
+        }
+    }
+
+    pub async fn update_status(
+        pool: &Pool<Postgres>,
+        tenant_id: i32,
+        request_id: String,
+        status: String,
+        execute_after: Option<DateTime<Utc>>,
+        transaction_id: Option<String>,
+    ) -> Result<(), DatabaseError> {
+        let query = query!(
+            "UPDATE recovery_requests SET status = $1, \
+            execute_after = COALESCE($2, execute_after), \
+            transaction_id = COALESCE($3, transaction_id), updated_at = now() \
+            WHERE tenant_id = $4 AND request_id = $5",
+            status,
+            execute_after,
+            transaction_id,
+            tenant_id,
+            request_id
+        );
+        let result = query.execute(pool).await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to update recovery request: {}, err: {:?}",
+                request_id, err
+            ))),
+        }
+    }
+
+    // moves an approved request to submitted, false when another submission got to it first
+    pub async fn claim_request(
+        pool: &Pool<Postgres>,
+        tenant_id: i32,
+        request_id: String,
+        from_status: String,
+        to_status: String,
+        transaction_id: Option<String>,
+    ) -> Result<bool, DatabaseError> {
+        let query = query!(
+            "UPDATE recovery_requests SET status = $1, transaction_id = $2, updated_at = now() \
+            WHERE tenant_id = $3 AND request_id = $4 AND status = $5",
+            to_status,
+            transaction_id,
+            tenant_id,
+            request_id,
+            from_status
+        );
+        let result = query.execute(pool).await;
+        match result {
+            Ok(result) => Ok(result.rows_affected() > 0),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to claim recovery request: {}, err: {:?}",
+                request_id, err
+            ))),
+        }
+    }
+
+    pub async fn add_approval(
+        pool: &Pool<Postgres>,
+        request_id: String,
+        guardian_address: String,
+        signature: String,
+    ) -> Result<(), DatabaseError> {
+        let query = query!(
+            "INSERT INTO recovery_approvals (request_id, guardian_address, signature) \
+            VALUES ($1, $2, $3) ON CONFLICT (request_id, guardian_address) DO NOTHING",
+            request_id,
+            guardian_address.to_lowercase(),
+            signature
+        );
+        let result = query.execute(pool).await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to add recovery approval: {}, err: {:?}",
+                request_id, err
+            ))),
+        }
+    }
+
+    // approvals from guardians that were removed since do not count
+    pub async fn count_approvals(
+        pool: &Pool<Postgres>,
+        tenant_id: i32,
+        request_id: String,
+        wallet_address: String,
+    ) -> Result<i64, DatabaseError> {
+        let query = query!(
+            "SELECT COUNT(*) as \"count!\" FROM recovery_approvals a JOIN guardians g \
+            ON g.guardian_address = a.guardian_address AND g.tenant_id = $2 \
+                AND g.wallet_address = $3 \
+            WHERE a.request_id = $1",
+            request_id,
+            tenant_id,
+            wallet_address.to_lowercase()
+        );
+        let result = query.fetch_one(pool).await;
+        match result {
+            Ok(row) => Ok(row.count),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to count recovery approvals: {}, err: {:?}",
+                request_id, err
+            ))),
+        }
+    }
+
+    // signatures of the current guardians only
+    pub async fn get_approvals(
+        pool: &Pool<Postgres>,
+        tenant_id: i32,
+        request_id: String,
+        wallet_address: String,
+    ) -> Result<Vec<RecoveryApproval>, DatabaseError> {
+        let query = query_as!(
+            RecoveryApproval,
+            "SELECT a.guardian_address, a.signature FROM recovery_approvals a JOIN guardians g \
+            ON g.guardian_address = a.guardian_address AND g.tenant_id = $2 \
+                AND g.wallet_address = $3 \
+            WHERE a.request_id = $1",
+            request_id,
+            tenant_id,
+            wallet_address.to_lowercase()
+        );
+        let result = query.fetch_all(pool).await;
+        match result {
+            Ok(approvals) => Ok(approvals),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to get recovery approvals: {}, err: {:?}",
+                request_id, err
+            ))),
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct RecoveryRequest {
+    pub id: i32,
+    pub request_id: String,
+    pub wallet_address: String,
+    pub new_owner: String,
+    pub status: String,
+    pub execute_after: Option<DateTime<Utc>>,
+    pub transaction_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub recovery_hash: Option<String>,
+    pub tenant_id: i32,
+}
+
+#[derive(Clone, Default)]
+pub struct RecoveryApproval {
+    pub guardian_address: String,
+    pub signature: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::Constants;
+    use crate::db::dao::{GuardianDao, Tenant, TenantDao};
+
+    const WALLET: &str = "0x0000000000000000000000000000000000000001";
+    const GUARDIAN: &str = "0x0000000000000000000000000000000000000002";
+
+    #[sqlx::test]
+    async fn requests_and_guardians_stay_within_the_tenant(pool: Pool<Postgres>) {
+        let other = TenantDao::create_tenant(
+            &pool,
+            Tenant {
+                name: String::from("other"),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        RecoveryDao::create_request(
+            &pool,
+            Constants::DEFAULT_TENANT_ID,
+            String::from("recovery_a"),
+            String::from(WALLET),
+            String::from(GUARDIAN),
+            String::from("0x00"),
+            String::from("PENDING"),
+        )
+        .await
+        .unwrap();
+        // the same wallet address under another tenant has its own guardians
+        GuardianDao::set_guardians(
+            &pool,
+            other.id,
+            String::from(WALLET),
+            vec![String::from(GUARDIAN)],
+        )
+        .await
+        .unwrap();
+        RecoveryDao::add_approval(
+            &pool,
+            String::from("recovery_a"),
+            String::from(GUARDIAN),
+            String::from("0x00"),
+        )
+        .await
+        .unwrap();
+
+        assert!(matches!(
+            RecoveryDao::get_request(&pool, other.id, String::from("recovery_a")).await,
+            Err(DatabaseError::NotFound)
+        ));
+        assert!(!RecoveryDao::claim_request(
+            &pool,
+            other.id,
+            String::from("recovery_a"),
+            String::from("PENDING"),
+            String::from("SUBMITTED"),
+            None,
+        )
+        .await
+        .unwrap());
+        assert!(GuardianDao::get_guardians(
+            &pool,
+            Constants::DEFAULT_TENANT_ID,
+            String::from(WALLET)
+        )
+        .await
+        .unwrap()
+        .is_empty());
+        assert_eq!(
+            RecoveryDao::count_approvals(
+                &pool,
+                Constants::DEFAULT_TENANT_ID,
+                String::from("recovery_a"),
+                String::from(WALLET),
+            )
+            .await
+            .unwrap(),
+            0
+        );
+        let request = RecoveryDao::get_request(
+            &pool,
+            Constants::DEFAULT_TENANT_ID,
+            String::from("recovery_a"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(request.status, "PENDING");
+    }
+}
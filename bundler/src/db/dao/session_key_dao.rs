@@ -0,0 +1,306 @@
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+use sqlx::{query, query_as, Executor, Pool, Postgres};
+
+use crate::errors::DatabaseError;
+
+#[derive(Clone)]
+pub struct SessionKeyDao;
+
+impl SessionKeyDao {
+    pub async fn create_session(
+        pool: &Pool<Postgres>,
+        session: SessionKey,
+    ) -> Result<(), DatabaseError> {
+        let query = query!(
+            "INSERT INTO session_keys (session_id, wallet_address, session_key, valid_after, \
+            valid_until, allowed_targets, currencies, tokens, spend_limits, permission_hash, \
+            status, tenant_id) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)",
+            session.session_id,
+            session.wallet_address.to_lowercase(),
+            session.session_key.to_lowercase(),
+            session.valid_after,
+            session.valid_until,
+            &session.allowed_targets,
+            &session.currencies,
+            &session.tokens,
+            &session.spend_limits,
+            session.permission_hash,
+            session.status,
+            session.tenant_id
+        );
+        let result = query.execute(pool).await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to create session: {}, err: {:?}",
+                session.session_id, err
+            ))),
+        }
+    }
+
+    pub async fn get_session(
+        pool: &Pool<Postgres>,
+        tenant_id: i32,
+        session_id: String,
+    ) -> Result<SessionKey, DatabaseError> {
+        let query = query_as!(
+            SessionKey,
+            "SELECT * FROM session_keys WHERE tenant_id = $1 AND session_id = $2",
+            tenant_id,
+            session_id
+        );
+        let result = query.fetch_optional(pool).await;
+        match result {
+            Ok(Some(session)) => Ok(session),
+            Ok(None) => Err(DatabaseError::NotFound),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to get session: {}, err: {:?}",
+                session_id, err
+            ))),
+        }
+    }
+
+    pub async fn get_sessions(
+        pool: &Pool<Postgres>,
+        tenant_id: i32,
+        wallet_address: String,
+    ) -> Result<Vec<SessionKey>, DatabaseError> {
+        let query = query_as!(
+            SessionKey,
+            "SELECT * FROM session_keys WHERE tenant_id = $1 AND wallet_address = $2 \
+            ORDER BY id DESC",
+            tenant_id,
+            wallet_address.to_lowercase()
+        );
+        let result = query.fetch_all(pool).await;
+        match result {
+            Ok(sessions) => Ok(sessions),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to get sessions for wallet: {}, err: {:?}",
+                wallet_address, err
+            ))),
+        }
+    }
+
+    pub async fn update_status(
+        pool: &Pool<Postgres>,
+        tenant_id: i32,
+        session_id: String,
+        status: String,
+        owner_signature: Option<String>,
+    ) -> Result<(), DatabaseError> {
+        let query = query!(
+            "UPDATE session_keys SET status = $1, \
+            owner_signature = COALESCE($2, owner_signature), updated_at = now() \
+            WHERE tenant_id = $3 AND session_id = $4",
+            status,
+            owner_signature,
+            tenant_id,
+            session_id
+        );
+        let result = query.execute(pool).await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to update session: {}, err: {:?}",
+                session_id, err
+            ))),
+        }
+    }
+
+    // serializes spends of a session, released on commit
+    pub async fn lock_session<'a, E>(
+        executor: E,
+        tenant_id: i32,
+        session_id: String,
+    ) -> Result<SessionKey, DatabaseError>
+    where
+        E: Executor<'a, Database = Postgres>,
+    {
+        let query = query_as!(
+            SessionKey,
+            "SELECT * FROM session_keys WHERE tenant_id = $1 AND session_id = $2 FOR UPDATE",
+            tenant_id,
+            session_id
+        );
+        let result = query.fetch_optional(executor).await;
+        match result {
+            Ok(Some(session)) => Ok(session),
+            Ok(None) => Err(DatabaseError::NotFound),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to lock session: {}, err: {:?}",
+                session_id, err
+            ))),
+        }
+    }
+
+    // the amount stays reserved for reservation_secs unless the spend is held or settled
+    pub async fn create_spend<'a, E>(
+        executor: E,
+        transaction_id: String,
+        session_id: String,
+        currency: String,
+        amount: BigDecimal,
+        reservation_secs: i32,
+    ) -> Result<(), DatabaseError>
+    where
+        E: Executor<'a, Database = Postgres>,
+    {
+        let query = query!(
+            "INSERT INTO session_key_spends (transaction_id, session_id, currency, amount, \
+                reserved_until) VALUES ($1, $2, $3, $4, now() + make_interval(secs => $5))",
+            transaction_id,
+            session_id,
+            currency,
+            amount,
+            reservation_secs as f64
+        );
+        let result = query.execute(executor).await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to create session spend: {}, err: {:?}",
+                transaction_id, err
+            ))),
+        }
+    }
+
+    pub async fn get_spent<'a, E>(
+        executor: E,
+        session_id: String,
+        currency: String,
+    ) -> Result<BigDecimal, DatabaseError>
+    where
+        E: Executor<'a, Database = Postgres>,
+    {
+        let query = query!(
+            "SELECT COALESCE(SUM(amount), 0) as \"amount!\" FROM session_key_spends \
+            WHERE session_id = $1 AND currency = $2 \
+                AND (reserved_until IS NULL OR reserved_until > now())",
+            session_id,
+            currency
+        );
+        let result = query.fetch_one(executor).await;
+        match result {
+            Ok(row) => Ok(row.amount),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to get session spend: {}, err: {:?}",
+                session_id, err
+            ))),
+        }
+    }
+
+    // the session a transfer was initiated with, if any. Spends belong to the session's tenant
+    pub async fn get_transaction_session(
+        pool: &Pool<Postgres>,
+        tenant_id: i32,
+        transaction_id: String,
+    ) -> Result<Option<String>, DatabaseError> {
+        let query = query!(
+            "SELECT sp.session_id FROM session_key_spends sp \
+            JOIN session_keys s ON s.session_id = sp.session_id \
+            WHERE s.tenant_id = $1 AND sp.transaction_id = $2",
+            tenant_id,
+            transaction_id
+        );
+        let result = query.fetch_optional(pool).await;
+        match result {
+            Ok(row) => Ok(row.map(|row| row.session_id)),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to get session for transaction: {}, err: {:?}",
+                transaction_id, err
+            ))),
+        }
+    }
+
+    // settles a live reservation, returns false when there is none to settle
+    pub async fn hold_spend(
+        pool: &Pool<Postgres>,
+        tenant_id: i32,
+        transaction_id: String,
+    ) -> Result<bool, DatabaseError> {
+        let query = query!(
+            "UPDATE session_key_spends sp SET reserved_until = NULL, updated_at = now() \
+            FROM session_keys s WHERE s.session_id = sp.session_id AND s.tenant_id = $1 \
+                AND sp.transaction_id = $2 AND sp.reserved_until > now()",
+            tenant_id,
+            transaction_id
+        );
+        let result = query.execute(pool).await;
+        match result {
+            Ok(result) => Ok(result.rows_affected() > 0),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to hold session spend: {}, err: {:?}",
+                transaction_id, err
+            ))),
+        }
+    }
+
+    pub async fn is_spend_expired(
+        pool: &Pool<Postgres>,
+        tenant_id: i32,
+        transaction_id: String,
+    ) -> Result<bool, DatabaseError> {
+        let query = query!(
+            "SELECT EXISTS(SELECT 1 FROM session_key_spends sp \
+                JOIN session_keys s ON s.session_id = sp.session_id \
+                WHERE s.tenant_id = $1 AND sp.transaction_id = $2 \
+                    AND sp.reserved_until <= now()) as \"expired!\"",
+            tenant_id,
+            transaction_id
+        );
+        let result = query.fetch_one(pool).await;
+        match result {
+            Ok(row) => Ok(row.expired),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to get session spend: {}, err: {:?}",
+                transaction_id, err
+            ))),
+        }
+    }
+
+    pub async fn update_spend(
+        pool: &Pool<Postgres>,
+        tenant_id: i32,
+        transaction_id: String,
+        amount: BigDecimal,
+    ) -> Result<(), DatabaseError> {
+        let query = query!(
+            "UPDATE session_key_spends sp SET amount = $1, reserved_until = NULL, \
+                updated_at = now() FROM session_keys s \
+            WHERE s.session_id = sp.session_id AND s.tenant_id = $2 AND sp.transaction_id = $3",
+            amount,
+            tenant_id,
+            transaction_id
+        );
+        let result = query.execute(pool).await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to update session spend: {}, err: {:?}",
+                transaction_id, err
+            ))),
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct SessionKey {
+    pub id: i32,
+    pub session_id: String,
+    pub wallet_address: String,
+    pub session_key: String,
+    pub valid_after: DateTime<Utc>,
+    pub valid_until: DateTime<Utc>,
+    pub allowed_targets: Vec<String>,
+    pub currencies: Vec<String>,
+    pub tokens: Vec<String>,
+    pub spend_limits: Vec<BigDecimal>,
+    pub permission_hash: String,
+    pub owner_signature: Option<String>,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub tenant_id: i32,
+}
@@ -0,0 +1,234 @@
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+use sqlx::{query, query_as, Executor, Pool, Postgres};
+
+use crate::errors::DatabaseError;
+
+#[derive(Clone)]
+pub struct SponsorshipDao;
+
+impl SponsorshipDao {
+    // serializes reservations against the tenant's budgets on a chain, released on commit
+    pub async fn lock_budget<'a, E>(executor: E, scope: &BudgetScope) -> Result<(), DatabaseError>
+    where
+        E: Executor<'a, Database = Postgres>,
+    {
+        let query = query!(
+            "SELECT true as \"locked!\" FROM (SELECT pg_advisory_xact_lock($1, hashtext($2))) lock",
+            scope.tenant_id,
+            scope.chain
+        );
+        let result = query.fetch_one(executor).await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to lock budget: {}, err: {:?}",
+                scope.chain, err
+            ))),
+        }
+    }
+
+    // the gas cost stays reserved for reservation_secs unless the sponsorship is held or settled,
+    // an expired reservation no longer counts as spent
+    pub async fn create_sponsorship<'a, E>(
+        executor: E,
+        scope: &BudgetScope,
+        transaction_id: String,
+        user_address: String,
+        gas_cost: BigDecimal,
+        reservation_secs: i32,
+    ) -> Result<(), DatabaseError>
+    where
+        E: Executor<'a, Database = Postgres>,
+    {
+        let query = query!(
+            "INSERT INTO sponsorships (transaction_id, user_address, chain, gas_cost, tenant_id, \
+                paymaster_client_id, reserved_until) \
+                VALUES ($1, $2, $3, $4, $5, $6, now() + make_interval(secs => $7))",
+            transaction_id,
+            user_address,
+            scope.chain,
+            gas_cost,
+            scope.tenant_id,
+            scope.paymaster_client_id,
+            reservation_secs as f64
+        );
+        let result = query.execute(executor).await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to create sponsorship: {}, err: {:?}",
+                transaction_id, err
+            ))),
+        }
+    }
+
+    pub async fn get_spent_gas<'a, E>(
+        executor: E,
+        scope: &BudgetScope,
+        user_address: String,
+    ) -> Result<SpentGas, DatabaseError>
+    where
+        E: Executor<'a, Database = Postgres>,
+    {
+        let query = query!(
+            "SELECT \
+                COALESCE(SUM(gas_cost) FILTER (WHERE user_address = $2 \
+                    AND created_at >= date_trunc('day', now())), 0) as \"user_daily!\", \
+                COALESCE(SUM(gas_cost) FILTER (WHERE user_address = $2 \
+                    AND created_at >= date_trunc('month', now())), 0) as \"user_monthly!\", \
+                COALESCE(SUM(gas_cost), 0) as \"chain!\" \
+            FROM sponsorships \
+            WHERE tenant_id = $1 AND chain = $3 AND paymaster_client_id IS NOT DISTINCT FROM $4 \
+                AND (reserved_until IS NULL OR reserved_until > now())",
+            scope.tenant_id,
+            user_address,
+            scope.chain,
+            scope.paymaster_client_id
+        );
+        let result = query.fetch_one(executor).await;
+        match result {
+            Ok(row) => Ok(SpentGas {
+                user_daily: row.user_daily,
+                user_monthly: row.user_monthly,
+                chain: row.chain,
+            }),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to get spent gas: {}, err: {:?}",
+                user_address, err
+            ))),
+        }
+    }
+
+    // settles a live reservation, returns false when there is none to settle
+    pub async fn hold_sponsorship<'a, E>(
+        executor: E,
+        transaction_id: String,
+    ) -> Result<bool, DatabaseError>
+    where
+        E: Executor<'a, Database = Postgres>,
+    {
+        let query = query!(
+            "UPDATE sponsorships SET reserved_until = NULL, updated_at = now() \
+            WHERE transaction_id = $1 AND reserved_until > now()",
+            transaction_id
+        );
+        let result = query.execute(executor).await;
+        match result {
+            Ok(result) => Ok(result.rows_affected() > 0),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to hold sponsorship: {}, err: {:?}",
+                transaction_id, err
+            ))),
+        }
+    }
+
+    pub async fn is_reservation_expired<'a, E>(
+        executor: E,
+        transaction_id: String,
+    ) -> Result<bool, DatabaseError>
+    where
+        E: Executor<'a, Database = Postgres>,
+    {
+        let query = query!(
+            "SELECT EXISTS(SELECT 1 FROM sponsorships \
+                WHERE transaction_id = $1 AND reserved_until <= now()) as \"expired!\"",
+            transaction_id
+        );
+        let result = query.fetch_one(executor).await;
+        match result {
+            Ok(row) => Ok(row.expired),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to get sponsorship: {}, err: {:?}",
+                transaction_id, err
+            ))),
+        }
+    }
+
+    pub async fn update_gas_cost<'a, E>(
+        executor: E,
+        transaction_id: String,
+        gas_cost: BigDecimal,
+    ) -> Result<(), DatabaseError>
+    where
+        E: Executor<'a, Database = Postgres>,
+    {
+        let query = query!(
+            "UPDATE sponsorships SET gas_cost = $1, reserved_until = NULL, updated_at = now() \
+            WHERE transaction_id = $2",
+            gas_cost,
+            transaction_id
+        );
+        let result = query.execute(executor).await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to update sponsorship gas cost: {}, err: {:?}",
+                transaction_id, err
+            ))),
+        }
+    }
+
+    pub async fn get_unreconciled_sponsorships(
+        pool: &Pool<Postgres>,
+        chain: String,
+    ) -> Result<Vec<UnreconciledSponsorship>, DatabaseError> {
+        let query = query_as!(
+            UnreconciledSponsorship,
+            "SELECT transaction_id, created_at FROM sponsorships \
+            WHERE chain = $1 AND paymaster_client_id IS NOT NULL AND reconciled_at IS NULL \
+            ORDER BY id",
+            chain
+        );
+        let result = query.fetch_all(pool).await;
+        match result {
+            Ok(sponsorships) => Ok(sponsorships),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to get unreconciled sponsorships: {}, err: {:?}",
+                chain, err
+            ))),
+        }
+    }
+
+    pub async fn reconcile_sponsorship(
+        pool: &Pool<Postgres>,
+        transaction_id: String,
+        gas_cost: BigDecimal,
+    ) -> Result<(), DatabaseError> {
+        let query = query!(
+            "UPDATE sponsorships SET gas_cost = $1, reserved_until = NULL, reconciled_at = now(), \
+                updated_at = now() WHERE transaction_id = $2",
+            gas_cost,
+            transaction_id
+        );
+        let result = query.execute(pool).await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to reconcile sponsorship: {}, err: {:?}",
+                transaction_id, err
+            ))),
+        }
+    }
+}
+
+// the budgets a sponsorship counts against, paymaster clients are budgeted apart from the tenant
+#[derive(Clone, Debug)]
+pub struct BudgetScope {
+    pub tenant_id: i32,
+    pub paymaster_client_id: Option<i32>,
+    pub chain: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct UnreconciledSponsorship {
+    pub transaction_id: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SpentGas {
+    pub user_daily: BigDecimal,
+    pub user_monthly: BigDecimal,
+    pub chain: BigDecimal,
+}
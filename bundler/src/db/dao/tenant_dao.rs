@@ -0,0 +1,197 @@
+use chrono::{DateTime, Utc};
+use sqlx::types::JsonValue;
+use sqlx::{query_as, Error, Executor, Pool, Postgres};
+
+use crate::errors::DatabaseError;
+use crate::models::config::settings::{Auth, SponsorshipPolicy};
+use crate::CONFIG;
+
+#[derive(Clone)]
+pub struct TenantDao;
+
+impl TenantDao {
+    pub async fn create_tenant<'a, E>(executor: E, tenant: Tenant) -> Result<Tenant, DatabaseError>
+    where
+        E: Executor<'a, Database = Postgres>,
+    {
+        let query = query_as!(
+            Tenant,
+            "INSERT INTO tenants (name, api_key_hash, auth, transaction_id_prefix, \
+            sponsorship_policy, supported_tokens, webhook_url, webhook_secret) VALUES \
+            ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING *",
+            tenant.name,
+            tenant.api_key_hash,
+            tenant.auth,
+            tenant.transaction_id_prefix,
+            tenant.sponsorship_policy,
+            &tenant.supported_tokens,
+            tenant.webhook_url,
+            tenant.webhook_secret
+        );
+        let result = query.fetch_one(executor).await;
+        match result {
+            Ok(tenant) => Ok(tenant),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to create tenant: {}, err: {:?}",
+                tenant.name, err
+            ))),
+        }
+    }
+
+    // the api key is kept, it is only set when the tenant is created. The webhook secret is kept
+    // unless a new one is passed
+    pub async fn update_tenant<'a, E>(
+        executor: E,
+        tenant: Tenant,
+        webhook_secret: Option<String>,
+    ) -> Result<Tenant, DatabaseError>
+    where
+        E: Executor<'a, Database = Postgres>,
+    {
+        let query = query_as!(
+            Tenant,
+            "UPDATE tenants SET auth = $1, transaction_id_prefix = $2, sponsorship_policy = $3, \
+            supported_tokens = $4, webhook_url = $5, is_active = $6, \
+            webhook_secret = COALESCE($7, webhook_secret), updated_at = now() \
+            WHERE id = $8 RETURNING *",
+            tenant.auth,
+            tenant.transaction_id_prefix,
+            tenant.sponsorship_policy,
+            &tenant.supported_tokens,
+            tenant.webhook_url,
+            tenant.is_active,
+            webhook_secret,
+            tenant.id
+        );
+        let result = query.fetch_one(executor).await;
+        match result {
+            Ok(tenant) => Ok(tenant),
+            Err(Error::RowNotFound) => Err(DatabaseError::NotFound),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to update tenant: {}, err: {:?}",
+                tenant.id, err
+            ))),
+        }
+    }
+
+    pub async fn get_tenant(pool: &Pool<Postgres>, id: i32) -> Result<Tenant, DatabaseError> {
+        let query = query_as!(Tenant, "SELECT * FROM tenants WHERE id = $1", id);
+        let result = query.fetch_one(pool).await;
+        match result {
+            Ok(tenant) => Ok(tenant),
+            Err(Error::RowNotFound) => Err(DatabaseError::NotFound),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to get tenant: {}, err: {:?}",
+                id, err
+            ))),
+        }
+    }
+
+    pub async fn get_active_tenant(
+        pool: &Pool<Postgres>,
+        api_key_hash: String,
+    ) -> Result<Option<Tenant>, DatabaseError> {
+        let query = query_as!(
+            Tenant,
+            "SELECT * FROM tenants WHERE api_key_hash = $1 AND is_active = true",
+            api_key_hash
+        );
+        let result = query.fetch_optional(pool).await;
+        match result {
+            Ok(tenant) => Ok(tenant),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to get tenant, err: {:?}",
+                err
+            ))),
+        }
+    }
+
+    pub async fn get_tenant_by_transaction_id(
+        pool: &Pool<Postgres>,
+        transaction_id: String,
+    ) -> Result<Tenant, DatabaseError> {
+        let query = query_as!(
+            Tenant,
+            "SELECT t.* FROM tenants t JOIN user_transactions ut ON ut.tenant_id = t.id \
+            WHERE ut.transaction_id = $1",
+            transaction_id
+        );
+        let result = query.fetch_one(pool).await;
+        match result {
+            Ok(tenant) => Ok(tenant),
+            Err(Error::RowNotFound) => Err(DatabaseError::NotFound),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to get tenant for transaction: {}, err: {:?}",
+                transaction_id, err
+            ))),
+        }
+    }
+
+    pub async fn get_tenants<'a, E>(executor: E) -> Result<Vec<Tenant>, DatabaseError>
+    where
+        E: Executor<'a, Database = Postgres>,
+    {
+        let query = query_as!(Tenant, "SELECT * FROM tenants ORDER BY id");
+        let result = query.fetch_all(executor).await;
+        match result {
+            Ok(tenants) => Ok(tenants),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to get tenants, err: {:?}",
+                err
+            ))),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Tenant {
+    pub id: i32,
+    pub name: String,
+    pub api_key_hash: Option<String>,
+    pub auth: Option<JsonValue>,
+    pub transaction_id_prefix: Option<String>,
+    pub sponsorship_policy: Option<JsonValue>,
+    pub supported_tokens: Vec<String>,
+    pub webhook_url: Option<String>,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub webhook_secret: String,
+}
+
+// unset settings are the global ones
+impl Tenant {
+    pub fn get_auth(&self) -> Result<Auth, String> {
+        match &self.auth {
+            Some(auth) => serde_json::from_value(auth.clone())
+                .map_err(|err| format!("Invalid auth for tenant {}: {:?}", self.id, err)),
+            None => Ok(CONFIG.auth.clone()),
+        }
+    }
+
+    pub fn get_transaction_id_prefix(&self) -> &str {
+        self.transaction_id_prefix
+            .as_deref()
+            .unwrap_or(&CONFIG.run_config.transaction_id_prefix)
+    }
+
+    pub fn get_sponsorship_policy(&self) -> Result<SponsorshipPolicy, String> {
+        match &self.sponsorship_policy {
+            Some(policy) => serde_json::from_value(policy.clone()).map_err(|err| {
+                format!(
+                    "Invalid sponsorship policy for tenant {}: {:?}",
+                    self.id, err
+                )
+            }),
+            None => Ok(CONFIG.get_chain().sponsorship_policy.clone()),
+        }
+    }
+
+    pub fn supports_token(&self, symbol: &str) -> bool {
+        self.supported_tokens.is_empty()
+            || self
+                .supported_tokens
+                .iter()
+                .any(|token| token.eq_ignore_ascii_case(symbol))
+    }
+}
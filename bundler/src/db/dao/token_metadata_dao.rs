@@ -1,5 +1,5 @@
 use chrono::{DateTime, Utc};
-use sqlx::{query, query_as, Error, Pool, Postgres};
+use sqlx::{query, query_as, Error, Executor, Pool, Postgres};
 
 use crate::errors::DatabaseError;
 
@@ -7,8 +7,8 @@ use crate::errors::DatabaseError;
 pub struct TokenMetadataDao;
 
 impl TokenMetadataDao {
-    pub async fn add_metadata(
-        pool: &Pool<Postgres>,
+    pub async fn add_metadata<'a, E>(
+        executor: E,
         chain: String,
         currency: String,
         address: String,
@@ -18,7 +18,10 @@ impl TokenMetadataDao {
         chain_id: i32,
         chain_name: String,
         token_image_url: String,
-    ) -> Result<(), DatabaseError> {
+    ) -> Result<(), DatabaseError>
+    where
+        E: Executor<'a, Database = Postgres>,
+    {
         let query = query!(
             "INSERT INTO token_metadata \
             (chain, symbol, contract_address, exponent, token_type, name, chain_id, chain_name,\
@@ -36,7 +39,7 @@ impl TokenMetadataDao {
             chain_name,
             token_image_url
         );
-        let result = query.execute(pool).await;
+        let result = query.execute(executor).await;
         match result {
             Ok(_) => Ok(()),
             Err(err) => Err(DatabaseError::ServerError(format!(
@@ -14,6 +14,7 @@ pub struct TransactionDao;
 impl TransactionDao {
     pub async fn list_transactions(
         pool: &Pool<Postgres>,
+        tenant_id: i32,
         page_size: i64,
         id: i32,
         user_wallet: String,
@@ -22,10 +23,11 @@ impl TransactionDao {
             UserTransaction,
             "SELECT t1.id, t1.user_address, t1.transaction_id, t1.from_address, t1.to_address, \
             t1.amount, t1.currency, t1.type as transaction_type, t1.status, t1.metadata, \
-            t1.created_at, t1.updated_at, t2.exponent from user_transactions t1 left join \
-            token_metadata t2 on lower(t1.currency) = lower(t2.symbol) \
+            t1.created_at, t1.updated_at, t2.exponent, t1.tenant_id from user_transactions t1 \
+            left join token_metadata t2 on lower(t1.currency) = lower(t2.symbol) \
             and lower(t1.metadata ->> 'chain') = lower(t2.chain) \
-            where user_address = $1 and id < $2 order by id desc limit $3",
+            where tenant_id = $1 and user_address = $2 and id < $3 order by id desc limit $4",
+            tenant_id,
             user_wallet,
             id,
             page_size
@@ -56,8 +58,8 @@ impl TransactionDao {
         }
         let query = query!(
             "INSERT INTO user_transactions (user_address, transaction_id, from_address,\
-                to_address, amount, currency, type, status, metadata) VALUES \
-                ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+                to_address, amount, currency, type, status, metadata, tenant_id) VALUES \
+                ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
             txn.user_address.clone(),
             txn.transaction_id.clone(),
             txn.from_address.clone(),
@@ -66,7 +68,8 @@ impl TransactionDao {
             txn.currency.clone(),
             txn.transaction_type.clone(),
             txn.status.clone(),
-            metadata
+            metadata,
+            txn.tenant_id
         );
         let result = query.execute(pool).await;
         match result {
@@ -80,6 +83,7 @@ impl TransactionDao {
 
     pub async fn get_transaction_by_id(
         pool: &Pool<Postgres>,
+        tenant_id: i32,
         txn_id: String,
         user_wallet_address: String,
     ) -> Result<UserTransaction, DatabaseError> {
@@ -87,11 +91,12 @@ impl TransactionDao {
             UserTransaction,
             "SELECT t1.id, t1.user_address, t1.transaction_id, t1.from_address, \
             t1.to_address, t1.amount, t1.currency, t1.type as transaction_type, \
-            t1.status, t1.metadata, t1.created_at, t1.updated_at, t2.exponent \
+            t1.status, t1.metadata, t1.created_at, t1.updated_at, t2.exponent, t1.tenant_id \
             from user_transactions t1 left join token_metadata t2 \
             on lower(t1.currency) = lower(t2.symbol) and \
             lower(t1.metadata ->> 'chain') = lower(t2.chain) \
-            where transaction_id = $1 and user_address = $2",
+            where tenant_id = $1 and transaction_id = $2 and user_address = $3",
+            tenant_id,
             txn_id,
             user_wallet_address,
         );
@@ -110,6 +115,7 @@ impl TransactionDao {
 
     pub async fn update_user_transaction(
         pool: &Pool<Postgres>,
+        tenant_id: i32,
         txn_id: String,
         txn_hash: Option<String>,
         status: String,
@@ -118,8 +124,10 @@ impl TransactionDao {
         match txn_hash {
             None => {
                 query = query!(
-                    "UPDATE user_transactions set status = $1 where transaction_id = $2",
+                    "UPDATE user_transactions set status = $1 \
+                    where tenant_id = $2 and transaction_id = $3",
                     status,
+                    tenant_id,
                     txn_id,
                 );
             }
@@ -127,9 +135,10 @@ impl TransactionDao {
                 query = query!(
                     "UPDATE user_transactions \
                     set status = $1, metadata = jsonb_set(metadata, '{transaction_hash}', $2) \
-                    where transaction_id = $3",
+                    where tenant_id = $3 and transaction_id = $4",
                     status,
                     Value::String(value),
+                    tenant_id,
                     txn_id,
                 );
             }
@@ -143,6 +152,30 @@ impl TransactionDao {
             ))),
         }
     }
+
+    pub async fn update_erc20_gas(
+        pool: &Pool<Postgres>,
+        tenant_id: i32,
+        txn_id: String,
+        value: u64,
+    ) -> Result<(), DatabaseError> {
+        let query = query!(
+            "UPDATE user_transactions \
+            set metadata = jsonb_set(metadata, '{gas_erc20,value}', $1) \
+            where tenant_id = $2 and transaction_id = $3",
+            Value::from(value),
+            tenant_id,
+            txn_id,
+        );
+        let result = query.execute(pool).await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to update erc20 gas: {}, err: {:?}",
+                txn_id, err
+            ))),
+        }
+    }
 }
 
 #[derive(Clone, Default)]
@@ -160,6 +193,7 @@ pub struct UserTransaction {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub exponent: i32,
+    pub tenant_id: i32,
 }
 
 #[derive(Default, Serialize, Deserialize, Clone)]
@@ -200,6 +234,11 @@ impl UserTransaction {
         Self::default()
     }
 
+    pub fn tenant_id(&mut self, tenant_id: i32) -> &mut UserTransaction {
+        self.tenant_id = tenant_id;
+        self
+    }
+
     pub fn user_address(&mut self, user_address: String) -> &mut UserTransaction {
         self.user_address = user_address;
         self
@@ -1,7 +1,7 @@
 use chrono::{DateTime, Utc};
 use serde_json::Value;
 use sqlx::types::JsonValue;
-use sqlx::{query, query_as, Pool, Postgres};
+use sqlx::{query, query_as, Executor, Pool, Postgres};
 use std::default::Default;
 
 use crate::errors::DatabaseError;
@@ -13,6 +13,7 @@ pub struct UserOperationDao;
 impl UserOperationDao {
     pub async fn create_user_operation(
         pool: &Pool<Postgres>,
+        tenant_id: i32,
         transaction_id: String,
         user_operation: UserOperation,
         status: String,
@@ -28,11 +29,12 @@ impl UserOperationDao {
             }
         }
         let query = query!(
-            "INSERT INTO user_operations (transaction_id, user_operation, status) VALUES \
-                ($1, $2, $3)",
+            "INSERT INTO user_operations (transaction_id, user_operation, status, tenant_id) \
+                VALUES ($1, $2, $3, $4)",
             transaction_id,
             metadata,
             status,
+            tenant_id,
         );
         let result = query.execute(pool).await;
         match result {
@@ -46,11 +48,13 @@ impl UserOperationDao {
 
     pub async fn get_user_operation(
         pool: &Pool<Postgres>,
+        tenant_id: i32,
         transaction_id: String,
     ) -> Result<UserOperationRecord, DatabaseError> {
         let query = query_as!(
             UserOperationRecord,
-            "SELECT * from user_operations where transaction_id = $1",
+            "SELECT * from user_operations where tenant_id = $1 and transaction_id = $2",
+            tenant_id,
             transaction_id
         );
         let result = query.fetch_one(pool).await;
@@ -63,14 +67,74 @@ impl UserOperationDao {
         }
     }
 
+    // the rows stay locked until the executor's transaction ends, so they can't be submitted
+    // while the caller rewrites them
+    pub async fn get_user_operations_by_status<'a, E>(
+        executor: E,
+        tenant_id: i32,
+        status: String,
+    ) -> Result<Vec<UserOperationRecord>, DatabaseError>
+    where
+        E: Executor<'a, Database = Postgres>,
+    {
+        let query = query_as!(
+            UserOperationRecord,
+            "SELECT * from user_operations where tenant_id = $1 and status = $2 FOR UPDATE",
+            tenant_id,
+            status
+        );
+        let result = query.fetch_all(executor).await;
+        match result {
+            Ok(rows) => Ok(rows),
+            Err(error) => Err(DatabaseError::ServerError(format!(
+                "Failed to fetch user operations: {:?}",
+                error
+            ))),
+        }
+    }
+
+    pub async fn update_user_operation<'a, E>(
+        executor: E,
+        tenant_id: i32,
+        transaction_id: String,
+        user_operation: UserOperation,
+    ) -> Result<(), DatabaseError>
+    where
+        E: Executor<'a, Database = Postgres>,
+    {
+        let metadata = serde_json::to_value(user_operation).map_err(|err| {
+            DatabaseError::ServerError(format!(
+                "UserOperation conversion failed: {}, err: {:?}",
+                transaction_id, err
+            ))
+        })?;
+        let query = query!(
+            "UPDATE user_operations SET user_operation = $1, updated_at = now() \
+            where tenant_id = $2 and transaction_id = $3",
+            metadata,
+            tenant_id,
+            transaction_id
+        );
+        let result = query.execute(executor).await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to update user operation: {}, err: {:?}",
+                transaction_id, err
+            ))),
+        }
+    }
+
     pub async fn update_user_operation_status(
         pool: &Pool<Postgres>,
+        tenant_id: i32,
         transaction_id: String,
         status: String,
     ) -> Result<(), DatabaseError> {
         let query = query!(
-            "UPDATE user_operations SET status = $1 where transaction_id = $2",
+            "UPDATE user_operations SET status = $1 where tenant_id = $2 and transaction_id = $3",
             status,
+            tenant_id,
             transaction_id
         );
         let result = query.execute(pool).await;
@@ -91,6 +155,7 @@ pub struct UserOperationRecord {
     pub status: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub tenant_id: i32,
 }
 
 impl From<JsonValue> for UserOperation {
@@ -98,3 +163,45 @@ impl From<JsonValue> for UserOperation {
         serde_json::from_value(json).unwrap()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[sqlx::test]
+    async fn operations_read_by_status_stay_locked_until_commit(pool: Pool<Postgres>) {
+        UserOperationDao::create_user_operation(
+            &pool,
+            1,
+            String::from("toad_a"),
+            UserOperation::default(),
+            String::from("INITIATED"),
+        )
+        .await
+        .unwrap();
+
+        let mut txn = pool.begin().await.unwrap();
+        let records = UserOperationDao::get_user_operations_by_status(
+            &mut *txn,
+            1,
+            String::from("INITIATED"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(records.len(), 1);
+        let locked = sqlx::query(
+            "SELECT * FROM user_operations WHERE transaction_id = 'toad_a' FOR UPDATE NOWAIT",
+        )
+        .execute(&pool)
+        .await;
+        assert!(locked.is_err());
+
+        txn.commit().await.unwrap();
+        sqlx::query(
+            "SELECT * FROM user_operations WHERE transaction_id = 'toad_a' FOR UPDATE NOWAIT",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+}
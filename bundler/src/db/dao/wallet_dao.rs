@@ -1,8 +1,11 @@
 use bigdecimal::BigDecimal;
+use ethers::types::U256;
 use log::error;
-use sqlx::{query, query_as, Error, Pool, Postgres};
+use sqlx::{query, query_as, Error, Executor, Pool, Postgres};
 
+use crate::constants::Constants;
 use crate::errors::DatabaseError;
+use crate::identity::Identity;
 
 #[derive(Clone)]
 pub struct WalletDao;
@@ -10,30 +13,76 @@ pub struct WalletDao;
 impl WalletDao {
     pub async fn update_wallet_deployed(
         pool: &Pool<Postgres>,
-        user_id: String,
+        tenant_id: i32,
+        wallet_address: String,
+        deployed: bool,
     ) -> Result<(), DatabaseError> {
         let query = query!(
-            "UPDATE users SET deployed = $1 WHERE external_user_id = $2",
-            true,
-            user_id
+            "UPDATE users SET deployed = $1 WHERE tenant_id = $2 AND wallet_address = $3",
+            deployed,
+            tenant_id,
+            wallet_address.to_lowercase()
         );
         let result = query.execute(pool).await;
         match result {
             Ok(_) => Ok(()),
             Err(err) => Err(DatabaseError::ServerError(format!(
-                "Failed to update deployed status for user: {}, err: {:?}",
-                user_id, err
+                "Failed to update deployed status for wallet: {}, err: {:?}",
+                wallet_address, err
+            ))),
+        }
+    }
+
+    pub async fn update_owner_address(
+        pool: &Pool<Postgres>,
+        tenant_id: i32,
+        wallet_address: String,
+        owner_address: String,
+    ) -> Result<(), DatabaseError> {
+        let query = query!(
+            "UPDATE users SET owner_address = $1 WHERE tenant_id = $2 AND wallet_address = $3",
+            owner_address.to_lowercase(),
+            tenant_id,
+            wallet_address.to_lowercase()
+        );
+        let result = query.execute(pool).await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to update owner for wallet: {}, err: {:?}",
+                wallet_address, err
+            ))),
+        }
+    }
+
+    pub async fn get_wallets(
+        pool: &Pool<Postgres>,
+        tenant_id: i32,
+    ) -> Result<Vec<WalletStatus>, DatabaseError> {
+        let query = query_as!(
+            WalletStatus,
+            "SELECT wallet_address, deployed FROM users WHERE tenant_id = $1 AND wallet_address <> ''",
+            tenant_id
+        );
+        let result = query.fetch_all(pool).await;
+        match result {
+            Ok(wallets) => Ok(wallets),
+            Err(err) => Err(DatabaseError::ServerError(format!(
+                "Failed to get wallets for tenant: {}, err: {:?}",
+                tenant_id, err
             ))),
         }
     }
 
     pub async fn get_wallet_by_external_user_id(
         pool: &Pool<Postgres>,
+        tenant_id: i32,
         external_user_id: String,
     ) -> Option<User> {
         let query = query_as!(
             User,
-            "SELECT * from users where external_user_id = $1",
+            "SELECT * from users where tenant_id = $1 and external_user_id = $2",
+            tenant_id,
             external_user_id
         );
         let result: Result<Option<User>, Error> = query.fetch_optional(pool).await;
@@ -49,28 +98,38 @@ impl WalletDao {
         };
     }
 
-    pub async fn create_wallet(
-        pool: &Pool<Postgres>,
+    pub async fn create_wallet<'a, E>(
+        executor: E,
+        tenant_id: i32,
         user_id: String,
         name: String,
         wallet_address: String,
         owner_address: String,
         external_user_id: String,
         salt: BigDecimal,
+        salt_version: i32,
+        owner_type: &str,
         deployed: bool,
-    ) -> Result<(), DatabaseError> {
+    ) -> Result<(), DatabaseError>
+    where
+        E: Executor<'a, Database = Postgres>,
+    {
         let query = query!(
-            "INSERT INTO users (email, name, wallet_address, owner_address, salt, external_user_id, \
-            deployed) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+            "INSERT INTO users (email, name, wallet_address, owner_address, salt, salt_version, \
+            owner_type, external_user_id, deployed, tenant_id) VALUES \
+            ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
             user_id,
             name,
             wallet_address.to_lowercase(),
             owner_address.to_lowercase(),
             salt,
+            salt_version,
+            owner_type,
             external_user_id,
-            deployed
+            deployed,
+            tenant_id
         );
-        let result = query.execute(pool).await;
+        let result = query.execute(executor).await;
         match result {
             Ok(_) => Ok(()),
             Err(err) => Err(DatabaseError::ServerError(format!(
@@ -90,19 +149,110 @@ pub struct User {
     pub owner_address: String,
     pub name: String,
     pub external_user_id: String,
+    pub salt_version: i32,
+    pub owner_type: String,
+    pub tenant_id: i32,
+}
+
+impl User {
+    // every salt version stores the uint256 the factory was called with, only versions newer
+    // than this build knows about are rejected
+    pub fn get_salt(&self) -> Option<U256> {
+        if self.salt_version > Constants::ACCOUNT_SALT_VERSION {
+            return None;
+        }
+        U256::from_dec_str(&self.salt.with_scale(0).to_string()).ok()
+    }
+
+    // owner_address holds the P-256 public key instead of an address
+    pub fn is_passkey_owner(&self) -> bool {
+        self.owner_type == Constants::PASSKEY_OWNER
+    }
 }
 
-// mapper to convert from firebase user to db user
-impl From<rs_firebase_admin_sdk::auth::User> for User {
-    fn from(user: rs_firebase_admin_sdk::auth::User) -> Self {
+#[derive(Clone, Debug)]
+pub struct WalletStatus {
+    pub wallet_address: String,
+    pub deployed: bool,
+}
+
+// mapper to convert from an authenticated identity to db user
+impl From<Identity> for User {
+    fn from(identity: Identity) -> Self {
         User {
-            email: user.email.unwrap(),
+            email: identity.email,
             wallet_address: Default::default(),
             salt: Default::default(),
             deployed: false,
             owner_address: Default::default(),
-            name: user.display_name.unwrap(),
-            external_user_id: user.uid,
+            name: identity.name,
+            external_user_id: identity.external_user_id,
+            salt_version: Default::default(),
+            owner_type: Default::default(),
+            tenant_id: Default::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::dao::{Tenant, TenantDao};
+
+    const WALLET: &str = "0x0000000000000000000000000000000000000001";
+
+    #[sqlx::test]
+    async fn wallet_updates_stay_within_the_tenant(pool: Pool<Postgres>) {
+        let other = TenantDao::create_tenant(
+            &pool,
+            Tenant {
+                name: String::from("other"),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        for tenant_id in [Constants::DEFAULT_TENANT_ID, other.id] {
+            WalletDao::create_wallet(
+                &pool,
+                tenant_id,
+                String::from("user@example.com"),
+                String::from("user"),
+                String::from(WALLET),
+                String::from(WALLET),
+                String::from("user"),
+                BigDecimal::from(0),
+                Constants::ACCOUNT_SALT_VERSION,
+                Constants::EOA_OWNER,
+                false,
+            )
+            .await
+            .unwrap();
         }
+
+        WalletDao::update_wallet_deployed(&pool, other.id, String::from(WALLET), true)
+            .await
+            .unwrap();
+        WalletDao::update_owner_address(
+            &pool,
+            other.id,
+            String::from(WALLET),
+            String::from("0x0000000000000000000000000000000000000002"),
+        )
+        .await
+        .unwrap();
+
+        let default_user = WalletDao::get_wallet_by_external_user_id(
+            &pool,
+            Constants::DEFAULT_TENANT_ID,
+            String::from("user"),
+        )
+        .await
+        .unwrap();
+        assert!(!default_user.deployed);
+        assert_eq!(default_user.owner_address, WALLET);
+        let other_wallets = WalletDao::get_wallets(&pool, other.id).await.unwrap();
+        assert_eq!(other_wallets.len(), 1);
+        assert!(other_wallets[0].deployed);
     }
 }
@@ -8,6 +8,7 @@ use crate::errors::{DatabaseError, ErrorResponse, ProviderError};
 #[derive(Debug, Display)]
 pub enum AdminError {
     Unauthorized,
+    Forbidden,
     InvalidCurrency,
     ValidationError(String),
     Database(String),
@@ -18,6 +19,7 @@ impl ResponseError for AdminError {
     fn status_code(&self) -> StatusCode {
         match self {
             AdminError::Unauthorized => StatusCode::UNAUTHORIZED,
+            AdminError::Forbidden => StatusCode::FORBIDDEN,
             AdminError::InvalidCurrency => StatusCode::BAD_REQUEST,
             AdminError::ValidationError(_) => StatusCode::BAD_REQUEST,
             AdminError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
@@ -29,6 +31,9 @@ impl ResponseError for AdminError {
         match self {
             AdminError::Unauthorized => HttpResponse::Unauthorized()
                 .json(ErrorResponse::from(String::from("Invalid credentials"))),
+            AdminError::Forbidden => HttpResponse::Forbidden().json(ErrorResponse::from(
+                String::from("Missing role for this action"),
+            )),
             AdminError::InvalidCurrency => HttpResponse::BadRequest()
                 .json(ErrorResponse::from(String::from("Invalid currency"))),
             AdminError::ValidationError(error) => {
@@ -16,6 +16,21 @@ pub enum DatabaseError {
 #[derive(Debug)]
 pub struct ProviderError(pub String);
 
+#[derive(Debug)]
+pub enum SponsorshipError {
+    Refused(String),
+    Database(String),
+}
+
+impl From<DatabaseError> for SponsorshipError {
+    fn from(error: DatabaseError) -> Self {
+        match error {
+            DatabaseError::NotFound => SponsorshipError::Database(String::from("Record not found")),
+            DatabaseError::ServerError(err) => SponsorshipError::Database(err),
+        }
+    }
+}
+
 #[derive(Serialize)]
 pub struct ErrorResponse {
     pub data: Value,
@@ -0,0 +1,54 @@
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, ResponseError};
+use derive_more::Display;
+use log::error;
+
+use crate::errors::{ErrorResponse, ProviderError};
+
+#[derive(Debug, Display)]
+pub enum MessageError {
+    NotFound,
+    InvalidRequest(String),
+    InvalidSignature,
+    SignatureMismatch,
+    Provider(String),
+}
+
+impl ResponseError for MessageError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            MessageError::NotFound => StatusCode::NOT_FOUND,
+            MessageError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+            MessageError::InvalidSignature => StatusCode::UNAUTHORIZED,
+            MessageError::SignatureMismatch => StatusCode::UNAUTHORIZED,
+            MessageError::Provider(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        match self {
+            MessageError::NotFound => {
+                HttpResponse::NotFound().json(ErrorResponse::from(String::from("User not found")))
+            }
+            MessageError::InvalidRequest(reason) => {
+                HttpResponse::BadRequest().json(ErrorResponse::from(reason.clone()))
+            }
+            MessageError::InvalidSignature => HttpResponse::Unauthorized()
+                .json(ErrorResponse::from(String::from("Invalid signature"))),
+            MessageError::SignatureMismatch => HttpResponse::Unauthorized().json(
+                ErrorResponse::from(String::from("Signature does not match the wallet owner")),
+            ),
+            MessageError::Provider(error) => {
+                error!("{error}");
+                HttpResponse::InternalServerError()
+                    .json(ErrorResponse::from(String::from("Internal server error")))
+            }
+        }
+    }
+}
+
+impl From<ProviderError> for MessageError {
+    fn from(error: ProviderError) -> Self {
+        MessageError::Provider(error.0)
+    }
+}
@@ -2,7 +2,12 @@ pub mod admin;
 pub mod balance;
 pub mod base;
 pub mod errors;
+pub mod message;
 pub mod metadata;
+pub mod paymaster;
+pub mod recovery;
+pub mod session;
+pub mod signer;
 pub mod transaction;
 pub mod transfer;
 pub mod wallet;
@@ -10,7 +15,12 @@ pub mod wallet;
 pub use admin::AdminError;
 pub use balance::BalanceError;
 pub use base::*;
+pub use message::MessageError;
 pub use metadata::MetadataError;
+pub use paymaster::PaymasterError;
+pub use recovery::RecoveryError;
+pub use session::SessionError;
+pub use signer::SignerError;
 pub use transaction::TransactionError;
 pub use transfer::TransferError;
 pub use wallet::WalletError;
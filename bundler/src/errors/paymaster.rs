@@ -0,0 +1,71 @@
+use derive_more::Display;
+use log::error;
+
+use crate::errors::{DatabaseError, ProviderError, SponsorshipError};
+
+// returned as JSON-RPC error objects, not as http errors
+#[derive(Debug, Display)]
+pub enum PaymasterError {
+    Unauthorized,
+    MethodNotFound(String),
+    InvalidParams(String),
+    SponsorshipRefused(String),
+    Provider(String),
+    Database(String),
+}
+
+impl PaymasterError {
+    pub fn code(&self) -> i64 {
+        match self {
+            PaymasterError::Unauthorized => -32001,
+            PaymasterError::MethodNotFound(_) => -32601,
+            PaymasterError::InvalidParams(_) => -32602,
+            PaymasterError::SponsorshipRefused(_) => -32002,
+            PaymasterError::Provider(_) => -32603,
+            PaymasterError::Database(_) => -32603,
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            PaymasterError::Unauthorized => String::from("Invalid api key"),
+            PaymasterError::MethodNotFound(method) => format!("Method not found: {}", method),
+            PaymasterError::InvalidParams(error) => format!("Invalid params: {}", error),
+            PaymasterError::SponsorshipRefused(reason) => {
+                format!("Sponsorship refused: {}", reason)
+            }
+            PaymasterError::Provider(error) => {
+                error!("{error}");
+                String::from("Internal error")
+            }
+            PaymasterError::Database(error) => {
+                error!("{error}");
+                String::from("Internal error")
+            }
+        }
+    }
+}
+
+impl From<DatabaseError> for PaymasterError {
+    fn from(error: DatabaseError) -> Self {
+        match error {
+            DatabaseError::NotFound => PaymasterError::Database(String::from("Record not found")),
+            DatabaseError::ServerError(err) => PaymasterError::Database(err),
+        }
+    }
+}
+
+impl From<ProviderError> for PaymasterError {
+    fn from(error: ProviderError) -> Self {
+        PaymasterError::Provider(error.0)
+    }
+}
+
+impl From<SponsorshipError> for PaymasterError {
+    fn from(error: SponsorshipError) -> Self {
+        match error {
+            SponsorshipError::Refused(reason) => PaymasterError::SponsorshipRefused(reason),
+            SponsorshipError::Database(err) => PaymasterError::Database(err),
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, ResponseError};
+use derive_more::Display;
+use log::error;
+
+use crate::errors::{DatabaseError, ErrorResponse, ProviderError, TransferError};
+
+#[derive(Debug, Display)]
+pub enum RecoveryError {
+    NotFound,
+    InvalidSignature,
+    InvalidRequest(String),
+    Database(String),
+    Provider(String),
+}
+
+impl ResponseError for RecoveryError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            RecoveryError::NotFound => StatusCode::NOT_FOUND,
+            RecoveryError::InvalidSignature => StatusCode::UNAUTHORIZED,
+            RecoveryError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+            RecoveryError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            RecoveryError::Provider(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        match self {
+            RecoveryError::NotFound => HttpResponse::NotFound().json(ErrorResponse::from(
+                String::from("Recovery request not found"),
+            )),
+            RecoveryError::InvalidSignature => HttpResponse::Unauthorized()
+                .json(ErrorResponse::from(String::from("Invalid signature"))),
+            RecoveryError::InvalidRequest(reason) => {
+                HttpResponse::BadRequest().json(ErrorResponse::from(reason.clone()))
+            }
+            RecoveryError::Database(error) => {
+                error!("{error}");
+                HttpResponse::InternalServerError()
+                    .json(ErrorResponse::from(String::from("Internal server error")))
+            }
+            RecoveryError::Provider(error) => {
+                error!("{error}");
+                HttpResponse::InternalServerError()
+                    .json(ErrorResponse::from(String::from("Internal server error")))
+            }
+        }
+    }
+}
+
+impl From<DatabaseError> for RecoveryError {
+    fn from(error: DatabaseError) -> Self {
+        match error {
+            DatabaseError::NotFound => RecoveryError::NotFound,
+            DatabaseError::ServerError(err) => RecoveryError::Database(err),
+        }
+    }
+}
+
+impl From<ProviderError> for RecoveryError {
+    fn from(error: ProviderError) -> Self {
+        RecoveryError::Provider(error.0)
+    }
+}
+
+// guardian changes are built like any other sponsored account operation
+impl From<TransferError> for RecoveryError {
+    fn from(error: TransferError) -> Self {
+        match error {
+            TransferError::SponsorshipRefused(reason) => {
+                RecoveryError::InvalidRequest(format!("Sponsorship refused: {}", reason))
+            }
+            TransferError::Database(err) => RecoveryError::Database(err),
+            TransferError::Provider(err) => RecoveryError::Provider(err),
+            err => RecoveryError::InvalidRequest(err.to_string()),
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, ResponseError};
+use derive_more::Display;
+use log::error;
+
+use crate::errors::{DatabaseError, ErrorResponse, ProviderError, TransferError};
+
+#[derive(Debug, Display)]
+pub enum SessionError {
+    NotFound,
+    InvalidSignature,
+    InvalidRequest(String),
+    Database(String),
+    Provider(String),
+}
+
+impl ResponseError for SessionError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            SessionError::NotFound => StatusCode::NOT_FOUND,
+            SessionError::InvalidSignature => StatusCode::UNAUTHORIZED,
+            SessionError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+            SessionError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            SessionError::Provider(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        match self {
+            SessionError::NotFound => HttpResponse::NotFound()
+                .json(ErrorResponse::from(String::from("Session not found"))),
+            SessionError::InvalidSignature => HttpResponse::Unauthorized()
+                .json(ErrorResponse::from(String::from("Invalid signature"))),
+            SessionError::InvalidRequest(reason) => {
+                HttpResponse::BadRequest().json(ErrorResponse::from(reason.clone()))
+            }
+            SessionError::Database(error) => {
+                error!("{error}");
+                HttpResponse::InternalServerError()
+                    .json(ErrorResponse::from(String::from("Internal server error")))
+            }
+            SessionError::Provider(error) => {
+                error!("{error}");
+                HttpResponse::InternalServerError()
+                    .json(ErrorResponse::from(String::from("Internal server error")))
+            }
+        }
+    }
+}
+
+impl From<DatabaseError> for SessionError {
+    fn from(error: DatabaseError) -> Self {
+        match error {
+            DatabaseError::NotFound => SessionError::NotFound,
+            DatabaseError::ServerError(err) => SessionError::Database(err),
+        }
+    }
+}
+
+impl From<ProviderError> for SessionError {
+    fn from(error: ProviderError) -> Self {
+        SessionError::Provider(error.0)
+    }
+}
+
+// the on chain revocation is built like any other sponsored account operation
+impl From<TransferError> for SessionError {
+    fn from(error: TransferError) -> Self {
+        match error {
+            TransferError::SponsorshipRefused(reason) => {
+                SessionError::InvalidRequest(format!("Sponsorship refused: {}", reason))
+            }
+            TransferError::Database(err) => SessionError::Database(err),
+            TransferError::Provider(err) => SessionError::Provider(err),
+            err => SessionError::InvalidRequest(err.to_string()),
+        }
+    }
+}
@@ -0,0 +1,9 @@
+use derive_more::Display;
+
+#[derive(Debug, Display)]
+pub enum SignerError {
+    Wallet(String),
+    Remote(String),
+}
+
+impl std::error::Error for SignerError {}
@@ -3,13 +3,19 @@ use actix_web::{HttpResponse, ResponseError};
 use derive_more::Display;
 use log::error;
 
-use crate::errors::{DatabaseError, ErrorResponse, ProviderError};
+use crate::errors::{DatabaseError, ErrorResponse, ProviderError, SessionError, SponsorshipError};
 
 #[derive(Debug, Display)]
 pub enum TransferError {
     NotFound,
     TxnNotFound,
     InvalidCurrency,
+    InvalidPaymaster,
+    WalletDeployed,
+    InvalidOwner,
+    InvalidSignature,
+    SponsorshipRefused(String),
+    SessionRefused(String),
     Provider(String),
     Database(String),
 }
@@ -20,6 +26,12 @@ impl ResponseError for TransferError {
             TransferError::NotFound => StatusCode::NOT_FOUND,
             TransferError::TxnNotFound => StatusCode::NOT_FOUND,
             TransferError::InvalidCurrency => StatusCode::BAD_REQUEST,
+            TransferError::InvalidPaymaster => StatusCode::BAD_REQUEST,
+            TransferError::WalletDeployed => StatusCode::CONFLICT,
+            TransferError::InvalidOwner => StatusCode::BAD_REQUEST,
+            TransferError::InvalidSignature => StatusCode::UNAUTHORIZED,
+            TransferError::SponsorshipRefused(_) => StatusCode::FORBIDDEN,
+            TransferError::SessionRefused(_) => StatusCode::FORBIDDEN,
             TransferError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
             TransferError::Provider(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
@@ -34,6 +46,21 @@ impl ResponseError for TransferError {
                 .json(ErrorResponse::from(String::from("Transaction not found"))),
             TransferError::InvalidCurrency => HttpResponse::BadRequest()
                 .json(ErrorResponse::from(String::from("Invalid chain/currency"))),
+            TransferError::InvalidPaymaster => HttpResponse::BadRequest()
+                .json(ErrorResponse::from(String::from("Invalid paymaster"))),
+            TransferError::WalletDeployed => HttpResponse::Conflict().json(ErrorResponse::from(
+                String::from("Wallet is already deployed"),
+            )),
+            TransferError::InvalidOwner => {
+                HttpResponse::BadRequest().json(ErrorResponse::from(String::from("Invalid owner")))
+            }
+            TransferError::InvalidSignature => HttpResponse::Unauthorized()
+                .json(ErrorResponse::from(String::from("Invalid signature"))),
+            TransferError::SponsorshipRefused(reason) => HttpResponse::Forbidden().json(
+                ErrorResponse::from(format!("Sponsorship refused: {}", reason)),
+            ),
+            TransferError::SessionRefused(reason) => HttpResponse::Forbidden()
+                .json(ErrorResponse::from(format!("Session refused: {}", reason))),
             TransferError::Database(error) => {
                 error!("{error}");
                 HttpResponse::InternalServerError()
@@ -62,3 +89,24 @@ impl From<ProviderError> for TransferError {
         TransferError::Provider(error.0)
     }
 }
+
+impl From<SponsorshipError> for TransferError {
+    fn from(error: SponsorshipError) -> Self {
+        match error {
+            SponsorshipError::Refused(reason) => TransferError::SponsorshipRefused(reason),
+            SponsorshipError::Database(err) => TransferError::Database(err),
+        }
+    }
+}
+
+impl From<SessionError> for TransferError {
+    fn from(error: SessionError) -> Self {
+        match error {
+            SessionError::NotFound => TransferError::SessionRefused(String::from("not found")),
+            SessionError::InvalidSignature => TransferError::InvalidSignature,
+            SessionError::InvalidRequest(reason) => TransferError::SessionRefused(reason),
+            SessionError::Database(err) => TransferError::Database(err),
+            SessionError::Provider(err) => TransferError::Provider(err),
+        }
+    }
+}
@@ -7,6 +7,8 @@ use crate::errors::{DatabaseError, ErrorResponse, ProviderError};
 
 #[derive(Debug, Display)]
 pub enum WalletError {
+    WalletExists,
+    InvalidPasskey(String),
     Database(String),
     Provider(String),
 }
@@ -14,6 +16,8 @@ pub enum WalletError {
 impl ResponseError for WalletError {
     fn status_code(&self) -> StatusCode {
         match self {
+            WalletError::WalletExists => StatusCode::CONFLICT,
+            WalletError::InvalidPasskey(_) => StatusCode::BAD_REQUEST,
             WalletError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
             WalletError::Provider(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
@@ -21,6 +25,11 @@ impl ResponseError for WalletError {
 
     fn error_response(&self) -> HttpResponse {
         match self {
+            WalletError::WalletExists => HttpResponse::Conflict()
+                .json(ErrorResponse::from(String::from("Wallet already exists"))),
+            WalletError::InvalidPasskey(error) => {
+                HttpResponse::BadRequest().json(ErrorResponse::from(error.clone()))
+            }
             WalletError::Database(error) => {
                 error!("{error}");
                 HttpResponse::InternalServerError()
@@ -1,45 +1,145 @@
-use actix_web::web::{Data, Json, Path, Query};
-use actix_web::{HttpRequest, HttpResponse};
+use actix_web::web::{Data, Json, Path, Query, ReqData};
+use actix_web::HttpResponse;
 use sqlx::{Pool, Postgres};
 
 use crate::errors::AdminError;
-use crate::models::admin::{AddMetadataRequest, PaymasterTopup};
+use crate::models::admin::{
+    AddMetadataRequest, AdminAuditRequest, AdminUser, BundlePnlRequest, DepositWithdrawal,
+    PaymasterClientRequest, PaymasterStake, PaymasterTopup, PaymasterVersionRequest,
+    StakeWithdrawal, TenantRequest,
+};
 use crate::models::response::BaseResponse;
 use crate::models::wallet::BalanceRequest;
-use crate::provider::helpers::get_user;
 use crate::provider::Web3Client;
 use crate::services::AdminService;
-use crate::CONFIG;
 
 pub async fn topup_paymaster_deposit(
+    pool: Data<Pool<Postgres>>,
     provider: Data<Web3Client>,
     body: Json<PaymasterTopup>,
-    req: HttpRequest,
     paymaster: Path<String>,
+    version: Query<PaymasterVersionRequest>,
+    admin: ReqData<AdminUser>,
 ) -> Result<HttpResponse, AdminError> {
-    if is_not_admin(get_user(req)) {
-        return Err(AdminError::Unauthorized);
-    }
-    let paymaster_req = body.into_inner();
     let response = AdminService::topup_paymaster_deposit(
-        provider.as_ref(),
-        paymaster_req.value,
-        paymaster.clone(),
-        paymaster_req.metadata,
+        pool.get_ref(),
+        provider.get_ref(),
+        &admin,
+        paymaster.into_inner(),
+        version.entrypoint_version,
+        body.into_inner(),
+    )
+    .await?;
+    Ok(HttpResponse::Ok().json(BaseResponse::init(response)))
+}
+
+pub async fn add_paymaster_stake(
+    pool: Data<Pool<Postgres>>,
+    provider: Data<Web3Client>,
+    body: Json<PaymasterStake>,
+    paymaster: Path<String>,
+    version: Query<PaymasterVersionRequest>,
+    admin: ReqData<AdminUser>,
+) -> Result<HttpResponse, AdminError> {
+    let response = AdminService::add_paymaster_stake(
+        pool.get_ref(),
+        provider.get_ref(),
+        &admin,
+        paymaster.into_inner(),
+        version.entrypoint_version,
+        body.into_inner(),
+    )
+    .await?;
+    Ok(HttpResponse::Ok().json(BaseResponse::init(response)))
+}
+
+pub async fn unlock_paymaster_stake(
+    pool: Data<Pool<Postgres>>,
+    provider: Data<Web3Client>,
+    paymaster: Path<String>,
+    version: Query<PaymasterVersionRequest>,
+    admin: ReqData<AdminUser>,
+) -> Result<HttpResponse, AdminError> {
+    let response = AdminService::unlock_paymaster_stake(
+        pool.get_ref(),
+        provider.get_ref(),
+        &admin,
+        paymaster.into_inner(),
+        version.entrypoint_version,
     )
     .await?;
     Ok(HttpResponse::Ok().json(BaseResponse::init(response)))
 }
 
+pub async fn withdraw_paymaster_stake(
+    pool: Data<Pool<Postgres>>,
+    provider: Data<Web3Client>,
+    body: Json<StakeWithdrawal>,
+    paymaster: Path<String>,
+    version: Query<PaymasterVersionRequest>,
+    admin: ReqData<AdminUser>,
+) -> Result<HttpResponse, AdminError> {
+    let response = AdminService::withdraw_paymaster_stake(
+        pool.get_ref(),
+        provider.get_ref(),
+        &admin,
+        paymaster.into_inner(),
+        version.entrypoint_version,
+        body.into_inner(),
+    )
+    .await?;
+    Ok(HttpResponse::Ok().json(BaseResponse::init(response)))
+}
+
+pub async fn withdraw_paymaster_deposit(
+    pool: Data<Pool<Postgres>>,
+    provider: Data<Web3Client>,
+    body: Json<DepositWithdrawal>,
+    paymaster: Path<String>,
+    version: Query<PaymasterVersionRequest>,
+    admin: ReqData<AdminUser>,
+) -> Result<HttpResponse, AdminError> {
+    let response = AdminService::withdraw_paymaster_deposit(
+        pool.get_ref(),
+        provider.get_ref(),
+        &admin,
+        paymaster.into_inner(),
+        version.entrypoint_version,
+        body.into_inner(),
+    )
+    .await?;
+    Ok(HttpResponse::Ok().json(BaseResponse::init(response)))
+}
+
+pub async fn get_paymaster_deposit_info(
+    provider: Data<Web3Client>,
+    paymaster: Path<String>,
+    version: Query<PaymasterVersionRequest>,
+) -> Result<HttpResponse, AdminError> {
+    let response = AdminService::get_paymaster_deposit_info(
+        provider.get_ref(),
+        paymaster.into_inner(),
+        version.entrypoint_version,
+    )
+    .await?;
+    Ok(HttpResponse::Ok().json(BaseResponse::init(response)))
+}
+
+pub async fn rotate_paymaster_signer(
+    pool: Data<Pool<Postgres>>,
+    provider: Data<Web3Client>,
+    admin: ReqData<AdminUser>,
+) -> Result<HttpResponse, AdminError> {
+    let response =
+        AdminService::rotate_paymaster_signer(pool.get_ref(), provider.get_ref(), &admin).await?;
+    Ok(HttpResponse::Ok().json(BaseResponse::init(response)))
+}
+
 pub async fn admin_get_balance(
     provider: Data<Web3Client>,
     body: Query<BalanceRequest>,
-    req: HttpRequest,
     entity: Path<String>,
 ) -> Result<HttpResponse, AdminError> {
-    if is_not_admin(get_user(req)) {
-        return Err(AdminError::Unauthorized);
-    }
     let response = AdminService::get_balance(
         provider.get_ref(),
         entity.clone(),
@@ -52,15 +152,65 @@ pub async fn admin_get_balance(
 pub async fn add_currency_metadata(
     pool: Data<Pool<Postgres>>,
     body: Json<AddMetadataRequest>,
-    req: HttpRequest,
+    admin: ReqData<AdminUser>,
+) -> Result<HttpResponse, AdminError> {
+    let response =
+        AdminService::add_currency_metadata(pool.get_ref(), &admin, body.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(BaseResponse::init(response)))
+}
+
+pub async fn create_paymaster_client(
+    pool: Data<Pool<Postgres>>,
+    body: Json<PaymasterClientRequest>,
+    admin: ReqData<AdminUser>,
+) -> Result<HttpResponse, AdminError> {
+    let response =
+        AdminService::create_paymaster_client(pool.get_ref(), &admin, body.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(BaseResponse::init(response)))
+}
+
+pub async fn get_bundle_pnl(
+    pool: Data<Pool<Postgres>>,
+    query: Query<BundlePnlRequest>,
 ) -> Result<HttpResponse, AdminError> {
-    if is_not_admin(get_user(req)) {
-        return Err(AdminError::Unauthorized);
-    }
-    let response = AdminService::add_currency_metadata(pool.get_ref(), body.into_inner()).await?;
+    let response = AdminService::get_bundle_pnl(pool.get_ref(), query.into_inner().period).await?;
+    Ok(HttpResponse::Ok().json(BaseResponse::init(response)))
+}
+
+pub async fn get_audit_log(
+    pool: Data<Pool<Postgres>>,
+    query: Query<AdminAuditRequest>,
+) -> Result<HttpResponse, AdminError> {
+    let response = AdminService::get_audit_log(pool.get_ref(), query.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(BaseResponse::init(response)))
+}
+
+pub async fn get_tenants(pool: Data<Pool<Postgres>>) -> Result<HttpResponse, AdminError> {
+    let response = AdminService::get_tenants(pool.get_ref()).await?;
     Ok(HttpResponse::Ok().json(BaseResponse::init(response)))
 }
 
-fn is_not_admin(user: String) -> bool {
-    !CONFIG.get_admins().contains(&user)
+pub async fn create_tenant(
+    pool: Data<Pool<Postgres>>,
+    body: Json<TenantRequest>,
+    admin: ReqData<AdminUser>,
+) -> Result<HttpResponse, AdminError> {
+    let response = AdminService::create_tenant(pool.get_ref(), &admin, body.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(BaseResponse::init(response)))
+}
+
+pub async fn update_tenant(
+    pool: Data<Pool<Postgres>>,
+    body: Json<TenantRequest>,
+    tenant_id: Path<i32>,
+    admin: ReqData<AdminUser>,
+) -> Result<HttpResponse, AdminError> {
+    let response = AdminService::update_tenant(
+        pool.get_ref(),
+        &admin,
+        tenant_id.into_inner(),
+        body.into_inner(),
+    )
+    .await?;
+    Ok(HttpResponse::Ok().json(BaseResponse::init(response)))
 }
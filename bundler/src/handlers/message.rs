@@ -0,0 +1,27 @@
+use actix_web::web::{Data, Json, ReqData};
+use actix_web::HttpResponse;
+
+use crate::db::dao::User;
+use crate::errors::MessageError;
+use crate::models::message::{MessageRequest, MessageSignRequest};
+use crate::models::response::BaseResponse;
+use crate::provider::Web3Client;
+use crate::services::MessageService;
+
+pub async fn prepare_message(
+    body: Json<MessageRequest>,
+    user: ReqData<User>,
+) -> Result<HttpResponse, MessageError> {
+    let data = MessageService::prepare(body.into_inner(), user.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(BaseResponse::init(data)))
+}
+
+pub async fn sign_message(
+    provider: Data<Web3Client>,
+    body: Json<MessageSignRequest>,
+    user: ReqData<User>,
+) -> Result<HttpResponse, MessageError> {
+    let data =
+        MessageService::sign(provider.get_ref(), body.into_inner(), user.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(BaseResponse::init(data)))
+}
@@ -1,17 +1,24 @@
-use actix_web::web::Data;
+use actix_web::web::{Data, ReqData};
 use actix_web::HttpResponse;
 use sqlx::{Pool, Postgres};
 
+use crate::db::dao::Tenant;
 use crate::errors::MetadataError;
 use crate::models::response::base_response::BaseResponse;
 use crate::services::TokenMetadataService;
 
-pub async fn get_metadata(pool: Data<Pool<Postgres>>) -> Result<HttpResponse, MetadataError> {
-    let response = TokenMetadataService::get_chain(pool.get_ref()).await?;
+pub async fn get_metadata(
+    pool: Data<Pool<Postgres>>,
+    tenant: ReqData<Tenant>,
+) -> Result<HttpResponse, MetadataError> {
+    let response = TokenMetadataService::get_chain(pool.get_ref(), &tenant).await?;
     Ok(HttpResponse::Ok().json(BaseResponse::init(response)))
 }
 
-pub async fn get_metadata_v2(pool: Data<Pool<Postgres>>) -> Result<HttpResponse, MetadataError> {
-    let response = TokenMetadataService::get_chain_v2(pool.get_ref()).await?;
+pub async fn get_metadata_v2(
+    pool: Data<Pool<Postgres>>,
+    tenant: ReqData<Tenant>,
+) -> Result<HttpResponse, MetadataError> {
+    let response = TokenMetadataService::get_chain_v2(pool.get_ref(), &tenant).await?;
     Ok(HttpResponse::Ok().json(BaseResponse::init(response)))
 }
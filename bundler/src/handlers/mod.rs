@@ -1,5 +1,10 @@
 pub mod admin;
 pub mod hello_world;
+pub mod message;
 pub mod metadata;
+pub mod paymaster;
+pub mod recovery;
+pub mod session;
+pub mod signer;
 pub mod transfer;
 pub mod wallet;
@@ -0,0 +1,45 @@
+use actix_web::web::{Data, Json};
+use actix_web::{HttpRequest, HttpResponse};
+use sqlx::{Pool, Postgres};
+
+use crate::constants::Constants;
+use crate::errors::PaymasterError;
+use crate::models::paymaster::{JsonRpcRequest, JsonRpcResponse};
+use crate::provider::Web3Client;
+use crate::services::PaymasterService;
+
+pub async fn paymaster_rpc(
+    pool: Data<Pool<Postgres>>,
+    provider: Data<Web3Client>,
+    req: HttpRequest,
+    body: Json<JsonRpcRequest>,
+) -> HttpResponse {
+    let request = body.into_inner();
+    // kept out of the url so it does not end up in access logs
+    let api_key = match req
+        .headers()
+        .get(Constants::API_KEY_HEADER)
+        .and_then(|api_key| api_key.to_str().ok())
+    {
+        Some(api_key) => api_key.to_string(),
+        None => {
+            return HttpResponse::Ok().json(JsonRpcResponse::error(
+                request.id,
+                PaymasterError::Unauthorized,
+            ))
+        }
+    };
+    let response = match PaymasterService::handle_request(
+        pool.get_ref(),
+        provider.get_ref(),
+        api_key,
+        &request.method,
+        request.params,
+    )
+    .await
+    {
+        Ok(result) => JsonRpcResponse::result(request.id, result),
+        Err(err) => JsonRpcResponse::error(request.id, err),
+    };
+    HttpResponse::Ok().json(response)
+}
@@ -0,0 +1,114 @@
+use actix_web::web::{Data, Json, Path, ReqData};
+use actix_web::HttpResponse;
+use sqlx::{Pool, Postgres};
+
+use crate::db::dao::{Tenant, User};
+use crate::errors::RecoveryError;
+use crate::models::recovery::{
+    GuardianRequest, RecoveryApprovalRequest, RecoveryCancelRequest, RecoveryInitRequest,
+};
+use crate::models::response::BaseResponse;
+use crate::provider::Web3Client;
+use crate::services::RecoveryService;
+
+pub async fn get_guardians(
+    pool: Data<Pool<Postgres>>,
+    user: ReqData<User>,
+) -> Result<HttpResponse, RecoveryError> {
+    let data = RecoveryService::get_guardians(pool.get_ref(), user.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(BaseResponse::init(data)))
+}
+
+pub async fn add_guardian(
+    pool: Data<Pool<Postgres>>,
+    provider: Data<Web3Client>,
+    tenant: ReqData<Tenant>,
+    body: Json<GuardianRequest>,
+    user: ReqData<User>,
+) -> Result<HttpResponse, RecoveryError> {
+    let data = RecoveryService::add_guardian(
+        pool.get_ref(),
+        provider.get_ref(),
+        &tenant,
+        body.into_inner(),
+        user.into_inner(),
+    )
+    .await?;
+    Ok(HttpResponse::Ok().json(BaseResponse::init(data)))
+}
+
+pub async fn remove_guardian(
+    pool: Data<Pool<Postgres>>,
+    provider: Data<Web3Client>,
+    tenant: ReqData<Tenant>,
+    body: Json<GuardianRequest>,
+    user: ReqData<User>,
+) -> Result<HttpResponse, RecoveryError> {
+    let data = RecoveryService::remove_guardian(
+        pool.get_ref(),
+        provider.get_ref(),
+        &tenant,
+        body.into_inner(),
+        user.into_inner(),
+    )
+    .await?;
+    Ok(HttpResponse::Ok().json(BaseResponse::init(data)))
+}
+
+pub async fn init_recovery(
+    pool: Data<Pool<Postgres>>,
+    provider: Data<Web3Client>,
+    body: Json<RecoveryInitRequest>,
+    user: ReqData<User>,
+) -> Result<HttpResponse, RecoveryError> {
+    let data = RecoveryService::init_recovery(
+        pool.get_ref(),
+        provider.get_ref(),
+        body.new_owner,
+        user.into_inner(),
+    )
+    .await?;
+    Ok(HttpResponse::Ok().json(BaseResponse::init(data)))
+}
+
+pub async fn cancel_recovery(
+    pool: Data<Pool<Postgres>>,
+    body: Json<RecoveryCancelRequest>,
+    request_id: Path<String>,
+    user: ReqData<User>,
+) -> Result<HttpResponse, RecoveryError> {
+    let data = RecoveryService::cancel_recovery(
+        pool.get_ref(),
+        request_id.into_inner(),
+        body.into_inner().signature,
+        user.into_inner(),
+    )
+    .await?;
+    Ok(HttpResponse::Ok().json(BaseResponse::init(data)))
+}
+
+pub async fn get_recovery(
+    pool: Data<Pool<Postgres>>,
+    tenant: ReqData<Tenant>,
+    request_id: Path<String>,
+) -> Result<HttpResponse, RecoveryError> {
+    let data =
+        RecoveryService::get_recovery(pool.get_ref(), tenant.id, request_id.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(BaseResponse::init(data)))
+}
+
+pub async fn approve_recovery(
+    pool: Data<Pool<Postgres>>,
+    tenant: ReqData<Tenant>,
+    body: Json<RecoveryApprovalRequest>,
+    request_id: Path<String>,
+) -> Result<HttpResponse, RecoveryError> {
+    let data = RecoveryService::approve_recovery(
+        pool.get_ref(),
+        &tenant,
+        request_id.into_inner(),
+        body.into_inner(),
+    )
+    .await?;
+    Ok(HttpResponse::Ok().json(BaseResponse::init(data)))
+}
@@ -0,0 +1,68 @@
+use actix_web::web::{Data, Json, Path, ReqData};
+use actix_web::HttpResponse;
+use sqlx::{Pool, Postgres};
+
+use crate::db::dao::{Tenant, User};
+use crate::errors::SessionError;
+use crate::models::response::BaseResponse;
+use crate::models::session::{SessionActivateRequest, SessionRequest};
+use crate::provider::Web3Client;
+use crate::services::SessionKeyService;
+
+pub async fn get_sessions(
+    pool: Data<Pool<Postgres>>,
+    user: ReqData<User>,
+) -> Result<HttpResponse, SessionError> {
+    let data = SessionKeyService::get_sessions(pool.get_ref(), user.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(BaseResponse::init(data)))
+}
+
+pub async fn create_session(
+    pool: Data<Pool<Postgres>>,
+    tenant: ReqData<Tenant>,
+    body: Json<SessionRequest>,
+    user: ReqData<User>,
+) -> Result<HttpResponse, SessionError> {
+    let data = SessionKeyService::create_session(
+        pool.get_ref(),
+        &tenant,
+        body.into_inner(),
+        user.into_inner(),
+    )
+    .await?;
+    Ok(HttpResponse::Ok().json(BaseResponse::init(data)))
+}
+
+pub async fn activate_session(
+    pool: Data<Pool<Postgres>>,
+    body: Json<SessionActivateRequest>,
+    session_id: Path<String>,
+    user: ReqData<User>,
+) -> Result<HttpResponse, SessionError> {
+    let data = SessionKeyService::activate_session(
+        pool.get_ref(),
+        session_id.into_inner(),
+        body.into_inner().signature,
+        user.into_inner(),
+    )
+    .await?;
+    Ok(HttpResponse::Ok().json(BaseResponse::init(data)))
+}
+
+pub async fn revoke_session(
+    pool: Data<Pool<Postgres>>,
+    provider: Data<Web3Client>,
+    tenant: ReqData<Tenant>,
+    session_id: Path<String>,
+    user: ReqData<User>,
+) -> Result<HttpResponse, SessionError> {
+    let data = SessionKeyService::revoke_session(
+        pool.get_ref(),
+        provider.get_ref(),
+        &tenant,
+        session_id.into_inner(),
+        user.into_inner(),
+    )
+    .await?;
+    Ok(HttpResponse::Ok().json(BaseResponse::init(data)))
+}
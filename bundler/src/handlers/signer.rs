@@ -0,0 +1,26 @@
+use actix_web::web::Json;
+use actix_web::HttpResponse;
+use ethers_signers::{LocalWallet, Signer};
+use zeroize::Zeroizing;
+
+use crate::errors::errors::ApiError;
+use crate::signer::{RemoteSignRequest, RemoteSignResponse};
+
+// local stand-in for a remote signing service, only routed in development
+pub async fn mock_sign(body: Json<RemoteSignRequest>) -> Result<HttpResponse, ApiError> {
+    let key = Zeroizing::new(std::env::var("MOCK_SIGNER_PRIVATE_KEY").map_err(|_| {
+        ApiError::InternalServer(String::from("MOCK_SIGNER_PRIVATE_KEY must be set"))
+    })?);
+    let wallet = key
+        .parse::<LocalWallet>()
+        .map_err(|_| ApiError::InternalServer(String::from("Invalid mock signer key")))?;
+    if wallet.address() != body.address {
+        return Err(ApiError::BadRequest(String::from("Unknown signer")));
+    }
+    let signature = wallet
+        .sign_hash(body.hash)
+        .map_err(|err| ApiError::InternalServer(err.to_string()))?;
+    Ok(HttpResponse::Ok().json(RemoteSignResponse {
+        signature: signature.to_vec().into(),
+    }))
+}
@@ -2,7 +2,7 @@ use actix_web::web::{Data, Json, ReqData};
 use actix_web::HttpResponse;
 use sqlx::{Pool, Postgres};
 
-use crate::db::dao::User;
+use crate::db::dao::{Tenant, User};
 use crate::errors::TransferError;
 use crate::models::response::BaseResponse;
 use crate::models::transfer::{TransferExecuteRequest, TransferRequest};
@@ -12,15 +12,15 @@ use crate::services::TransferService;
 pub async fn init_transfer(
     pool: Data<Pool<Postgres>>,
     provider: Data<Web3Client>,
+    tenant: ReqData<Tenant>,
     body: Json<TransferRequest>,
     user: ReqData<User>,
 ) -> Result<HttpResponse, TransferError> {
     let data = TransferService::init(
         pool.get_ref(),
         provider.get_ref(),
-        body.get_receiver(),
-        body.get_value(),
-        body.metadata.get_currency(),
+        &tenant,
+        body.into_inner(),
         user.into_inner(),
     )
     .await?;
@@ -38,6 +38,7 @@ pub async fn execute_transfer(
         provider.get_ref(),
         body.transaction_id.clone(),
         body.get_signature(),
+        body.passkey.clone(),
         req.into_inner(),
     )
     .await?;
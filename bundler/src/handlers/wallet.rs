@@ -1,12 +1,12 @@
-use actix_web::web::{Data, Query, ReqData};
+use actix_web::web::{Data, Json, Query, ReqData};
 use actix_web::{HttpRequest, HttpResponse};
 use sqlx::{Pool, Postgres};
 
-use crate::db::dao::User;
-use crate::errors::{BalanceError, TransactionError, WalletError};
+use crate::db::dao::{Tenant, User};
+use crate::errors::{BalanceError, TransactionError, TransferError, WalletError};
 use crate::models::response::BaseResponse;
 use crate::models::transaction::{ListTransactionsParams, PollTransactionParams};
-use crate::models::wallet::BalanceRequest;
+use crate::models::wallet::{BalanceRequest, OwnerRotationRequest, PasskeyRegistrationRequest};
 use crate::provider::helpers::get_user_wallet;
 use crate::provider::Web3Client;
 use crate::services::{BalanceService, TransferService, WalletService};
@@ -27,6 +27,58 @@ pub async fn get_address(
     Ok(HttpResponse::Ok().json(BaseResponse::init(wallet_address)))
 }
 
+pub async fn register_passkey(
+    pool: Data<Pool<Postgres>>,
+    provider: Data<Web3Client>,
+    body: Json<PasskeyRegistrationRequest>,
+    user: ReqData<User>,
+) -> Result<HttpResponse, WalletError> {
+    let body = body.into_inner();
+    let wallet_address = WalletService::register_passkey(
+        pool.get_ref(),
+        provider.get_ref(),
+        user.into_inner(),
+        body.credential_id,
+        body.public_key,
+    )
+    .await?;
+    Ok(HttpResponse::Ok().json(BaseResponse::init(wallet_address)))
+}
+
+pub async fn deploy_wallet(
+    pool: Data<Pool<Postgres>>,
+    provider: Data<Web3Client>,
+    tenant: ReqData<Tenant>,
+    user: ReqData<User>,
+) -> Result<HttpResponse, TransferError> {
+    let data = TransferService::deploy_wallet(
+        pool.get_ref(),
+        provider.get_ref(),
+        &tenant,
+        user.into_inner(),
+    )
+    .await?;
+    Ok(HttpResponse::Ok().json(BaseResponse::init(data)))
+}
+
+pub async fn rotate_owner(
+    pool: Data<Pool<Postgres>>,
+    provider: Data<Web3Client>,
+    tenant: ReqData<Tenant>,
+    body: Json<OwnerRotationRequest>,
+    user: ReqData<User>,
+) -> Result<HttpResponse, TransferError> {
+    let data = TransferService::rotate_owner(
+        pool.get_ref(),
+        provider.get_ref(),
+        &tenant,
+        body.new_owner,
+        user.into_inner(),
+    )
+    .await?;
+    Ok(HttpResponse::Ok().json(BaseResponse::init(data)))
+}
+
 pub async fn get_balance(
     pool: Data<Pool<Postgres>>,
     provider: Data<Web3Client>,
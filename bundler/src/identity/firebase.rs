@@ -0,0 +1,75 @@
+use async_trait::async_trait;
+use rs_firebase_admin_sdk::{
+    auth::{FirebaseAuthService, UserIdentifiers},
+    App, CustomServiceAccount, GcpCredentials, LiveAuthAdmin,
+};
+use serde_json::Value;
+
+use crate::identity::{get_claim, get_expiry, Identity, IdentityProvider};
+use crate::models::config::env::ENV;
+use crate::models::config::settings::JwtIssuer;
+use crate::provider::jwt::JwtValidator;
+use crate::CONFIG;
+
+// the token's user id claim must name an existing firebase user, whose profile is used
+pub struct FirebaseIdentityProvider {
+    validator: JwtValidator,
+    user_id_claim: String,
+    auth_admin: LiveAuthAdmin,
+}
+
+impl FirebaseIdentityProvider {
+    pub async fn load(issuer: &JwtIssuer) -> Result<Self, String> {
+        let live_app = App::live(Self::get_credentials().await?)
+            .await
+            .map_err(|err| format!("Failed to connect to firebase: {:?}", err))?;
+        Ok(Self {
+            validator: JwtValidator::new(issuer),
+            user_id_claim: issuer
+                .user_id_claim
+                .clone()
+                .unwrap_or(String::from("verifierId")),
+            auth_admin: live_app.auth(),
+        })
+    }
+
+    async fn get_credentials() -> Result<GcpCredentials, String> {
+        match CONFIG.env {
+            ENV::Development => GcpCredentials::new()
+                .await
+                .map_err(|err| format!("Failed to load gcp credentials: {:?}", err)),
+            _ => {
+                let credentials = std::env::var("GOOGLE_APPLICATION_CREDENTIALS")
+                    .map_err(|_| String::from("GOOGLE_APPLICATION_CREDENTIALS env var not set"))?;
+                Ok(GcpCredentials::from(
+                    CustomServiceAccount::from_json(credentials.as_str())
+                        .map_err(|err| format!("Invalid service account: {:?}", err))?,
+                ))
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl IdentityProvider for FirebaseIdentityProvider {
+    async fn authenticate(&self, token: &str) -> Result<Identity, String> {
+        let claims: Value = self.validator.verify(token).await?;
+        let user_id = get_claim(&claims, &self.user_id_claim)
+            .ok_or(format!("Missing {} claim", self.user_id_claim))?;
+        let user = self
+            .auth_admin
+            .get_user(
+                // Build a filter for finding the user
+                UserIdentifiers::builder().with_uid(user_id.clone()).build(),
+            )
+            .await
+            .map_err(|err| format!("Error while fetching user: {:?}", err))?
+            .ok_or(format!("Unknown firebase user: {}", user_id))?;
+        Ok(Identity {
+            external_user_id: user.uid,
+            name: user.display_name.unwrap_or_default(),
+            email: user.email.unwrap_or_default(),
+            expires_at: get_expiry(&claims),
+        })
+    }
+}
@@ -0,0 +1,58 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::identity::{get_claim, get_expiry, Identity, IdentityProvider};
+use crate::models::config::settings::LocalIdentity;
+use crate::provider::jwt::JwtValidator;
+
+// for development and tests, no calls leave the machine. identity::load refuses it in other
+// environments
+pub struct LocalIdentityProvider {
+    validator: JwtValidator,
+    users: HashMap<String, LocalUser>,
+}
+
+#[derive(Deserialize)]
+struct LocalUser {
+    id: String,
+    name: String,
+    email: String,
+}
+
+impl LocalIdentityProvider {
+    pub fn load(local: &LocalIdentity) -> Result<Self, String> {
+        let key_set = std::fs::read_to_string(&local.key_set_path)
+            .map_err(|err| format!("Failed to read {}: {:?}", local.key_set_path, err))?;
+        let users = std::fs::read_to_string(&local.users_path)
+            .map_err(|err| format!("Failed to read {}: {:?}", local.users_path, err))?;
+        let users: Vec<LocalUser> = serde_json::from_str(&users)
+            .map_err(|err| format!("Invalid users in {}: {:?}", local.users_path, err))?;
+        Ok(Self {
+            validator: JwtValidator::local(&key_set, local.issuer.clone(), local.audience.clone())?,
+            users: users
+                .into_iter()
+                .map(|user| (user.id.clone(), user))
+                .collect(),
+        })
+    }
+}
+
+#[async_trait]
+impl IdentityProvider for LocalIdentityProvider {
+    async fn authenticate(&self, token: &str) -> Result<Identity, String> {
+        let claims: Value = self.validator.verify(token).await?;
+        let user_id = get_claim(&claims, "sub").ok_or(String::from("Missing sub claim"))?;
+        let user = self
+            .users
+            .get(&user_id)
+            .ok_or(format!("Unknown user: {}", user_id))?;
+        Ok(Identity {
+            external_user_id: user.id.clone(),
+            name: user.name.clone(),
+            email: user.email.clone(),
+            expires_at: get_expiry(&claims),
+        })
+    }
+}
@@ -0,0 +1,165 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::lock::Mutex;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::db::dao::Tenant;
+use crate::models::config::env::ENV;
+use crate::models::config::settings::Auth;
+use crate::CONFIG;
+
+#[cfg(feature = "firebase")]
+pub mod firebase;
+pub mod local;
+pub mod oidc;
+
+#[cfg(feature = "firebase")]
+pub use firebase::FirebaseIdentityProvider;
+pub use local::LocalIdentityProvider;
+pub use oidc::OidcIdentityProvider;
+
+// the user a token was issued for
+#[derive(Clone, Debug)]
+pub struct Identity {
+    pub external_user_id: String,
+    pub name: String,
+    pub email: String,
+    // exp of the token, in seconds
+    pub expires_at: u64,
+}
+
+#[async_trait]
+pub trait IdentityProvider: Send + Sync {
+    // verifies the bearer token and resolves the user it was issued for
+    async fn authenticate(&self, token: &str) -> Result<Identity, String>;
+}
+
+// a tenant's provider and the tenant version it was loaded for
+type TenantProvider = (DateTime<Utc>, Arc<dyn IdentityProvider>);
+
+// the global provider is loaded at startup, see init_services. Tenants with their own auth get
+// theirs on first use, and again once the tenant is updated
+pub struct IdentityProviders {
+    default: Arc<dyn IdentityProvider>,
+    tenants: Mutex<HashMap<i32, TenantProvider>>,
+}
+
+impl IdentityProviders {
+    pub fn new(default: Arc<dyn IdentityProvider>) -> Self {
+        Self {
+            default,
+            tenants: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub async fn get(&self, tenant: &Tenant) -> Result<Arc<dyn IdentityProvider>, String> {
+        if tenant.auth.is_none() {
+            return Ok(self.default.clone());
+        }
+        if let Some((updated_at, provider)) = self.tenants.lock().await.get(&tenant.id) {
+            if *updated_at == tenant.updated_at {
+                return Ok(provider.clone());
+            }
+        }
+        // loading can fetch keys, other tenants are not held up meanwhile. Concurrent first
+        // requests may each load the provider, the newest tenant version is kept
+        let provider = load(&tenant.get_auth()?).await?;
+        let mut providers = self.tenants.lock().await;
+        match providers.get(&tenant.id) {
+            Some((updated_at, cached)) if *updated_at >= tenant.updated_at => Ok(cached.clone()),
+            _ => {
+                providers.insert(tenant.id, (tenant.updated_at, provider.clone()));
+                Ok(provider)
+            }
+        }
+    }
+}
+
+pub async fn load(auth: &Auth) -> Result<Arc<dyn IdentityProvider>, String> {
+    match auth {
+        #[cfg(feature = "firebase")]
+        Auth::Firebase(issuer) => Ok(Arc::new(FirebaseIdentityProvider::load(issuer).await?)),
+        #[cfg(not(feature = "firebase"))]
+        Auth::Firebase(_) => Err(String::from(
+            "The firebase identity provider needs the firebase feature",
+        )),
+        Auth::Oidc(issuer) => Ok(Arc::new(OidcIdentityProvider::new(issuer))),
+        Auth::Local(local) => match CONFIG.env {
+            ENV::Development => Ok(Arc::new(LocalIdentityProvider::load(local)?)),
+            // anyone with the key set file can mint tokens for its users
+            _ => Err(format!(
+                "The local identity provider is only available in development, not {}",
+                CONFIG.env
+            )),
+        },
+    }
+}
+
+pub fn get_claim(claims: &Value, name: &str) -> Option<String> {
+    claims.get(name).and_then(Value::as_str).map(String::from)
+}
+
+// the validator already rejected tokens without exp
+pub fn get_expiry(claims: &Value) -> u64 {
+    claims
+        .get("exp")
+        .and_then(Value::as_u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use serde_json::json;
+
+    struct NoIdentity;
+
+    #[async_trait]
+    impl IdentityProvider for NoIdentity {
+        async fn authenticate(&self, _token: &str) -> Result<Identity, String> {
+            Err(String::from("No identity"))
+        }
+    }
+
+    fn is_same(a: &Arc<dyn IdentityProvider>, b: &Arc<dyn IdentityProvider>) -> bool {
+        std::ptr::eq(Arc::as_ptr(a) as *const (), Arc::as_ptr(b) as *const ())
+    }
+
+    fn tenant(updated_at: DateTime<Utc>) -> Tenant {
+        Tenant {
+            id: 2,
+            auth: Some(json!({
+                "provider": "oidc",
+                "jwks_url": "https://issuer.example/jwks",
+                "issuer": "https://issuer.example",
+                "audience": ["toad"],
+            })),
+            updated_at,
+            ..Default::default()
+        }
+    }
+
+    #[actix_web::test]
+    async fn tenant_providers_are_reloaded_once_the_tenant_changes() {
+        let providers = IdentityProviders::new(Arc::new(NoIdentity));
+        let created_at = Utc::now();
+        let updated_at = created_at + Duration::seconds(1);
+
+        let first = providers.get(&tenant(created_at)).await.unwrap();
+        assert!(is_same(
+            &first,
+            &providers.get(&tenant(created_at)).await.unwrap()
+        ));
+        let updated = providers.get(&tenant(updated_at)).await.unwrap();
+        assert!(!is_same(&first, &updated));
+        // a request that read the tenant before the update does not replace the newer provider
+        providers.get(&tenant(created_at)).await.unwrap();
+        assert!(is_same(
+            &updated,
+            &providers.get(&tenant(updated_at)).await.unwrap()
+        ));
+    }
+}
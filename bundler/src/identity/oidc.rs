@@ -0,0 +1,34 @@
+use async_trait::async_trait;
+use serde_json::Value;
+
+use crate::identity::{get_claim, get_expiry, Identity, IdentityProvider};
+use crate::models::config::settings::JwtIssuer;
+use crate::provider::jwt::JwtValidator;
+
+pub struct OidcIdentityProvider {
+    validator: JwtValidator,
+    user_id_claim: String,
+}
+
+impl OidcIdentityProvider {
+    pub fn new(issuer: &JwtIssuer) -> Self {
+        Self {
+            validator: JwtValidator::new(issuer),
+            user_id_claim: issuer.user_id_claim.clone().unwrap_or(String::from("sub")),
+        }
+    }
+}
+
+#[async_trait]
+impl IdentityProvider for OidcIdentityProvider {
+    async fn authenticate(&self, token: &str) -> Result<Identity, String> {
+        let claims: Value = self.validator.verify(token).await?;
+        Ok(Identity {
+            external_user_id: get_claim(&claims, &self.user_id_claim)
+                .ok_or(format!("Missing {} claim", self.user_id_claim))?,
+            name: get_claim(&claims, "name").unwrap_or_default(),
+            email: get_claim(&claims, "email").unwrap_or_default(),
+            expires_at: get_expiry(&claims),
+        })
+    }
+}
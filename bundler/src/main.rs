@@ -5,6 +5,7 @@ use crate::models::config::server::Server;
 use crate::models::config::settings::Settings;
 use crate::provider::web3_provider::Web3Provider;
 use crate::server::{init_services, run};
+use crate::signer::Signers;
 
 mod bundler;
 mod constants;
@@ -12,16 +13,24 @@ mod contracts;
 mod db;
 mod errors;
 mod handlers;
+mod identity;
 mod middleware;
 mod models;
 mod provider;
 mod routes;
 mod server;
 mod services;
+mod signer;
 
 lazy_static! {
     static ref CONFIG: Settings = Settings::new().expect("Failed to load config.");
     static ref PROVIDER: Provider<Http> = Web3Provider::new(CONFIG.get_chain().get_url());
+    static ref SIGNERS: Signers = Signers::load(
+        &CONFIG.signers,
+        &CONFIG.run_config.relayer_pool,
+        CONFIG.get_chain().chain_id
+    )
+    .expect("Failed to load signers.");
 }
 
 #[actix_web::main]
@@ -1,19 +1,73 @@
+use actix_web::error::PayloadError;
+use actix_web::http::Method;
+use actix_web::web::{Bytes, Data};
 use actix_web::{
-    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
-    Error,
+    dev::{forward_ready, Payload, Service, ServiceRequest, ServiceResponse, Transform},
+    Error, HttpMessage,
 };
 use futures::future::LocalBoxFuture;
-use log::error;
-use reqwest::header::HeaderName;
+use futures::{stream, Stream};
+use hmac::{Hmac, Mac};
+use lazy_static::lazy_static;
+use log::{error, warn};
+use sha2::{Digest, Sha256};
+use sqlx::{Pool, Postgres};
 use std::future::{ready, Ready};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use zeroize::Zeroizing;
 
-use crate::errors::errors::ApiError;
+use crate::db::dao::AdminNonceDao;
+use crate::errors::AdminError;
+use crate::models::admin::{AdminRole, AdminUser};
+use crate::models::config::settings::AdminKey;
+use crate::CONFIG;
 
+const ADMIN_KEY_HEADER: &str = "x-admin-key";
+const ADMIN_TIMESTAMP_HEADER: &str = "x-admin-timestamp";
+const ADMIN_SIGNATURE_HEADER: &str = "x-admin-signature";
+const ADMIN_NONCE_HEADER: &str = "x-admin-nonce";
+const MAX_NONCE_LEN: usize = 64;
+
+lazy_static! {
+    // secrets are read once, see init_services
+    pub static ref ADMIN_KEYS: Vec<AdminCredential> =
+        AdminCredential::load(&CONFIG.admin_auth.keys).expect("Failed to load admin keys.");
+}
+
+pub struct AdminCredential {
+    id: String,
+    secret: Zeroizing<String>,
+    roles: Vec<AdminRole>,
+}
+
+impl AdminCredential {
+    fn load(keys: &[AdminKey]) -> Result<Vec<AdminCredential>, String> {
+        keys.iter()
+            .map(|key| {
+                Ok(AdminCredential {
+                    id: key.id.clone(),
+                    secret: Zeroizing::new(
+                        std::env::var(&key.secret_env_var)
+                            .map_err(|_| format!("{} must be set", key.secret_env_var))?,
+                    ),
+                    roles: key.roles.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+// admin requests are signed with an api key: x-admin-signature is the hex HMAC-SHA256 of
+// "{timestamp}\n{nonce}\n{method}\n{path and query}\n{hex sha256 of the body}" with the key's
+// secret. x-admin-nonce is any unique value of up to 64 characters, a key can use it once.
+// The key needs the role of the route, see get_required_role
 pub struct AdminAuthMiddleware;
 
 impl<S, B> Transform<S, ServiceRequest> for AdminAuthMiddleware
 where
-    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
     S::Future: 'static,
     B: 'static,
 {
@@ -24,17 +78,19 @@ where
     type Future = Ready<Result<Self::Transform, Self::InitError>>;
 
     fn new_transform(&self, service: S) -> Self::Future {
-        ready(Ok(AdminAuthMiddlewareService { service }))
+        ready(Ok(AdminAuthMiddlewareService {
+            service: Arc::new(service),
+        }))
     }
 }
 
 pub struct AdminAuthMiddlewareService<S> {
-    service: S,
+    service: Arc<S>,
 }
 
 impl<S, B> Service<ServiceRequest> for AdminAuthMiddlewareService<S>
 where
-    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
     S::Future: 'static,
     B: 'static,
 {
@@ -44,12 +100,197 @@ where
 
     forward_ready!(service);
 
-    fn call(&self, req: ServiceRequest) -> Self::Future {
-        let header = req.headers().get(HeaderName::from_static("user"));
-        if header.is_none() || "admin" != header.unwrap().to_str().unwrap().to_string() {
-            error!("Unauthorized request");
-            return Box::pin(async { Err(Error::from(ApiError::Unauthorized)) });
-        }
-        Box::pin(self.service.call(req))
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        Box::pin(async move {
+            let admin = match authenticate(&mut req).await {
+                Ok(admin) => admin,
+                Err(err) => {
+                    error!("Unauthorized admin request: {}", err);
+                    return Err(Error::from(AdminError::Unauthorized));
+                }
+            };
+            let role = get_required_role(req.method(), req.path());
+            if !admin.has_role(role) {
+                warn!(
+                    "Admin key {} is missing {:?} for {} {}",
+                    admin.key_id,
+                    role,
+                    req.method(),
+                    req.path()
+                );
+                return Err(Error::from(AdminError::Forbidden));
+            }
+            req.extensions_mut().insert(admin);
+            service.call(req).await
+        })
+    }
+}
+
+async fn authenticate(req: &mut ServiceRequest) -> Result<AdminUser, String> {
+    let key_id = get_header(req, ADMIN_KEY_HEADER)?;
+    let timestamp = get_header(req, ADMIN_TIMESTAMP_HEADER)?
+        .parse::<u64>()
+        .map_err(|_| String::from("Invalid timestamp"))?;
+    let nonce = get_header(req, ADMIN_NONCE_HEADER)?;
+    if nonce.is_empty() || nonce.len() > MAX_NONCE_LEN {
+        return Err(String::from("Invalid nonce"));
+    }
+    let signature = ethers::utils::hex::decode(get_header(req, ADMIN_SIGNATURE_HEADER)?)
+        .map_err(|_| String::from("Invalid signature"))?;
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    if now.abs_diff(timestamp) > CONFIG.admin_auth.max_skew_secs {
+        return Err(format!("Stale request from {}", key_id));
+    }
+    let key = ADMIN_KEYS
+        .iter()
+        .find(|key| key.id == key_id)
+        .ok_or(format!("Unknown admin key: {}", key_id))?;
+
+    let body = req
+        .extract::<Bytes>()
+        .await
+        .map_err(|err| format!("Failed to read body: {:?}", err))?;
+    let path = req
+        .uri()
+        .path_and_query()
+        .map(|path| path.as_str())
+        .unwrap_or(req.path());
+    sign(&key.secret, timestamp, &nonce, req.method(), path, &body)?
+        .verify_slice(&signature)
+        .map_err(|_| format!("Signature mismatch for {}", key_id))?;
+
+    // the timestamp is accepted max_skew_secs either side of now, the nonce has to be kept as
+    // long to reject a replay
+    let pool = req
+        .app_data::<Data<Pool<Postgres>>>()
+        .cloned()
+        .ok_or(String::from("Database pool is not configured"))?;
+    let unused = AdminNonceDao::use_nonce(
+        &pool,
+        key_id.clone(),
+        nonce,
+        CONFIG.admin_auth.max_skew_secs * 2,
+    )
+    .await
+    .map_err(|err| format!("Failed to check nonce: {:?}", err))?;
+    if !unused {
+        return Err(format!("Replayed request from {}", key_id));
+    }
+
+    // the handler reads the body again
+    let payload: Pin<Box<dyn Stream<Item = Result<Bytes, PayloadError>>>> =
+        Box::pin(stream::once(async move { Ok(body) }));
+    req.set_payload(Payload::from(payload));
+    Ok(AdminUser {
+        key_id,
+        roles: key.roles.clone(),
+    })
+}
+
+fn sign(
+    secret: &str,
+    timestamp: u64,
+    nonce: &str,
+    method: &Method,
+    path: &str,
+    body: &[u8],
+) -> Result<Hmac<Sha256>, String> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .map_err(|_| String::from("Invalid admin secret"))?;
+    mac.update(format!("{}\n{}\n{}\n{}\n", timestamp, nonce, method, path).as_bytes());
+    mac.update(ethers::utils::hex::encode(Sha256::digest(body)).as_bytes());
+    Ok(mac)
+}
+
+fn get_header(req: &ServiceRequest, name: &str) -> Result<String, String> {
+    req.headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(String::from)
+        .ok_or(format!("Missing {} header", name))
+}
+
+// reads only need read_only, metadata writes need metadata and everything else moves or guards
+// funds
+fn get_required_role(method: &Method, path: &str) -> AdminRole {
+    let route = path
+        .split_once("/admin/")
+        .map(|(_, route)| route)
+        .unwrap_or_default();
+    if method == Method::GET {
+        AdminRole::ReadOnly
+    } else if route.starts_with("metadata") {
+        AdminRole::Metadata
+    } else {
+        AdminRole::Treasury
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signature(nonce: &str, body: &[u8]) -> String {
+        ethers::utils::hex::encode(
+            sign(
+                "secret",
+                1700000000,
+                nonce,
+                &Method::POST,
+                "/admin/paymaster/deposit?chain=base",
+                body,
+            )
+            .unwrap()
+            .finalize()
+            .into_bytes(),
+        )
+    }
+
+    #[test]
+    fn signs_the_documented_message() {
+        let body = br#"{"amount":"1"}"#;
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"secret").unwrap();
+        mac.update(
+            format!(
+                "1700000000\nnonce-1\nPOST\n/admin/paymaster/deposit?chain=base\n{}",
+                ethers::utils::hex::encode(Sha256::digest(body))
+            )
+            .as_bytes(),
+        );
+        assert_eq!(
+            signature("nonce-1", body),
+            ethers::utils::hex::encode(mac.finalize().into_bytes())
+        );
+    }
+
+    #[test]
+    fn signature_covers_the_nonce_and_body() {
+        let body = br#"{"amount":"1"}"#;
+        assert_ne!(signature("nonce-1", body), signature("nonce-2", body));
+        assert_ne!(
+            signature("nonce-1", body),
+            signature("nonce-1", br#"{"amount":"2"}"#)
+        );
+    }
+
+    #[test]
+    fn routes_require_their_role() {
+        assert_eq!(
+            get_required_role(&Method::GET, "/stg/admin/paymaster/deposit"),
+            AdminRole::ReadOnly
+        );
+        assert_eq!(
+            get_required_role(&Method::POST, "/stg/admin/metadata/tokens"),
+            AdminRole::Metadata
+        );
+        assert_eq!(
+            get_required_role(&Method::POST, "/stg/admin/paymaster/deposit"),
+            AdminRole::Treasury
+        );
     }
 }
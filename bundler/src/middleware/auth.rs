@@ -10,8 +10,10 @@ use std::future::{ready, Future, Ready};
 use std::pin::Pin;
 use std::sync::Arc;
 
-use crate::db::dao::WalletDao;
+use crate::db::dao::{Tenant, User, WalletDao};
 use crate::errors::errors::ApiError;
+use crate::identity::IdentityProviders;
+use crate::middleware::auth_cache::AUTH_CACHE;
 use crate::services::auth_service::AuthService;
 
 pub struct ToadAuthMiddleware;
@@ -75,31 +77,55 @@ where
                 error!("Middleware error: failed to extract token");
                 return Err(Error::from(ApiError::Unauthorized));
             }
-            let data = AuthService::decode_jwt(token.unwrap());
-            if data.is_err() || data.as_ref().unwrap().verifier_id.is_none() {
-                error!("Middleware error: failed to decode token");
+            let token = token.unwrap();
+            // set by TenantMiddleware
+            let tenant = req.extensions().get::<Tenant>().cloned();
+            if tenant.is_none() {
+                error!("Middleware error: request is not scoped to a tenant");
                 return Err(Error::from(ApiError::Unauthorized));
             }
+            let tenant = tenant.unwrap();
+            let identity = match AUTH_CACHE.get_identity(tenant.id, token) {
+                Some(identity) => identity,
+                None => {
+                    let providers = req.app_data::<Data<IdentityProviders>>().cloned();
+                    let provider = providers.unwrap().get(&tenant).await;
+                    if let Err(err) = &provider {
+                        error!("Middleware error: {}", err);
+                        return Err(Error::from(ApiError::Unauthorized));
+                    }
+                    let identity =
+                        AuthService::authenticate(provider.unwrap().as_ref(), token).await;
+                    if identity.is_err() {
+                        error!("Middleware error: failed to authenticate token");
+                        return Err(Error::from(ApiError::Unauthorized));
+                    }
+                    let identity = identity.unwrap();
+                    AUTH_CACHE.put_identity(tenant.id, token, identity.clone());
+                    identity
+                }
+            };
 
-            let verifier_id = data.unwrap().verifier_id.unwrap();
-
-            // Validate verifier_id
-            let user = AuthService::is_valid_id(verifier_id.clone()).await;
-            if user.is_none() {
-                return Err(Error::from(ApiError::Unauthorized));
+            // Fetch user from the cache or the database
+            let mut db_user = AUTH_CACHE.get_user(tenant.id, &identity.external_user_id);
+            if db_user.is_none() {
+                let pool = req.app_data::<Data<Pool<Postgres>>>().cloned();
+                db_user = WalletDao::get_wallet_by_external_user_id(
+                    pool.unwrap().as_ref(),
+                    tenant.id,
+                    identity.external_user_id.clone(),
+                )
+                .await;
+                if let Some(user) = &db_user {
+                    AUTH_CACHE.put_user(user.clone());
+                }
             }
-
-            // Fetch user from the database
-            let pool = req.app_data::<Data<Pool<Postgres>>>().cloned();
-            let mut db_user;
-            db_user = WalletDao::get_wallet_by_external_user_id(
-                pool.unwrap().as_ref(),
-                verifier_id.clone(),
-            )
-            .await;
             if db_user.is_none() {
-                debug!("Probably a new user. Not found on db, but exists on firebase");
-                db_user = Some(crate::db::dao::User::from(user.unwrap()));
+                debug!("Probably a new user. Not found on db, but known to the identity provider");
+                db_user = Some(User {
+                    tenant_id: tenant.id,
+                    ..User::from(identity)
+                });
             }
 
             // Insert db_user into req's extensions
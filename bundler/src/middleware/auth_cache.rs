@@ -0,0 +1,157 @@
+use lazy_static::lazy_static;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::db::dao::{Tenant, User};
+use crate::identity::Identity;
+use crate::CONFIG;
+
+lazy_static! {
+    pub static ref AUTH_CACHE: AuthCache = AuthCache::new(
+        CONFIG.auth_cache.identity_ttl_secs,
+        CONFIG.auth_cache.user_ttl_secs,
+        CONFIG.auth_cache.tenant_ttl_secs,
+        CONFIG.auth_cache.max_entries
+    );
+}
+
+// tenants, verified tokens and the user rows they resolve to, kept per process and per tenant.
+// WalletService and AdminService drop the rows they write, other instances pick the change up once the
+// entry expires
+pub struct AuthCache {
+    identities: TtlMap<Vec<u8>, Identity>,
+    users: TtlMap<(i32, String), User>,
+    // by api key hash, None is the default tenant
+    tenants: TtlMap<Option<String>, Tenant>,
+    identity_ttl: Duration,
+    user_ttl: Duration,
+    tenant_ttl: Duration,
+}
+
+impl AuthCache {
+    pub fn new(
+        identity_ttl_secs: u64,
+        user_ttl_secs: u64,
+        tenant_ttl_secs: u64,
+        max_entries: usize,
+    ) -> Self {
+        Self {
+            identities: TtlMap::new(max_entries),
+            users: TtlMap::new(max_entries),
+            tenants: TtlMap::new(max_entries),
+            identity_ttl: Duration::from_secs(identity_ttl_secs),
+            user_ttl: Duration::from_secs(user_ttl_secs),
+            tenant_ttl: Duration::from_secs(tenant_ttl_secs),
+        }
+    }
+
+    // a token is only good for the tenant whose provider verified it
+    pub fn get_identity(&self, tenant_id: i32, token: &str) -> Option<Identity> {
+        self.identities.get(&Self::get_token_key(tenant_id, token))
+    }
+
+    pub fn put_identity(&self, tenant_id: i32, token: &str, identity: Identity) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let ttl = self
+            .identity_ttl
+            .min(Duration::from_secs(identity.expires_at.saturating_sub(now)));
+        if !ttl.is_zero() {
+            self.identities
+                .insert(Self::get_token_key(tenant_id, token), identity, ttl);
+        }
+    }
+
+    pub fn get_user(&self, tenant_id: i32, external_user_id: &str) -> Option<User> {
+        self.users.get(&(tenant_id, external_user_id.to_string()))
+    }
+
+    pub fn put_user(&self, user: User) {
+        if !self.user_ttl.is_zero() {
+            self.users.insert(
+                (user.tenant_id, user.external_user_id.clone()),
+                user,
+                self.user_ttl,
+            );
+        }
+    }
+
+    pub fn invalidate_user(&self, tenant_id: i32, external_user_id: &str) {
+        self.users.retain(|user| {
+            user.tenant_id != tenant_id || user.external_user_id != external_user_id
+        });
+    }
+
+    pub fn invalidate_wallet(&self, tenant_id: i32, wallet_address: &str) {
+        self.users.retain(|user| {
+            user.tenant_id != tenant_id || !user.wallet_address.eq_ignore_ascii_case(wallet_address)
+        });
+    }
+
+    pub fn get_tenant(&self, api_key_hash: &Option<String>) -> Option<Tenant> {
+        self.tenants.get(api_key_hash)
+    }
+
+    pub fn put_tenant(&self, api_key_hash: Option<String>, tenant: Tenant) {
+        if !self.tenant_ttl.is_zero() {
+            self.tenants.insert(api_key_hash, tenant, self.tenant_ttl);
+        }
+    }
+
+    pub fn invalidate_tenant(&self, tenant_id: i32) {
+        self.tenants.retain(|tenant| tenant.id != tenant_id);
+    }
+
+    // tokens are only held as their hash
+    fn get_token_key(tenant_id: i32, token: &str) -> Vec<u8> {
+        let mut hasher = Sha256::new();
+        hasher.update(tenant_id.to_be_bytes());
+        hasher.update(token.as_bytes());
+        hasher.finalize().to_vec()
+    }
+}
+
+struct TtlMap<K, V> {
+    entries: Mutex<HashMap<K, (V, Instant)>>,
+    max_entries: usize,
+}
+
+impl<K: Eq + Hash, V: Clone> TtlMap<K, V> {
+    fn new(max_entries: usize) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            max_entries,
+        }
+    }
+
+    fn get(&self, key: &K) -> Option<V> {
+        match self.entries.lock().unwrap().get(key) {
+            Some((value, expires_at)) if *expires_at > Instant::now() => Some(value.clone()),
+            _ => None,
+        }
+    }
+
+    fn insert(&self, key: K, value: V, ttl: Duration) {
+        let mut entries = self.entries.lock().unwrap();
+        let now = Instant::now();
+        if entries.len() >= self.max_entries {
+            entries.retain(|_, (_, expires_at)| *expires_at > now);
+            if entries.len() >= self.max_entries {
+                entries.clear();
+            }
+        }
+        entries.insert(key, (value, now + ttl));
+    }
+
+    fn retain(&self, keep: impl Fn(&V) -> bool) {
+        self.entries
+            .lock()
+            .unwrap()
+            .retain(|_, (value, _)| keep(value));
+    }
+}
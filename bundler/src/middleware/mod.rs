@@ -1,2 +1,4 @@
 pub mod admin_auth;
 pub mod auth;
+pub mod auth_cache;
+pub mod tenant;
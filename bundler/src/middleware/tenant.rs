@@ -0,0 +1,102 @@
+use actix_web::web::Data;
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    Error, HttpMessage,
+};
+use futures::future::LocalBoxFuture;
+use log::error;
+use sqlx::{Pool, Postgres};
+use std::future::{ready, Ready};
+use std::sync::Arc;
+
+use crate::constants::Constants;
+use crate::db::dao::{Tenant, TenantDao};
+use crate::errors::errors::ApiError;
+use crate::middleware::auth_cache::AUTH_CACHE;
+use crate::provider::helpers::get_api_key_hash;
+
+// scopes the request to the tenant of its api key, requests without one belong to the default
+// tenant. Must wrap ToadAuthMiddleware, which authenticates against the tenant's provider
+pub struct TenantMiddleware;
+
+impl<S, B> Transform<S, ServiceRequest> for TenantMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = TenantMiddlewareService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(TenantMiddlewareService {
+            service: Arc::new(service),
+        }))
+    }
+}
+
+pub struct TenantMiddlewareService<S> {
+    service: Arc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for TenantMiddlewareService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        Box::pin(async move {
+            let tenant = match get_tenant(&req).await {
+                Ok(tenant) => tenant,
+                Err(err) => {
+                    error!("Unknown tenant: {}", err);
+                    return Err(Error::from(ApiError::Unauthorized));
+                }
+            };
+            req.extensions_mut().insert(tenant);
+            service.call(req).await
+        })
+    }
+}
+
+async fn get_tenant(req: &ServiceRequest) -> Result<Tenant, String> {
+    let api_key_hash = match req.headers().get(Constants::API_KEY_HEADER) {
+        Some(api_key) => Some(get_api_key_hash(
+            api_key
+                .to_str()
+                .map_err(|_| String::from("Invalid api key"))?,
+        )),
+        None => None,
+    };
+    if let Some(tenant) = AUTH_CACHE.get_tenant(&api_key_hash) {
+        return Ok(tenant);
+    }
+    let pool = req
+        .app_data::<Data<Pool<Postgres>>>()
+        .ok_or(String::from("Database pool is not configured"))?;
+    let tenant = match &api_key_hash {
+        Some(api_key_hash) => TenantDao::get_active_tenant(pool, api_key_hash.clone())
+            .await
+            .map_err(|err| format!("{:?}", err))?
+            .ok_or(String::from("Invalid api key"))?,
+        None => TenantDao::get_tenant(pool, Constants::DEFAULT_TENANT_ID)
+            .await
+            .map_err(|err| format!("{:?}", err))?,
+    };
+    if !tenant.is_active {
+        return Err(format!("Tenant {} is not active", tenant.id));
+    }
+    AUTH_CACHE.put_tenant(api_key_hash, tenant.clone());
+    Ok(tenant)
+}
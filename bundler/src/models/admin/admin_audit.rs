@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::db::dao::AdminAuditEntry;
+
+// every filter is optional, from and to are unix timestamps and to is exclusive. id is the last
+// id of the previous page
+#[derive(Deserialize)]
+pub struct AdminAuditRequest {
+    pub actor: Option<String>,
+    pub action: Option<String>,
+    pub outcome: Option<String>,
+    pub from: Option<u64>,
+    pub to: Option<u64>,
+    pub id: Option<i32>,
+    pub page_size: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct AdminAuditResponse {
+    pub id: i32,
+    pub actor: String,
+    pub action: String,
+    pub parameters: Value,
+    pub transaction_hash: Option<String>,
+    pub outcome: String,
+    pub error: Option<String>,
+    pub timestamp: i64,
+}
+
+impl From<AdminAuditEntry> for AdminAuditResponse {
+    fn from(entry: AdminAuditEntry) -> Self {
+        AdminAuditResponse {
+            id: entry.id,
+            actor: entry.actor,
+            action: entry.action,
+            parameters: entry.parameters,
+            transaction_hash: entry.transaction_hash,
+            outcome: entry.outcome,
+            error: entry.error,
+            timestamp: entry.created_at.timestamp(),
+        }
+    }
+}
@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AdminRole {
+    ReadOnly,
+    // paymaster deposits, stakes, clients and signers
+    Treasury,
+    Metadata,
+}
+
+// the admin key a request was signed with, see AdminAuthMiddleware
+#[derive(Clone, Debug)]
+pub struct AdminUser {
+    pub key_id: String,
+    pub roles: Vec<AdminRole>,
+}
+
+impl AdminUser {
+    // every role can read
+    pub fn has_role(&self, role: AdminRole) -> bool {
+        role == AdminRole::ReadOnly || self.roles.contains(&role)
+    }
+}
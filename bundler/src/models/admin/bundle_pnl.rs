@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+// period is one of day, week, month or year and covers the current one, all time if unset
+#[derive(Deserialize)]
+pub struct BundlePnlRequest {
+    pub period: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct BundlePnlResponse {
+    pub chain: String,
+    pub beneficiary: String,
+    pub bundles: i64,
+    pub user_ops: i64,
+    pub gas_cost: String,
+    pub refund: String,
+    pub profit: String,
+    pub currency: String,
+}
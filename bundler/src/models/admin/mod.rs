@@ -1,9 +1,26 @@
 pub mod add_metadata_request;
+pub mod admin_audit;
+pub mod admin_user;
+pub mod bundle_pnl;
 pub mod metadata_response;
 pub mod metadata_response_v2;
+pub mod paymaster_client;
+pub mod paymaster_signer;
+pub mod paymaster_stake;
 pub mod paymaster_topup;
+pub mod tenant;
 
 pub use add_metadata_request::AddMetadataRequest;
+pub use admin_audit::{AdminAuditRequest, AdminAuditResponse};
+pub use admin_user::{AdminRole, AdminUser};
+pub use bundle_pnl::{BundlePnlRequest, BundlePnlResponse};
 pub use metadata_response::MetadataResponse;
 pub use metadata_response_v2::MetadataResponseV2;
+pub use paymaster_client::{PaymasterClientRequest, PaymasterClientResponse};
+pub use paymaster_signer::{PaymasterSignerRotation, ResignedOperation};
+pub use paymaster_stake::{
+    DepositInfoResponse, DepositWithdrawal, PaymasterStake, PaymasterVersionRequest,
+    StakeWithdrawal,
+};
 pub use paymaster_topup::PaymasterTopup;
+pub use tenant::{TenantRequest, TenantResponse};
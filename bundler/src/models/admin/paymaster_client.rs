@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Deserialize, Serialize)]
+pub struct PaymasterClientRequest {
+    pub name: String,
+    // the config's sponsorship_policy section as json, unset falls back to the config
+    pub sponsorship_policy: Option<Value>,
+}
+
+// the api key is only returned when the client is created
+#[derive(Serialize)]
+pub struct PaymasterClientResponse {
+    pub name: String,
+    pub api_key: String,
+}
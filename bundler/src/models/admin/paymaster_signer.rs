@@ -0,0 +1,15 @@
+use ethers::types::Address;
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct PaymasterSignerRotation {
+    pub verifying_signer: Address,
+    pub resigned: Vec<ResignedOperation>,
+}
+
+// the user has to sign the new hash before the transfer can be executed
+#[derive(Serialize)]
+pub struct ResignedOperation {
+    pub transaction_id: String,
+    pub msg_hash: [u8; 32],
+}
@@ -0,0 +1,42 @@
+use ethers::types::Address;
+use serde::{Deserialize, Serialize};
+
+use crate::models::contract_interaction::EntryPointVersion;
+use crate::models::Metadata;
+
+// selects the verifying paymaster, and the EntryPoint it is staked in, of another supported
+// version than the one ops are built for
+#[derive(Deserialize, Serialize)]
+pub struct PaymasterVersionRequest {
+    pub entrypoint_version: Option<EntryPointVersion>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct PaymasterStake {
+    pub value: String,
+    pub unstake_delay_sec: u32,
+    pub metadata: Metadata,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct StakeWithdrawal {
+    pub withdraw_address: Address,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct DepositWithdrawal {
+    pub withdraw_address: Address,
+    pub value: String,
+    pub metadata: Metadata,
+}
+
+#[derive(Serialize)]
+pub struct DepositInfoResponse {
+    pub address: String,
+    pub deposit: String,
+    pub staked: bool,
+    pub stake: String,
+    pub unstake_delay_sec: u32,
+    pub withdraw_time: u64,
+    pub currency: String,
+}
@@ -1,8 +1,8 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::models::Metadata;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 pub struct PaymasterTopup {
     pub value: String,
     pub metadata: Metadata,
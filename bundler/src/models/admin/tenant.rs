@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::db::dao::Tenant;
+
+// unset settings fall back to the global config. An update replaces every setting, the name and
+// api key stay as they were created
+#[derive(Deserialize, Serialize)]
+pub struct TenantRequest {
+    #[serde(default)]
+    pub name: String,
+    pub auth: Option<Value>,
+    pub transaction_id_prefix: Option<String>,
+    pub sponsorship_policy: Option<Value>,
+    // empty means every token
+    #[serde(default)]
+    pub supported_tokens: Vec<String>,
+    pub webhook_url: Option<String>,
+    #[serde(default = "default_is_active")]
+    pub is_active: bool,
+    // replaces the webhook secret, the new one is returned once
+    #[serde(default)]
+    pub rotate_webhook_secret: bool,
+}
+
+// the api key is only returned when the tenant is created, the webhook secret then and when it
+// is rotated
+#[derive(Serialize)]
+pub struct TenantResponse {
+    pub id: i32,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub webhook_secret: Option<String>,
+    pub auth: Option<Value>,
+    pub transaction_id_prefix: Option<String>,
+    pub sponsorship_policy: Option<Value>,
+    pub supported_tokens: Vec<String>,
+    pub webhook_url: Option<String>,
+    pub is_active: bool,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl From<Tenant> for TenantResponse {
+    fn from(tenant: Tenant) -> Self {
+        TenantResponse {
+            id: tenant.id,
+            name: tenant.name,
+            api_key: None,
+            webhook_secret: None,
+            auth: tenant.auth,
+            transaction_id_prefix: tenant.transaction_id_prefix,
+            sponsorship_policy: tenant.sponsorship_policy,
+            supported_tokens: tenant.supported_tokens,
+            webhook_url: tenant.webhook_url,
+            is_active: tenant.is_active,
+            created_at: tenant.created_at.timestamp(),
+            updated_at: tenant.updated_at.timestamp(),
+        }
+    }
+}
+
+fn default_is_active() -> bool {
+    true
+}
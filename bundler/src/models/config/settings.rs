@@ -1,8 +1,10 @@
 use config::{Config, ConfigError, File, Map};
-use ethers::types::Address;
+use ethers::types::{Address, Bytes, U256};
 use serde::Deserialize;
 
+use crate::models::admin::AdminRole;
 use crate::models::config::env::ENV;
+use crate::models::contract_interaction::EntryPointVersion;
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct RunConfig {
@@ -12,6 +14,9 @@ pub struct RunConfig {
     pub paymaster_account_owner: Address,
     pub deployed_by_identifier: String,
     pub transaction_id_prefix: String,
+    // extra relayer keys, bundles, mints and deposits are sent from the least busy one
+    #[serde(default)]
+    pub relayer_pool: Vec<SignerBackend>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -32,10 +37,65 @@ pub struct Chain {
     pub url: String,
     pub explorer_url: String,
     pub simple_account_factory_address: Address,
+    // both are needed to derive account addresses offline, see SimpleAccountFactoryProvider
+    pub simple_account_implementation: Option<Address>,
+    pub account_proxy_creation_code: Option<Bytes>,
+    // accounts are upgraded to this implementation to rotate their owner
+    pub rotatable_account_implementation: Option<Address>,
+    // deploys accounts for passkey owners
+    pub passkey_account_factory_address: Option<Address>,
+    // validates user operations signed by session keys
+    pub session_key_validator_address: Option<Address>,
     pub usdc_address: Address,
     pub currency: String,
     pub entrypoint_address: Address,
+    pub entrypoint_v7_address: Option<Address>,
+    #[serde(default = "default_entrypoint_versions")]
+    pub entrypoint_versions: Vec<EntryPointVersion>,
     pub verifying_paymaster_address: Address,
+    // deployed against EntryPoint v0.7, required when v0.7 is the version ops are built for
+    pub simple_account_factory_v7_address: Option<Address>,
+    pub verifying_paymaster_v7_address: Option<Address>,
+    // receives the handleOps refunds, defaults to the run config's account owner
+    pub beneficiary: Option<Address>,
+    pub token_paymaster: Option<TokenPaymaster>,
+    #[serde(default)]
+    pub sponsorship_policy: SponsorshipPolicy,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct TokenPaymaster {
+    pub address: Address,
+    // markup applied on the oracle price, 100 means no markup (the contract accepts 100 to 200)
+    pub price_markup_percent: u64,
+    pub refund_postop_cost: u64,
+    // the approval added to each batch covers this many times the quoted token cost
+    #[serde(default = "default_approval_multiplier")]
+    pub approval_multiplier: u64,
+}
+
+// limits on ops signed by the verifying paymaster, budgets are in gwei and unset means unlimited.
+// Empty allowlists allow everything
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct SponsorshipPolicy {
+    pub user_daily_budget_gwei: Option<u64>,
+    pub user_monthly_budget_gwei: Option<u64>,
+    pub chain_budget_gwei: Option<u64>,
+    #[serde(default)]
+    pub allowed_currencies: Vec<String>,
+    #[serde(default)]
+    pub allowed_destinations: Vec<Address>,
+    // minimum transfer value per currency, in the currency's smallest unit
+    #[serde(default)]
+    pub minimum_amounts: Map<String, u64>,
+}
+
+fn default_approval_multiplier() -> u64 {
+    2
+}
+
+fn default_entrypoint_versions() -> Vec<EntryPointVersion> {
+    vec![EntryPointVersion::V06]
 }
 
 impl Server {
@@ -58,6 +118,95 @@ impl Chain {
                 .expect("PROVIDER_API_KEY must be set to connect with a node provider")
         )
     }
+
+    // the first declared version is the one new user operations are built for
+    pub fn get_entrypoint_version(&self) -> EntryPointVersion {
+        self.entrypoint_versions
+            .first()
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn supports_entrypoint(&self, version: EntryPointVersion) -> bool {
+        self.entrypoint_versions.contains(&version)
+    }
+
+    pub fn get_entrypoint_address(&self, version: EntryPointVersion) -> Option<Address> {
+        if !self.supports_entrypoint(version) {
+            return None;
+        }
+        match version {
+            EntryPointVersion::V06 => Some(self.entrypoint_address),
+            EntryPointVersion::V07 => self.entrypoint_v7_address,
+        }
+    }
+
+    pub fn get_verifying_paymaster_address(&self, version: EntryPointVersion) -> Option<Address> {
+        match version {
+            EntryPointVersion::V06 => Some(self.verifying_paymaster_address),
+            EntryPointVersion::V07 => self.verifying_paymaster_v7_address,
+        }
+    }
+
+    pub fn is_verifying_paymaster(&self, address: Address) -> bool {
+        address == self.verifying_paymaster_address
+            || Some(address) == self.verifying_paymaster_v7_address
+    }
+
+    // accounts are created by the factory of the version ops are built for, see validate
+    pub fn get_simple_account_factory_address(&self) -> Address {
+        match self.get_entrypoint_version() {
+            EntryPointVersion::V06 => self.simple_account_factory_address,
+            EntryPointVersion::V07 => self.simple_account_factory_v7_address.unwrap_or_default(),
+        }
+    }
+
+    // v0.6 contracts can't serve v0.7 ops, so a chain building v0.7 ops needs all of them
+    fn validate(&self, name: &str) -> Result<(), ConfigError> {
+        // offline address derivation needs both settings, with neither the factory is asked over
+        // rpc, see SimpleAccountFactoryProvider
+        if self.simple_account_implementation.is_some()
+            != self.account_proxy_creation_code.is_some()
+        {
+            return Err(ConfigError::Message(format!(
+                "chains.{} has to set both or neither of simple_account_implementation and \
+                account_proxy_creation_code",
+                name
+            )));
+        }
+        if self.get_entrypoint_version() != EntryPointVersion::V07 {
+            return Ok(());
+        }
+        let missing: Vec<&str> = [
+            ("entrypoint_v7_address", self.entrypoint_v7_address),
+            (
+                "simple_account_factory_v7_address",
+                self.simple_account_factory_v7_address,
+            ),
+            (
+                "verifying_paymaster_v7_address",
+                self.verifying_paymaster_v7_address,
+            ),
+        ]
+        .into_iter()
+        .filter(|(_, address)| address.is_none())
+        .map(|(field, _)| field)
+        .collect();
+        if missing.is_empty() {
+            return Ok(());
+        }
+        Err(ConfigError::Message(format!(
+            "chains.{} builds v0.7 ops but does not set {}",
+            name,
+            missing.join(", ")
+        )))
+    }
+}
+
+impl TokenPaymaster {
+    pub fn get_price_markup(&self) -> U256 {
+        U256::from(self.price_markup_percent) * U256::exp10(24)
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -67,6 +216,328 @@ pub struct DefaultGas {
     pub pre_verification_gas: u64,
     pub max_fee_per_gas: u64,
     pub max_priority_fee_per_gas: u64,
+    #[serde(default)]
+    pub paymaster_verification_gas_limit: u64,
+    #[serde(default)]
+    pub paymaster_post_op_gas_limit: u64,
+}
+
+// amounts are in ether, the treasury key is only needed when auto_topup is set
+#[derive(Debug, Deserialize, Clone)]
+pub struct BalanceMonitor {
+    #[serde(default = "default_monitor_interval_secs")]
+    pub interval_secs: u64,
+    pub paymaster_deposit_threshold: String,
+    pub paymaster_topup_amount: String,
+    pub relayer_balance_threshold: String,
+    pub relayer_topup_amount: String,
+    #[serde(default)]
+    pub auto_topup: bool,
+    pub webhook_url: Option<String>,
+}
+
+fn default_monitor_interval_secs() -> u64 {
+    300
+}
+
+// periodically corrects users.deployed against the code at each wallet address
+#[derive(Debug, Deserialize, Clone)]
+pub struct WalletReconciliation {
+    #[serde(default = "default_reconciliation_interval_secs")]
+    pub interval_secs: u64,
+}
+
+fn default_reconciliation_interval_secs() -> u64 {
+    3600
+}
+
+// settles the sponsorships of paymaster client ops from their UserOperationEvent, ops are found
+// if they were mined within the last lookback_blocks
+#[derive(Debug, Deserialize, Clone)]
+pub struct SponsorshipReconciliation {
+    #[serde(default = "default_sponsorship_reconciliation_interval_secs")]
+    pub interval_secs: u64,
+    #[serde(default = "default_sponsorship_reconciliation_lookback_blocks")]
+    pub lookback_blocks: u64,
+}
+
+impl Default for SponsorshipReconciliation {
+    fn default() -> Self {
+        Self {
+            interval_secs: default_sponsorship_reconciliation_interval_secs(),
+            lookback_blocks: default_sponsorship_reconciliation_lookback_blocks(),
+        }
+    }
+}
+
+fn default_sponsorship_reconciliation_interval_secs() -> u64 {
+    300
+}
+
+fn default_sponsorship_reconciliation_lookback_blocks() -> u64 {
+    5000
+}
+
+// guardian recovery, approved requests are submitted once the delay has passed
+#[derive(Debug, Deserialize, Clone)]
+pub struct Recovery {
+    #[serde(default = "default_recovery_delay_secs")]
+    pub delay_secs: u64,
+    #[serde(default = "default_recovery_interval_secs")]
+    pub interval_secs: u64,
+}
+
+impl Default for Recovery {
+    fn default() -> Self {
+        Self {
+            delay_secs: default_recovery_delay_secs(),
+            interval_secs: default_recovery_interval_secs(),
+        }
+    }
+}
+
+fn default_recovery_delay_secs() -> u64 {
+    172800
+}
+
+fn default_recovery_interval_secs() -> u64 {
+    60
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct SessionKeys {
+    #[serde(default = "default_session_max_duration_secs")]
+    pub max_duration_secs: u64,
+}
+
+impl Default for SessionKeys {
+    fn default() -> Self {
+        Self {
+            max_duration_secs: default_session_max_duration_secs(),
+        }
+    }
+}
+
+fn default_session_max_duration_secs() -> u64 {
+    604800
+}
+
+// WebAuthn relying party, passkey assertions are only accepted for this rp id and origins
+#[derive(Debug, Deserialize, Clone)]
+pub struct Passkey {
+    pub rp_id: String,
+    #[serde(default)]
+    pub origins: Vec<String>,
+    #[serde(default)]
+    pub require_user_verification: bool,
+}
+
+// identity provider users authenticate with, see identity::load
+#[derive(Debug, Deserialize, Clone)]
+#[serde(tag = "provider", rename_all = "snake_case")]
+pub enum Auth {
+    // web3auth id tokens, the user id claim names a firebase user. Needs the firebase feature
+    Firebase(JwtIssuer),
+    // any OpenID Connect issuer, the user comes from the token claims
+    Oidc(JwtIssuer),
+    // development only, tokens signed with a static key set for the users listed in a file
+    Local(LocalIdentity),
+}
+
+// issuer of the id tokens, the accepted audiences can be set with AUTH_AUDIENCE
+#[derive(Debug, Deserialize, Clone)]
+pub struct JwtIssuer {
+    pub jwks_url: String,
+    pub issuer: String,
+    pub audience: Vec<String>,
+    // defaults to the provider's, verifierId for firebase and sub for oidc
+    pub user_id_claim: Option<String>,
+    // used when the key set response has no Cache-Control max-age
+    #[serde(default = "default_jwks_max_age_secs")]
+    pub jwks_max_age_secs: u64,
+    // unknown key ids refetch the key set at most this often
+    #[serde(default = "default_jwks_min_refresh_secs")]
+    pub jwks_min_refresh_secs: u64,
+}
+
+fn default_jwks_max_age_secs() -> u64 {
+    3600
+}
+
+fn default_jwks_min_refresh_secs() -> u64 {
+    60
+}
+
+// HMAC keys for the admin endpoints, see AdminAuthMiddleware
+#[derive(Debug, Deserialize, Clone)]
+pub struct AdminAuth {
+    #[serde(default)]
+    pub keys: Vec<AdminKey>,
+    // signed requests are accepted this long around their timestamp
+    #[serde(default = "default_admin_max_skew_secs")]
+    pub max_skew_secs: u64,
+}
+
+impl AdminAuth {
+    // without keys every admin request is rejected, only development may run that way
+    fn validate(&self, env: &ENV) -> Result<(), ConfigError> {
+        match (env, self.keys.is_empty()) {
+            (ENV::Development, _) | (_, false) => Ok(()),
+            _ => Err(ConfigError::Message(format!(
+                "admin_auth.keys must be set in {}",
+                env
+            ))),
+        }
+    }
+}
+
+impl Default for AdminAuth {
+    fn default() -> Self {
+        Self {
+            keys: vec![],
+            max_skew_secs: default_admin_max_skew_secs(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct AdminKey {
+    pub id: String,
+    pub secret_env_var: String,
+    pub roles: Vec<AdminRole>,
+}
+
+fn default_admin_max_skew_secs() -> u64 {
+    300
+}
+
+// authenticated requests reuse verified tokens, user rows and tenants for this long, tokens never
+// outlive their exp
+#[derive(Debug, Deserialize, Clone)]
+pub struct AuthCache {
+    #[serde(default = "default_identity_ttl_secs")]
+    pub identity_ttl_secs: u64,
+    #[serde(default = "default_user_ttl_secs")]
+    pub user_ttl_secs: u64,
+    #[serde(default = "default_tenant_ttl_secs")]
+    pub tenant_ttl_secs: u64,
+    #[serde(default = "default_auth_cache_max_entries")]
+    pub max_entries: usize,
+}
+
+impl Default for AuthCache {
+    fn default() -> Self {
+        Self {
+            identity_ttl_secs: default_identity_ttl_secs(),
+            user_ttl_secs: default_user_ttl_secs(),
+            tenant_ttl_secs: default_tenant_ttl_secs(),
+            max_entries: default_auth_cache_max_entries(),
+        }
+    }
+}
+
+fn default_identity_ttl_secs() -> u64 {
+    300
+}
+
+fn default_user_ttl_secs() -> u64 {
+    60
+}
+
+fn default_tenant_ttl_secs() -> u64 {
+    60
+}
+
+fn default_auth_cache_max_entries() -> usize {
+    10000
+}
+
+// key_set_path is a JWKS file, users_path a JSON list of { id, name, email }. Tokens carry the
+// user id in sub
+#[derive(Debug, Deserialize, Clone)]
+pub struct LocalIdentity {
+    pub key_set_path: String,
+    pub users_path: String,
+    pub issuer: String,
+    #[serde(default = "default_local_audience")]
+    pub audience: Vec<String>,
+}
+
+fn default_local_audience() -> Vec<String> {
+    vec![String::from("toad-local")]
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(tag = "backend", rename_all = "snake_case")]
+pub enum SignerBackend {
+    Env {
+        env_var: String,
+    },
+    // encrypted JSON keystore, the password is read from an env var
+    Keystore {
+        path: String,
+        password_env_var: String,
+    },
+    // signs 32 byte digests over http, see signer::RemoteSigner
+    Remote {
+        url: String,
+        address: Address,
+        auth_token_env_var: Option<String>,
+    },
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct SignerSettings {
+    #[serde(default = "default_relayer_signer")]
+    pub relayer: SignerBackend,
+    #[serde(default = "default_relayer_signer")]
+    pub bundler: SignerBackend,
+    #[serde(default = "default_paymaster_signer")]
+    pub paymaster: SignerBackend,
+    // older paymaster keys, kept to sign for the on chain verifying signer until it is rotated
+    #[serde(default)]
+    pub previous_paymasters: Vec<SignerBackend>,
+    #[serde(default = "default_treasury_signer")]
+    pub treasury: SignerBackend,
+    #[serde(default = "default_session_signer")]
+    pub session: SignerBackend,
+}
+
+impl Default for SignerSettings {
+    fn default() -> Self {
+        Self {
+            relayer: default_relayer_signer(),
+            bundler: default_relayer_signer(),
+            paymaster: default_paymaster_signer(),
+            previous_paymasters: vec![],
+            treasury: default_treasury_signer(),
+            session: default_session_signer(),
+        }
+    }
+}
+
+fn default_relayer_signer() -> SignerBackend {
+    SignerBackend::Env {
+        env_var: String::from("WALLET_PRIVATE_KEY"),
+    }
+}
+
+fn default_paymaster_signer() -> SignerBackend {
+    SignerBackend::Env {
+        env_var: String::from("VERIFYING_PAYMASTER_PRIVATE_KEY"),
+    }
+}
+
+fn default_treasury_signer() -> SignerBackend {
+    SignerBackend::Env {
+        env_var: String::from("TREASURY_PRIVATE_KEY"),
+    }
+}
+
+fn default_session_signer() -> SignerBackend {
+    SignerBackend::Env {
+        env_var: String::from("SESSION_KEY_PRIVATE_KEY"),
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -76,7 +547,22 @@ pub struct Settings {
     pub server: Server,
     pub chains: Map<String, Chain>,
     pub default_gas: DefaultGas,
-    pub admins: Vec<String>,
+    pub balance_monitor: Option<BalanceMonitor>,
+    pub wallet_reconciliation: Option<WalletReconciliation>,
+    #[serde(default)]
+    pub sponsorship_reconciliation: SponsorshipReconciliation,
+    #[serde(default)]
+    pub recovery: Recovery,
+    pub auth: Auth,
+    #[serde(default)]
+    pub auth_cache: AuthCache,
+    pub passkey: Option<Passkey>,
+    #[serde(default)]
+    pub session_keys: SessionKeys,
+    #[serde(default)]
+    pub signers: SignerSettings,
+    #[serde(default)]
+    pub admin_auth: AdminAuth,
     pub env: ENV,
 }
 
@@ -86,27 +572,36 @@ const CONFIG_FILE_PREFIX: &str = "./config";
 impl Settings {
     pub fn new() -> Result<Self, ConfigError> {
         let env = std::env::var("RUN_ENV").unwrap_or_else(|_| "Development".into());
-        let admins_env = std::env::var("ADMIN").expect("ADMIN env variable not set");
-        let admins = admins_env
-            .split(",")
-            .map(|s| s.to_string())
-            .collect::<Vec<String>>();
-
-        let s = Config::builder()
+        let mut builder = Config::builder()
             .add_source(File::with_name(CONFIG_FILE_PATH).required(false))
             .add_source(File::with_name(&format!("{}/{}", CONFIG_FILE_PREFIX, env)).required(false))
-            .set_override("env", env)?
-            .set_override("admins", admins)?
-            .build()?;
+            .set_override("env", env)?;
+        if let Ok(audience) = std::env::var("AUTH_AUDIENCE") {
+            builder = builder.set_override(
+                "auth.audience",
+                audience
+                    .split(',')
+                    .map(|s| s.to_string())
+                    .collect::<Vec<String>>(),
+            )?;
+        }
+        let s = builder.build()?;
 
-        s.try_deserialize()
+        let settings: Settings = s.try_deserialize()?;
+        if let Some(chain) = settings.chains.get(&settings.run_config.current_chain) {
+            chain.validate(&settings.run_config.current_chain)?;
+        }
+        settings.admin_auth.validate(&settings.env)?;
+        Ok(settings)
     }
 
     pub fn get_chain(&self) -> &Chain {
         &self.chains[&self.run_config.current_chain]
     }
 
-    pub fn get_admins(&self) -> &Vec<String> {
-        &self.admins
+    pub fn get_beneficiary(&self) -> Address {
+        self.get_chain()
+            .beneficiary
+            .unwrap_or(self.run_config.account_owner)
     }
 }
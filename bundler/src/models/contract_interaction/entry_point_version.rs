@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EntryPointVersion {
+    #[default]
+    #[serde(rename = "v0.6")]
+    V06,
+    #[serde(rename = "v0.7")]
+    V07,
+}
+
+impl fmt::Display for EntryPointVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EntryPointVersion::V06 => write!(f, "v0.6"),
+            EntryPointVersion::V07 => write!(f, "v0.7"),
+        }
+    }
+}
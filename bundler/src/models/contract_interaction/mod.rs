@@ -1,3 +1,7 @@
+pub mod entry_point_version;
+pub mod packed_user_operation;
 pub mod user_operation;
 
+pub use entry_point_version::EntryPointVersion;
+pub use packed_user_operation::PackedUserOperation;
 pub use user_operation::*;
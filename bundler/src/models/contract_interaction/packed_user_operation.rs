@@ -0,0 +1,118 @@
+use ethers::abi::{encode, AbiEncode, Token};
+use ethers::types::{Address, Bytes, H256, U256};
+use ethers::utils::keccak256;
+use serde::{Deserialize, Serialize};
+use std::ops::Deref;
+
+use crate::models::contract_interaction::UserOperation;
+use crate::CONFIG;
+
+// EntryPoint v0.7 layout: gas limits and fees are packed as two uint128 values into a bytes32
+// and paymasterAndData carries the paymaster's own verification and post op gas limits
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+pub struct PackedUserOperation {
+    pub sender: Address,
+    pub nonce: U256,
+    pub init_code: Bytes,
+    pub calldata: Bytes,
+    pub account_gas_limits: H256,
+    pub pre_verification_gas: U256,
+    pub gas_fees: H256,
+    pub paymaster_and_data: Bytes,
+    pub signature: Bytes,
+}
+
+impl PackedUserOperation {
+    pub fn pack_without_signature(&self) -> Bytes {
+        encode(&[
+            Token::Address(self.sender),
+            Token::Uint(self.nonce),
+            Token::FixedBytes(keccak256(self.init_code.deref()).to_vec()),
+            Token::FixedBytes(keccak256(self.calldata.deref()).to_vec()),
+            Token::FixedBytes(self.account_gas_limits.as_bytes().to_vec()),
+            Token::Uint(self.pre_verification_gas),
+            Token::FixedBytes(self.gas_fees.as_bytes().to_vec()),
+            Token::FixedBytes(keccak256(self.paymaster_and_data.deref()).to_vec()),
+        ])
+        .into()
+    }
+
+    pub fn hash(&self, entry_point: Address, chain_id: u64) -> [u8; 32] {
+        keccak256(
+            [
+                keccak256(self.pack_without_signature().deref()).to_vec(),
+                entry_point.encode(),
+                chain_id.encode(),
+            ]
+            .concat(),
+        )
+    }
+
+    pub fn gas_limits(
+        &mut self,
+        verification_gas_limit: u64,
+        call_gas_limit: u64,
+    ) -> &mut PackedUserOperation {
+        self.account_gas_limits = pack_u128(verification_gas_limit, call_gas_limit);
+        self
+    }
+
+    pub fn gas_fees(
+        &mut self,
+        max_priority_fee_per_gas: u64,
+        max_fee_per_gas: u64,
+    ) -> &mut PackedUserOperation {
+        self.gas_fees = pack_u128(max_priority_fee_per_gas, max_fee_per_gas);
+        self
+    }
+
+    pub fn paymaster_and_data(
+        &mut self,
+        paymaster: Address,
+        paymaster_verification_gas_limit: u64,
+        paymaster_post_op_gas_limit: u64,
+        data: ethers::abi::Bytes,
+    ) -> &mut PackedUserOperation {
+        self.paymaster_and_data = Bytes::from(
+            [
+                paymaster.as_bytes(),
+                &(paymaster_verification_gas_limit as u128).to_be_bytes(),
+                &(paymaster_post_op_gas_limit as u128).to_be_bytes(),
+                &data,
+            ]
+            .concat(),
+        );
+        self
+    }
+}
+
+impl From<UserOperation> for PackedUserOperation {
+    fn from(value: UserOperation) -> Self {
+        let mut user_op = Self {
+            sender: value.sender,
+            nonce: U256::from(value.nonce),
+            init_code: value.init_code,
+            calldata: value.calldata,
+            pre_verification_gas: U256::from(value.pre_verification_gas),
+            signature: value.signature,
+            ..Default::default()
+        };
+        user_op
+            .gas_limits(value.verification_gas_limit, value.call_gas_limit)
+            .gas_fees(value.max_priority_fee_per_gas, value.max_fee_per_gas);
+        if value.paymaster_and_data.len() >= Address::len_bytes() {
+            let (paymaster, data) = value.paymaster_and_data.split_at(Address::len_bytes());
+            user_op.paymaster_and_data(
+                Address::from_slice(paymaster),
+                CONFIG.default_gas.paymaster_verification_gas_limit,
+                CONFIG.default_gas.paymaster_post_op_gas_limit,
+                data.to_vec(),
+            );
+        }
+        user_op
+    }
+}
+
+fn pack_u128(high: u64, low: u64) -> H256 {
+    H256::from_slice(&[(high as u128).to_be_bytes(), (low as u128).to_be_bytes()].concat())
+}
@@ -1,11 +1,12 @@
 use ethers::abi::AbiEncode;
 use ethers::contract::{Eip712, EthAbiType};
 use ethers::prelude::EthAbiCodec;
-use ethers::types::{Address, Bytes, H256};
+use ethers::types::{Address, Bytes, H256, U256};
 use ethers::utils::keccak256;
 use serde::{Deserialize, Serialize};
 use std::ops::Deref;
 
+use crate::models::contract_interaction::{EntryPointVersion, PackedUserOperation};
 use crate::CONFIG;
 
 #[derive(Clone, Default, Debug, EthAbiType, Eip712, Serialize, Deserialize)]
@@ -56,6 +57,28 @@ impl UserOperation {
         )
     }
 
+    pub fn hash_for_version(
+        &self,
+        version: EntryPointVersion,
+        entry_point: Address,
+        chain_id: u64,
+    ) -> [u8; 32] {
+        match version {
+            EntryPointVersion::V06 => self.hash(entry_point, chain_id),
+            EntryPointVersion::V07 => {
+                PackedUserOperation::from(self.clone()).hash(entry_point, chain_id)
+            }
+        }
+    }
+
+    // the max cost EntryPoint v0.6 requires from a paymaster, verification gas is charged thrice
+    pub fn get_paymaster_prefund(&self) -> U256 {
+        let required_gas = U256::from(self.call_gas_limit)
+            + U256::from(self.verification_gas_limit) * U256::from(3)
+            + U256::from(self.pre_verification_gas);
+        required_gas * U256::from(self.max_fee_per_gas)
+    }
+
     pub fn init_code(
         &mut self,
         factory_address: Address,
@@ -92,12 +115,30 @@ impl UserOperation {
         self
     }
 
+    pub fn token_paymaster_and_data(
+        &mut self,
+        paymaster: Address,
+        price: U256,
+    ) -> &mut UserOperation {
+        let mut price_bytes = [0u8; 32];
+        price.to_big_endian(&mut price_bytes);
+        self.paymaster_and_data = Bytes::from([paymaster.as_bytes(), &price_bytes].concat());
+        self
+    }
+
     pub fn signature(&mut self, signature: Bytes) -> &mut UserOperation {
         self.signature = signature;
         self
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct UserOpsPerAggregator {
+    pub user_ops: Vec<UserOperation>,
+    pub aggregator: Address,
+    pub signature: Bytes,
+}
+
 #[derive(EthAbiCodec, EthAbiType)]
 pub struct UserOperationUnsigned {
     pub sender: Address,
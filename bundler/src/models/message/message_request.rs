@@ -0,0 +1,17 @@
+use ethers::types::transaction::eip712::TypedData;
+use ethers::types::Bytes;
+use serde::Deserialize;
+
+// exactly one of an EIP-191 message (eg: a SIWE message) or EIP-712 typed data
+#[derive(Deserialize)]
+pub struct MessageRequest {
+    pub message: Option<String>,
+    pub typed_data: Option<TypedData>,
+}
+
+#[derive(Deserialize)]
+pub struct MessageSignRequest {
+    #[serde(flatten)]
+    pub message: MessageRequest,
+    pub signature: Bytes,
+}
@@ -0,0 +1,18 @@
+use ethers::types::transaction::eip712::TypedData;
+use ethers::types::{Bytes, H256};
+use serde::Serialize;
+
+// hash is what the dApp passes to isValidSignature, the owner signs typed_data (owner_hash)
+#[derive(Serialize)]
+pub struct MessagePrepareResponse {
+    pub hash: H256,
+    pub owner_hash: H256,
+    pub typed_data: TypedData,
+}
+
+// signature is the owner's signature over owner_hash, the wallet accepted it for hash
+#[derive(Serialize)]
+pub struct MessageSignResponse {
+    pub hash: H256,
+    pub signature: Bytes,
+}
@@ -0,0 +1,5 @@
+pub mod message_request;
+pub mod message_response;
+
+pub use message_request::{MessageRequest, MessageSignRequest};
+pub use message_response::{MessagePrepareResponse, MessageSignResponse};
@@ -3,8 +3,12 @@ pub mod config;
 pub mod contract_interaction;
 pub mod currency;
 pub mod hello_world;
+pub mod message;
 pub mod metadata;
+pub mod paymaster;
+pub mod recovery;
 pub mod response;
+pub mod session;
 pub mod transaction;
 pub mod transaction_type;
 pub mod transfer;
@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::errors::PaymasterError;
+
+#[derive(Deserialize)]
+pub struct JsonRpcRequest {
+    pub jsonrpc: String,
+    pub id: Value,
+    pub method: String,
+    #[serde(default)]
+    pub params: Vec<Value>,
+}
+
+#[derive(Serialize)]
+pub struct JsonRpcResponse {
+    pub jsonrpc: String,
+    pub id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<JsonRpcError>,
+}
+
+#[derive(Serialize)]
+pub struct JsonRpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl JsonRpcResponse {
+    pub fn result(id: Value, result: Value) -> JsonRpcResponse {
+        JsonRpcResponse {
+            jsonrpc: String::from("2.0"),
+            id,
+            result: Some(result),
+            error: None,
+        }
+    }
+
+    pub fn error(id: Value, error: PaymasterError) -> JsonRpcResponse {
+        JsonRpcResponse {
+            jsonrpc: String::from("2.0"),
+            id,
+            result: None,
+            error: Some(JsonRpcError {
+                code: error.code(),
+                message: error.message(),
+            }),
+        }
+    }
+}
@@ -0,0 +1,7 @@
+pub mod json_rpc;
+pub mod paymaster_data_response;
+pub mod rpc_user_operation;
+
+pub use json_rpc::{JsonRpcError, JsonRpcRequest, JsonRpcResponse};
+pub use paymaster_data_response::{PaymasterDataResponse, PaymasterStubDataResponse, Sponsor};
+pub use rpc_user_operation::RpcUserOperation;
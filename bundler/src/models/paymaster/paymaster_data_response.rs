@@ -0,0 +1,21 @@
+use ethers::types::Bytes;
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct Sponsor {
+    pub name: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymasterStubDataResponse {
+    pub sponsor: Sponsor,
+    pub paymaster_and_data: Bytes,
+    pub is_final: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymasterDataResponse {
+    pub paymaster_and_data: Bytes,
+}
@@ -0,0 +1,49 @@
+use ethers::types::{Address, Bytes, U256};
+use serde::Deserialize;
+
+use crate::models::contract_interaction::UserOperation;
+
+// EntryPoint v0.6 user operation as sent over JSON-RPC, numbers are hex encoded
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcUserOperation {
+    pub sender: Address,
+    pub nonce: U256,
+    pub init_code: Bytes,
+    pub call_data: Bytes,
+    pub call_gas_limit: U256,
+    pub verification_gas_limit: U256,
+    pub pre_verification_gas: U256,
+    pub max_fee_per_gas: U256,
+    pub max_priority_fee_per_gas: U256,
+    #[serde(default)]
+    pub paymaster_and_data: Bytes,
+    #[serde(default)]
+    pub signature: Bytes,
+}
+
+impl TryFrom<RpcUserOperation> for UserOperation {
+    type Error = String;
+
+    fn try_from(value: RpcUserOperation) -> Result<Self, Self::Error> {
+        let to_u64 = |name: &str, number: U256| {
+            u64::try_from(number).map_err(|_| format!("{} is too large", name))
+        };
+        Ok(UserOperation {
+            sender: value.sender,
+            nonce: to_u64("nonce", value.nonce)?,
+            init_code: value.init_code,
+            calldata: value.call_data,
+            call_gas_limit: to_u64("callGasLimit", value.call_gas_limit)?,
+            verification_gas_limit: to_u64("verificationGasLimit", value.verification_gas_limit)?,
+            pre_verification_gas: to_u64("preVerificationGas", value.pre_verification_gas)?,
+            max_fee_per_gas: to_u64("maxFeePerGas", value.max_fee_per_gas)?,
+            max_priority_fee_per_gas: to_u64(
+                "maxPriorityFeePerGas",
+                value.max_priority_fee_per_gas,
+            )?,
+            paymaster_and_data: value.paymaster_and_data,
+            signature: value.signature,
+        })
+    }
+}
@@ -0,0 +1,8 @@
+use ethers::types::Address;
+use serde::Deserialize;
+
+// the change is an op on the account, signed by the owner like a transfer
+#[derive(Deserialize)]
+pub struct GuardianRequest {
+    pub guardian: Address,
+}
@@ -0,0 +1,7 @@
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct GuardiansResponse {
+    pub guardians: Vec<String>,
+    pub threshold: usize,
+}
@@ -0,0 +1,11 @@
+pub mod guardian_request;
+pub mod guardians_response;
+pub mod recovery_request;
+pub mod recovery_response;
+pub mod recovery_status;
+
+pub use guardian_request::GuardianRequest;
+pub use guardians_response::GuardiansResponse;
+pub use recovery_request::{RecoveryApprovalRequest, RecoveryCancelRequest, RecoveryInitRequest};
+pub use recovery_response::RecoveryResponse;
+pub use recovery_status::RecoveryStatus;
@@ -0,0 +1,18 @@
+use ethers::types::{Address, Bytes};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct RecoveryInitRequest {
+    pub new_owner: Address,
+}
+
+#[derive(Deserialize)]
+pub struct RecoveryApprovalRequest {
+    pub guardian: Address,
+    pub signature: Bytes,
+}
+
+#[derive(Deserialize)]
+pub struct RecoveryCancelRequest {
+    pub signature: Bytes,
+}
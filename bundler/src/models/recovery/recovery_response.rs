@@ -0,0 +1,16 @@
+use serde::Serialize;
+
+// guardians approve by signing recovery_hash as an EIP-191 message, execute_after is a unix
+// timestamp
+#[derive(Serialize)]
+pub struct RecoveryResponse {
+    pub request_id: String,
+    pub wallet_address: String,
+    pub new_owner: String,
+    pub status: String,
+    pub approvals: i64,
+    pub threshold: usize,
+    pub execute_after: Option<i64>,
+    pub transaction_id: Option<String>,
+    pub recovery_hash: String,
+}
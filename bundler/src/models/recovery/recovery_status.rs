@@ -0,0 +1,13 @@
+use derive_more::Display;
+
+#[derive(Display)]
+pub enum RecoveryStatus {
+    #[display(fmt = "pending")]
+    Pending,
+    #[display(fmt = "approved")]
+    Approved,
+    #[display(fmt = "submitted")]
+    Submitted,
+    #[display(fmt = "cancelled")]
+    Cancelled,
+}
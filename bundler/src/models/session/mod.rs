@@ -0,0 +1,7 @@
+pub mod session_request;
+pub mod session_response;
+pub mod session_status;
+
+pub use session_request::{SessionActivateRequest, SessionRequest, SpendLimit};
+pub use session_response::{SessionResponse, SessionRevokeResponse, SpendLimitResponse};
+pub use session_status::SessionStatus;
@@ -0,0 +1,25 @@
+use ethers::types::{Address, Bytes};
+use serde::Deserialize;
+
+// without a session_key the backend's session key is authorized and signs for the session
+#[derive(Deserialize)]
+pub struct SessionRequest {
+    pub session_key: Option<Address>,
+    pub valid_after: Option<u64>,
+    pub valid_until: u64,
+    pub spend_limits: Vec<SpendLimit>,
+    #[serde(default)]
+    pub allowed_targets: Vec<Address>,
+}
+
+// amount is in the currency's smallest unit, like transfer values
+#[derive(Deserialize)]
+pub struct SpendLimit {
+    pub currency: String,
+    pub amount: String,
+}
+
+#[derive(Deserialize)]
+pub struct SessionActivateRequest {
+    pub signature: Bytes,
+}
@@ -0,0 +1,31 @@
+use serde::Serialize;
+
+use crate::models::transfer::TransferInitResponse;
+
+// the owner activates the session by signing permission_hash, times are unix timestamps
+#[derive(Serialize)]
+pub struct SessionResponse {
+    pub session_id: String,
+    pub wallet_address: String,
+    pub session_key: String,
+    pub valid_after: i64,
+    pub valid_until: i64,
+    pub allowed_targets: Vec<String>,
+    pub spend_limits: Vec<SpendLimitResponse>,
+    pub permission_hash: String,
+    pub status: String,
+}
+
+#[derive(Serialize)]
+pub struct SpendLimitResponse {
+    pub currency: String,
+    pub amount: String,
+    pub spent: String,
+}
+
+// revocation is immediate here, the owner signs revocation.msg_hash to revoke it on chain too
+#[derive(Serialize)]
+pub struct SessionRevokeResponse {
+    pub session: SessionResponse,
+    pub revocation: Option<TransferInitResponse>,
+}
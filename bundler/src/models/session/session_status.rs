@@ -0,0 +1,11 @@
+use derive_more::Display;
+
+#[derive(Display)]
+pub enum SessionStatus {
+    #[display(fmt = "pending")]
+    Pending,
+    #[display(fmt = "active")]
+    Active,
+    #[display(fmt = "revoked")]
+    Revoked,
+}
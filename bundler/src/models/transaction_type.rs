@@ -1,11 +1,21 @@
 pub enum TransactionType {
     Debit,
+    Deploy,
+    OwnerRotation,
+    Recovery,
+    GuardianChange,
+    SessionRevocation,
 }
 
 impl TransactionType {
     pub fn to_string(&self) -> String {
         match self {
             Self::Debit => String::from("debit"),
+            Self::Deploy => String::from("deploy"),
+            Self::OwnerRotation => String::from("owner_rotation"),
+            Self::Recovery => String::from("recovery"),
+            Self::GuardianChange => String::from("guardian_change"),
+            Self::SessionRevocation => String::from("session_revocation"),
         }
     }
 }
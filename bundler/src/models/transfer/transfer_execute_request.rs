@@ -1,10 +1,15 @@
 use ethers::types::Bytes;
 use serde::{Deserialize, Serialize};
 
+use crate::models::wallet::PasskeyAssertion;
+
 #[derive(Serialize, Deserialize)]
 pub struct TransferExecuteRequest {
     pub transaction_id: String,
     pub signature: Vec<u8>,
+    // replaces the signature for wallets owned by a passkey
+    #[serde(default)]
+    pub passkey: Option<PasskeyAssertion>,
 }
 
 impl TransferExecuteRequest {
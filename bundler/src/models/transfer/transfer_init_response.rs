@@ -5,4 +5,6 @@ pub struct TransferInitResponse {
     pub msg_hash: [u8; 32],
     pub status: String,
     pub transaction_id: String,
+    // the paymaster paying for the op, which can differ from the requested one
+    pub paymaster: String,
 }
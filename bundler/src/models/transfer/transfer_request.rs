@@ -1,5 +1,6 @@
 use serde::Deserialize;
 
+use crate::constants::Constants;
 use crate::models::Metadata;
 
 #[derive(Deserialize)]
@@ -7,6 +8,9 @@ pub struct TransferRequest {
     pub receiver: String,
     pub value: String,
     pub metadata: Metadata,
+    pub paymaster: Option<String>,
+    // initiates the transfer under an active session, signed with the session key
+    pub session_id: Option<String>,
 }
 
 impl TransferRequest {
@@ -17,4 +21,11 @@ impl TransferRequest {
     pub fn get_value(&self) -> String {
         self.value.trim().to_string()
     }
+
+    pub fn get_paymaster(&self) -> String {
+        match &self.paymaster {
+            Some(paymaster) => paymaster.trim().to_lowercase(),
+            None => Constants::VERIFYING_PAYMASTER.to_string(),
+        }
+    }
 }
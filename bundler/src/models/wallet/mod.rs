@@ -1,7 +1,13 @@
 pub mod address_response;
 pub mod balance_request;
 pub mod balance_response;
+pub mod owner_rotation_request;
+pub mod passkey_assertion;
+pub mod passkey_registration_request;
 
 pub use address_response::AddressResponse;
 pub use balance_request::*;
 pub use balance_response::BalanceResponse;
+pub use owner_rotation_request::OwnerRotationRequest;
+pub use passkey_assertion::PasskeyAssertion;
+pub use passkey_registration_request::PasskeyRegistrationRequest;
@@ -0,0 +1,7 @@
+use ethers::types::Address;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct OwnerRotationRequest {
+    pub new_owner: Address,
+}
@@ -0,0 +1,11 @@
+use ethers::types::Bytes;
+use serde::{Deserialize, Serialize};
+
+// the parts of a navigator.credentials.get() response the account needs, signature is DER
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PasskeyAssertion {
+    pub credential_id: String,
+    pub authenticator_data: Bytes,
+    pub client_data_json: String,
+    pub signature: Bytes,
+}
@@ -0,0 +1,9 @@
+use ethers::types::Bytes;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct PasskeyRegistrationRequest {
+    pub credential_id: String,
+    // SEC1 encoded P-256 public key of the credential, compressed or uncompressed
+    pub public_key: Bytes,
+}
@@ -1,16 +1,19 @@
-use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
+use std::str::FromStr;
 
 use actix_web::http::header::HeaderName;
 use actix_web::web::Json;
 use actix_web::HttpRequest;
+use bigdecimal::BigDecimal;
 use ethers::providers::Middleware;
-use ethers::types::Address;
+use ethers::types::{Address, H256, U256};
+use ethers::utils::keccak256;
 use rand::distributions::Alphanumeric;
 use rand::Rng;
 use serde::Serialize;
 
+use crate::constants::Constants;
 use crate::errors::errors::ApiError;
+use crate::errors::ProviderError;
 use crate::models::response::BaseResponse;
 use crate::{CONFIG, PROVIDER};
 
@@ -24,15 +27,6 @@ where
     }))
 }
 
-pub fn get_user(req: HttpRequest) -> String {
-    req.headers()
-        .get(HeaderName::from_static("user"))
-        .unwrap()
-        .to_str()
-        .unwrap()
-        .to_string()
-}
-
 pub fn get_user_wallet(req: HttpRequest) -> String {
     req.headers()
         .get(HeaderName::from_static("user_address"))
@@ -42,20 +36,36 @@ pub fn get_user_wallet(req: HttpRequest) -> String {
         .to_string()
 }
 
-pub fn get_hash(s: String) -> u64 {
-    let mut hasher = DefaultHasher::new();
-    s.hash(&mut hasher);
-    hasher.finish()
+// salt v1: keccak256("toad.account.v1:<external user id>"), with ":<index>" appended when the
+// address for an earlier index is taken by an account we did not deploy. External user ids are
+// only unique within a tenant, so other tenants hash "toad.account.v1.tenant.<tenant id>:<..>"
+// instead, the default tenant keeps the prefix its wallets were created with.
+// v0 salts were u64 DefaultHasher outputs, they are kept as stored since the address is derived
+// from them and DefaultHasher is not stable across rust releases
+pub fn get_account_salt(tenant_id: i32, external_user_id: &str, index: u64) -> U256 {
+    let mut preimage = if tenant_id == Constants::DEFAULT_TENANT_ID {
+        format!("toad.account.v1:{}", external_user_id)
+    } else {
+        format!("toad.account.v1.tenant.{}:{}", tenant_id, external_user_id)
+    };
+    if index > 0 {
+        preimage = format!("{}:{}", preimage, index);
+    }
+    U256::from(keccak256(preimage))
 }
 
-pub async fn contract_exists_at(address: String) -> bool {
-    let formatted_address: Address = address.parse().unwrap();
-    let code = PROVIDER.get_code(formatted_address, None).await.unwrap();
-    !code.is_empty()
+pub async fn contract_exists_at(address: String) -> Result<bool, ProviderError> {
+    let formatted_address: Address = address
+        .parse()
+        .map_err(|_| ProviderError(format!("Invalid address: {}", address)))?;
+    let code = PROVIDER
+        .get_code(formatted_address, None)
+        .await
+        .map_err(|err| ProviderError(format!("Failed to get code: {}, err: {:?}", address, err)))?;
+    Ok(!code.is_empty())
 }
 
-pub fn generate_txn_id() -> String {
-    let prefix = &CONFIG.run_config.transaction_id_prefix;
+pub fn generate_txn_id(prefix: &str) -> String {
     let id: String = rand::thread_rng()
         .sample_iter(&Alphanumeric)
         .take(6)
@@ -64,6 +74,51 @@ pub fn generate_txn_id() -> String {
     format!("{}_{}", prefix, id).to_string()
 }
 
+pub fn generate_api_key() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect()
+}
+
+// for ids that are the only credential of an unauthenticated lookup
+pub fn generate_secret_id(prefix: &str) -> String {
+    format!("{}_{}", prefix, generate_api_key())
+}
+
+// only the hash of an api key is stored
+pub fn get_api_key_hash(api_key: &str) -> String {
+    format!("{:?}", H256::from(keccak256(api_key)))
+}
+
 pub fn get_explorer_url(txn_hash: &str) -> String {
     CONFIG.get_chain().explorer_url.clone() + &txn_hash.clone()
 }
+
+pub fn to_decimal(value: U256) -> BigDecimal {
+    BigDecimal::from_str(&value.to_string()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn salts_other_tenants_apart_from_the_default_tenant() {
+        let default_salt = get_account_salt(Constants::DEFAULT_TENANT_ID, "user", 0);
+        assert_eq!(default_salt, U256::from(keccak256("toad.account.v1:user")));
+        assert_eq!(
+            get_account_salt(Constants::DEFAULT_TENANT_ID, "user", 1),
+            U256::from(keccak256("toad.account.v1:user:1"))
+        );
+
+        let tenant_salt = get_account_salt(2, "user", 0);
+        assert_eq!(
+            tenant_salt,
+            U256::from(keccak256("toad.account.v1.tenant.2:user"))
+        );
+        assert_ne!(tenant_salt, default_salt);
+        assert_ne!(tenant_salt, get_account_salt(3, "user", 0));
+    }
+}
@@ -0,0 +1,542 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use futures::lock::Mutex;
+use jwks_client::keyset::{JwtKey, KeyStore};
+use log::{info, warn};
+use p256::ecdsa::signature::Verifier;
+use p256::ecdsa::{Signature, VerifyingKey};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::models::config::settings::JwtIssuer;
+
+// signing keys of the identity provider, RS256 keys are checked by jwks_client and ES256 keys by
+// p256. Keys of other types are ignored
+pub struct KeySet {
+    rsa: KeyStore,
+    ec: HashMap<String, VerifyingKey>,
+}
+
+impl KeySet {
+    pub fn parse(jwks: &str) -> Result<Self, String> {
+        let jwks: Jwks =
+            serde_json::from_str(jwks).map_err(|err| format!("Invalid key set: {:?}", err))?;
+        let mut key_set = KeySet {
+            rsa: KeyStore::new(),
+            ec: HashMap::new(),
+        };
+        for key in jwks.keys {
+            let kid = match key.kid {
+                Some(kid) => kid,
+                None => continue,
+            };
+            match (key.kty.as_str(), key.crv.as_deref()) {
+                ("RSA", _) => {
+                    if let (Some(n), Some(e)) = (key.n, key.e) {
+                        key_set.rsa.add_key(&JwtKey::new(&kid, &n, &e));
+                    }
+                }
+                ("EC", Some("P-256")) => {
+                    if let (Some(x), Some(y)) = (key.x, key.y) {
+                        let point = [vec![0x04], decode_segment(&x)?, decode_segment(&y)?].concat();
+                        let public_key = VerifyingKey::from_sec1_bytes(&point)
+                            .map_err(|_| format!("Invalid P-256 key: {}", kid))?;
+                        key_set.ec.insert(kid, public_key);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(key_set)
+    }
+
+    pub fn contains(&self, kid: &str) -> bool {
+        self.rsa.key_by_id(kid).is_some() || self.ec.contains_key(kid)
+    }
+
+    fn verify_signature(&self, token: &str, header: &Header) -> Result<(), String> {
+        match header.alg.as_str() {
+            "RS256" => self
+                .rsa
+                .verify(token)
+                .map(|_| ())
+                .map_err(|err| format!("Invalid token: {}", err.msg)),
+            "ES256" => {
+                let public_key = header
+                    .kid
+                    .as_ref()
+                    .and_then(|kid| self.ec.get(kid))
+                    .ok_or(String::from("Unknown signing key"))?;
+                let (message, signature) = token
+                    .rsplit_once('.')
+                    .ok_or(String::from("Malformed token"))?;
+                // JWS carries r || s, not DER
+                let signature = Signature::from_slice(&decode_segment(signature)?)
+                    .map_err(|_| String::from("Invalid signature"))?;
+                public_key
+                    .verify(message.as_bytes(), &signature)
+                    .map_err(|_| String::from("Signature verification failed"))
+            }
+            alg => Err(format!("Unsupported algorithm: {}", alg)),
+        }
+    }
+}
+
+// verifies id tokens against the issuer's JWKS. The keys are cached for the max-age the issuer
+// sends (or the configured one) and refetched early when a token names a key id we do not know,
+// which is how a key rotation shows up. A static key set is never refreshed.
+// Tokens are verified against a snapshot of the keys, only the refresh itself is serialized
+pub struct JwtValidator {
+    jwks_url: Option<String>,
+    issuer: String,
+    audience: Vec<String>,
+    max_age: Duration,
+    min_refresh_interval: Duration,
+    client: reqwest::Client,
+    cache: RwLock<CachedKeys>,
+    refreshing: Mutex<()>,
+}
+
+#[derive(Default)]
+struct CachedKeys {
+    keys: Option<Arc<KeySet>>,
+    fetched_at: Option<Instant>,
+    expires_at: Option<Instant>,
+}
+
+impl JwtValidator {
+    pub fn new(issuer: &JwtIssuer) -> Self {
+        Self {
+            jwks_url: Some(issuer.jwks_url.clone()),
+            issuer: issuer.issuer.clone(),
+            audience: issuer.audience.clone(),
+            max_age: Duration::from_secs(issuer.jwks_max_age_secs),
+            min_refresh_interval: Duration::from_secs(issuer.jwks_min_refresh_secs),
+            client: reqwest::Client::new(),
+            cache: RwLock::new(CachedKeys::default()),
+            refreshing: Mutex::new(()),
+        }
+    }
+
+    pub fn with_key_set(key_set: KeySet, issuer: String, audience: Vec<String>) -> Self {
+        Self {
+            jwks_url: None,
+            issuer,
+            audience,
+            max_age: Duration::ZERO,
+            min_refresh_interval: Duration::ZERO,
+            client: reqwest::Client::new(),
+            cache: RwLock::new(CachedKeys {
+                keys: Some(Arc::new(key_set)),
+                ..Default::default()
+            }),
+            refreshing: Mutex::new(()),
+        }
+    }
+
+    // for a JWKS document kept next to the config, eg: the local identity provider's
+    pub fn local(jwks: &str, issuer: String, audience: Vec<String>) -> Result<Self, String> {
+        Ok(Self::with_key_set(KeySet::parse(jwks)?, issuer, audience))
+    }
+
+    // checks the signature, iss, aud, exp and nbf, then returns the payload
+    pub async fn verify<T: DeserializeOwned>(&self, token: &str) -> Result<T, String> {
+        let mut segments = token.split('.');
+        let (header, payload) = match (segments.next(), segments.next(), segments.next()) {
+            (Some(header), Some(payload), Some(_)) if segments.next().is_none() => (
+                decode_json::<Header>(header)?,
+                decode_json::<Value>(payload)?,
+            ),
+            _ => return Err(String::from("Malformed token")),
+        };
+        let kid = header.kid.clone().ok_or(String::from("Missing key id"))?;
+
+        self.get_keys(&kid)
+            .await?
+            .verify_signature(token, &header)?;
+
+        self.validate_claims(
+            &serde_json::from_value::<Claims>(payload.clone())
+                .map_err(|err| format!("Invalid claims: {:?}", err))?,
+        )?;
+        serde_json::from_value(payload).map_err(|err| format!("Invalid payload: {:?}", err))
+    }
+
+    // while another request refreshes, tokens signed by a key we already have are checked
+    // against the current keys instead of waiting for the fetch
+    async fn get_keys(&self, kid: &str) -> Result<Arc<KeySet>, String> {
+        if self.jwks_url.is_some() && self.needs_refresh(kid) {
+            let refreshing = match self.refreshing.try_lock() {
+                Some(refreshing) => Some(refreshing),
+                None if self.snapshot().map_or(false, |keys| keys.contains(kid)) => None,
+                None => Some(self.refreshing.lock().await),
+            };
+            // the request holding the lock before us may have loaded the keys already
+            if refreshing.is_some() && self.needs_refresh(kid) {
+                self.refresh().await?;
+            }
+        }
+        self.snapshot()
+            .ok_or(String::from("Signing keys are not loaded"))
+    }
+
+    fn needs_refresh(&self, kid: &str) -> bool {
+        let cache = self.read_cache();
+        let now = Instant::now();
+        let expired = cache
+            .expires_at
+            .map_or(true, |expires_at| expires_at <= now);
+        let rotated = cache
+            .keys
+            .as_ref()
+            .map_or(false, |keys| !keys.contains(kid))
+            && cache.fetched_at.map_or(true, |fetched_at| {
+                now.duration_since(fetched_at) >= self.min_refresh_interval
+            });
+        expired || rotated
+    }
+
+    fn snapshot(&self) -> Option<Arc<KeySet>> {
+        self.read_cache().keys.clone()
+    }
+
+    fn read_cache(&self) -> RwLockReadGuard<'_, CachedKeys> {
+        self.cache.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    // the key set is fetched without holding the cache lock
+    async fn refresh(&self) -> Result<(), String> {
+        let result = self.fetch_keys().await;
+        let now = Instant::now();
+        let mut cache = self.cache.write().unwrap_or_else(PoisonError::into_inner);
+        cache.fetched_at = Some(now);
+        match result {
+            Ok((keys, max_age)) => {
+                info!("Loaded signing keys from {:?}", self.jwks_url);
+                cache.keys = Some(Arc::new(keys));
+                cache.expires_at = Some(now + max_age);
+                Ok(())
+            }
+            // keep serving the keys we have, the next attempt waits for the refresh interval
+            Err(err) if cache.keys.is_some() => {
+                warn!("Failed to refresh signing keys: {}", err);
+                cache.expires_at = Some(now + self.min_refresh_interval);
+                Ok(())
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    async fn fetch_keys(&self) -> Result<(KeySet, Duration), String> {
+        let jwks_url = self
+            .jwks_url
+            .as_ref()
+            .ok_or(String::from("No key set url"))?;
+        let response = self
+            .client
+            .get(jwks_url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|err| format!("Failed to fetch key set: {:?}", err))?;
+        let max_age = response
+            .headers()
+            .get(reqwest::header::CACHE_CONTROL)
+            .and_then(|value| value.to_str().ok())
+            .and_then(get_max_age)
+            .unwrap_or(self.max_age);
+        let body = response
+            .text()
+            .await
+            .map_err(|err| format!("Failed to read key set: {:?}", err))?;
+        Ok((KeySet::parse(&body)?, max_age))
+    }
+
+    fn validate_claims(&self, claims: &Claims) -> Result<(), String> {
+        if claims.iss.as_deref() != Some(self.issuer.as_str()) {
+            return Err(format!("Unexpected issuer: {:?}", claims.iss));
+        }
+        let audience = match &claims.aud {
+            Some(Audience::One(audience)) => vec![audience.clone()],
+            Some(Audience::Many(audience)) => audience.clone(),
+            None => vec![],
+        };
+        if !audience.iter().any(|aud| self.audience.contains(aud)) {
+            return Err(format!("Unexpected audience: {:?}", audience));
+        }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        match claims.exp {
+            Some(exp) if exp > now => {}
+            Some(_) => return Err(String::from("Token expired")),
+            None => return Err(String::from("Missing expiry")),
+        }
+        if claims.nbf.map_or(false, |nbf| nbf > now) {
+            return Err(String::from("Token not valid yet"));
+        }
+        Ok(())
+    }
+}
+
+fn get_max_age(cache_control: &str) -> Option<Duration> {
+    cache_control
+        .split(',')
+        .filter_map(|directive| directive.trim().strip_prefix("max-age="))
+        .find_map(|value| value.parse::<u64>().ok())
+        .map(Duration::from_secs)
+}
+
+fn decode_segment(segment: &str) -> Result<Vec<u8>, String> {
+    URL_SAFE_NO_PAD
+        .decode(segment)
+        .map_err(|_| String::from("Invalid base64url segment"))
+}
+
+fn decode_json<T: DeserializeOwned>(segment: &str) -> Result<T, String> {
+    serde_json::from_slice(&decode_segment(segment)?).map_err(|_| String::from("Malformed token"))
+}
+
+#[derive(Deserialize)]
+struct Jwks {
+    keys: Vec<Jwk>,
+}
+
+#[derive(Deserialize)]
+struct Jwk {
+    kty: String,
+    kid: Option<String>,
+    n: Option<String>,
+    e: Option<String>,
+    crv: Option<String>,
+    x: Option<String>,
+    y: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Header {
+    alg: String,
+    kid: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Claims {
+    iss: Option<String>,
+    aud: Option<Audience>,
+    exp: Option<u64>,
+    nbf: Option<u64>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Audience {
+    One(String),
+    Many(Vec<String>),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use p256::ecdsa::signature::Signer;
+    use p256::ecdsa::SigningKey;
+    use serde_json::json;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    const ISSUER: &str = "https://issuer.example";
+    const AUDIENCE: &str = "toad";
+
+    fn signing_key(seed: u8) -> SigningKey {
+        SigningKey::from_bytes(&[seed; 32].into()).unwrap()
+    }
+
+    fn jwks(keys: &[(&str, &SigningKey)]) -> String {
+        let keys: Vec<Value> = keys
+            .iter()
+            .map(|(kid, key)| {
+                let point = key.verifying_key().to_encoded_point(false);
+                json!({
+                    "kty": "EC",
+                    "crv": "P-256",
+                    "kid": kid,
+                    "x": URL_SAFE_NO_PAD.encode(point.x().unwrap()),
+                    "y": URL_SAFE_NO_PAD.encode(point.y().unwrap()),
+                })
+            })
+            .collect();
+        json!({ "keys": keys }).to_string()
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    fn token(kid: &str, key: &SigningKey, claims: Value) -> String {
+        let header = URL_SAFE_NO_PAD.encode(json!({ "alg": "ES256", "kid": kid }).to_string());
+        let payload = URL_SAFE_NO_PAD.encode(claims.to_string());
+        let message = format!("{}.{}", header, payload);
+        let signature: Signature = key.sign(message.as_bytes());
+        format!(
+            "{}.{}",
+            message,
+            URL_SAFE_NO_PAD.encode(signature.to_bytes())
+        )
+    }
+
+    fn claims() -> Value {
+        json!({ "iss": ISSUER, "aud": AUDIENCE, "sub": "user", "exp": now() + 600 })
+    }
+
+    fn validator() -> JwtValidator {
+        JwtValidator::local(
+            &jwks(&[("key-1", &signing_key(1))]),
+            String::from(ISSUER),
+            vec![String::from(AUDIENCE)],
+        )
+        .unwrap()
+    }
+
+    // answers every request with the key set currently in jwks
+    fn serve_jwks(jwks: Arc<RwLock<String>>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/jwks", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut request = [0u8; 1024];
+                let _ = stream.read(&mut request);
+                let body = jwks.read().unwrap().clone();
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nCache-Control: max-age=3600\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+            }
+        });
+        url
+    }
+
+    #[actix_web::test]
+    async fn accepts_a_valid_token() {
+        let claims: Value = validator()
+            .verify(&token("key-1", &signing_key(1), claims()))
+            .await
+            .unwrap();
+        assert_eq!(claims["sub"], "user");
+    }
+
+    #[actix_web::test]
+    async fn rejects_expired_and_missing_expiry() {
+        let mut expired = claims();
+        expired["exp"] = json!(now() - 1);
+        let mut missing = claims();
+        missing.as_object_mut().unwrap().remove("exp");
+
+        let validator = validator();
+        let result = validator
+            .verify::<Value>(&token("key-1", &signing_key(1), expired))
+            .await;
+        assert_eq!(result.unwrap_err(), "Token expired");
+        let result = validator
+            .verify::<Value>(&token("key-1", &signing_key(1), missing))
+            .await;
+        assert_eq!(result.unwrap_err(), "Missing expiry");
+    }
+
+    #[actix_web::test]
+    async fn checks_audience_and_issuer() {
+        let validator = validator();
+        let mut listed = claims();
+        listed["aud"] = json!(["other", AUDIENCE]);
+        assert!(validator
+            .verify::<Value>(&token("key-1", &signing_key(1), listed))
+            .await
+            .is_ok());
+
+        let mut audience = claims();
+        audience["aud"] = json!("other");
+        let result = validator
+            .verify::<Value>(&token("key-1", &signing_key(1), audience))
+            .await;
+        assert!(result.unwrap_err().starts_with("Unexpected audience"));
+
+        let mut issuer = claims();
+        issuer["iss"] = json!("https://other.example");
+        let result = validator
+            .verify::<Value>(&token("key-1", &signing_key(1), issuer))
+            .await;
+        assert!(result.unwrap_err().starts_with("Unexpected issuer"));
+    }
+
+    #[actix_web::test]
+    async fn rejects_a_token_signed_by_another_key() {
+        let result = validator()
+            .verify::<Value>(&token("key-1", &signing_key(2), claims()))
+            .await;
+        assert_eq!(result.unwrap_err(), "Signature verification failed");
+    }
+
+    #[actix_web::test]
+    async fn refetches_the_key_set_for_a_rotated_key() {
+        let key_set = Arc::new(RwLock::new(jwks(&[("key-1", &signing_key(1))])));
+        let validator = JwtValidator::new(&JwtIssuer {
+            jwks_url: serve_jwks(key_set.clone()),
+            issuer: String::from(ISSUER),
+            audience: vec![String::from(AUDIENCE)],
+            user_id_claim: None,
+            jwks_max_age_secs: 3600,
+            jwks_min_refresh_secs: 0,
+        });
+        assert!(validator
+            .verify::<Value>(&token("key-1", &signing_key(1), claims()))
+            .await
+            .is_ok());
+
+        *key_set.write().unwrap() = jwks(&[("key-2", &signing_key(2))]);
+        // key-1 is still cached, the max-age has not passed
+        assert!(validator
+            .verify::<Value>(&token("key-1", &signing_key(1), claims()))
+            .await
+            .is_ok());
+        assert!(validator
+            .verify::<Value>(&token("key-2", &signing_key(2), claims()))
+            .await
+            .is_ok());
+        // the refetch for key-2 dropped key-1
+        let result = validator
+            .verify::<Value>(&token("key-1", &signing_key(1), claims()))
+            .await;
+        assert_eq!(result.unwrap_err(), "Unknown signing key");
+    }
+
+    #[actix_web::test]
+    async fn waits_the_refresh_interval_before_refetching_for_unknown_keys() {
+        let key_set = Arc::new(RwLock::new(jwks(&[("key-1", &signing_key(1))])));
+        let validator = JwtValidator::new(&JwtIssuer {
+            jwks_url: serve_jwks(key_set.clone()),
+            issuer: String::from(ISSUER),
+            audience: vec![String::from(AUDIENCE)],
+            user_id_claim: None,
+            jwks_max_age_secs: 3600,
+            jwks_min_refresh_secs: 60,
+        });
+        assert!(validator
+            .verify::<Value>(&token("key-1", &signing_key(1), claims()))
+            .await
+            .is_ok());
+
+        *key_set.write().unwrap() = jwks(&[("key-2", &signing_key(2))]);
+        let result = validator
+            .verify::<Value>(&token("key-2", &signing_key(2), claims()))
+            .await;
+        assert_eq!(result.unwrap_err(), "Unknown signing key");
+    }
+}
@@ -1,16 +1,29 @@
+use actix_web::rt::spawn;
 use ethers::abi::RawLog;
-use ethers::providers::Middleware;
-use ethers::types::{Filter, H256};
+use ethers::contract::parse_log;
+use ethers::providers::{Middleware, PendingTransaction};
+use ethers::types::{Address, Filter, TransactionReceipt, H256, U256};
+use log::error;
 use sqlx::{Pool, Postgres};
+use std::str::FromStr;
 
-use crate::db::dao::TransactionDao;
+use crate::bundler::Bundler;
+use crate::contracts::entrypoint_provider::AccountDeployedFilter;
+use crate::contracts::rotatable_account_provider::{GuardiansChangedFilter, OwnerChangedFilter};
+use crate::contracts::token_paymaster_provider::{
+    TokenPaymasterProvider, UserOperationSponsoredFilter,
+};
+use crate::db::dao::{Bundle, BundleDao, GuardianDao, SponsorshipDao, TransactionDao};
 use crate::models::transfer::Status::{FAILED, SUCCESS};
+use crate::provider::helpers::to_decimal;
 use crate::provider::Web3Client;
+use crate::services::{WalletService, WebhookService};
 use crate::{CONFIG, PROVIDER};
 
 pub async fn user_op_event_listener(
     pool: Pool<Postgres>,
     client: Web3Client,
+    tenant_id: i32,
     user_op_hash: [u8; 32],
     txn_id: String,
 ) -> Result<(), String> {
@@ -21,7 +34,7 @@ pub async fn user_op_event_listener(
         .map_err(|_| String::from("Failed to get event"))?;
 
     let filter = Filter::new()
-        .address(CONFIG.get_chain().entrypoint_address)
+        .address(Bundler::get_entrypoint_address().map_err(|err| err.0)?)
         .topic0(event.signature())
         .topic1(H256::from(user_op_hash));
 
@@ -41,16 +54,274 @@ pub async fn user_op_event_listener(
         })
         .unwrap();
 
-    let success_param = log
-        .params
-        .into_iter()
-        .find(|param| param.name == "success")
-        .unwrap();
-    let success = success_param.value.into_bool().unwrap();
+    let get_param = |name: &str| {
+        log.params
+            .iter()
+            .find(|param| param.name == name)
+            .map(|param| param.value.clone())
+            .unwrap()
+    };
+    let success = get_param("success").into_bool().unwrap();
+    let sender = get_param("sender").into_address().unwrap();
+    let paymaster = get_param("paymaster").into_address().unwrap();
 
+    let receipt = PROVIDER
+        .get_transaction_receipt(log_data.transaction_hash.unwrap())
+        .await
+        .map_err(|err| format!("Listener: Failed to get receipt: {:?}", err))?
+        .ok_or(String::from("Listener: Receipt not found"))?;
     let status = if success { SUCCESS } else { FAILED };
+    TransactionDao::update_user_transaction(
+        &pool,
+        tenant_id,
+        txn_id.clone(),
+        Some(txn_hash.clone()),
+        status.to_string(),
+    )
+    .await
+    .map_err(|_| String::from("Listener: Failed to update database"))?;
+
+    // the account is deployed during validation, so even a failed op can deploy it. The wallet
+    // reconciliation picks up a flag that failed to update here
+    if let Err(err) = update_wallet_deployed(
+        &pool,
+        &receipt,
+        tenant_id,
+        log_data.address,
+        H256::from(user_op_hash),
+    )
+    .await
+    {
+        error!("{}", err);
+    }
+    if success {
+        update_owner(&pool, &receipt, tenant_id, sender).await?;
+        update_guardians(&pool, &receipt, tenant_id, sender).await?;
+    }
+
+    if CONFIG.get_chain().is_verifying_paymaster(paymaster) {
+        let actual_gas_cost = get_param("actualGasCost").into_uint().unwrap();
+        SponsorshipDao::update_gas_cost(&pool, txn_id.clone(), to_decimal(actual_gas_cost))
+            .await
+            .map_err(|_| String::from("Listener: Failed to update sponsorship"))?;
+    }
+    if let Ok(token_paymaster) = TokenPaymasterProvider::get_address() {
+        if paymaster == token_paymaster {
+            update_token_charge(
+                &pool,
+                &receipt,
+                tenant_id,
+                token_paymaster,
+                sender,
+                txn_id.clone(),
+            )
+            .await?;
+        }
+    }
+
+    spawn(WebhookService::notify_transaction(
+        pool,
+        txn_id,
+        status.to_string(),
+        Some(txn_hash),
+    ));
+    Ok(())
+}
+
+async fn update_wallet_deployed(
+    pool: &Pool<Postgres>,
+    receipt: &TransactionReceipt,
+    tenant_id: i32,
+    entry_point: Address,
+    user_op_hash: H256,
+) -> Result<(), String> {
+    let deployed = receipt
+        .logs
+        .iter()
+        .filter(|log| log.address == entry_point && log.topics.get(1) == Some(&user_op_hash))
+        .find_map(|log| parse_log::<AccountDeployedFilter>(log.clone()).ok());
+    match deployed {
+        Some(event) => {
+            WalletService::update_deployed(pool, tenant_id, format!("{:?}", event.sender), true)
+                .await
+                .map_err(|_| String::from("Listener: Failed to update database"))
+        }
+        None => Ok(()),
+    }
+}
+
+// emitted by the rotatable account implementation when the owner sets the guardians
+async fn update_guardians(
+    pool: &Pool<Postgres>,
+    receipt: &TransactionReceipt,
+    tenant_id: i32,
+    sender: Address,
+) -> Result<(), String> {
+    let guardians_changed = receipt
+        .logs
+        .iter()
+        .filter(|log| log.address == sender)
+        .filter_map(|log| parse_log::<GuardiansChangedFilter>(log.clone()).ok())
+        .last();
+    match guardians_changed {
+        Some(event) => GuardianDao::set_guardians(
+            pool,
+            tenant_id,
+            format!("{:?}", sender),
+            event
+                .guardians
+                .iter()
+                .map(|guardian| format!("{:?}", guardian))
+                .collect(),
+        )
+        .await
+        .map_err(|_| String::from("Listener: Failed to update guardians")),
+        None => Ok(()),
+    }
+}
+
+// emitted by the rotatable account implementation when the owner is rotated
+async fn update_owner(
+    pool: &Pool<Postgres>,
+    receipt: &TransactionReceipt,
+    tenant_id: i32,
+    sender: Address,
+) -> Result<(), String> {
+    let owner_changed = receipt
+        .logs
+        .iter()
+        .filter(|log| log.address == sender)
+        .filter_map(|log| parse_log::<OwnerChangedFilter>(log.clone()).ok())
+        .last();
+    match owner_changed {
+        Some(event) => WalletService::update_owner(
+            pool,
+            tenant_id,
+            format!("{:?}", sender),
+            format!("{:?}", event.new_owner),
+        )
+        .await
+        .map_err(|_| String::from("Listener: Failed to update owner")),
+        None => Ok(()),
+    }
+}
+
+// the token paymaster refunds the unused part of the pre charge in postOp, the final charge is
+// only known from its UserOperationSponsored event
+async fn update_token_charge(
+    pool: &Pool<Postgres>,
+    receipt: &TransactionReceipt,
+    tenant_id: i32,
+    token_paymaster: Address,
+    sender: Address,
+    txn_id: String,
+) -> Result<(), String> {
+    let sponsored = receipt
+        .logs
+        .iter()
+        .filter(|log| {
+            log.address == token_paymaster && log.topics.get(1) == Some(&H256::from(sender))
+        })
+        .find_map(|log| parse_log::<UserOperationSponsoredFilter>(log.clone()).ok());
+    match sponsored {
+        Some(event) => TransactionDao::update_erc20_gas(
+            pool,
+            tenant_id,
+            txn_id,
+            u64::try_from(event.actual_token_charge)
+                .map_err(|err| format!("Listener: Invalid token charge: {:?}", err))?,
+        )
+        .await
+        .map_err(|_| String::from("Listener: Failed to update database")),
+        None => Ok(()),
+    }
+}
 
-    TransactionDao::update_user_transaction(&pool, txn_id, Some(txn_hash), status.to_string())
+// records what the handleOps transaction cost the relayer against what the EntryPoint refunded
+// to the beneficiary
+pub async fn bundle_listener(
+    pool: Pool<Postgres>,
+    client: Web3Client,
+    txn_hash: String,
+) -> Result<(), String> {
+    let hash = H256::from_str(&txn_hash).map_err(|_| String::from("Invalid txn hash"))?;
+    let receipt = PendingTransaction::new(hash, &PROVIDER)
         .await
-        .map_err(|_| String::from("Listener: Failed to update database"))
+        .map_err(|err| format!("Bundle listener: Failed to get receipt: {:?}", err))?
+        .ok_or(String::from("Bundle listener: Transaction dropped"))?;
+
+    let provider = client.get_entrypoint_provider();
+    let event = provider
+        .abi()
+        .event("UserOperationEvent")
+        .map_err(|_| String::from("Failed to get event"))?;
+    let entry_point = Bundler::get_entrypoint_address().map_err(|err| err.0)?;
+
+    let gas_cost = get_gas_cost(&receipt);
+    let mut user_op_count = 0;
+    let mut refund = U256::zero();
+    for log in receipt.logs {
+        if log.address != entry_point || log.topics.first() != Some(&event.signature()) {
+            continue;
+        }
+        let log = event
+            .parse_log(RawLog {
+                topics: log.topics,
+                data: log.data.to_vec(),
+            })
+            .map_err(|err| format!("Bundle listener: Failed to parse log: {:?}", err))?;
+        let actual_gas_cost = log
+            .params
+            .into_iter()
+            .find(|param| param.name == "actualGasCost")
+            .and_then(|param| param.value.into_uint())
+            .unwrap_or_default();
+        user_op_count += 1;
+        refund += actual_gas_cost;
+    }
+
+    BundleDao::create_bundle(
+        &pool,
+        Bundle {
+            transaction_hash: txn_hash,
+            chain: CONFIG.run_config.current_chain.clone(),
+            beneficiary: format!("{:?}", CONFIG.get_beneficiary()),
+            relayer: format!("{:?}", receipt.from),
+            user_op_count,
+            gas_cost: to_decimal(gas_cost),
+            refund: to_decimal(refund),
+        },
+    )
+    .await
+    .map_err(|_| String::from("Bundle listener: Failed to update database"))
+}
+
+// OP-stack chains like Base also charge the fee for posting the transaction to L1, which is only
+// reported in their receipts as l1Fee
+fn get_gas_cost(receipt: &TransactionReceipt) -> U256 {
+    let l1_fee = receipt
+        .other
+        .get_deserialized::<U256>("l1Fee")
+        .and_then(Result::ok)
+        .unwrap_or_default();
+    receipt.gas_used.unwrap_or_default() * receipt.effective_gas_price.unwrap_or_default() + l1_fee
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn adds_the_l1_fee_to_the_gas_cost() {
+        let mut receipt = TransactionReceipt {
+            gas_used: Some(U256::from(100)),
+            effective_gas_price: Some(U256::from(3)),
+            ..Default::default()
+        };
+        assert_eq!(get_gas_cost(&receipt), U256::from(300));
+
+        receipt.other.insert(String::from("l1Fee"), json!("0x3e8"));
+        assert_eq!(get_gas_cost(&receipt), U256::from(1300));
+    }
 }
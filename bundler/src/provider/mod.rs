@@ -1,6 +1,8 @@
 pub mod helpers;
+pub mod jwt;
 pub mod listeners;
 pub mod web3_client;
 pub mod web3_provider;
+pub mod webauthn;
 
 pub use web3_client::Web3Client;
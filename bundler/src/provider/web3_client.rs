@@ -1,28 +1,83 @@
+use ethers::abi::Abi;
 use ethers::middleware::SignerMiddleware;
 use ethers::providers::{Http, Provider};
-use ethers::types::Address;
-use ethers_signers::{LocalWallet, Signer};
+use ethers::types::{Address, Bytes};
+use ethers_signers::Signer;
 use std::sync::Arc;
 
+use crate::contracts::aggregator_provider::{Aggregator, AggregatorProvider};
+use crate::contracts::base_paymaster_provider::{BasePaymaster, BasePaymasterProvider};
 use crate::contracts::entrypoint_provider::{EntryPoint, EntryPointProvider};
+use crate::contracts::entrypoint_v7_provider::{EntryPointV7, EntryPointV7Provider};
+use crate::contracts::erc1271_provider::{Erc1271Provider, ERC1271};
+use crate::contracts::passkey_account_factory_provider::{
+    PasskeyAccountFactory, PasskeyAccountFactoryProvider,
+};
+use crate::contracts::rotatable_account_provider::{RotatableAccount, RotatableAccountProvider};
+use crate::contracts::session_key_validator_provider::{
+    SessionKeyValidator, SessionKeyValidatorProvider,
+};
 use crate::contracts::simple_account_factory_provider::{
     SimpleAccountFactory, SimpleAccountFactoryProvider,
 };
 use crate::contracts::simple_account_provider::{SimpleAccount, SimpleAccountProvider};
+use crate::contracts::token_paymaster_provider::{TokenPaymaster, TokenPaymasterProvider};
 use crate::contracts::usdc_provider::{USDCProvider, ERC20};
 use crate::contracts::verifying_paymaster_provider::{
     VerifyingPaymaster, VerifyingPaymasterProvider,
 };
-use crate::CONFIG;
+use crate::signer::{RelayerPool, ToadSigner};
+use crate::{CONFIG, SIGNERS};
 
 #[derive(Clone)]
 pub struct Web3Client {
     pub client: Arc<Provider<Http>>,
+    relayer_pool: Arc<RelayerPool>,
 }
 
 impl Web3Client {
     pub fn new(client: Arc<Provider<Http>>) -> Self {
-        Self { client }
+        Self {
+            client,
+            relayer_pool: Arc::new(RelayerPool::new(SIGNERS.relayers.clone())),
+        }
+    }
+
+    // sends from the least busy relayer, for calls that do not need a specific sender
+    pub async fn execute_from_pool(
+        &self,
+        to: Address,
+        value: String,
+        data: Bytes,
+        abi: &Abi,
+    ) -> Result<String, String> {
+        self.relayer_pool
+            .execute(self.client.clone(), to, value, data, abi)
+            .await
+    }
+
+    // sends from the relayer key, which owns the paymasters
+    pub async fn execute_as_owner(
+        &self,
+        to: Address,
+        value: String,
+        data: Bytes,
+        abi: &Abi,
+    ) -> Result<String, String> {
+        self.relayer_pool
+            .execute_as(
+                self.client.clone(),
+                Self::get_relayer_wallet().address(),
+                to,
+                value,
+                data,
+                abi,
+            )
+            .await
+    }
+
+    pub fn get_relayer_addresses(&self) -> Vec<Address> {
+        self.relayer_pool.get_addresses()
     }
 
     pub fn get_usdc_provider(&self) -> ERC20<Provider<Http>> {
@@ -31,51 +86,89 @@ impl Web3Client {
 
     pub fn get_factory_provider(&self) -> SimpleAccountFactory<Provider<Http>> {
         SimpleAccountFactoryProvider::init_abi(
-            CONFIG.get_chain().simple_account_factory_address,
+            CONFIG.get_chain().get_simple_account_factory_address(),
             self.client.clone(),
         )
     }
 
-    pub fn get_verifying_paymaster_provider(&self) -> VerifyingPaymaster<Provider<Http>> {
-        VerifyingPaymasterProvider::init_abi(
-            CONFIG.get_chain().verifying_paymaster_address,
-            self.client.clone(),
-        )
+    pub fn get_passkey_factory_provider(
+        &self,
+        address: Address,
+    ) -> PasskeyAccountFactory<Provider<Http>> {
+        PasskeyAccountFactoryProvider::init_abi(address, self.client.clone())
+    }
+
+    pub fn get_verifying_paymaster_provider(
+        &self,
+        address: Address,
+    ) -> VerifyingPaymaster<Provider<Http>> {
+        VerifyingPaymasterProvider::init_abi(address, self.client.clone())
+    }
+
+    pub fn get_token_paymaster_provider(&self, address: Address) -> TokenPaymaster<Provider<Http>> {
+        TokenPaymasterProvider::init_abi(address, self.client.clone())
+    }
+
+    pub fn get_base_paymaster_provider(&self, address: Address) -> BasePaymaster<Provider<Http>> {
+        BasePaymasterProvider::init_abi(address, self.client.clone())
     }
 
     pub fn get_entrypoint_provider(&self) -> EntryPoint<Provider<Http>> {
         EntryPointProvider::init_abi(CONFIG.get_chain().entrypoint_address, self.client.clone())
     }
 
+    pub fn get_entrypoint_v7_provider(&self, address: Address) -> EntryPointV7<Provider<Http>> {
+        EntryPointV7Provider::init_abi(address, self.client.clone())
+    }
+
+    pub fn get_aggregator_provider(&self, address: Address) -> Aggregator<Provider<Http>> {
+        AggregatorProvider::init_abi(address, self.client.clone())
+    }
+
+    pub fn get_erc1271_provider(&self, address: Address) -> ERC1271<Provider<Http>> {
+        Erc1271Provider::init_abi(address, self.client.clone())
+    }
+
     pub fn get_scw_provider_by_address(&self, address: Address) -> SimpleAccount<Provider<Http>> {
         SimpleAccountProvider::init_abi(address, self.client.clone())
     }
 
-    pub fn get_relayer_signer(&self) -> SignerMiddleware<Arc<Provider<Http>>, LocalWallet> {
-        SignerMiddleware::new(
-            self.client.clone(),
-            Self::get_relayer_wallet().with_chain_id(CONFIG.get_chain().chain_id),
-        )
+    pub fn get_rotatable_account_provider(
+        &self,
+        address: Address,
+    ) -> RotatableAccount<Provider<Http>> {
+        RotatableAccountProvider::init_abi(address, self.client.clone())
     }
 
-    pub fn get_bundler_signer(&self) -> SignerMiddleware<Arc<Provider<Http>>, LocalWallet> {
-        SignerMiddleware::new(
-            self.client.clone(),
-            Self::get_relayer_wallet().with_chain_id(CONFIG.get_chain().chain_id),
-        )
+    pub fn get_session_key_validator_provider(
+        &self,
+        address: Address,
+    ) -> SessionKeyValidator<Provider<Http>> {
+        SessionKeyValidatorProvider::init_abi(address, self.client.clone())
+    }
+
+    pub fn get_treasury_signer(&self) -> Option<SignerMiddleware<Arc<Provider<Http>>, ToadSigner>> {
+        Self::get_treasury_wallet().map(|wallet| SignerMiddleware::new(self.client.clone(), wallet))
+    }
+
+    pub fn get_relayer_wallet() -> ToadSigner {
+        SIGNERS.relayer.clone()
+    }
+
+    pub fn get_verifying_paymaster_wallet() -> ToadSigner {
+        SIGNERS.paymasters[0].clone()
+    }
+
+    // the primary key followed by the keys kept around for rotation
+    pub fn get_verifying_paymaster_wallets() -> Vec<ToadSigner> {
+        SIGNERS.paymasters.clone()
     }
 
-    pub fn get_relayer_wallet() -> LocalWallet {
-        std::env::var("WALLET_PRIVATE_KEY")
-            .expect("WALLET_PRIVATE_KEY must be set")
-            .parse::<LocalWallet>()
-            .unwrap()
+    pub fn get_treasury_wallet() -> Option<ToadSigner> {
+        SIGNERS.treasury.clone()
     }
 
-    pub fn get_verifying_paymaster_wallet() -> LocalWallet {
-        std::env::var("VERIFYING_PAYMASTER_PRIVATE_KEY")
-            .expect("VERIFYING_PAYMASTER_PRIVATE_KEY must be set")
-            .parse::<LocalWallet>()
-            .unwrap()
+    pub fn get_session_wallet() -> Option<ToadSigner> {
+        SIGNERS.session.clone()
     }
 }
@@ -3,15 +3,15 @@ use ethers::middleware::signer::SignerMiddlewareError;
 use ethers::middleware::SignerMiddleware;
 use ethers::prelude::ProviderError as EtherProviderError;
 use ethers::providers::{Http, Middleware, Provider};
-use ethers::types::{Address, Bytes, TransactionRequest};
+use ethers::types::{Address, Bytes, TransactionRequest, U256};
 use ethers::utils::format_ether;
-use ethers_signers::LocalWallet;
 use log::error;
 use serde_json::Value;
 use std::num::ParseIntError;
 use std::sync::Arc;
 
 use crate::errors::ProviderError;
+use crate::signer::ToadSigner;
 use crate::PROVIDER;
 
 #[derive(Clone)]
@@ -35,21 +35,36 @@ impl Web3Provider {
     }
 
     pub async fn execute(
-        signer: SignerMiddleware<Arc<Provider<Http>>, LocalWallet>,
+        signer: SignerMiddleware<Arc<Provider<Http>>, ToadSigner>,
         to: Address,
         value: String,
         data: Bytes,
         abi: &Abi,
+    ) -> Result<String, String> {
+        Self::execute_with_nonce(signer, to, value, data, None, abi).await
+    }
+
+    // without a nonce the signer middleware uses the pending transaction count
+    pub async fn execute_with_nonce(
+        signer: SignerMiddleware<Arc<Provider<Http>>, ToadSigner>,
+        to: Address,
+        value: String,
+        data: Bytes,
+        nonce: Option<U256>,
+        abi: &Abi,
     ) -> Result<String, String> {
         let amount: Result<isize, ParseIntError> = value.parse();
         if amount.is_err() {
             return Err(String::from("Invalid gas value"));
         }
-        let txn = TransactionRequest::new()
+        let mut txn = TransactionRequest::new()
             .from(signer.address())
             .to(to)
             .value(amount.unwrap())
             .data(data);
+        if let Some(nonce) = nonce {
+            txn = txn.nonce(nonce);
+        }
         let result = signer.send_transaction(txn, None).await;
         return match result {
             Ok(transaction) => Ok(format!("{:?}", transaction.tx_hash())),
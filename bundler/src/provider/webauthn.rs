@@ -0,0 +1,379 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use ethers::abi::{encode, Token};
+use ethers::types::{Bytes, U256};
+use p256::ecdsa::signature::Verifier;
+use p256::ecdsa::{Signature, VerifyingKey};
+use p256::elliptic_curve::sec1::ToEncodedPoint;
+use p256::PublicKey;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::str::FromStr;
+
+use crate::models::config::settings::Passkey;
+use crate::CONFIG;
+
+const AUTHENTICATOR_DATA_MIN_LENGTH: usize = 37;
+const FLAG_USER_PRESENT: u8 = 0x01;
+const FLAG_USER_VERIFIED: u8 = 0x04;
+
+pub fn parse_public_key(public_key: &[u8]) -> Result<PublicKey, String> {
+    PublicKey::from_sec1_bytes(public_key).map_err(|_| String::from("Invalid P-256 public key"))
+}
+
+// users.owner_address of a passkey owner
+pub fn parse_owner(owner: &str) -> Result<PublicKey, String> {
+    let public_key =
+        Bytes::from_str(owner).map_err(|_| String::from("Invalid P-256 public key"))?;
+    parse_public_key(&public_key)
+}
+
+// 0x04 || x || y
+pub fn encode_public_key(public_key: &PublicKey) -> String {
+    format!(
+        "0x{}",
+        ethers::utils::hex::encode(public_key.to_encoded_point(false).as_bytes())
+    )
+}
+
+pub fn get_coordinates(public_key: &PublicKey) -> (U256, U256) {
+    let point = public_key.to_encoded_point(false);
+    // an uncompressed point always carries both coordinates
+    (
+        U256::from_big_endian(point.x().unwrap()),
+        U256::from_big_endian(point.y().unwrap()),
+    )
+}
+
+// checks the assertion the same way the account will: the client data must be a webauthn.get
+// over the challenge, and the signature is over authenticatorData || sha256(clientDataJSON)
+pub fn verify_assertion(
+    public_key: &PublicKey,
+    authenticator_data: &[u8],
+    client_data_json: &str,
+    signature: &[u8],
+    challenge: &[u8],
+) -> Result<Signature, String> {
+    let config = CONFIG
+        .passkey
+        .as_ref()
+        .ok_or(String::from("Passkeys are not configured"))?;
+    verify_assertion_for(
+        config,
+        public_key,
+        authenticator_data,
+        client_data_json,
+        signature,
+        challenge,
+    )
+}
+
+fn verify_assertion_for(
+    config: &Passkey,
+    public_key: &PublicKey,
+    authenticator_data: &[u8],
+    client_data_json: &str,
+    signature: &[u8],
+    challenge: &[u8],
+) -> Result<Signature, String> {
+    let client_data: ClientData = serde_json::from_str(client_data_json)
+        .map_err(|err| format!("Invalid client data: {:?}", err))?;
+    if client_data.type_ != "webauthn.get" {
+        return Err(format!(
+            "Unexpected client data type: {}",
+            client_data.type_
+        ));
+    }
+    if client_data.challenge != URL_SAFE_NO_PAD.encode(challenge) {
+        return Err(String::from("Challenge mismatch"));
+    }
+    if !config.origins.is_empty() && !config.origins.contains(&client_data.origin) {
+        return Err(format!("Unexpected origin: {}", client_data.origin));
+    }
+
+    if authenticator_data.len() < AUTHENTICATOR_DATA_MIN_LENGTH {
+        return Err(String::from("Authenticator data too short"));
+    }
+    if authenticator_data[..32] != Sha256::digest(config.rp_id.as_bytes())[..] {
+        return Err(String::from("Relying party mismatch"));
+    }
+    let flags = authenticator_data[32];
+    if flags & FLAG_USER_PRESENT == 0 {
+        return Err(String::from("User not present"));
+    }
+    if config.require_user_verification && flags & FLAG_USER_VERIFIED == 0 {
+        return Err(String::from("User not verified"));
+    }
+
+    let signature =
+        Signature::from_der(signature).map_err(|_| String::from("Invalid DER signature"))?;
+    // authenticators return either s, the account only accepts the low one
+    let signature = signature.normalize_s().unwrap_or(signature);
+    let message = [
+        authenticator_data,
+        Sha256::digest(client_data_json.as_bytes()).as_slice(),
+    ]
+    .concat();
+    VerifyingKey::from(public_key)
+        .verify(&message, &signature)
+        .map_err(|_| String::from("Signature verification failed"))?;
+    Ok(signature)
+}
+
+// abi.encode(WebAuthnAuth(authenticatorData, clientDataJSON, challengeIndex, typeIndex, r, s)),
+// the indexes point at "challenge" and "type" in clientDataJSON so the account does not have
+// to parse JSON
+pub fn encode_signature(
+    authenticator_data: &[u8],
+    client_data_json: &str,
+    signature: &Signature,
+) -> Result<Bytes, String> {
+    let challenge_index = client_data_json
+        .find("\"challenge\":")
+        .ok_or(String::from("Challenge not found in client data"))?;
+    let type_index = client_data_json
+        .find("\"type\":")
+        .ok_or(String::from("Type not found in client data"))?;
+    let (r, s) = signature.split_bytes();
+    Ok(Bytes::from(encode(&[Token::Tuple(vec![
+        Token::Bytes(authenticator_data.to_vec()),
+        Token::String(client_data_json.to_string()),
+        Token::Uint(U256::from(challenge_index)),
+        Token::Uint(U256::from(type_index)),
+        Token::Uint(U256::from_big_endian(&r)),
+        Token::Uint(U256::from_big_endian(&s)),
+    ])])))
+}
+
+#[derive(Deserialize)]
+struct ClientData {
+    #[serde(rename = "type")]
+    type_: String,
+    challenge: String,
+    origin: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::abi::{decode, ParamType};
+    use p256::ecdsa::signature::Signer;
+    use p256::ecdsa::SigningKey;
+
+    const RP_ID: &str = "toad.cash";
+    const ORIGIN: &str = "https://toad.cash";
+
+    // an assertion by the RFC 6979 A.2.5 P-256 key, the signature is deterministic. The same
+    // bytes are checked against the account in contracts/test/PasskeyAccount.t.sol
+    const VECTOR_PUBLIC_KEY: &str = "0x0460fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb67903fe1008b8bc99a41ae9e95628bc64f2f1b20c2d7e9f5177a3c294d4462299";
+    const VECTOR_AUTHENTICATOR_DATA: &str =
+        "ca3a15f92c27b3fcf1b1026ad7781230b6a0f046798b1bff6c34879077ff81370500000001";
+    const VECTOR_CLIENT_DATA_JSON: &str = "{\"type\":\"webauthn.get\",\"challenge\":\"AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE\",\"origin\":\"https://toad.cash\",\"crossOrigin\":false}";
+    const VECTOR_SIGNATURE: &str = "3045022100e664d72f4116f8cbe9fcf5b8354407311fa0baff07a27dcc3d3f7d95b03489cf022010fb12ea831633b034ddaad50829b462cea47c3153108c166e18bdc0b4324dab";
+    const VECTOR_ENCODED: [&str; 16] = [
+        "0000000000000000000000000000000000000000000000000000000000000020",
+        "00000000000000000000000000000000000000000000000000000000000000c0",
+        "0000000000000000000000000000000000000000000000000000000000000120",
+        "0000000000000000000000000000000000000000000000000000000000000017",
+        "0000000000000000000000000000000000000000000000000000000000000001",
+        "e664d72f4116f8cbe9fcf5b8354407311fa0baff07a27dcc3d3f7d95b03489cf",
+        "10fb12ea831633b034ddaad50829b462cea47c3153108c166e18bdc0b4324dab",
+        "0000000000000000000000000000000000000000000000000000000000000025",
+        "ca3a15f92c27b3fcf1b1026ad7781230b6a0f046798b1bff6c34879077ff8137",
+        "0500000001000000000000000000000000000000000000000000000000000000",
+        "0000000000000000000000000000000000000000000000000000000000000082",
+        "7b2274797065223a22776562617574686e2e676574222c226368616c6c656e67",
+        "65223a2241514542415145424151454241514542415145424151454241514542",
+        "415145424151454241514542415145222c226f726967696e223a226874747073",
+        "3a2f2f746f61642e63617368222c2263726f73734f726967696e223a66616c73",
+        "657d000000000000000000000000000000000000000000000000000000000000",
+    ];
+
+    fn config() -> Passkey {
+        Passkey {
+            rp_id: String::from(RP_ID),
+            origins: vec![String::from(ORIGIN)],
+            require_user_verification: true,
+        }
+    }
+
+    fn signing_key() -> SigningKey {
+        SigningKey::from_bytes(&[7u8; 32].into()).unwrap()
+    }
+
+    // rpIdHash || flags (user present and verified) || signCount
+    fn authenticator_data() -> Vec<u8> {
+        [
+            Sha256::digest(RP_ID.as_bytes()).as_slice(),
+            &[FLAG_USER_PRESENT | FLAG_USER_VERIFIED],
+            &[0, 0, 0, 1],
+        ]
+        .concat()
+    }
+
+    fn client_data_json(challenge: &[u8]) -> String {
+        format!(
+            "{{\"type\":\"webauthn.get\",\"challenge\":\"{}\",\"origin\":\"{}\",\"crossOrigin\":false}}",
+            URL_SAFE_NO_PAD.encode(challenge),
+            ORIGIN
+        )
+    }
+
+    fn sign(authenticator_data: &[u8], client_data_json: &str) -> Vec<u8> {
+        let message = [
+            authenticator_data,
+            Sha256::digest(client_data_json.as_bytes()).as_slice(),
+        ]
+        .concat();
+        let signature: Signature = signing_key().sign(&message);
+        signature.to_der().as_bytes().to_vec()
+    }
+
+    fn verify(
+        authenticator_data: &[u8],
+        client_data_json: &str,
+        signature: &[u8],
+        challenge: &[u8],
+    ) -> Result<Signature, String> {
+        verify_assertion_for(
+            &config(),
+            &PublicKey::from(signing_key().verifying_key()),
+            authenticator_data,
+            client_data_json,
+            signature,
+            challenge,
+        )
+    }
+
+    #[test]
+    fn accepts_a_valid_assertion() {
+        let challenge = [1u8; 32];
+        let client_data_json = client_data_json(&challenge);
+        let signature = sign(&authenticator_data(), &client_data_json);
+        let verified = verify(
+            &authenticator_data(),
+            &client_data_json,
+            &signature,
+            &challenge,
+        )
+        .unwrap();
+        assert!(verified.normalize_s().is_none());
+    }
+
+    #[test]
+    fn accepts_high_s_signatures_as_their_low_s_form() {
+        let challenge = [1u8; 32];
+        let client_data_json = client_data_json(&challenge);
+        let signature =
+            Signature::from_der(&sign(&authenticator_data(), &client_data_json)).unwrap();
+        let (r, s) = signature.split_scalars();
+        let high_s = Signature::from_scalars(r, -*s).unwrap();
+        let verified = verify(
+            &authenticator_data(),
+            &client_data_json,
+            high_s.to_der().as_bytes(),
+            &challenge,
+        )
+        .unwrap();
+        assert_eq!(verified, signature);
+    }
+
+    #[test]
+    fn rejects_tampered_assertions() {
+        let challenge = [1u8; 32];
+        let client_data_json = client_data_json(&challenge);
+        let signature = sign(&authenticator_data(), &client_data_json);
+
+        let mut counter_changed = authenticator_data();
+        counter_changed[36] = 2;
+        assert_eq!(
+            verify(&counter_changed, &client_data_json, &signature, &challenge),
+            Err(String::from("Signature verification failed"))
+        );
+        let origin_changed =
+            client_data_json.replace("\"crossOrigin\":false", "\"crossOrigin\":true");
+        assert_eq!(
+            verify(
+                &authenticator_data(),
+                &origin_changed,
+                &signature,
+                &challenge
+            ),
+            Err(String::from("Signature verification failed"))
+        );
+        assert_eq!(
+            verify(
+                &authenticator_data(),
+                &client_data_json,
+                &signature,
+                &[2u8; 32]
+            ),
+            Err(String::from("Challenge mismatch"))
+        );
+
+        let mut not_verified = authenticator_data();
+        not_verified[32] = FLAG_USER_PRESENT;
+        let signature = sign(&not_verified, &client_data_json);
+        assert_eq!(
+            verify(&not_verified, &client_data_json, &signature, &challenge),
+            Err(String::from("User not verified"))
+        );
+    }
+
+    #[test]
+    fn encodes_the_assertion_for_the_account() {
+        let challenge = [1u8; 32];
+        let client_data_json = client_data_json(&challenge);
+        let signature =
+            Signature::from_der(&sign(&authenticator_data(), &client_data_json)).unwrap();
+        let encoded =
+            encode_signature(&authenticator_data(), &client_data_json, &signature).unwrap();
+
+        let tokens = decode(
+            &[ParamType::Tuple(vec![
+                ParamType::Bytes,
+                ParamType::String,
+                ParamType::Uint(256),
+                ParamType::Uint(256),
+                ParamType::Uint(256),
+                ParamType::Uint(256),
+            ])],
+            &encoded,
+        )
+        .unwrap();
+        let fields = tokens[0].clone().into_tuple().unwrap();
+        let index = |field: &Token| field.clone().into_uint().unwrap().as_usize();
+        assert_eq!(
+            fields[0].clone().into_bytes().unwrap(),
+            authenticator_data()
+        );
+        assert!(client_data_json[index(&fields[2])..].starts_with("\"challenge\":"));
+        assert!(client_data_json[index(&fields[3])..].starts_with("\"type\":"));
+        let (r, s) = signature.split_bytes();
+        assert_eq!(
+            fields[4].clone().into_uint().unwrap(),
+            U256::from_big_endian(&r)
+        );
+        assert_eq!(
+            fields[5].clone().into_uint().unwrap(),
+            U256::from_big_endian(&s)
+        );
+    }
+
+    #[test]
+    fn encodes_the_fixed_vector() {
+        let authenticator_data = ethers::utils::hex::decode(VECTOR_AUTHENTICATOR_DATA).unwrap();
+        let signature = verify_assertion_for(
+            &config(),
+            &parse_owner(VECTOR_PUBLIC_KEY).unwrap(),
+            &authenticator_data,
+            VECTOR_CLIENT_DATA_JSON,
+            &ethers::utils::hex::decode(VECTOR_SIGNATURE).unwrap(),
+            &[1u8; 32],
+        )
+        .unwrap();
+        let encoded =
+            encode_signature(&authenticator_data, VECTOR_CLIENT_DATA_JSON, &signature).unwrap();
+        assert_eq!(ethers::utils::hex::encode(encoded), VECTOR_ENCODED.concat());
+    }
+}
@@ -1,12 +1,31 @@
 use actix_web::web;
 use actix_web::web::ServiceConfig;
 
-use crate::handlers::admin::{add_currency_metadata, admin_get_balance, topup_paymaster_deposit};
+use crate::handlers::admin::{
+    add_currency_metadata, add_paymaster_stake, admin_get_balance, create_paymaster_client,
+    create_tenant, get_audit_log, get_bundle_pnl, get_paymaster_deposit_info, get_tenants,
+    rotate_paymaster_signer, topup_paymaster_deposit, unlock_paymaster_stake, update_tenant,
+    withdraw_paymaster_deposit, withdraw_paymaster_stake,
+};
 use crate::handlers::hello_world::hello_world;
+use crate::handlers::message::{prepare_message, sign_message};
 use crate::handlers::metadata::{get_metadata, get_metadata_v2};
+use crate::handlers::paymaster::paymaster_rpc;
+use crate::handlers::recovery::{
+    add_guardian, approve_recovery, cancel_recovery, get_guardians, get_recovery, init_recovery,
+    remove_guardian,
+};
+use crate::handlers::session::{activate_session, create_session, get_sessions, revoke_session};
+use crate::handlers::signer::mock_sign;
 use crate::handlers::transfer::{execute_transfer, init_transfer};
-use crate::handlers::wallet::{get_address, get_balance, list_transactions, poll_transaction};
+use crate::handlers::wallet::{
+    deploy_wallet, get_address, get_balance, list_transactions, poll_transaction, register_passkey,
+    rotate_owner,
+};
+use crate::middleware::admin_auth::AdminAuthMiddleware;
 use crate::middleware::auth::ToadAuthMiddleware;
+use crate::middleware::tenant::TenantMiddleware;
+use crate::models::config::env::ENV;
 use crate::CONFIG;
 
 pub fn routes(cfg: &mut ServiceConfig) {
@@ -16,29 +35,111 @@ pub fn routes(cfg: &mut ServiceConfig) {
                 web::scope("v1")
                     .service(
                         web::scope("user")
+                            // the last wrap runs first, users authenticate against their tenant
                             .wrap(ToadAuthMiddleware)
+                            .wrap(TenantMiddleware)
                             .route("address", web::get().to(get_address))
+                            .route("passkey", web::post().to(register_passkey))
+                            // both are signed and submitted with transfer/execute
+                            .route("deploy", web::post().to(deploy_wallet))
+                            .route("owner/rotate", web::post().to(rotate_owner))
                             .route("balance", web::get().to(get_balance))
                             .service(
                                 web::scope("transfer")
                                     .route("init", web::post().to(init_transfer))
                                     .route("execute", web::post().to(execute_transfer)),
                             )
+                            .route("guardians", web::get().to(get_guardians))
+                            .route("guardians", web::post().to(add_guardian))
+                            .route("guardians/remove", web::post().to(remove_guardian))
+                            .route("recovery", web::post().to(init_recovery))
+                            .route(
+                                "recovery/{request_id}/cancel",
+                                web::post().to(cancel_recovery),
+                            )
+                            // ERC-1271 signatures for dApps, eg: sign in with ethereum
+                            .route("message/prepare", web::post().to(prepare_message))
+                            .route("message/sign", web::post().to(sign_message))
+                            .route("sessions", web::get().to(get_sessions))
+                            .route("sessions", web::post().to(create_session))
+                            .route(
+                                "sessions/{session_id}/activate",
+                                web::post().to(activate_session),
+                            )
+                            .route(
+                                "sessions/{session_id}/revoke",
+                                web::post().to(revoke_session),
+                            )
                             .route("transactions", web::get().to(list_transactions))
                             .route("transaction", web::get().to(poll_transaction)),
                     )
                     .service(
                         web::scope("admin")
+                            .wrap(AdminAuthMiddleware)
                             .route(
                                 "deposit/{paymaster}",
                                 web::post().to(topup_paymaster_deposit),
                             ) // the paymaster name
+                            .route(
+                                "deposit/{paymaster}",
+                                web::get().to(get_paymaster_deposit_info),
+                            )
+                            .route(
+                                "deposit/{paymaster}/withdraw",
+                                web::post().to(withdraw_paymaster_deposit),
+                            )
+                            .route("stake/{paymaster}", web::post().to(add_paymaster_stake))
+                            .route(
+                                "stake/{paymaster}/unlock",
+                                web::post().to(unlock_paymaster_stake),
+                            )
+                            .route(
+                                "stake/{paymaster}/withdraw",
+                                web::post().to(withdraw_paymaster_stake),
+                            )
                             .route("balance/{entity}", web::get().to(admin_get_balance))
-                            .route("metadata", web::post().to(add_currency_metadata)),
+                            .route("bundles/pnl", web::get().to(get_bundle_pnl))
+                            .route("audit", web::get().to(get_audit_log))
+                            .route("tenants", web::get().to(get_tenants))
+                            .route("tenants", web::post().to(create_tenant))
+                            .route("tenants/{tenant_id}", web::post().to(update_tenant))
+                            .route("metadata", web::post().to(add_currency_metadata))
+                            .route("paymaster/client", web::post().to(create_paymaster_client))
+                            .route(
+                                "paymaster/signer/rotate",
+                                web::post().to(rotate_paymaster_signer),
+                            ),
+                    )
+                    // ERC-7677 paymaster web service, clients send their api key in x-api-key
+                    .route("paymaster", web::post().to(paymaster_rpc))
+                    // guardians authenticate with their signature, not a user token. The request
+                    // is looked up within the tenant of the api key
+                    .service(
+                        web::resource("recovery/{request_id}")
+                            .wrap(TenantMiddleware)
+                            .route(web::get().to(get_recovery)),
+                    )
+                    .service(
+                        web::resource("recovery/{request_id}/approve")
+                            .wrap(TenantMiddleware)
+                            .route(web::post().to(approve_recovery)),
                     )
                     .route("hello", web::get().to(hello_world))
-                    .route("metadata", web::get().to(get_metadata)),
+                    .service(
+                        web::resource("metadata")
+                            .wrap(TenantMiddleware)
+                            .route(web::get().to(get_metadata)),
+                    ),
             )
-            .service(web::scope("v2").route("metadata", web::get().to(get_metadata_v2))),
+            .service(
+                web::scope("v2").service(
+                    web::resource("metadata")
+                        .wrap(TenantMiddleware)
+                        .route(web::get().to(get_metadata_v2)),
+                ),
+            ),
     );
+    if let ENV::Development = CONFIG.env {
+        cfg.route("mock/signer/sign", web::post().to(mock_sign));
+    }
 }
@@ -1,4 +1,5 @@
 use actix_web::middleware::Logger;
+use actix_web::rt::spawn;
 use actix_web::web::Data;
 use actix_web::{App, HttpServer};
 use dotenvy::dotenv;
@@ -8,29 +9,70 @@ use sqlx::{Pool, Postgres};
 use std::sync::Arc;
 
 use crate::db::connection::DatabaseConnection;
+use crate::identity::IdentityProviders;
+use crate::middleware::admin_auth::ADMIN_KEYS;
 use crate::models::config::server::Server;
 use crate::provider::Web3Client;
 use crate::routes::routes;
 use crate::services::hello_world_service::HelloWorldService;
-use crate::{CONFIG, PROVIDER};
+use crate::services::{
+    BalanceMonitorService, RecoveryService, SponsorshipReconciliationService,
+    WalletReconciliationService,
+};
+use crate::{CONFIG, PROVIDER, SIGNERS};
 
 #[derive(Clone)]
 pub struct ToadService {
     pub hello_world_service: HelloWorldService,
     pub web3_client: Web3Client,
     pub db_pool: Pool<Postgres>,
+    pub identity_providers: Arc<IdentityProviders>,
 }
 
 pub async fn init_services() -> ToadService {
     init_logging();
     info!("Starting server...");
 
+    lazy_static::initialize(&SIGNERS);
+    lazy_static::initialize(&ADMIN_KEYS);
     let client = Arc::new(PROVIDER.clone());
+    let web3_client = Web3Client::new(client.clone());
+    let db_pool = DatabaseConnection::init().await;
+    let identity_providers = Arc::new(IdentityProviders::new(
+        crate::identity::load(&CONFIG.auth)
+            .await
+            .expect("Failed to load identity provider."),
+    ));
+
+    if let Some(balance_monitor) = CONFIG.balance_monitor.clone() {
+        spawn(BalanceMonitorService::run(
+            db_pool.clone(),
+            web3_client.clone(),
+            balance_monitor,
+        ));
+    }
+    if let Some(wallet_reconciliation) = CONFIG.wallet_reconciliation.clone() {
+        spawn(WalletReconciliationService::run(
+            db_pool.clone(),
+            wallet_reconciliation,
+        ));
+    }
+    spawn(SponsorshipReconciliationService::run(
+        db_pool.clone(),
+        web3_client.clone(),
+        CONFIG.sponsorship_reconciliation.clone(),
+    ));
+    spawn(RecoveryService::run(
+        db_pool.clone(),
+        web3_client.clone(),
+        CONFIG.recovery.clone(),
+    ));
 
     ToadService {
         hello_world_service: HelloWorldService {},
-        web3_client: Web3Client::new(client.clone()),
-        db_pool: DatabaseConnection::init().await,
+        web3_client,
+        db_pool,
+        identity_providers,
     }
 }
 
@@ -50,6 +92,7 @@ pub async fn run(service: ToadService, server: Server) -> std::io::Result<()> {
             .app_data(Data::new(service.hello_world_service.clone()))
             .app_data(Data::new(service.web3_client.clone()))
             .app_data(Data::new(service.db_pool.clone()))
+            .app_data(Data::from(service.identity_providers.clone()))
     })
     .bind(server.url())?
     .run()
@@ -1,18 +1,39 @@
-use ethers::types::Address;
-use ethers::utils::parse_ether;
-use sqlx::{Pool, Postgres};
+use actix_web::rt::spawn;
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, TimeZone, Utc};
+use ethers::types::{Address, Signature};
+use ethers::utils::{format_ether, parse_ether};
+use ethers_signers::Signer;
+use log::error;
+use serde_json::{json, Value};
+use sqlx::{Pool, Postgres, Transaction};
 
+use crate::bundler::Bundler;
 use crate::constants::Constants;
+use crate::contracts::base_paymaster_provider::BasePaymasterProvider;
 use crate::contracts::entrypoint_provider::EntryPointProvider;
+use crate::contracts::token_paymaster_provider::TokenPaymasterProvider;
 use crate::contracts::verifying_paymaster_provider::VerifyingPaymasterProvider;
-use crate::db::dao::TokenMetadataDao;
-use crate::errors::AdminError;
-use crate::models::admin::{AddMetadataRequest, MetadataResponse};
+use crate::db::dao::{
+    AdminAuditDao, AdminAuditEntry, AdminAuditFilter, BundleDao, PaymasterClientDao, Tenant,
+    TenantDao, TokenMetadataDao, UserOperationDao,
+};
+use crate::errors::{AdminError, DatabaseError};
+use crate::middleware::auth_cache::AUTH_CACHE;
+use crate::models::admin::{
+    AddMetadataRequest, AdminAuditRequest, AdminAuditResponse, AdminUser, BundlePnlResponse,
+    DepositInfoResponse, DepositWithdrawal, MetadataResponse, PaymasterClientRequest,
+    PaymasterClientResponse, PaymasterSignerRotation, PaymasterStake, PaymasterTopup,
+    ResignedOperation, StakeWithdrawal, TenantRequest, TenantResponse,
+};
+use crate::models::config::settings::{Auth, SponsorshipPolicy};
+use crate::models::contract_interaction::EntryPointVersion;
 use crate::models::transfer::{Status, TransactionResponse, TransferResponse};
 use crate::models::wallet::{Balance, BalanceResponse};
-use crate::models::Metadata;
+use crate::provider::helpers::{generate_api_key, get_api_key_hash};
 use crate::provider::web3_provider::Web3Provider;
 use crate::provider::Web3Client;
+use crate::services::WebhookService;
 use crate::CONFIG;
 
 #[derive(Clone)]
@@ -20,46 +41,259 @@ pub struct AdminService;
 
 impl AdminService {
     pub async fn topup_paymaster_deposit(
+        pool: &Pool<Postgres>,
+        provider: &Web3Client,
+        admin: &AdminUser,
+        paymaster: String,
+        version: Option<EntryPointVersion>,
+        topup: PaymasterTopup,
+    ) -> Result<TransferResponse, AdminError> {
+        let parameters = json!({
+            "paymaster": paymaster,
+            "entrypoint_version": version,
+            "request": topup,
+        });
+        let response = Self::add_deposit(provider, paymaster, version, topup).await;
+        Self::audit_transfer(
+            pool,
+            admin,
+            "topup_paymaster_deposit",
+            parameters,
+            &response,
+        )
+        .await;
+        response
+    }
+
+    pub async fn add_paymaster_stake(
+        pool: &Pool<Postgres>,
+        provider: &Web3Client,
+        admin: &AdminUser,
+        paymaster: String,
+        version: Option<EntryPointVersion>,
+        stake: PaymasterStake,
+    ) -> Result<TransferResponse, AdminError> {
+        let parameters = json!({
+            "paymaster": paymaster,
+            "entrypoint_version": version,
+            "request": stake,
+        });
+        let response = Self::add_stake(provider, paymaster, version, stake).await;
+        Self::audit_transfer(pool, admin, "add_paymaster_stake", parameters, &response).await;
+        response
+    }
+
+    pub async fn unlock_paymaster_stake(
+        pool: &Pool<Postgres>,
+        provider: &Web3Client,
+        admin: &AdminUser,
+        paymaster: String,
+        version: Option<EntryPointVersion>,
+    ) -> Result<TransferResponse, AdminError> {
+        let parameters = json!({ "paymaster": paymaster, "entrypoint_version": version });
+        let response = Self::unlock_stake(provider, paymaster, version).await;
+        Self::audit_transfer(pool, admin, "unlock_paymaster_stake", parameters, &response).await;
+        response
+    }
+
+    pub async fn withdraw_paymaster_stake(
+        pool: &Pool<Postgres>,
+        provider: &Web3Client,
+        admin: &AdminUser,
+        paymaster: String,
+        version: Option<EntryPointVersion>,
+        withdrawal: StakeWithdrawal,
+    ) -> Result<TransferResponse, AdminError> {
+        let parameters = json!({
+            "paymaster": paymaster,
+            "entrypoint_version": version,
+            "request": withdrawal,
+        });
+        let response = Self::withdraw_stake(provider, paymaster, version, withdrawal).await;
+        Self::audit_transfer(
+            pool,
+            admin,
+            "withdraw_paymaster_stake",
+            parameters,
+            &response,
+        )
+        .await;
+        response
+    }
+
+    pub async fn withdraw_paymaster_deposit(
+        pool: &Pool<Postgres>,
         provider: &Web3Client,
-        eth_value: String,
+        admin: &AdminUser,
         paymaster: String,
-        metadata: Metadata,
+        version: Option<EntryPointVersion>,
+        withdrawal: DepositWithdrawal,
     ) -> Result<TransferResponse, AdminError> {
-        if metadata.currency != Constants::NATIVE {
+        let parameters = json!({
+            "paymaster": paymaster,
+            "entrypoint_version": version,
+            "request": withdrawal,
+        });
+        let response = Self::withdraw_deposit(provider, paymaster, version, withdrawal).await;
+        Self::audit_transfer(
+            pool,
+            admin,
+            "withdraw_paymaster_deposit",
+            parameters,
+            &response,
+        )
+        .await;
+        response
+    }
+
+    async fn add_deposit(
+        provider: &Web3Client,
+        paymaster: String,
+        version: Option<EntryPointVersion>,
+        topup: PaymasterTopup,
+    ) -> Result<TransferResponse, AdminError> {
+        if topup.metadata.currency != Constants::NATIVE {
             return Err(AdminError::InvalidCurrency);
         }
-        if paymaster != Constants::VERIFYING_PAYMASTER {
-            return Err(AdminError::ValidationError(String::from(
-                "Invalid Paymaster",
-            )));
+        let (paymaster_address, entry_point) = Self::get_paymaster(&paymaster, version)?;
+        let value = parse_ether(topup.value)
+            .map_err(|_| AdminError::ValidationError(String::from("Invalid value")))?;
+
+        let data = EntryPointProvider::add_deposit(provider, paymaster_address).await?;
+        let response = provider
+            .execute_from_pool(
+                entry_point,
+                value.to_string(),
+                data,
+                provider.get_entrypoint_provider().abi(),
+            )
+            .await;
+        Self::get_transfer_response(response)
+    }
+
+    async fn add_stake(
+        provider: &Web3Client,
+        paymaster: String,
+        version: Option<EntryPointVersion>,
+        stake: PaymasterStake,
+    ) -> Result<TransferResponse, AdminError> {
+        if stake.metadata.currency != Constants::NATIVE {
+            return Err(AdminError::InvalidCurrency);
         }
-        let value = parse_ether(eth_value)
+        let (paymaster_address, _) = Self::get_paymaster(&paymaster, version)?;
+        let value = parse_ether(stake.value)
             .map_err(|_| AdminError::ValidationError(String::from("Invalid value")))?;
 
-        let data = EntryPointProvider::add_deposit(
+        // stake and deposit withdrawals are owner only, so they are sent from the relayer key
+        let data =
+            BasePaymasterProvider::add_stake(provider, paymaster_address, stake.unstake_delay_sec)?;
+        let response = provider
+            .execute_as_owner(
+                paymaster_address,
+                value.to_string(),
+                data,
+                provider
+                    .get_base_paymaster_provider(paymaster_address)
+                    .abi(),
+            )
+            .await;
+        Self::get_transfer_response(response)
+    }
+
+    async fn unlock_stake(
+        provider: &Web3Client,
+        paymaster: String,
+        version: Option<EntryPointVersion>,
+    ) -> Result<TransferResponse, AdminError> {
+        let (paymaster_address, _) = Self::get_paymaster(&paymaster, version)?;
+        let data = BasePaymasterProvider::unlock_stake(provider, paymaster_address)?;
+        let response = provider
+            .execute_as_owner(
+                paymaster_address,
+                String::from("0"),
+                data,
+                provider
+                    .get_base_paymaster_provider(paymaster_address)
+                    .abi(),
+            )
+            .await;
+        Self::get_transfer_response(response)
+    }
+
+    async fn withdraw_stake(
+        provider: &Web3Client,
+        paymaster: String,
+        version: Option<EntryPointVersion>,
+        withdrawal: StakeWithdrawal,
+    ) -> Result<TransferResponse, AdminError> {
+        let (paymaster_address, _) = Self::get_paymaster(&paymaster, version)?;
+        let data = BasePaymasterProvider::withdraw_stake(
             provider,
-            CONFIG.get_chain().verifying_paymaster_address,
-        )
-        .await?;
-        let response = Web3Provider::execute(
-            provider.get_relayer_signer(),
-            CONFIG.get_chain().entrypoint_address,
-            value.to_string(),
-            data,
-            provider.get_entrypoint_provider().abi(),
-        )
-        .await;
-        match response {
-            Ok(txn_hash) => Ok(TransferResponse {
-                transaction: TransactionResponse::new(
-                    txn_hash.clone(),
-                    Status::PENDING,
-                    CONFIG.get_chain().explorer_url.clone() + &txn_hash.clone(),
-                ),
-                transaction_id: "".to_string(),
-            }),
-            Err(err) => Err(AdminError::Provider(err)),
+            paymaster_address,
+            withdrawal.withdraw_address,
+        )?;
+        let response = provider
+            .execute_as_owner(
+                paymaster_address,
+                String::from("0"),
+                data,
+                provider
+                    .get_base_paymaster_provider(paymaster_address)
+                    .abi(),
+            )
+            .await;
+        Self::get_transfer_response(response)
+    }
+
+    async fn withdraw_deposit(
+        provider: &Web3Client,
+        paymaster: String,
+        version: Option<EntryPointVersion>,
+        withdrawal: DepositWithdrawal,
+    ) -> Result<TransferResponse, AdminError> {
+        if withdrawal.metadata.currency != Constants::NATIVE {
+            return Err(AdminError::InvalidCurrency);
         }
+        let (paymaster_address, _) = Self::get_paymaster(&paymaster, version)?;
+        let value = parse_ether(withdrawal.value)
+            .map_err(|_| AdminError::ValidationError(String::from("Invalid value")))?;
+
+        let data = BasePaymasterProvider::withdraw_to(
+            provider,
+            paymaster_address,
+            withdrawal.withdraw_address,
+            value,
+        )?;
+        let response = provider
+            .execute_as_owner(
+                paymaster_address,
+                String::from("0"),
+                data,
+                provider
+                    .get_base_paymaster_provider(paymaster_address)
+                    .abi(),
+            )
+            .await;
+        Self::get_transfer_response(response)
+    }
+
+    pub async fn get_paymaster_deposit_info(
+        provider: &Web3Client,
+        paymaster: String,
+        version: Option<EntryPointVersion>,
+    ) -> Result<DepositInfoResponse, AdminError> {
+        let (paymaster_address, entry_point) = Self::get_paymaster(&paymaster, version)?;
+        let deposit_info =
+            EntryPointProvider::get_deposit_info(provider, entry_point, paymaster_address).await?;
+        Ok(DepositInfoResponse {
+            address: format!("{:?}", paymaster_address),
+            deposit: format_ether(deposit_info.deposit),
+            staked: deposit_info.staked,
+            stake: format_ether(deposit_info.stake),
+            unstake_delay_sec: deposit_info.unstake_delay_sec,
+            withdraw_time: deposit_info.withdraw_time,
+            currency: CONFIG.get_chain().currency.clone(),
+        })
     }
 
     pub async fn get_balance(
@@ -71,9 +305,12 @@ impl AdminService {
             return Err(AdminError::InvalidCurrency);
         }
         if Constants::PAYMASTER == entity {
-            let paymaster_address = &CONFIG.get_chain().verifying_paymaster_address;
+            // the deposit is the one of the paymaster ops are built for
+            let paymaster_address = VerifyingPaymasterProvider::get_address(
+                CONFIG.get_chain().get_entrypoint_version(),
+            )?;
             let deposit = VerifyingPaymasterProvider::get_deposit(provider).await?;
-            return Self::get_balance_response(paymaster_address, deposit, data.currency);
+            return Self::get_balance_response(&paymaster_address, deposit, data.currency);
         }
         if Constants::RELAYER == entity {
             let relayer_address = &CONFIG.run_config.account_owner;
@@ -85,10 +322,13 @@ impl AdminService {
 
     pub async fn add_currency_metadata(
         pool: &Pool<Postgres>,
+        admin: &AdminUser,
         metadata: AddMetadataRequest,
     ) -> Result<MetadataResponse, AdminError> {
-        TokenMetadataDao::add_metadata(
-            pool,
+        let parameters = json!(metadata);
+        let mut txn = Self::begin(pool).await?;
+        let result = TokenMetadataDao::add_metadata(
+            &mut *txn,
             metadata.get_chain_name().clone(),
             metadata.get_symbol(),
             metadata.get_contract_address(),
@@ -99,6 +339,16 @@ impl AdminService {
             metadata.get_chain_display_name(),
             metadata.get_token_image_url(),
         )
+        .await
+        .map_err(AdminError::from);
+        Self::commit_audited(
+            pool,
+            txn,
+            admin,
+            "add_currency_metadata",
+            parameters,
+            result,
+        )
         .await?;
 
         let supported_currencies =
@@ -116,6 +366,491 @@ impl AdminService {
         Ok(exponent_metadata)
     }
 
+    pub async fn create_paymaster_client(
+        pool: &Pool<Postgres>,
+        admin: &AdminUser,
+        request: PaymasterClientRequest,
+    ) -> Result<PaymasterClientResponse, AdminError> {
+        let parameters = json!(request);
+        let mut txn = Self::begin(pool).await?;
+        let result = Self::create_client(&mut txn, request).await;
+        Self::commit_audited(
+            pool,
+            txn,
+            admin,
+            "create_paymaster_client",
+            parameters,
+            result,
+        )
+        .await
+    }
+
+    async fn create_client(
+        txn: &mut Transaction<'_, Postgres>,
+        request: PaymasterClientRequest,
+    ) -> Result<PaymasterClientResponse, AdminError> {
+        let name = request.name.trim().to_string();
+        if name.is_empty() {
+            return Err(AdminError::ValidationError(String::from("Invalid name")));
+        }
+        if let Some(policy) = &request.sponsorship_policy {
+            serde_json::from_value::<SponsorshipPolicy>(policy.clone()).map_err(|err| {
+                AdminError::ValidationError(format!("Invalid sponsorship policy: {}", err))
+            })?;
+        }
+        let api_key = generate_api_key();
+        PaymasterClientDao::create_client(
+            &mut **txn,
+            name.clone(),
+            get_api_key_hash(&api_key),
+            request.sponsorship_policy,
+        )
+        .await?;
+        Ok(PaymasterClientResponse { name, api_key })
+    }
+
+    pub async fn get_tenants(pool: &Pool<Postgres>) -> Result<Vec<TenantResponse>, AdminError> {
+        let tenants = TenantDao::get_tenants(pool).await?;
+        Ok(tenants.into_iter().map(TenantResponse::from).collect())
+    }
+
+    pub async fn create_tenant(
+        pool: &Pool<Postgres>,
+        admin: &AdminUser,
+        request: TenantRequest,
+    ) -> Result<TenantResponse, AdminError> {
+        let parameters = json!(request);
+        let mut txn = Self::begin(pool).await?;
+        let result = Self::add_tenant(&mut txn, request).await;
+        Self::commit_audited(pool, txn, admin, "create_tenant", parameters, result).await
+    }
+
+    async fn add_tenant(
+        txn: &mut Transaction<'_, Postgres>,
+        request: TenantRequest,
+    ) -> Result<TenantResponse, AdminError> {
+        let name = request.name.trim().to_string();
+        if name.is_empty() {
+            return Err(AdminError::ValidationError(String::from("Invalid name")));
+        }
+        let api_key = generate_api_key();
+        let tenant = Self::to_tenant(
+            Tenant {
+                name,
+                api_key_hash: Some(get_api_key_hash(&api_key)),
+                webhook_secret: generate_api_key(),
+                ..Default::default()
+            },
+            request,
+        )?;
+        let tenant = TenantDao::create_tenant(&mut **txn, tenant).await?;
+        Ok(TenantResponse {
+            api_key: Some(api_key),
+            webhook_secret: Some(tenant.webhook_secret.clone()),
+            ..TenantResponse::from(tenant)
+        })
+    }
+
+    pub async fn update_tenant(
+        pool: &Pool<Postgres>,
+        admin: &AdminUser,
+        tenant_id: i32,
+        request: TenantRequest,
+    ) -> Result<TenantResponse, AdminError> {
+        let parameters = json!({ "tenant_id": tenant_id, "request": request });
+        let mut txn = Self::begin(pool).await?;
+        let result = Self::change_tenant(&mut txn, tenant_id, request).await;
+        let response =
+            Self::commit_audited(pool, txn, admin, "update_tenant", parameters, result).await?;
+        // dropped once committed, so a request in between can't cache the old settings
+        AUTH_CACHE.invalidate_tenant(tenant_id);
+        Ok(response)
+    }
+
+    async fn change_tenant(
+        txn: &mut Transaction<'_, Postgres>,
+        tenant_id: i32,
+        request: TenantRequest,
+    ) -> Result<TenantResponse, AdminError> {
+        let webhook_secret = request.rotate_webhook_secret.then(generate_api_key);
+        let tenant = Self::to_tenant(
+            Tenant {
+                id: tenant_id,
+                ..Default::default()
+            },
+            request,
+        )?;
+        let tenant = TenantDao::update_tenant(&mut **txn, tenant, webhook_secret.clone())
+            .await
+            .map_err(|err| match err {
+                DatabaseError::NotFound => {
+                    AdminError::ValidationError(String::from("Invalid tenant"))
+                }
+                err => AdminError::from(err),
+            })?;
+        Ok(TenantResponse {
+            webhook_secret,
+            ..TenantResponse::from(tenant)
+        })
+    }
+
+    // settings are checked here, a tenant that can't be parsed later would fail its requests
+    fn to_tenant(tenant: Tenant, request: TenantRequest) -> Result<Tenant, AdminError> {
+        if let Some(auth) = &request.auth {
+            serde_json::from_value::<Auth>(auth.clone())
+                .map_err(|err| AdminError::ValidationError(format!("Invalid auth: {}", err)))?;
+        }
+        if let Some(policy) = &request.sponsorship_policy {
+            serde_json::from_value::<SponsorshipPolicy>(policy.clone()).map_err(|err| {
+                AdminError::ValidationError(format!("Invalid sponsorship policy: {}", err))
+            })?;
+        }
+        if let Some(prefix) = &request.transaction_id_prefix {
+            if prefix.is_empty() || !prefix.chars().all(|c| c.is_ascii_alphanumeric()) {
+                return Err(AdminError::ValidationError(String::from(
+                    "Invalid transaction id prefix",
+                )));
+            }
+        }
+        if let Some(webhook_url) = &request.webhook_url {
+            let url = reqwest::Url::parse(webhook_url)
+                .map_err(|_| AdminError::ValidationError(String::from("Invalid webhook url")))?;
+            if !["http", "https"].contains(&url.scheme()) {
+                return Err(AdminError::ValidationError(String::from(
+                    "Invalid webhook url",
+                )));
+            }
+        }
+        let supported_tokens: Vec<String> = request
+            .supported_tokens
+            .iter()
+            .map(|token| token.trim().to_string())
+            .collect();
+        if supported_tokens.iter().any(|token| token.is_empty()) {
+            return Err(AdminError::ValidationError(String::from(
+                "Invalid supported token",
+            )));
+        }
+        Ok(Tenant {
+            auth: request.auth,
+            transaction_id_prefix: request.transaction_id_prefix,
+            sponsorship_policy: request.sponsorship_policy,
+            supported_tokens,
+            webhook_url: request.webhook_url,
+            is_active: request.is_active,
+            ..tenant
+        })
+    }
+
+    pub async fn get_bundle_pnl(
+        pool: &Pool<Postgres>,
+        period: Option<String>,
+    ) -> Result<BundlePnlResponse, AdminError> {
+        if let Some(period) = &period {
+            if !["day", "week", "month", "year"].contains(&period.as_str()) {
+                return Err(AdminError::ValidationError(String::from("Invalid period")));
+            }
+        }
+        let pnl =
+            BundleDao::get_profit_and_loss(pool, CONFIG.run_config.current_chain.clone(), period)
+                .await?;
+        Ok(BundlePnlResponse {
+            chain: CONFIG.run_config.current_chain.clone(),
+            beneficiary: format!("{:?}", CONFIG.get_beneficiary()),
+            bundles: pnl.bundles,
+            user_ops: pnl.user_ops,
+            gas_cost: Self::to_ether(&pnl.gas_cost),
+            refund: Self::to_ether(&pnl.refund),
+            profit: Self::to_ether(&(&pnl.refund - &pnl.gas_cost)),
+            currency: CONFIG.get_chain().currency.clone(),
+        })
+    }
+
+    pub async fn get_audit_log(
+        pool: &Pool<Postgres>,
+        request: AdminAuditRequest,
+    ) -> Result<Vec<AdminAuditResponse>, AdminError> {
+        if let Some(outcome) = &request.outcome {
+            if ![Constants::AUDIT_SUCCESS, Constants::AUDIT_FAILURE].contains(&outcome.as_str()) {
+                return Err(AdminError::ValidationError(String::from("Invalid outcome")));
+            }
+        }
+        let page_size = request.page_size.unwrap_or(10);
+        if !(1..=100).contains(&page_size) {
+            return Err(AdminError::ValidationError(String::from(
+                "Invalid page size",
+            )));
+        }
+        let filter = AdminAuditFilter {
+            actor: request.actor,
+            action: request.action,
+            outcome: request.outcome,
+            from: request.from.map(Self::to_datetime).transpose()?,
+            to: request.to.map(Self::to_datetime).transpose()?,
+        };
+        let entries =
+            AdminAuditDao::get_entries(pool, filter, request.id.unwrap_or(i32::MAX), page_size)
+                .await?;
+        Ok(entries.into_iter().map(AdminAuditResponse::from).collect())
+    }
+
+    // re-signs initiated ops whose paymaster signature is not from the on chain verifying signer
+    pub async fn rotate_paymaster_signer(
+        pool: &Pool<Postgres>,
+        provider: &Web3Client,
+        admin: &AdminUser,
+    ) -> Result<PaymasterSignerRotation, AdminError> {
+        let mut txn = Self::begin(pool).await?;
+        let result = Self::resign_operations(&mut txn, provider).await;
+        let rotation = Self::commit_audited(
+            pool,
+            txn,
+            admin,
+            "rotate_paymaster_signer",
+            json!({}),
+            result,
+        )
+        .await?;
+        // the owners have to sign the new msg_hash, their tenants tell them
+        let resigned = rotation
+            .resigned
+            .iter()
+            .map(|operation| (operation.transaction_id.clone(), operation.msg_hash))
+            .collect();
+        spawn(WebhookService::notify_resigned(pool.clone(), resigned));
+        Ok(rotation)
+    }
+
+    async fn resign_operations(
+        txn: &mut Transaction<'_, Postgres>,
+        provider: &Web3Client,
+    ) -> Result<PaymasterSignerRotation, AdminError> {
+        let version = CONFIG.get_chain().get_entrypoint_version();
+        // looked up once, every op is re-signed by the same key
+        let wallet = VerifyingPaymasterProvider::get_signing_wallet(provider, version).await?;
+        let verifying_signer = wallet.address();
+        let paymaster = VerifyingPaymasterProvider::get_address(version)?;
+        let mut resigned = vec![];
+        let mut records = vec![];
+        for tenant in TenantDao::get_tenants(&mut **txn).await? {
+            records.extend(
+                UserOperationDao::get_user_operations_by_status(
+                    &mut **txn,
+                    tenant.id,
+                    Status::INITIATED.to_string(),
+                )
+                .await?,
+            );
+        }
+        for record in records {
+            let mut user_op = record.user_operation;
+            let Ok((address, valid_until, valid_after, signature)) =
+                VerifyingPaymasterProvider::parse_paymaster_and_data(&user_op.paymaster_and_data)
+            else {
+                continue;
+            };
+            if address != paymaster {
+                continue;
+            }
+            let hash = VerifyingPaymasterProvider::get_paymaster_hash(
+                provider,
+                version,
+                user_op.clone(),
+                valid_until,
+                valid_after,
+            )
+            .await?;
+            let signer = Signature::try_from(signature.as_ref())
+                .ok()
+                .and_then(|signature| signature.recover(hash.to_vec()).ok());
+            if signer == Some(verifying_signer) {
+                continue;
+            }
+
+            // the hash does not cover the paymaster signature, so it is signed as is
+            let signed_hash = VerifyingPaymasterProvider::sign_hash(&wallet, hash).await?;
+            user_op.paymaster_and_data(
+                VerifyingPaymasterProvider::get_validity_data(valid_until, valid_after),
+                paymaster,
+                Some(signed_hash),
+            );
+            UserOperationDao::update_user_operation(
+                &mut **txn,
+                record.tenant_id,
+                record.transaction_id.clone(),
+                user_op.clone(),
+            )
+            .await?;
+            resigned.push(ResignedOperation {
+                transaction_id: record.transaction_id,
+                msg_hash: Bundler::get_user_op_hash(&user_op)?,
+            });
+        }
+        Ok(PaymasterSignerRotation {
+            verifying_signer,
+            resigned,
+        })
+    }
+
+    async fn begin(pool: &Pool<Postgres>) -> Result<Transaction<'static, Postgres>, AdminError> {
+        pool.begin()
+            .await
+            .map_err(|err| AdminError::Database(format!("Failed to start transaction: {:?}", err)))
+    }
+
+    // the audit entry commits with the action's writes. A failed action is rolled back and
+    // recorded on its own
+    async fn commit_audited<T>(
+        pool: &Pool<Postgres>,
+        mut txn: Transaction<'_, Postgres>,
+        admin: &AdminUser,
+        action: &str,
+        parameters: Value,
+        result: Result<T, AdminError>,
+    ) -> Result<T, AdminError> {
+        match result {
+            Ok(response) => {
+                AdminAuditDao::create_entry(
+                    &mut *txn,
+                    Self::get_audit_entry(admin, action, parameters, None, None),
+                )
+                .await?;
+                txn.commit().await.map_err(|err| {
+                    AdminError::Database(format!("Failed to commit {}: {:?}", action, err))
+                })?;
+                Ok(response)
+            }
+            Err(err) => {
+                drop(txn);
+                Self::audit_failure(pool, admin, action, parameters, &err).await;
+                Err(err)
+            }
+        }
+    }
+
+    // the transaction is sent by now, so a failed audit write is logged and not returned, the
+    // caller must not retry a transfer that went through
+    async fn audit_transfer(
+        pool: &Pool<Postgres>,
+        admin: &AdminUser,
+        action: &str,
+        parameters: Value,
+        response: &Result<TransferResponse, AdminError>,
+    ) {
+        let response = match response {
+            Ok(response) => response,
+            Err(err) => return Self::audit_failure(pool, admin, action, parameters, err).await,
+        };
+        let entry = Self::get_audit_entry(
+            admin,
+            action,
+            parameters,
+            Some(response.transaction.transaction_hash.clone()),
+            None,
+        );
+        if let Err(err) = AdminAuditDao::create_entry(pool, entry).await {
+            error!("Failed to audit {} by {}: {:?}", action, admin.key_id, err);
+        }
+    }
+
+    async fn audit_failure(
+        pool: &Pool<Postgres>,
+        admin: &AdminUser,
+        action: &str,
+        parameters: Value,
+        err: &AdminError,
+    ) {
+        let entry = Self::get_audit_entry(admin, action, parameters, None, Some(err.to_string()));
+        if let Err(err) = AdminAuditDao::create_entry(pool, entry).await {
+            error!("Failed to audit {} by {}: {:?}", action, admin.key_id, err);
+        }
+    }
+
+    fn get_audit_entry(
+        admin: &AdminUser,
+        action: &str,
+        parameters: Value,
+        transaction_hash: Option<String>,
+        error: Option<String>,
+    ) -> AdminAuditEntry {
+        AdminAuditEntry {
+            actor: admin.key_id.clone(),
+            action: action.to_string(),
+            parameters,
+            transaction_hash,
+            outcome: match error {
+                None => Constants::AUDIT_SUCCESS,
+                Some(_) => Constants::AUDIT_FAILURE,
+            }
+            .to_string(),
+            error,
+            ..Default::default()
+        }
+    }
+
+    fn to_datetime(timestamp: u64) -> Result<DateTime<Utc>, AdminError> {
+        Utc.timestamp_opt(timestamp as i64, 0)
+            .single()
+            .ok_or(AdminError::ValidationError(String::from(
+                "Invalid timestamp",
+            )))
+    }
+
+    // the paymaster and the EntryPoint it deposits and stakes in. A chain can serve several
+    // EntryPoint versions, each with its own verifying paymaster, the token paymaster serves the
+    // version ops are built for
+    fn get_paymaster(
+        paymaster: &str,
+        version: Option<EntryPointVersion>,
+    ) -> Result<(Address, Address), AdminError> {
+        let chain = CONFIG.get_chain();
+        let version = version.unwrap_or_else(|| chain.get_entrypoint_version());
+        let entry_point =
+            chain
+                .get_entrypoint_address(version)
+                .ok_or(AdminError::ValidationError(format!(
+                    "Unsupported entrypoint version {}",
+                    version
+                )))?;
+        let paymaster_address = match paymaster {
+            Constants::VERIFYING_PAYMASTER => VerifyingPaymasterProvider::get_address(version)
+                .map_err(|_| AdminError::ValidationError(String::from("Invalid Paymaster")))?,
+            Constants::TOKEN_PAYMASTER if version == chain.get_entrypoint_version() => {
+                TokenPaymasterProvider::get_address()
+                    .map_err(|_| AdminError::ValidationError(String::from("Invalid Paymaster")))?
+            }
+            _ => {
+                return Err(AdminError::ValidationError(String::from(
+                    "Invalid Paymaster",
+                )))
+            }
+        };
+        Ok((paymaster_address, entry_point))
+    }
+
+    fn get_transfer_response(
+        response: Result<String, String>,
+    ) -> Result<TransferResponse, AdminError> {
+        match response {
+            Ok(txn_hash) => Ok(TransferResponse {
+                transaction: TransactionResponse::new(
+                    txn_hash.clone(),
+                    Status::PENDING,
+                    CONFIG.get_chain().explorer_url.clone() + &txn_hash.clone(),
+                ),
+                transaction_id: "".to_string(),
+            }),
+            Err(err) => Err(AdminError::Provider(err)),
+        }
+    }
+
+    // the ledger stores wei, profit can be negative so format_ether does not fit
+    fn to_ether(wei: &BigDecimal) -> String {
+        (wei * BigDecimal::new(1.into(), 18))
+            .normalized()
+            .to_string()
+    }
+
     fn get_balance_response(
         address: &Address,
         balance: String,
@@ -0,0 +1,264 @@
+use actix_web::rt::time::interval;
+use ethers::providers::{Middleware, PendingTransaction};
+use ethers::types::{Address, BlockNumber, Bytes, H256, U256, U64};
+use ethers::utils::parse_ether;
+use ethers_signers::Signer;
+use log::{error, info, warn};
+use serde_json::json;
+use sqlx::{Pool, Postgres};
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::bundler::Bundler;
+use crate::constants::Constants;
+use crate::contracts::entrypoint_provider::EntryPointProvider;
+use crate::contracts::verifying_paymaster_provider::VerifyingPaymasterProvider;
+use crate::db::dao::BalanceTopupDao;
+use crate::models::config::settings::BalanceMonitor;
+use crate::provider::helpers::to_decimal;
+use crate::provider::web3_provider::Web3Provider;
+use crate::provider::Web3Client;
+use crate::{CONFIG, PROVIDER};
+
+// addresses below their threshold, so an alert is sent once per crossing rather than every tick
+#[derive(Default)]
+struct Alerts {
+    below: HashSet<Address>,
+}
+
+impl Alerts {
+    // true when the address just dropped below its threshold
+    fn crossed(&mut self, address: Address, below: bool) -> bool {
+        if below {
+            self.below.insert(address)
+        } else {
+            self.below.remove(&address);
+            false
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct BalanceMonitorService;
+
+impl BalanceMonitorService {
+    // top ups are awaited until mined, so the next tick sees the new balance
+    pub async fn run(pool: Pool<Postgres>, provider: Web3Client, config: BalanceMonitor) {
+        if config.auto_topup && Web3Client::get_treasury_wallet().is_none() {
+            error!("Balance monitor: auto top up needs a treasury signer, monitor not started");
+            return;
+        }
+        info!("Balance monitor: checking every {}s", config.interval_secs);
+        let mut alerts = Alerts::default();
+        let mut ticker = interval(Duration::from_secs(config.interval_secs));
+        loop {
+            ticker.tick().await;
+            if let Err(err) =
+                Self::check_paymaster_deposit(&pool, &provider, &config, &mut alerts).await
+            {
+                error!("Balance monitor: paymaster: {}", err);
+            }
+            // every relayer in the pool is rebalanced from the treasury key
+            for relayer in provider.get_relayer_addresses() {
+                if let Err(err) =
+                    Self::check_relayer_balance(&pool, &provider, &config, &mut alerts, relayer)
+                        .await
+                {
+                    error!("Balance monitor: relayer {:?}: {}", relayer, err);
+                }
+            }
+        }
+    }
+
+    async fn check_paymaster_deposit(
+        pool: &Pool<Postgres>,
+        provider: &Web3Client,
+        config: &BalanceMonitor,
+        alerts: &mut Alerts,
+    ) -> Result<(), String> {
+        // the paymaster and entrypoint ops are built for
+        let paymaster =
+            VerifyingPaymasterProvider::get_address(CONFIG.get_chain().get_entrypoint_version())
+                .map_err(|err| err.0)?;
+        let deposit = VerifyingPaymasterProvider::get_deposit(provider)
+            .await
+            .map_err(|err| err.0)?;
+        let below = Self::to_wei(&deposit)? < Self::to_wei(&config.paymaster_deposit_threshold)?;
+        if alerts.crossed(paymaster, below) {
+            Self::alert(
+                config,
+                format!(
+                    "Paymaster {:?} deposit {} {} is below {}",
+                    paymaster,
+                    deposit,
+                    CONFIG.get_chain().currency,
+                    config.paymaster_deposit_threshold
+                ),
+            )
+            .await;
+        }
+        if !below || !config.auto_topup || Self::has_pending_topup().await? {
+            return Ok(());
+        }
+
+        let amount = Self::to_wei(&config.paymaster_topup_amount)?;
+        let data = EntryPointProvider::add_deposit(provider, paymaster)
+            .await
+            .map_err(|err| err.0)?;
+        let txn_hash = Web3Provider::execute(
+            provider
+                .get_treasury_signer()
+                .ok_or(String::from("Treasury signer is not configured"))?,
+            Bundler::get_entrypoint_address().map_err(|err| err.0)?,
+            amount.to_string(),
+            data,
+            provider.get_entrypoint_provider().abi(),
+        )
+        .await?;
+        Self::record_topup(
+            pool,
+            Constants::PAYMASTER,
+            paymaster,
+            amount,
+            txn_hash.clone(),
+        )
+        .await?;
+        Self::wait_for_topup(txn_hash).await
+    }
+
+    async fn check_relayer_balance(
+        pool: &Pool<Postgres>,
+        provider: &Web3Client,
+        config: &BalanceMonitor,
+        alerts: &mut Alerts,
+        relayer: Address,
+    ) -> Result<(), String> {
+        let balance = Web3Provider::get_balance(relayer)
+            .await
+            .map_err(|err| err.0)?;
+        let below = Self::to_wei(&balance)? < Self::to_wei(&config.relayer_balance_threshold)?;
+        if alerts.crossed(relayer, below) {
+            Self::alert(
+                config,
+                format!(
+                    "Relayer {:?} balance {} {} is below {}",
+                    relayer,
+                    balance,
+                    CONFIG.get_chain().currency,
+                    config.relayer_balance_threshold
+                ),
+            )
+            .await;
+        }
+        if !below || !config.auto_topup || Self::has_pending_topup().await? {
+            return Ok(());
+        }
+
+        let amount = Self::to_wei(&config.relayer_topup_amount)?;
+        let txn_hash = Web3Provider::execute(
+            provider
+                .get_treasury_signer()
+                .ok_or(String::from("Treasury signer is not configured"))?,
+            relayer,
+            amount.to_string(),
+            Bytes::default(),
+            provider.get_entrypoint_provider().abi(),
+        )
+        .await?;
+        Self::record_topup(pool, Constants::RELAYER, relayer, amount, txn_hash.clone()).await?;
+        Self::wait_for_topup(txn_hash).await
+    }
+
+    // a top up that is still pending from an earlier tick (eg: the wait timed out) is not resent
+    async fn has_pending_topup() -> Result<bool, String> {
+        let treasury = Web3Client::get_treasury_wallet()
+            .ok_or(String::from("Treasury signer is not configured"))?
+            .address();
+        let pending = PROVIDER
+            .get_transaction_count(treasury, Some(BlockNumber::Pending.into()))
+            .await
+            .map_err(|err| format!("Failed to get pending nonce: {:?}", err))?;
+        let mined = PROVIDER
+            .get_transaction_count(treasury, Some(BlockNumber::Latest.into()))
+            .await
+            .map_err(|err| format!("Failed to get nonce: {:?}", err))?;
+        if pending > mined {
+            info!("Balance monitor: treasury has pending transactions, top up skipped");
+        }
+        Ok(pending > mined)
+    }
+
+    async fn wait_for_topup(txn_hash: String) -> Result<(), String> {
+        let hash = H256::from_str(&txn_hash).map_err(|_| String::from("Invalid txn hash"))?;
+        let receipt = PendingTransaction::new(hash, &PROVIDER)
+            .await
+            .map_err(|err| format!("Failed to get top up receipt: {:?}", err))?
+            .ok_or(format!("Top up dropped: {}", txn_hash))?;
+        if receipt.status != Some(U64::from(1)) {
+            return Err(format!("Top up reverted: {}", txn_hash));
+        }
+        Ok(())
+    }
+
+    async fn record_topup(
+        pool: &Pool<Postgres>,
+        entity: &str,
+        address: Address,
+        amount: U256,
+        txn_hash: String,
+    ) -> Result<(), String> {
+        info!(
+            "Balance monitor: topped up {} {:?} with {} wei, txn_hash -> {}",
+            entity, address, amount, txn_hash
+        );
+        BalanceTopupDao::create_topup(
+            pool,
+            entity.to_string(),
+            format!("{:?}", address),
+            to_decimal(amount),
+            CONFIG.get_chain().currency.clone(),
+            txn_hash,
+        )
+        .await
+        .map_err(|err| format!("{:?}", err))
+    }
+
+    async fn alert(config: &BalanceMonitor, message: String) {
+        warn!("Balance monitor: {}", message);
+        if let Some(webhook_url) = &config.webhook_url {
+            let response = reqwest::Client::new()
+                .post(webhook_url)
+                .json(&json!({ "text": message }))
+                .send()
+                .await;
+            if let Err(err) = response {
+                error!("Balance monitor: webhook failed: {:?}", err);
+            }
+        }
+    }
+
+    fn to_wei(ether: &str) -> Result<U256, String> {
+        parse_ether(ether).map_err(|err| format!("Invalid amount {}: {:?}", ether, err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alerts_once_per_threshold_crossing() {
+        let mut alerts = Alerts::default();
+        let relayer = Address::repeat_byte(1);
+        let paymaster = Address::repeat_byte(2);
+
+        assert!(!alerts.crossed(relayer, false));
+        assert!(alerts.crossed(relayer, true));
+        assert!(!alerts.crossed(relayer, true));
+        assert!(alerts.crossed(paymaster, true));
+
+        assert!(!alerts.crossed(relayer, false));
+        assert!(alerts.crossed(relayer, true));
+    }
+}
@@ -0,0 +1,243 @@
+use ethers::types::transaction::eip712::{Eip712, TypedData};
+use ethers::types::{Address, Bytes, Signature, H256};
+use ethers::utils::hash_message;
+use serde_json::json;
+
+use crate::contracts::erc1271_provider::Erc1271Provider;
+use crate::contracts::simple_account_provider::SimpleAccountProvider;
+use crate::db::dao::User;
+use crate::errors::MessageError;
+use crate::models::message::{
+    MessagePrepareResponse, MessageRequest, MessageSignRequest, MessageSignResponse,
+};
+use crate::provider::Web3Client;
+use crate::CONFIG;
+
+// the account's ERC-1271 domain, the owner signs the hash wrapped in it so a signature for one
+// wallet can not be replayed against another wallet of the same owner. Only the rotatable
+// implementation implements it, the SimpleAccount the factory deploys has no isValidSignature
+const ACCOUNT_DOMAIN_NAME: &str = "SimpleAccount";
+const ACCOUNT_DOMAIN_VERSION: &str = "1";
+
+#[derive(Clone)]
+pub struct MessageService;
+
+impl MessageService {
+    pub async fn prepare(
+        request: MessageRequest,
+        user: User,
+    ) -> Result<MessagePrepareResponse, MessageError> {
+        Self::get_owner(&user)?;
+        let wallet_address = Self::get_signing_wallet(&user).await?;
+        let hash = Self::get_hash(&request)?;
+        let typed_data =
+            Self::get_account_message(wallet_address, CONFIG.get_chain().chain_id, hash)?;
+        Ok(MessagePrepareResponse {
+            hash,
+            owner_hash: Self::encode_typed_data(&typed_data)?,
+            typed_data,
+        })
+    }
+
+    // returns the owner's signature once the wallet accepts it over isValidSignature
+    pub async fn sign(
+        provider: &Web3Client,
+        request: MessageSignRequest,
+        user: User,
+    ) -> Result<MessageSignResponse, MessageError> {
+        let owner = Self::get_owner(&user)?;
+        let wallet_address = Self::get_signing_wallet(&user).await?;
+        let hash = Self::get_hash(&request.message)?;
+        let owner_hash = Self::encode_typed_data(&Self::get_account_message(
+            wallet_address,
+            CONFIG.get_chain().chain_id,
+            hash,
+        )?)?;
+        if Self::recover_signer(owner_hash, &request.signature)? != owner {
+            return Err(MessageError::SignatureMismatch);
+        }
+        if !Erc1271Provider::is_valid_signature(
+            provider,
+            wallet_address,
+            hash,
+            request.signature.clone(),
+        )
+        .await?
+        {
+            return Err(MessageError::InvalidSignature);
+        }
+        Ok(MessageSignResponse {
+            hash,
+            signature: request.signature,
+        })
+    }
+
+    // accounts that are not deployed or still on the SimpleAccount implementation can't verify
+    // the signature, rotating the owner once upgrades them
+    async fn get_signing_wallet(user: &User) -> Result<Address, MessageError> {
+        let unsupported = || {
+            MessageError::InvalidRequest(String::from(
+                "Wallet does not support message signing, rotate the owner once to enable it",
+            ))
+        };
+        let implementation = CONFIG
+            .get_chain()
+            .rotatable_account_implementation
+            .ok_or_else(unsupported)?;
+        let wallet_address: Address = user.wallet_address.parse().map_err(|_| {
+            MessageError::Provider(format!("Invalid wallet address: {}", user.wallet_address))
+        })?;
+        if SimpleAccountProvider::get_implementation(wallet_address).await? != implementation {
+            return Err(unsupported());
+        }
+        Ok(wallet_address)
+    }
+
+    fn recover_signer(owner_hash: H256, signature: &Bytes) -> Result<Address, MessageError> {
+        Signature::try_from(signature.as_ref())
+            .and_then(|signature| signature.recover(owner_hash))
+            .map_err(|_| MessageError::InvalidSignature)
+    }
+
+    fn get_owner(user: &User) -> Result<Address, MessageError> {
+        if user.wallet_address.is_empty() {
+            return Err(MessageError::NotFound);
+        }
+        if user.is_passkey_owner() {
+            return Err(MessageError::InvalidRequest(String::from(
+                "Message signing is only supported for EOA owners",
+            )));
+        }
+        user.owner_address.parse().map_err(|_| {
+            MessageError::Provider(format!("Invalid owner address: {}", user.owner_address))
+        })
+    }
+
+    // the hash the dApp verifies, as it computes it for an EOA signer
+    fn get_hash(request: &MessageRequest) -> Result<H256, MessageError> {
+        match (&request.message, &request.typed_data) {
+            (Some(message), None) => Ok(hash_message(message)),
+            (None, Some(typed_data)) => Self::encode_typed_data(typed_data),
+            _ => Err(MessageError::InvalidRequest(String::from(
+                "Either message or typed_data is required",
+            ))),
+        }
+    }
+
+    fn get_account_message(
+        wallet_address: Address,
+        chain_id: u64,
+        hash: H256,
+    ) -> Result<TypedData, MessageError> {
+        serde_json::from_value(json!({
+            "types": {
+                "EIP712Domain": [
+                    { "name": "name", "type": "string" },
+                    { "name": "version", "type": "string" },
+                    { "name": "chainId", "type": "uint256" },
+                    { "name": "verifyingContract", "type": "address" }
+                ],
+                "AccountMessage": [
+                    { "name": "hash", "type": "bytes32" }
+                ]
+            },
+            "primaryType": "AccountMessage",
+            "domain": {
+                "name": ACCOUNT_DOMAIN_NAME,
+                "version": ACCOUNT_DOMAIN_VERSION,
+                "chainId": chain_id,
+                "verifyingContract": wallet_address
+            },
+            "message": {
+                "hash": hash
+            }
+        }))
+        .map_err(|err| {
+            MessageError::Provider(format!("Failed to build account message: {:?}", err))
+        })
+    }
+
+    fn encode_typed_data(typed_data: &TypedData) -> Result<H256, MessageError> {
+        typed_data
+            .encode_eip712()
+            .map(H256::from)
+            .map_err(|err| MessageError::InvalidRequest(format!("Invalid typed data: {}", err)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::abi::{encode, Token};
+    use ethers::types::U256;
+    use ethers::utils::keccak256;
+    use ethers_signers::{LocalWallet, Signer};
+
+    use super::*;
+
+    #[test]
+    fn account_message_matches_the_contract_digest() {
+        let wallet_address = Address::from_low_u64_be(7);
+        let hash = H256::repeat_byte(0xab);
+        let typed_data = MessageService::get_account_message(wallet_address, 84532, hash).unwrap();
+
+        let domain_separator = keccak256(encode(&[
+            Token::FixedBytes(
+                keccak256("EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)")
+                    .to_vec(),
+            ),
+            Token::FixedBytes(keccak256(ACCOUNT_DOMAIN_NAME).to_vec()),
+            Token::FixedBytes(keccak256(ACCOUNT_DOMAIN_VERSION).to_vec()),
+            Token::Uint(U256::from(84532)),
+            Token::Address(wallet_address),
+        ]));
+        let struct_hash = keccak256(encode(&[
+            Token::FixedBytes(keccak256("AccountMessage(bytes32 hash)").to_vec()),
+            Token::FixedBytes(hash.as_bytes().to_vec()),
+        ]));
+        let digest = keccak256([&[0x19, 0x01], &domain_separator[..], &struct_hash[..]].concat());
+
+        assert_eq!(
+            MessageService::encode_typed_data(&typed_data).unwrap(),
+            H256::from(digest)
+        );
+    }
+
+    #[test]
+    fn owner_signature_verifies_only_for_its_wallet() {
+        let owner = LocalWallet::from_bytes(&[1u8; 32]).unwrap();
+        let hash = MessageService::get_hash(&MessageRequest {
+            message: Some(String::from("sign in")),
+            typed_data: None,
+        })
+        .unwrap();
+        let owner_hash = |wallet_address| {
+            MessageService::encode_typed_data(
+                &MessageService::get_account_message(wallet_address, 84532, hash).unwrap(),
+            )
+            .unwrap()
+        };
+
+        let wallet_address = Address::from_low_u64_be(7);
+        let signature = Bytes::from(
+            owner
+                .sign_hash(owner_hash(wallet_address))
+                .unwrap()
+                .to_vec(),
+        );
+
+        assert_eq!(
+            MessageService::recover_signer(owner_hash(wallet_address), &signature).unwrap(),
+            owner.address()
+        );
+        assert_ne!(
+            MessageService::recover_signer(owner_hash(Address::from_low_u64_be(8)), &signature)
+                .unwrap(),
+            owner.address()
+        );
+        assert!(MessageService::recover_signer(
+            owner_hash(wallet_address),
+            &Bytes::from(vec![0u8; 10])
+        )
+        .is_err());
+    }
+}
@@ -3,7 +3,6 @@ use log::{error, info};
 
 use crate::contracts::usdc_provider::USDCProvider;
 use crate::models::config::env::ENV;
-use crate::provider::web3_provider::Web3Provider;
 use crate::provider::Web3Client;
 use crate::CONFIG;
 
@@ -22,14 +21,14 @@ impl MintService {
         info!("minting for {:?}", receiver);
         let call_data =
             USDCProvider::mint(&provider.clone(), receiver, "100000000".to_string()).unwrap();
-        let response = Web3Provider::execute(
-            provider.get_relayer_signer(),
-            CONFIG.get_chain().usdc_address,
-            "0".to_string(),
-            call_data,
-            provider.get_usdc_provider().abi(),
-        )
-        .await;
+        let response = provider
+            .execute_from_pool(
+                CONFIG.get_chain().usdc_address,
+                "0".to_string(),
+                call_data,
+                provider.get_usdc_provider().abi(),
+            )
+            .await;
         match response {
             Ok(txn_hash) => {
                 info!(
@@ -1,15 +1,33 @@
 pub mod admin_service;
 pub mod auth_service;
+pub mod balance_monitor_service;
 pub mod balance_service;
 pub mod hello_world_service;
+pub mod message_service;
 pub mod mint_service;
+pub mod paymaster_service;
+pub mod recovery_service;
+pub mod session_key_service;
+pub mod sponsorship_reconciliation_service;
+pub mod sponsorship_service;
 pub mod token_metadata_service;
 pub mod transfer_service;
+pub mod wallet_reconciliation_service;
 pub mod wallet_service;
+pub mod webhook_service;
 
 pub use admin_service::AdminService;
+pub use balance_monitor_service::BalanceMonitorService;
 pub use balance_service::BalanceService;
+pub use message_service::MessageService;
 pub use mint_service::MintService;
+pub use paymaster_service::PaymasterService;
+pub use recovery_service::RecoveryService;
+pub use session_key_service::SessionKeyService;
+pub use sponsorship_reconciliation_service::SponsorshipReconciliationService;
+pub use sponsorship_service::SponsorshipService;
 pub use token_metadata_service::TokenMetadataService;
 pub use transfer_service::TransferService;
+pub use wallet_reconciliation_service::WalletReconciliationService;
 pub use wallet_service::WalletService;
+pub use webhook_service::WebhookService;
@@ -0,0 +1,159 @@
+use chrono::Utc;
+use ethers::types::{Address, Bytes, H256, U256};
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::{Pool, Postgres};
+
+use crate::constants::Constants;
+use crate::contracts::verifying_paymaster_provider::VerifyingPaymasterProvider;
+use crate::db::dao::{PaymasterClient, PaymasterClientDao};
+use crate::errors::PaymasterError;
+use crate::models::contract_interaction::{EntryPointVersion, UserOperation};
+use crate::models::paymaster::{
+    PaymasterDataResponse, PaymasterStubDataResponse, RpcUserOperation, Sponsor,
+};
+use crate::provider::helpers::get_api_key_hash;
+use crate::provider::Web3Client;
+use crate::services::SponsorshipService;
+use crate::CONFIG;
+
+// well formed signature that recovers to an address, so gas estimation does not revert
+const DUMMY_SIGNATURE: &str = "0xfffffffffffffffffffffffffffffff0000000000000000000000000000000007aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa1c";
+
+#[derive(Clone)]
+pub struct PaymasterService;
+
+impl PaymasterService {
+    pub async fn handle_request(
+        pool: &Pool<Postgres>,
+        provider: &Web3Client,
+        api_key: String,
+        method: &str,
+        params: Vec<Value>,
+    ) -> Result<Value, PaymasterError> {
+        let client =
+            match PaymasterClientDao::get_active_client(pool, get_api_key_hash(&api_key)).await? {
+                Some(client) => client,
+                None => return Err(PaymasterError::Unauthorized),
+            };
+        match method {
+            Constants::PM_GET_PAYMASTER_STUB_DATA => {
+                let user_op = Self::get_user_operation(params)?;
+                Self::to_value(Self::get_stub_data(pool, &client, user_op).await?)
+            }
+            Constants::PM_GET_PAYMASTER_DATA => {
+                let user_op = Self::get_user_operation(params)?;
+                Self::to_value(Self::get_data(pool, provider, &client, user_op).await?)
+            }
+            _ => Err(PaymasterError::MethodNotFound(method.to_string())),
+        }
+    }
+
+    async fn get_stub_data(
+        pool: &Pool<Postgres>,
+        client: &PaymasterClient,
+        mut user_op: UserOperation,
+    ) -> Result<PaymasterStubDataResponse, PaymasterError> {
+        SponsorshipService::check_client_budget(
+            pool,
+            client,
+            format!("{:?}", user_op.sender),
+            &user_op,
+        )
+        .await?;
+        let signature: Bytes = DUMMY_SIGNATURE.parse().unwrap();
+        user_op.paymaster_and_data(
+            VerifyingPaymasterProvider::get_validity_data(
+                VerifyingPaymasterProvider::VALID_UNTIL,
+                VerifyingPaymasterProvider::VALID_AFTER,
+            ),
+            CONFIG.get_chain().verifying_paymaster_address,
+            Some(signature.to_vec()),
+        );
+        Ok(PaymasterStubDataResponse {
+            sponsor: Sponsor {
+                name: CONFIG.run_config.deployed_by_identifier.clone(),
+            },
+            paymaster_and_data: user_op.paymaster_and_data,
+            is_final: false,
+        })
+    }
+
+    async fn get_data(
+        pool: &Pool<Postgres>,
+        provider: &Web3Client,
+        client: &PaymasterClient,
+        mut user_op: UserOperation,
+    ) -> Result<PaymasterDataResponse, PaymasterError> {
+        // the reservation is released once the op can no longer be mined
+        let valid_until =
+            Utc::now().timestamp() as u64 + SponsorshipService::RESERVATION_SECS as u64;
+        let valid_after = VerifyingPaymasterProvider::VALID_AFTER;
+        let signed_hash = VerifyingPaymasterProvider::get_signed_hash(
+            provider,
+            EntryPointVersion::V06,
+            user_op.clone(),
+            valid_until,
+            valid_after,
+        )
+        .await?;
+        user_op.paymaster_and_data(
+            VerifyingPaymasterProvider::get_validity_data(valid_until, valid_after),
+            CONFIG.get_chain().verifying_paymaster_address,
+            Some(signed_hash),
+        );
+
+        // external ops have no transaction, they are tracked by their user op hash. The signature
+        // is only returned once the budget is reserved
+        let user_op_hash = format!(
+            "{:?}",
+            H256::from(user_op.hash(
+                CONFIG.get_chain().entrypoint_address,
+                CONFIG.get_chain().chain_id,
+            ))
+        );
+        SponsorshipService::reserve_for_client(
+            pool,
+            client,
+            user_op_hash,
+            format!("{:?}", user_op.sender),
+            &user_op,
+        )
+        .await?;
+
+        Ok(PaymasterDataResponse {
+            paymaster_and_data: user_op.paymaster_and_data,
+        })
+    }
+
+    // params: [userOp, entryPoint, chainId, context]
+    fn get_user_operation(params: Vec<Value>) -> Result<UserOperation, PaymasterError> {
+        if params.len() < 3 {
+            return Err(PaymasterError::InvalidParams(String::from(
+                "Expected userOp, entryPoint and chainId",
+            )));
+        }
+        // the verifying paymaster is deployed against the v0.6 entrypoint
+        let entry_point: Address = serde_json::from_value(params[1].clone())
+            .map_err(|_| PaymasterError::InvalidParams(String::from("Invalid entryPoint")))?;
+        if entry_point != CONFIG.get_chain().entrypoint_address {
+            return Err(PaymasterError::InvalidParams(String::from(
+                "Unsupported entryPoint",
+            )));
+        }
+        let chain_id: U256 = serde_json::from_value(params[2].clone())
+            .map_err(|_| PaymasterError::InvalidParams(String::from("Invalid chainId")))?;
+        if chain_id != U256::from(CONFIG.get_chain().chain_id) {
+            return Err(PaymasterError::InvalidParams(String::from(
+                "Unsupported chainId",
+            )));
+        }
+        let user_op: RpcUserOperation = serde_json::from_value(params[0].clone())
+            .map_err(|err| PaymasterError::InvalidParams(format!("Invalid userOp: {}", err)))?;
+        UserOperation::try_from(user_op).map_err(PaymasterError::InvalidParams)
+    }
+
+    fn to_value<T: Serialize>(response: T) -> Result<Value, PaymasterError> {
+        serde_json::to_value(response).map_err(|err| PaymasterError::Provider(err.to_string()))
+    }
+}
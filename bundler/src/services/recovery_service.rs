@@ -0,0 +1,623 @@
+use actix_web::rt::spawn;
+use actix_web::rt::time::interval;
+use bigdecimal::BigDecimal;
+use chrono::{Duration as ChronoDuration, Utc};
+use ethers::types::{Address, Bytes, Signature, H256};
+use log::{error, info};
+use sqlx::{Pool, Postgres};
+use std::time::Duration;
+
+use crate::bundler::Bundler;
+use crate::contracts::rotatable_account_provider::RotatableAccountProvider;
+use crate::contracts::simple_account_provider::SimpleAccountProvider;
+use crate::db::dao::{
+    GuardianDao, RecoveryDao, RecoveryRequest, SponsorshipDao, Tenant, TenantDao, TransactionDao,
+    User, UserOperationDao, UserTransaction,
+};
+use crate::errors::RecoveryError;
+use crate::models::config::settings::Recovery;
+use crate::models::contract_interaction::UserOperation;
+use crate::models::recovery::{
+    GuardianRequest, GuardiansResponse, RecoveryApprovalRequest, RecoveryResponse, RecoveryStatus,
+};
+use crate::models::transfer::{Status, TransferInitResponse};
+use crate::models::TransactionType;
+use crate::provider::helpers::generate_secret_id;
+use crate::provider::listeners::{bundle_listener, user_op_event_listener};
+use crate::provider::Web3Client;
+use crate::services::{SponsorshipService, TransferService};
+use crate::CONFIG;
+
+#[derive(Clone)]
+pub struct RecoveryService;
+
+impl RecoveryService {
+    pub async fn get_guardians(
+        pool: &Pool<Postgres>,
+        user: User,
+    ) -> Result<GuardiansResponse, RecoveryError> {
+        let guardians =
+            GuardianDao::get_guardians(pool, user.tenant_id, user.wallet_address).await?;
+        Ok(GuardiansResponse {
+            threshold: Self::get_threshold(guardians.len()),
+            guardians,
+        })
+    }
+
+    // guardians are kept by the account, the owner signs the op that sets them and executes it with
+    // transfer/execute. The guardians table follows the GuardiansChanged event
+    pub async fn add_guardian(
+        pool: &Pool<Postgres>,
+        provider: &Web3Client,
+        tenant: &Tenant,
+        request: GuardianRequest,
+        user: User,
+    ) -> Result<TransferInitResponse, RecoveryError> {
+        let wallet_address = Self::get_recoverable_wallet(&user).await?;
+        if request.guardian.is_zero() {
+            return Err(RecoveryError::InvalidRequest(String::from(
+                "Invalid guardian",
+            )));
+        }
+        if format!("{:?}", request.guardian) == user.owner_address
+            || request.guardian == wallet_address
+        {
+            return Err(RecoveryError::InvalidRequest(String::from(
+                "The owner can not be a guardian",
+            )));
+        }
+        let mut guardians = Self::get_guardian_addresses(pool, &user).await?;
+        if guardians.contains(&request.guardian) {
+            return Err(RecoveryError::InvalidRequest(String::from(
+                "Already a guardian",
+            )));
+        }
+        guardians.push(request.guardian);
+        Self::set_guardians(pool, provider, tenant, guardians, user).await
+    }
+
+    pub async fn remove_guardian(
+        pool: &Pool<Postgres>,
+        provider: &Web3Client,
+        tenant: &Tenant,
+        request: GuardianRequest,
+        user: User,
+    ) -> Result<TransferInitResponse, RecoveryError> {
+        Self::get_recoverable_wallet(&user).await?;
+        let mut guardians = Self::get_guardian_addresses(pool, &user).await?;
+        if !guardians.contains(&request.guardian) {
+            return Err(RecoveryError::InvalidRequest(String::from(
+                "Not a guardian",
+            )));
+        }
+        guardians.retain(|guardian| *guardian != request.guardian);
+        Self::set_guardians(pool, provider, tenant, guardians, user).await
+    }
+
+    // started by the authenticated user, usually from a new device without the owner key
+    pub async fn init_recovery(
+        pool: &Pool<Postgres>,
+        provider: &Web3Client,
+        new_owner: Address,
+        user: User,
+    ) -> Result<RecoveryResponse, RecoveryError> {
+        let wallet_address = Self::get_recoverable_wallet(&user).await?;
+        if new_owner.is_zero() || format!("{:?}", new_owner) == user.owner_address {
+            return Err(RecoveryError::InvalidRequest(String::from("Invalid owner")));
+        }
+        if GuardianDao::get_guardians(pool, user.tenant_id, user.wallet_address.clone())
+            .await?
+            .is_empty()
+        {
+            return Err(RecoveryError::InvalidRequest(String::from(
+                "No guardians set",
+            )));
+        }
+        if RecoveryDao::get_open_request(
+            pool,
+            user.tenant_id,
+            user.wallet_address.clone(),
+            Self::open_statuses(),
+        )
+        .await?
+        .is_some()
+        {
+            return Err(RecoveryError::InvalidRequest(String::from(
+                "A recovery is already in progress",
+            )));
+        }
+        let recovery_hash =
+            RotatableAccountProvider::get_recovery_hash(provider, wallet_address, new_owner)
+                .await?;
+
+        // guardians look the request up by its id alone
+        let request_id = generate_secret_id("recovery");
+        RecoveryDao::create_request(
+            pool,
+            user.tenant_id,
+            request_id.clone(),
+            user.wallet_address,
+            format!("{:?}", new_owner),
+            format!("{:?}", H256::from(recovery_hash)),
+            RecoveryStatus::Pending.to_string(),
+        )
+        .await?;
+        Self::get_recovery(pool, user.tenant_id, request_id).await
+    }
+
+    pub async fn get_recovery(
+        pool: &Pool<Postgres>,
+        tenant_id: i32,
+        request_id: String,
+    ) -> Result<RecoveryResponse, RecoveryError> {
+        let request = RecoveryDao::get_request(pool, tenant_id, request_id).await?;
+        let guardians =
+            GuardianDao::get_guardians(pool, tenant_id, request.wallet_address.clone()).await?;
+        let approvals = RecoveryDao::count_approvals(
+            pool,
+            tenant_id,
+            request.request_id.clone(),
+            request.wallet_address.clone(),
+        )
+        .await?;
+        Ok(RecoveryResponse {
+            recovery_hash: request.recovery_hash.unwrap_or_default(),
+            request_id: request.request_id,
+            wallet_address: request.wallet_address,
+            new_owner: request.new_owner,
+            status: request.status,
+            approvals,
+            threshold: Self::get_threshold(guardians.len()),
+            execute_after: request.execute_after.map(|time| time.timestamp()),
+            transaction_id: request.transaction_id,
+        })
+    }
+
+    // guardians are not users of ours, their signature is the only authentication. The account
+    // checks the signatures again when the recovery is submitted
+    pub async fn approve_recovery(
+        pool: &Pool<Postgres>,
+        tenant: &Tenant,
+        request_id: String,
+        approval: RecoveryApprovalRequest,
+    ) -> Result<RecoveryResponse, RecoveryError> {
+        let request = RecoveryDao::get_request(pool, tenant.id, request_id.clone()).await?;
+        if request.status != RecoveryStatus::Pending.to_string() {
+            return Err(RecoveryError::InvalidRequest(String::from(
+                "Recovery is not pending",
+            )));
+        }
+        let guardian = format!("{:?}", approval.guardian);
+        let guardians =
+            GuardianDao::get_guardians(pool, tenant.id, request.wallet_address.clone()).await?;
+        if !guardians.contains(&guardian) {
+            return Err(RecoveryError::InvalidSignature);
+        }
+        let recovery_hash: H256 = request
+            .recovery_hash
+            .as_deref()
+            .and_then(|hash| hash.parse().ok())
+            .ok_or(RecoveryError::InvalidRequest(String::from(
+                "Recovery can not be approved",
+            )))?;
+        Self::verify_signature(
+            recovery_hash.as_bytes(),
+            &approval.signature,
+            approval.guardian,
+        )?;
+        RecoveryDao::add_approval(
+            pool,
+            request_id.clone(),
+            guardian,
+            approval.signature.to_string(),
+        )
+        .await?;
+
+        let approvals = RecoveryDao::count_approvals(
+            pool,
+            tenant.id,
+            request_id.clone(),
+            request.wallet_address,
+        )
+        .await?;
+        if approvals as usize >= Self::get_threshold(guardians.len()) {
+            let execute_after =
+                Utc::now() + ChronoDuration::seconds(CONFIG.recovery.delay_secs as i64);
+            RecoveryDao::update_status(
+                pool,
+                tenant.id,
+                request_id.clone(),
+                RecoveryStatus::Approved.to_string(),
+                Some(execute_after),
+                None,
+            )
+            .await?;
+            info!(
+                "recovery {} approved, executes after {}",
+                request_id, execute_after
+            );
+        }
+        Self::get_recovery(pool, tenant.id, request_id).await
+    }
+
+    // the current owner can stop a recovery until it is submitted
+    pub async fn cancel_recovery(
+        pool: &Pool<Postgres>,
+        request_id: String,
+        signature: Bytes,
+        user: User,
+    ) -> Result<RecoveryResponse, RecoveryError> {
+        let request = RecoveryDao::get_request(pool, user.tenant_id, request_id.clone()).await?;
+        if request.wallet_address != user.wallet_address {
+            return Err(RecoveryError::NotFound);
+        }
+        if !Self::open_statuses().contains(&request.status) {
+            return Err(RecoveryError::InvalidRequest(String::from(
+                "Recovery can not be cancelled",
+            )));
+        }
+        Self::verify_signature(
+            format!("Cancel recovery request {}", request_id).as_bytes(),
+            &signature,
+            user.owner_address
+                .parse()
+                .map_err(|_| RecoveryError::InvalidSignature)?,
+        )?;
+        RecoveryDao::update_status(
+            pool,
+            user.tenant_id,
+            request_id.clone(),
+            RecoveryStatus::Cancelled.to_string(),
+            None,
+            None,
+        )
+        .await?;
+        Self::get_recovery(pool, user.tenant_id, request_id).await
+    }
+
+    pub async fn run(pool: Pool<Postgres>, provider: Web3Client, config: Recovery) {
+        let mut ticker = interval(Duration::from_secs(config.interval_secs));
+        loop {
+            ticker.tick().await;
+            let requests =
+                RecoveryDao::get_due_requests(&pool, RecoveryStatus::Approved.to_string()).await;
+            match requests {
+                Ok(requests) => {
+                    for request in requests {
+                        let request_id = request.request_id.clone();
+                        if let Err(err) = Self::submit(&pool, &provider, request).await {
+                            error!("Recovery: {}: {}", request_id, err);
+                        }
+                    }
+                }
+                Err(err) => error!("Recovery: {:?}", err),
+            }
+        }
+    }
+
+    // the request is claimed before anything is sent so it is submitted once, a failed submission
+    // gives it back for the next tick
+    async fn submit(
+        pool: &Pool<Postgres>,
+        provider: &Web3Client,
+        request: RecoveryRequest,
+    ) -> Result<(), String> {
+        // the request is submitted by the scheduler, the tenant is the one it was started under
+        let tenant = TenantDao::get_tenant(pool, request.tenant_id)
+            .await
+            .map_err(|err| format!("{:?}", err))?;
+        let user_txn = TransferService::get_account_transaction(
+            &tenant,
+            &request.wallet_address,
+            &request.new_owner,
+            TransactionType::Recovery,
+        );
+        let claimed = RecoveryDao::claim_request(
+            pool,
+            tenant.id,
+            request.request_id.clone(),
+            RecoveryStatus::Approved.to_string(),
+            RecoveryStatus::Submitted.to_string(),
+            Some(user_txn.transaction_id.clone()),
+        )
+        .await
+        .map_err(|err| format!("{:?}", err))?;
+        if !claimed {
+            return Ok(());
+        }
+        let result = Self::send(pool, provider, &tenant, &request, user_txn).await;
+        if result.is_err() {
+            RecoveryDao::claim_request(
+                pool,
+                tenant.id,
+                request.request_id.clone(),
+                RecoveryStatus::Submitted.to_string(),
+                RecoveryStatus::Approved.to_string(),
+                None,
+            )
+            .await
+            .map_err(|err| format!("{:?}", err))?;
+        }
+        result
+    }
+
+    // recoverOwner carries the guardian signatures, the op itself is not signed by anyone
+    async fn send(
+        pool: &Pool<Postgres>,
+        provider: &Web3Client,
+        tenant: &Tenant,
+        request: &RecoveryRequest,
+        mut user_txn: UserTransaction,
+    ) -> Result<(), String> {
+        let wallet_address: Address = request.wallet_address.parse().unwrap();
+        let new_owner: Address = request.new_owner.parse().unwrap();
+        let signatures = RecoveryDao::get_approvals(
+            pool,
+            tenant.id,
+            request.request_id.clone(),
+            request.wallet_address.clone(),
+        )
+        .await
+        .map_err(|err| format!("{:?}", err))?
+        .into_iter()
+        .map(|approval| {
+            let guardian: Address = approval
+                .guardian_address
+                .parse()
+                .map_err(|_| format!("Invalid guardian: {}", approval.guardian_address))?;
+            let signature: Bytes = approval
+                .signature
+                .parse()
+                .map_err(|_| format!("Invalid approval of {}", approval.guardian_address))?;
+            Ok((guardian, signature))
+        })
+        .collect::<Result<Vec<(Address, Bytes)>, String>>()?;
+
+        let mut user_op0 = UserOperation::new();
+        user_op0
+            .nonce(
+                Bundler::get_nonce(provider, wallet_address)
+                    .await
+                    .map_err(|err| err.0)?
+                    .low_u64(),
+            )
+            .sender(wallet_address)
+            .calldata(
+                RotatableAccountProvider::recover_owner(
+                    provider,
+                    new_owner,
+                    RotatableAccountProvider::pack_guardian_signatures(signatures),
+                )
+                .map_err(|err| err.0)?,
+            );
+        let transaction_id = user_txn.transaction_id.clone();
+        SponsorshipService::reserve(
+            pool,
+            tenant,
+            transaction_id.clone(),
+            request.wallet_address.clone(),
+            &user_op0,
+        )
+        .await
+        .map_err(|err| format!("{:?}", err))?;
+        TransferService::set_verifying_paymaster_and_data(provider, &mut user_op0, wallet_address)
+            .await
+            .map_err(|err| format!("{:?}", err))?;
+        let user_op_hash = Bundler::get_user_op_hash(&user_op0).map_err(|err| err.0)?;
+
+        SponsorshipService::hold(pool, transaction_id.clone())
+            .await
+            .map_err(|err| format!("{:?}", err))?;
+        user_txn.status(Status::PENDING.to_string());
+        TransactionDao::create_user_transaction(pool, user_txn)
+            .await
+            .map_err(|err| format!("{:?}", err))?;
+        UserOperationDao::create_user_operation(
+            pool,
+            tenant.id,
+            transaction_id.clone(),
+            user_op0.clone(),
+            Status::PENDING.to_string(),
+        )
+        .await
+        .map_err(|err| format!("{:?}", err))?;
+
+        let txn_hash = match Bundler::submit(provider, user_op0, CONFIG.get_beneficiary()).await {
+            Ok(txn_hash) => txn_hash,
+            Err(err) => {
+                TransactionDao::update_user_transaction(
+                    pool,
+                    tenant.id,
+                    transaction_id.clone(),
+                    None,
+                    Status::FAILED.to_string(),
+                )
+                .await
+                .map_err(|err| format!("{:?}", err))?;
+                // nothing was spent, release the reserved budget
+                SponsorshipDao::update_gas_cost(pool, transaction_id, BigDecimal::from(0))
+                    .await
+                    .map_err(|err| format!("{:?}", err))?;
+                return Err(err.0);
+            }
+        };
+        UserOperationDao::update_user_operation_status(
+            pool,
+            tenant.id,
+            transaction_id.clone(),
+            Status::SUCCESS.to_string(),
+        )
+        .await
+        .map_err(|err| format!("{:?}", err))?;
+        info!(
+            "recovery {} submitted, txn_hash -> {}",
+            request.request_id, txn_hash
+        );
+
+        spawn(bundle_listener(pool.clone(), provider.clone(), txn_hash));
+        spawn(user_op_event_listener(
+            pool.clone(),
+            provider.clone(),
+            tenant.id,
+            user_op_hash,
+            transaction_id,
+        ));
+        Ok(())
+    }
+
+    async fn set_guardians(
+        pool: &Pool<Postgres>,
+        provider: &Web3Client,
+        tenant: &Tenant,
+        guardians: Vec<Address>,
+        user: User,
+    ) -> Result<TransferInitResponse, RecoveryError> {
+        let wallet_address: Address = user.wallet_address.parse().unwrap();
+        let mut user_op0 = UserOperation::new();
+        // like changeOwner, only the owner or the account itself may set the guardians
+        user_op0.calldata(SimpleAccountProvider::execute(
+            provider,
+            wallet_address,
+            String::from("0"),
+            RotatableAccountProvider::set_guardians(provider, guardians)?,
+        )?);
+        let user_txn = TransferService::get_account_transaction(
+            tenant,
+            &user.wallet_address,
+            &user.wallet_address,
+            TransactionType::GuardianChange,
+        );
+        Ok(TransferService::create_sponsored_operation(
+            pool, provider, tenant, user, user_op0, user_txn,
+        )
+        .await?)
+    }
+
+    // guardians are kept by the rotatable implementation, accounts are upgraded to it by rotating
+    // their owner once
+    async fn get_recoverable_wallet(user: &User) -> Result<Address, RecoveryError> {
+        if user.wallet_address.is_empty() {
+            return Err(RecoveryError::NotFound);
+        }
+        let implementation =
+            CONFIG
+                .get_chain()
+                .rotatable_account_implementation
+                .ok_or(RecoveryError::Provider(String::from(
+                    "Rotatable account implementation is not configured for the current chain",
+                )))?;
+        let wallet_address: Address = user.wallet_address.parse().unwrap();
+        if SimpleAccountProvider::get_implementation(wallet_address).await? != implementation {
+            return Err(RecoveryError::InvalidRequest(String::from(
+                "Wallet does not support recovery, rotate the owner once to enable it",
+            )));
+        }
+        Ok(wallet_address)
+    }
+
+    async fn get_guardian_addresses(
+        pool: &Pool<Postgres>,
+        user: &User,
+    ) -> Result<Vec<Address>, RecoveryError> {
+        Ok(
+            GuardianDao::get_guardians(pool, user.tenant_id, user.wallet_address.clone())
+                .await?
+                .into_iter()
+                .filter_map(|guardian| guardian.parse().ok())
+                .collect(),
+        )
+    }
+
+    // a strict majority of the current guardians, as RotatableAccount checks in recoverOwner
+    fn get_threshold(guardians: usize) -> usize {
+        guardians / 2 + 1
+    }
+
+    fn open_statuses() -> Vec<String> {
+        vec![
+            RecoveryStatus::Pending.to_string(),
+            RecoveryStatus::Approved.to_string(),
+        ]
+    }
+
+    // EIP-191 personal messages
+    fn verify_signature(
+        message: &[u8],
+        signature: &Bytes,
+        signer: Address,
+    ) -> Result<(), RecoveryError> {
+        let signature =
+            Signature::try_from(signature.as_ref()).map_err(|_| RecoveryError::InvalidSignature)?;
+        match signature.recover(message) {
+            Ok(address) if address == signer => Ok(()),
+            _ => Err(RecoveryError::InvalidSignature),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::Constants;
+    use crate::signer::ToadSigner;
+    use ethers_signers::Signer;
+
+    #[actix_web::test]
+    async fn guardians_approve_with_a_personal_signature_over_the_recovery_hash() {
+        let guardian = ToadSigner::mock(1);
+        let recovery_hash = H256::repeat_byte(7);
+        let signature = guardian
+            .sign_message(recovery_hash.as_bytes())
+            .await
+            .unwrap();
+        let signature = Bytes::from(signature.to_vec());
+
+        RecoveryService::verify_signature(recovery_hash.as_bytes(), &signature, guardian.address())
+            .unwrap();
+        assert!(RecoveryService::verify_signature(
+            H256::repeat_byte(8).as_bytes(),
+            &signature,
+            guardian.address()
+        )
+        .is_err());
+        assert!(RecoveryService::verify_signature(
+            recovery_hash.as_bytes(),
+            &signature,
+            ToadSigner::mock(2).address()
+        )
+        .is_err());
+    }
+
+    #[sqlx::test]
+    async fn approved_requests_are_claimed_once(pool: Pool<Postgres>) {
+        RecoveryDao::create_request(
+            &pool,
+            Constants::DEFAULT_TENANT_ID,
+            String::from("recovery_a"),
+            format!("{:?}", Address::repeat_byte(1)),
+            format!("{:?}", Address::repeat_byte(2)),
+            format!("{:?}", H256::repeat_byte(3)),
+            RecoveryStatus::Approved.to_string(),
+        )
+        .await
+        .unwrap();
+        let claim = || {
+            RecoveryDao::claim_request(
+                &pool,
+                Constants::DEFAULT_TENANT_ID,
+                String::from("recovery_a"),
+                RecoveryStatus::Approved.to_string(),
+                RecoveryStatus::Submitted.to_string(),
+                Some(String::from("toad_a")),
+            )
+        };
+        let claims = futures::future::join_all([claim(), claim()]).await;
+        assert_eq!(
+            claims
+                .into_iter()
+                .filter(|claimed| *claimed.as_ref().unwrap())
+                .count(),
+            1
+        );
+    }
+}
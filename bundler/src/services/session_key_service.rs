@@ -0,0 +1,660 @@
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, TimeZone, Utc};
+use ethers::types::{Address, Bytes, Signature, U256};
+use ethers::utils::keccak256;
+use ethers_signers::Signer;
+use sqlx::{Pool, Postgres};
+use std::str::FromStr;
+
+use crate::contracts::session_key_validator_provider::{
+    SessionKeyValidatorProvider, SessionPermission,
+};
+use crate::contracts::simple_account_provider::SimpleAccountProvider;
+use crate::db::dao::{SessionKey, SessionKeyDao, Tenant, TokenMetadataDao, User};
+use crate::errors::SessionError;
+use crate::models::contract_interaction::UserOperation;
+use crate::models::session::{
+    SessionRequest, SessionResponse, SessionRevokeResponse, SessionStatus, SpendLimitResponse,
+};
+use crate::models::{Currency, TransactionType};
+use crate::provider::helpers::{contract_exists_at, generate_txn_id};
+use crate::provider::Web3Client;
+use crate::services::{SponsorshipService, TransferService};
+use crate::CONFIG;
+
+#[derive(Clone)]
+pub struct SessionKeyService;
+
+impl SessionKeyService {
+    pub async fn create_session(
+        pool: &Pool<Postgres>,
+        tenant: &Tenant,
+        request: SessionRequest,
+        user: User,
+    ) -> Result<SessionResponse, SessionError> {
+        if user.wallet_address.is_empty() {
+            return Err(SessionError::NotFound);
+        }
+        if user.is_passkey_owner() {
+            return Err(SessionError::InvalidRequest(String::from(
+                "Session keys are only supported for EOA owners",
+            )));
+        }
+        Self::check_session_wallet(&user).await?;
+        let session_key = match request.session_key {
+            Some(session_key) => session_key,
+            None => Web3Client::get_session_wallet()
+                .ok_or(SessionError::InvalidRequest(String::from(
+                    "A session key is required",
+                )))?
+                .address(),
+        };
+        let now = Utc::now().timestamp() as u64;
+        let valid_after = request.valid_after.unwrap_or(now);
+        if request.valid_until <= valid_after.max(now)
+            || request.valid_until - now > CONFIG.session_keys.max_duration_secs
+        {
+            return Err(SessionError::InvalidRequest(String::from(
+                "Invalid session validity",
+            )));
+        }
+        if request.spend_limits.is_empty() {
+            return Err(SessionError::InvalidRequest(String::from(
+                "At least one spend limit is required",
+            )));
+        }
+
+        let mut currencies: Vec<String> = vec![];
+        let mut tokens = vec![];
+        let mut spend_limits = vec![];
+        for limit in request.spend_limits {
+            let (currency, token) = Self::get_token(pool, limit.currency).await?;
+            if currencies.contains(&currency) {
+                return Err(SessionError::InvalidRequest(format!(
+                    "Duplicate spend limit for {}",
+                    currency
+                )));
+            }
+            let amount = U256::from_dec_str(limit.amount.trim()).map_err(|_| {
+                SessionError::InvalidRequest(format!("Invalid spend limit for {}", currency))
+            })?;
+            currencies.push(currency);
+            tokens.push(token);
+            spend_limits.push(amount);
+        }
+
+        let session_id = format!(
+            "session_{}",
+            generate_txn_id(tenant.get_transaction_id_prefix())
+        );
+        let permission = SessionPermission {
+            session_key,
+            valid_after,
+            valid_until: request.valid_until,
+            allowed_targets: request.allowed_targets,
+            tokens,
+            spend_limits,
+            salt: keccak256(session_id.as_bytes()),
+        };
+        let permission_hash = SessionKeyValidatorProvider::get_permission_hash(
+            user.wallet_address.parse().unwrap(),
+            &permission,
+        )?;
+        SessionKeyDao::create_session(
+            pool,
+            SessionKey {
+                session_id: session_id.clone(),
+                wallet_address: user.wallet_address.clone(),
+                session_key: format!("{:?}", session_key),
+                valid_after: Self::to_datetime(valid_after)?,
+                valid_until: Self::to_datetime(request.valid_until)?,
+                allowed_targets: permission
+                    .allowed_targets
+                    .iter()
+                    .map(|target| format!("{:?}", target))
+                    .collect(),
+                currencies,
+                tokens: permission
+                    .tokens
+                    .iter()
+                    .map(|token| format!("{:?}", token))
+                    .collect(),
+                spend_limits: permission
+                    .spend_limits
+                    .iter()
+                    .map(|limit| BigDecimal::from_str(&limit.to_string()).unwrap())
+                    .collect(),
+                permission_hash: Bytes::from(permission_hash.to_vec()).to_string(),
+                status: SessionStatus::Pending.to_string(),
+                tenant_id: user.tenant_id,
+                ..Default::default()
+            },
+        )
+        .await?;
+        Self::get_session(pool, session_id, &user).await
+    }
+
+    // the owner's signature over permission_hash is what the validator checks on chain
+    pub async fn activate_session(
+        pool: &Pool<Postgres>,
+        session_id: String,
+        signature: Bytes,
+        user: User,
+    ) -> Result<SessionResponse, SessionError> {
+        let session = Self::get_user_session(pool, session_id.clone(), &user).await?;
+        if session.status != SessionStatus::Pending.to_string() {
+            return Err(SessionError::InvalidRequest(String::from(
+                "Session is not pending",
+            )));
+        }
+        let permission_hash = SessionKeyValidatorProvider::get_permission_hash(
+            session.wallet_address.parse().unwrap(),
+            &Self::get_permission(&session)?,
+        )?;
+        let owner = Signature::try_from(signature.as_ref())
+            .and_then(|signature| signature.recover(&permission_hash[..]))
+            .map_err(|_| SessionError::InvalidSignature)?;
+        if Some(owner) != user.owner_address.parse().ok() {
+            return Err(SessionError::InvalidSignature);
+        }
+        SessionKeyDao::update_status(
+            pool,
+            user.tenant_id,
+            session_id.clone(),
+            SessionStatus::Active.to_string(),
+            Some(signature.to_string()),
+        )
+        .await?;
+        Self::get_session(pool, session_id, &user).await
+    }
+
+    pub async fn get_sessions(
+        pool: &Pool<Postgres>,
+        user: User,
+    ) -> Result<Vec<SessionResponse>, SessionError> {
+        let sessions =
+            SessionKeyDao::get_sessions(pool, user.tenant_id, user.wallet_address).await?;
+        let mut response = vec![];
+        for session in sessions {
+            response.push(Self::to_response(pool, session).await?);
+        }
+        Ok(response)
+    }
+
+    // the bundler stops accepting the session right away, an active session is also revoked on
+    // chain once the owner signs the returned operation
+    pub async fn revoke_session(
+        pool: &Pool<Postgres>,
+        provider: &Web3Client,
+        tenant: &Tenant,
+        session_id: String,
+        user: User,
+    ) -> Result<SessionRevokeResponse, SessionError> {
+        let session = Self::get_user_session(pool, session_id.clone(), &user).await?;
+        if session.status == SessionStatus::Revoked.to_string() {
+            return Err(SessionError::InvalidRequest(String::from(
+                "Session is already revoked",
+            )));
+        }
+        SessionKeyDao::update_status(
+            pool,
+            user.tenant_id,
+            session_id.clone(),
+            SessionStatus::Revoked.to_string(),
+            None,
+        )
+        .await?;
+
+        let mut revocation = None;
+        if session.status == SessionStatus::Active.to_string()
+            && contract_exists_at(user.wallet_address.clone()).await?
+        {
+            let validator = SessionKeyValidatorProvider::get_validator_address()?;
+            let mut user_op0 = UserOperation::new();
+            user_op0.calldata(SimpleAccountProvider::execute(
+                provider,
+                validator,
+                String::from("0"),
+                SessionKeyValidatorProvider::revoke_session(
+                    provider,
+                    Self::get_permission_hash(&session)?,
+                )?,
+            )?);
+            let user_txn = TransferService::get_account_transaction(
+                tenant,
+                &user.wallet_address,
+                &format!("{:?}", validator),
+                TransactionType::SessionRevocation,
+            );
+            revocation = Some(
+                TransferService::create_sponsored_operation(
+                    pool,
+                    provider,
+                    tenant,
+                    user.clone(),
+                    user_op0,
+                    user_txn,
+                )
+                .await?,
+            );
+        }
+
+        Ok(SessionRevokeResponse {
+            session: Self::get_session(pool, session_id, &user).await?,
+            revocation,
+        })
+    }
+
+    // the same limits the validator enforces on chain, checked and reserved before the operation
+    // is built. The session row is locked so concurrent transfers can't overspend it, and the
+    // reservation expires with the initiated operation unless it is held
+    pub async fn reserve(
+        pool: &Pool<Postgres>,
+        transaction_id: String,
+        session_id: String,
+        user: &User,
+        to: &str,
+        amount: &BigDecimal,
+        currency: &str,
+    ) -> Result<(), SessionError> {
+        let mut txn = pool
+            .begin()
+            .await
+            .map_err(|err| SessionError::Database(err.to_string()))?;
+        let session =
+            SessionKeyDao::lock_session(&mut *txn, user.tenant_id, session_id.clone()).await?;
+        if session.wallet_address != user.wallet_address {
+            return Err(SessionError::NotFound);
+        }
+        Self::check_active(&session)?;
+        let index = session
+            .currencies
+            .iter()
+            .position(|known| known.eq_ignore_ascii_case(currency))
+            .ok_or(SessionError::InvalidRequest(format!(
+                "{} is not allowed",
+                currency
+            )))?;
+        if !session.allowed_targets.is_empty()
+            && !session.allowed_targets.contains(&to.to_lowercase())
+        {
+            return Err(SessionError::InvalidRequest(format!(
+                "{} is not an allowed receiver",
+                to
+            )));
+        }
+        let currency = session.currencies[index].clone();
+        let spent =
+            SessionKeyDao::get_spent(&mut *txn, session_id.clone(), currency.clone()).await?;
+        if spent + amount > session.spend_limits[index] {
+            return Err(SessionError::InvalidRequest(String::from(
+                "Spend limit exceeded",
+            )));
+        }
+        SessionKeyDao::create_spend(
+            &mut *txn,
+            transaction_id,
+            session_id,
+            currency,
+            amount.clone(),
+            SponsorshipService::RESERVATION_SECS,
+        )
+        .await?;
+        txn.commit()
+            .await
+            .map_err(|err| SessionError::Database(err.to_string()))
+    }
+
+    // a transfer executed after its reservation expired could go over the limit
+    pub async fn hold(
+        pool: &Pool<Postgres>,
+        tenant_id: i32,
+        transaction_id: String,
+    ) -> Result<(), SessionError> {
+        if !SessionKeyDao::hold_spend(pool, tenant_id, transaction_id.clone()).await?
+            && SessionKeyDao::is_spend_expired(pool, tenant_id, transaction_id).await?
+        {
+            return Err(SessionError::InvalidRequest(String::from(
+                "Reservation expired, initiate the transaction again",
+            )));
+        }
+        Ok(())
+    }
+
+    // None for transfers that were not initiated with a session. Backend held keys sign here,
+    // otherwise signature has to come from the session key
+    pub async fn get_signature(
+        pool: &Pool<Postgres>,
+        transaction_id: String,
+        user_op_hash: [u8; 32],
+        signature: Bytes,
+        user: &User,
+    ) -> Result<Option<Bytes>, SessionError> {
+        let session_id =
+            match SessionKeyDao::get_transaction_session(pool, user.tenant_id, transaction_id)
+                .await?
+            {
+                Some(session_id) => session_id,
+                None => return Ok(None),
+            };
+        let session = Self::get_user_session(pool, session_id, user).await?;
+        Self::check_active(&session)?;
+        let permission = Self::get_permission(&session)?;
+
+        let session_signature = match Web3Client::get_session_wallet() {
+            Some(wallet) if wallet.address() == permission.session_key => Bytes::from(
+                wallet
+                    .sign_message(user_op_hash)
+                    .await
+                    .map_err(|err| SessionError::Provider(err.to_string()))?
+                    .to_vec(),
+            ),
+            _ => {
+                let signer = Signature::try_from(signature.as_ref())
+                    .and_then(|signature| signature.recover(&user_op_hash[..]))
+                    .map_err(|_| SessionError::InvalidSignature)?;
+                if signer != permission.session_key {
+                    return Err(SessionError::InvalidSignature);
+                }
+                signature
+            }
+        };
+        let owner_signature = Bytes::from_str(&session.owner_signature.unwrap_or_default())
+            .map_err(|err| SessionError::Database(err.to_string()))?;
+        Ok(Some(SessionKeyValidatorProvider::encode_signature(
+            &permission,
+            owner_signature,
+            session_signature,
+        )?))
+    }
+
+    pub async fn get_session(
+        pool: &Pool<Postgres>,
+        session_id: String,
+        user: &User,
+    ) -> Result<SessionResponse, SessionError> {
+        let session = Self::get_user_session(pool, session_id, user).await?;
+        Self::to_response(pool, session).await
+    }
+
+    async fn get_user_session(
+        pool: &Pool<Postgres>,
+        session_id: String,
+        user: &User,
+    ) -> Result<SessionKey, SessionError> {
+        let session = SessionKeyDao::get_session(pool, user.tenant_id, session_id).await?;
+        if session.wallet_address != user.wallet_address {
+            return Err(SessionError::NotFound);
+        }
+        Ok(session)
+    }
+
+    async fn to_response(
+        pool: &Pool<Postgres>,
+        session: SessionKey,
+    ) -> Result<SessionResponse, SessionError> {
+        let mut spend_limits = vec![];
+        for (currency, amount) in session.currencies.iter().zip(session.spend_limits.iter()) {
+            let spent =
+                SessionKeyDao::get_spent(pool, session.session_id.clone(), currency.clone())
+                    .await?;
+            spend_limits.push(SpendLimitResponse {
+                currency: currency.clone(),
+                amount: amount.with_scale(0).to_string(),
+                spent: spent.with_scale(0).to_string(),
+            });
+        }
+        Ok(SessionResponse {
+            session_id: session.session_id,
+            wallet_address: session.wallet_address,
+            session_key: session.session_key,
+            valid_after: session.valid_after.timestamp(),
+            valid_until: session.valid_until.timestamp(),
+            allowed_targets: session.allowed_targets,
+            spend_limits,
+            permission_hash: session.permission_hash,
+            status: session.status,
+        })
+    }
+
+    // the rotatable implementation hands session signatures to the validator, rotating the owner
+    // once upgrades the wallet
+    async fn check_session_wallet(user: &User) -> Result<(), SessionError> {
+        let unsupported = || {
+            SessionError::InvalidRequest(String::from(
+                "Wallet does not support session keys, rotate the owner once to enable it",
+            ))
+        };
+        let implementation = CONFIG
+            .get_chain()
+            .rotatable_account_implementation
+            .ok_or_else(unsupported)?;
+        let wallet_address: Address = user.wallet_address.parse().map_err(|_| {
+            SessionError::Provider(format!("Invalid wallet address: {}", user.wallet_address))
+        })?;
+        if SimpleAccountProvider::get_implementation(wallet_address).await? != implementation {
+            return Err(unsupported());
+        }
+        Ok(())
+    }
+
+    fn check_active(session: &SessionKey) -> Result<(), SessionError> {
+        let now = Utc::now();
+        if session.status != SessionStatus::Active.to_string()
+            || now < session.valid_after
+            || now >= session.valid_until
+        {
+            return Err(SessionError::InvalidRequest(String::from(
+                "Session is not active",
+            )));
+        }
+        Ok(())
+    }
+
+    // rebuilt from the stored session so the hash always matches what the owner signed
+    fn get_permission(session: &SessionKey) -> Result<SessionPermission, SessionError> {
+        let invalid =
+            |_| SessionError::Database(format!("Invalid session: {}", session.session_id));
+        Ok(SessionPermission {
+            session_key: session.session_key.parse().map_err(invalid)?,
+            valid_after: session.valid_after.timestamp() as u64,
+            valid_until: session.valid_until.timestamp() as u64,
+            allowed_targets: session
+                .allowed_targets
+                .iter()
+                .map(|target| target.parse())
+                .collect::<Result<Vec<Address>, _>>()
+                .map_err(invalid)?,
+            tokens: session
+                .tokens
+                .iter()
+                .map(|token| token.parse())
+                .collect::<Result<Vec<Address>, _>>()
+                .map_err(invalid)?,
+            spend_limits: session
+                .spend_limits
+                .iter()
+                .map(|limit| U256::from_dec_str(&limit.with_scale(0).to_string()))
+                .collect::<Result<Vec<U256>, _>>()
+                .map_err(|_| {
+                    SessionError::Database(format!("Invalid session: {}", session.session_id))
+                })?,
+            salt: keccak256(session.session_id.as_bytes()),
+        })
+    }
+
+    fn get_permission_hash(session: &SessionKey) -> Result<[u8; 32], SessionError> {
+        Ok(SessionKeyValidatorProvider::get_permission_hash(
+            session.wallet_address.parse().unwrap(),
+            &Self::get_permission(session)?,
+        )?)
+    }
+
+    // erc20 transfers always go through the configured usdc contract, see TransferService
+    async fn get_token(
+        pool: &Pool<Postgres>,
+        currency: String,
+    ) -> Result<(String, Address), SessionError> {
+        let metadata = TokenMetadataDao::get_metadata_for_chain(
+            pool,
+            CONFIG.run_config.current_chain.clone(),
+            Some(currency.clone()),
+        )
+        .await?;
+        let metadata = metadata
+            .into_iter()
+            .next()
+            .ok_or(SessionError::InvalidRequest(format!(
+                "Invalid currency: {}",
+                currency
+            )))?;
+        match Currency::from_str(metadata.token_type) {
+            Some(Currency::Erc20) => Ok((metadata.symbol, CONFIG.get_chain().usdc_address)),
+            Some(Currency::Native) => Ok((metadata.symbol, Address::zero())),
+            None => Err(SessionError::InvalidRequest(format!(
+                "Invalid currency: {}",
+                currency
+            ))),
+        }
+    }
+
+    fn to_datetime(timestamp: u64) -> Result<DateTime<Utc>, SessionError> {
+        Utc.timestamp_opt(timestamp as i64, 0)
+            .single()
+            .ok_or(SessionError::InvalidRequest(String::from(
+                "Invalid timestamp",
+            )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::Constants;
+    use crate::db::dao::TenantDao;
+    use chrono::Duration;
+
+    const WALLET: &str = "0x0000000000000000000000000000000000000001";
+
+    async fn create_session(pool: &Pool<Postgres>) {
+        SessionKeyDao::create_session(
+            pool,
+            SessionKey {
+                session_id: String::from("session_a"),
+                wallet_address: String::from(WALLET),
+                session_key: String::from(WALLET),
+                valid_after: Utc::now() - Duration::minutes(1),
+                valid_until: Utc::now() + Duration::hours(1),
+                currencies: vec![String::from("USDC")],
+                tokens: vec![String::from(WALLET)],
+                spend_limits: vec![BigDecimal::from(10)],
+                status: SessionStatus::Active.to_string(),
+                tenant_id: Constants::DEFAULT_TENANT_ID,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    }
+
+    async fn reserve(pool: &Pool<Postgres>, transaction_id: &str) -> Result<(), SessionError> {
+        reserve_as(pool, Constants::DEFAULT_TENANT_ID, transaction_id).await
+    }
+
+    async fn reserve_as(
+        pool: &Pool<Postgres>,
+        tenant_id: i32,
+        transaction_id: &str,
+    ) -> Result<(), SessionError> {
+        let user = User {
+            wallet_address: String::from(WALLET),
+            tenant_id,
+            ..Default::default()
+        };
+        SessionKeyService::reserve(
+            pool,
+            String::from(transaction_id),
+            String::from("session_a"),
+            &user,
+            WALLET,
+            &BigDecimal::from(4),
+            "usdc",
+        )
+        .await
+    }
+
+    #[sqlx::test]
+    async fn concurrent_transfers_do_not_overspend_the_session(pool: Pool<Postgres>) {
+        create_session(&pool).await;
+        let results = futures::future::join_all(
+            ["a", "b", "c", "d"].map(|transaction_id| reserve(&pool, transaction_id)),
+        )
+        .await;
+        // the limit of 10 fits two transfers of 4
+        assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 2);
+    }
+
+    #[sqlx::test]
+    async fn expired_reservations_release_the_session_limit(pool: Pool<Postgres>) {
+        create_session(&pool).await;
+        reserve(&pool, "a").await.unwrap();
+        reserve(&pool, "b").await.unwrap();
+        assert!(reserve(&pool, "c").await.is_err());
+
+        sqlx::query(
+            "UPDATE session_key_spends SET reserved_until = now() WHERE transaction_id = 'a'",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        assert!(
+            SessionKeyService::hold(&pool, Constants::DEFAULT_TENANT_ID, String::from("a"))
+                .await
+                .is_err()
+        );
+        SessionKeyService::hold(&pool, Constants::DEFAULT_TENANT_ID, String::from("b"))
+            .await
+            .unwrap();
+        reserve(&pool, "c").await.unwrap();
+        // held spends keep counting
+        assert!(reserve(&pool, "d").await.is_err());
+    }
+
+    #[sqlx::test]
+    async fn sessions_are_not_visible_to_other_tenants(pool: Pool<Postgres>) {
+        create_session(&pool).await;
+        let other = TenantDao::create_tenant(
+            &pool,
+            Tenant {
+                name: String::from("other"),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        // the same wallet address under another tenant is another wallet
+        assert!(matches!(
+            reserve_as(&pool, other.id, "a").await,
+            Err(SessionError::NotFound)
+        ));
+        assert!(
+            SessionKeyDao::get_sessions(&pool, other.id, String::from(WALLET))
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        reserve(&pool, "a").await.unwrap();
+        assert!(
+            !SessionKeyDao::hold_spend(&pool, other.id, String::from("a"))
+                .await
+                .unwrap()
+        );
+        assert_eq!(
+            SessionKeyDao::get_transaction_session(&pool, other.id, String::from("a"))
+                .await
+                .unwrap(),
+            None
+        );
+    }
+}
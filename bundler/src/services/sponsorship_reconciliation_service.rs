@@ -0,0 +1,171 @@
+use actix_web::rt::time::interval;
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use ethers::abi::RawLog;
+use ethers::providers::Middleware;
+use ethers::types::{Filter, H256, U256};
+use log::{error, info};
+use sqlx::{Pool, Postgres};
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::db::dao::SponsorshipDao;
+use crate::models::config::settings::SponsorshipReconciliation;
+use crate::provider::helpers::to_decimal;
+use crate::provider::Web3Client;
+use crate::services::SponsorshipService;
+use crate::{CONFIG, PROVIDER};
+
+// covers the node's head lagging behind the wall clock
+const EXPIRY_GRACE_SECS: i64 = 60;
+
+#[derive(Clone)]
+pub struct SponsorshipReconciliationService;
+
+impl SponsorshipReconciliationService {
+    // paymaster client ops are submitted by the client, so nothing listens for them being mined
+    pub async fn run(
+        pool: Pool<Postgres>,
+        provider: Web3Client,
+        config: SponsorshipReconciliation,
+    ) {
+        let mut ticker = interval(Duration::from_secs(config.interval_secs));
+        loop {
+            ticker.tick().await;
+            if let Err(err) = Self::reconcile(&pool, &provider, config.lookback_blocks).await {
+                error!("Sponsorship reconciliation: {}", err);
+            }
+        }
+    }
+
+    async fn reconcile(
+        pool: &Pool<Postgres>,
+        provider: &Web3Client,
+        lookback_blocks: u64,
+    ) -> Result<(), String> {
+        let sponsorships = SponsorshipDao::get_unreconciled_sponsorships(
+            pool,
+            CONFIG.run_config.current_chain.clone(),
+        )
+        .await
+        .map_err(|err| format!("{:?}", err))?;
+        if sponsorships.is_empty() {
+            return Ok(());
+        }
+        let hashes = sponsorships
+            .iter()
+            .filter_map(|sponsorship| sponsorship.transaction_id.parse().ok())
+            .collect();
+        let gas_costs = Self::get_gas_costs(provider, hashes, lookback_blocks).await?;
+
+        let now = Utc::now();
+        let mut settled = 0;
+        let mut released = 0;
+        for sponsorship in sponsorships {
+            let hash: Option<H256> = sponsorship.transaction_id.parse().ok();
+            let gas_cost = match hash.and_then(|hash| gas_costs.get(&hash)) {
+                Some(gas_cost) => {
+                    settled += 1;
+                    to_decimal(*gas_cost)
+                }
+                None if Self::is_expired(sponsorship.created_at, now) => {
+                    released += 1;
+                    BigDecimal::from(0)
+                }
+                None => continue,
+            };
+            SponsorshipDao::reconcile_sponsorship(pool, sponsorship.transaction_id, gas_cost)
+                .await
+                .map_err(|err| format!("{:?}", err))?;
+        }
+        info!(
+            "Sponsorship reconciliation: settled {}, released {}",
+            settled, released
+        );
+        Ok(())
+    }
+
+    // actualGasCost of the mined ops, by user op hash
+    async fn get_gas_costs(
+        provider: &Web3Client,
+        hashes: Vec<H256>,
+        lookback_blocks: u64,
+    ) -> Result<HashMap<H256, U256>, String> {
+        let event = provider
+            .get_entrypoint_provider()
+            .abi()
+            .event("UserOperationEvent")
+            .map_err(|_| String::from("Failed to get event"))?
+            .clone();
+        let latest = PROVIDER
+            .get_block_number()
+            .await
+            .map_err(|err| format!("Failed to get block number: {:?}", err))?
+            .as_u64();
+        // the verifying paymaster serves clients on the v0.6 entrypoint only
+        let filter = Filter::new()
+            .address(CONFIG.get_chain().entrypoint_address)
+            .topic0(event.signature())
+            .topic1(hashes)
+            .from_block(latest.saturating_sub(lookback_blocks));
+        let logs = PROVIDER
+            .get_logs(&filter)
+            .await
+            .map_err(|err| format!("Failed to get logs: {:?}", err))?;
+
+        let mut gas_costs = HashMap::new();
+        for log in logs {
+            let hash = match log.topics.get(1) {
+                Some(hash) => *hash,
+                None => continue,
+            };
+            let log = event
+                .parse_log(RawLog {
+                    topics: log.topics,
+                    data: log.data.to_vec(),
+                })
+                .map_err(|err| format!("Failed to parse log: {:?}", err))?;
+            let actual_gas_cost = log
+                .params
+                .into_iter()
+                .find(|param| param.name == "actualGasCost")
+                .and_then(|param| param.value.into_uint())
+                .unwrap_or_default();
+            gas_costs.insert(hash, actual_gas_cost);
+        }
+        Ok(gas_costs)
+    }
+
+    // client ops are signed valid until RESERVATION_SECS after the sponsorship is created
+    fn is_expired(created_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        created_at
+            + ChronoDuration::seconds(
+                SponsorshipService::RESERVATION_SECS as i64 + EXPIRY_GRACE_SECS,
+            )
+            < now
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn releases_ops_once_they_can_no_longer_be_mined() {
+        let created_at = Utc::now();
+        let valid_until =
+            created_at + ChronoDuration::seconds(SponsorshipService::RESERVATION_SECS as i64);
+        assert!(!SponsorshipReconciliationService::is_expired(
+            created_at,
+            valid_until
+        ));
+        assert!(!SponsorshipReconciliationService::is_expired(
+            created_at,
+            valid_until + ChronoDuration::seconds(EXPIRY_GRACE_SECS)
+        ));
+        assert!(SponsorshipReconciliationService::is_expired(
+            created_at,
+            valid_until + ChronoDuration::seconds(EXPIRY_GRACE_SECS + 1)
+        ));
+    }
+}
@@ -0,0 +1,333 @@
+use bigdecimal::BigDecimal;
+use ethers::types::Address;
+use ethers::utils::parse_units;
+use log::error;
+use sqlx::{Pool, Postgres};
+
+use crate::constants::Constants;
+use crate::db::dao::{BudgetScope, PaymasterClient, SpentGas, SponsorshipDao, Tenant};
+use crate::errors::SponsorshipError;
+use crate::models::config::settings::SponsorshipPolicy;
+use crate::models::contract_interaction::UserOperation;
+use crate::provider::helpers::to_decimal;
+use crate::CONFIG;
+
+#[derive(Clone)]
+pub struct SponsorshipService;
+
+impl SponsorshipService {
+    pub const RESERVATION_SECS: i32 = 900;
+
+    pub fn check_transfer(
+        tenant: &Tenant,
+        to: &str,
+        amount: &BigDecimal,
+        currency: &str,
+    ) -> Result<(), SponsorshipError> {
+        let policy = Self::get_policy(tenant.get_sponsorship_policy())?;
+        if !policy.allowed_currencies.is_empty()
+            && !policy
+                .allowed_currencies
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(currency))
+        {
+            return Err(SponsorshipError::Refused(format!(
+                "{} is not sponsored",
+                currency
+            )));
+        }
+        if !policy.allowed_destinations.is_empty() {
+            let destination: Address = to
+                .parse()
+                .map_err(|_| SponsorshipError::Refused(String::from("Invalid receiver")))?;
+            if !policy.allowed_destinations.contains(&destination) {
+                return Err(SponsorshipError::Refused(String::from(
+                    "Receiver is not sponsored",
+                )));
+            }
+        }
+        // config keys are case insensitive
+        let minimum_amount = policy
+            .minimum_amounts
+            .iter()
+            .find(|(symbol, _)| symbol.eq_ignore_ascii_case(currency))
+            .map(|(_, amount)| *amount);
+        if let Some(minimum_amount) = minimum_amount {
+            if *amount < BigDecimal::from(minimum_amount) {
+                return Err(SponsorshipError::Refused(format!(
+                    "Minimum sponsored amount is {}",
+                    minimum_amount
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    // read only, ops that are signed reserve their gas cost with reserve_for_client
+    pub async fn check_client_budget(
+        pool: &Pool<Postgres>,
+        client: &PaymasterClient,
+        user_address: String,
+        user_op: &UserOperation,
+    ) -> Result<BigDecimal, SponsorshipError> {
+        let policy = Self::get_policy(client.get_sponsorship_policy())?;
+        let gas_cost = to_decimal(user_op.get_paymaster_prefund());
+        let scope = Self::get_scope(Constants::DEFAULT_TENANT_ID, Some(client.id));
+        let spent = SponsorshipDao::get_spent_gas(pool, &scope, user_address).await?;
+        Self::check_spent(&policy, &spent, &gas_cost)?;
+        Ok(gas_cost)
+    }
+
+    // the max prefund is reserved for RESERVATION_SECS when the op is signed. Submitting the op
+    // holds it (see hold) and the listener replaces it with the actual gas cost once mined
+    pub async fn reserve(
+        pool: &Pool<Postgres>,
+        tenant: &Tenant,
+        transaction_id: String,
+        user_address: String,
+        user_op: &UserOperation,
+    ) -> Result<BigDecimal, SponsorshipError> {
+        let policy = Self::get_policy(tenant.get_sponsorship_policy())?;
+        let gas_cost = to_decimal(user_op.get_paymaster_prefund());
+        Self::reserve_gas(
+            pool,
+            &policy,
+            &Self::get_scope(tenant.id, None),
+            transaction_id,
+            user_address,
+            gas_cost.clone(),
+        )
+        .await?;
+        Ok(gas_cost)
+    }
+
+    // client ops are submitted elsewhere, the reservation is settled by
+    // SponsorshipReconciliationService once the op is mined or expired
+    pub async fn reserve_for_client(
+        pool: &Pool<Postgres>,
+        client: &PaymasterClient,
+        transaction_id: String,
+        user_address: String,
+        user_op: &UserOperation,
+    ) -> Result<BigDecimal, SponsorshipError> {
+        let policy = Self::get_policy(client.get_sponsorship_policy())?;
+        let gas_cost = to_decimal(user_op.get_paymaster_prefund());
+        Self::reserve_gas(
+            pool,
+            &policy,
+            &Self::get_scope(Constants::DEFAULT_TENANT_ID, Some(client.id)),
+            transaction_id.clone(),
+            user_address,
+            gas_cost.clone(),
+        )
+        .await?;
+        Self::hold(pool, transaction_id).await?;
+        Ok(gas_cost)
+    }
+
+    // an op submitted after its reservation expired would spend budget that was given away
+    pub async fn hold(
+        pool: &Pool<Postgres>,
+        transaction_id: String,
+    ) -> Result<(), SponsorshipError> {
+        if !SponsorshipDao::hold_sponsorship(pool, transaction_id.clone()).await?
+            && SponsorshipDao::is_reservation_expired(pool, transaction_id).await?
+        {
+            return Err(SponsorshipError::Refused(String::from(
+                "Reservation expired, initiate the transaction again",
+            )));
+        }
+        Ok(())
+    }
+
+    async fn reserve_gas(
+        pool: &Pool<Postgres>,
+        policy: &SponsorshipPolicy,
+        scope: &BudgetScope,
+        transaction_id: String,
+        user_address: String,
+        gas_cost: BigDecimal,
+    ) -> Result<(), SponsorshipError> {
+        let mut txn = pool
+            .begin()
+            .await
+            .map_err(|err| SponsorshipError::Database(err.to_string()))?;
+        SponsorshipDao::lock_budget(&mut *txn, scope).await?;
+        let spent = SponsorshipDao::get_spent_gas(&mut *txn, scope, user_address.clone()).await?;
+        Self::check_spent(policy, &spent, &gas_cost)?;
+        SponsorshipDao::create_sponsorship(
+            &mut *txn,
+            scope,
+            transaction_id,
+            user_address,
+            gas_cost,
+            Self::RESERVATION_SECS,
+        )
+        .await?;
+        txn.commit()
+            .await
+            .map_err(|err| SponsorshipError::Database(err.to_string()))
+    }
+
+    fn check_spent(
+        policy: &SponsorshipPolicy,
+        spent: &SpentGas,
+        gas_cost: &BigDecimal,
+    ) -> Result<(), SponsorshipError> {
+        let budgets = [
+            ("Daily", &spent.user_daily, policy.user_daily_budget_gwei),
+            (
+                "Monthly",
+                &spent.user_monthly,
+                policy.user_monthly_budget_gwei,
+            ),
+            ("Chain", &spent.chain, policy.chain_budget_gwei),
+        ];
+        for (name, spent, budget) in budgets {
+            if let Some(budget) = budget {
+                if spent + gas_cost > Self::gwei_to_wei(budget) {
+                    return Err(SponsorshipError::Refused(format!(
+                        "{} gas budget exhausted",
+                        name
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn get_scope(tenant_id: i32, paymaster_client_id: Option<i32>) -> BudgetScope {
+        BudgetScope {
+            tenant_id,
+            paymaster_client_id,
+            chain: CONFIG.run_config.current_chain.clone(),
+        }
+    }
+
+    // a broken policy is not sponsored rather than sponsored without limits
+    fn get_policy(
+        policy: Result<SponsorshipPolicy, String>,
+    ) -> Result<SponsorshipPolicy, SponsorshipError> {
+        policy.map_err(|err| {
+            error!("{}", err);
+            SponsorshipError::Refused(String::from("Invalid sponsorship policy"))
+        })
+    }
+
+    fn gwei_to_wei(value: u64) -> BigDecimal {
+        to_decimal(parse_units(value, "gwei").unwrap().into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> SponsorshipPolicy {
+        SponsorshipPolicy {
+            user_daily_budget_gwei: Some(10),
+            chain_budget_gwei: Some(100),
+            ..Default::default()
+        }
+    }
+
+    fn gwei(value: u64) -> BigDecimal {
+        SponsorshipService::gwei_to_wei(value)
+    }
+
+    #[test]
+    fn refuses_ops_over_the_first_exhausted_budget() {
+        let spent = SpentGas {
+            user_daily: gwei(6),
+            user_monthly: gwei(6),
+            chain: gwei(6),
+        };
+        assert!(SponsorshipService::check_spent(&policy(), &spent, &gwei(4)).is_ok());
+        match SponsorshipService::check_spent(&policy(), &spent, &gwei(5)) {
+            Err(SponsorshipError::Refused(reason)) => {
+                assert_eq!(reason, "Daily gas budget exhausted")
+            }
+            _ => panic!("expected the daily budget to refuse"),
+        }
+        let spent = SpentGas {
+            chain: gwei(99),
+            ..Default::default()
+        };
+        match SponsorshipService::check_spent(&policy(), &spent, &gwei(2)) {
+            Err(SponsorshipError::Refused(reason)) => {
+                assert_eq!(reason, "Chain gas budget exhausted")
+            }
+            _ => panic!("expected the chain budget to refuse"),
+        }
+    }
+
+    async fn reserve_in(
+        pool: &Pool<Postgres>,
+        paymaster_client_id: Option<i32>,
+        transaction_id: &str,
+    ) -> Result<(), SponsorshipError> {
+        SponsorshipService::reserve_gas(
+            pool,
+            &policy(),
+            &BudgetScope {
+                tenant_id: 1,
+                paymaster_client_id,
+                chain: String::from("test"),
+            },
+            transaction_id.to_string(),
+            String::from("0x0000000000000000000000000000000000000001"),
+            gwei(4),
+        )
+        .await
+    }
+
+    async fn reserve(pool: &Pool<Postgres>, transaction_id: &str) -> Result<(), SponsorshipError> {
+        reserve_in(pool, None, transaction_id).await
+    }
+
+    #[sqlx::test]
+    async fn concurrent_reservations_do_not_overspend(pool: Pool<Postgres>) {
+        let results = futures::future::join_all(
+            ["a", "b", "c", "d"].map(|transaction_id| reserve(&pool, transaction_id)),
+        )
+        .await;
+        // the daily budget of 10 gwei fits two reservations of 4
+        assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 2);
+    }
+
+    #[sqlx::test]
+    async fn expired_reservations_release_the_budget(pool: Pool<Postgres>) {
+        reserve(&pool, "a").await.unwrap();
+        reserve(&pool, "b").await.unwrap();
+        assert!(reserve(&pool, "c").await.is_err());
+
+        sqlx::query("UPDATE sponsorships SET reserved_until = now() WHERE transaction_id = 'a'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert!(SponsorshipService::hold(&pool, String::from("a"))
+            .await
+            .is_err());
+        SponsorshipService::hold(&pool, String::from("b"))
+            .await
+            .unwrap();
+        reserve(&pool, "c").await.unwrap();
+        // held and settled sponsorships keep counting
+        assert!(reserve(&pool, "d").await.is_err());
+    }
+
+    #[sqlx::test]
+    async fn paymaster_clients_are_budgeted_apart_from_the_tenant(pool: Pool<Postgres>) {
+        sqlx::query("INSERT INTO paymaster_clients (id, name, api_key_hash) VALUES (7, 'a', 'a')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        reserve(&pool, "a").await.unwrap();
+        reserve(&pool, "b").await.unwrap();
+        assert!(reserve(&pool, "c").await.is_err());
+
+        reserve_in(&pool, Some(7), "d").await.unwrap();
+        reserve_in(&pool, Some(7), "e").await.unwrap();
+        assert!(reserve_in(&pool, Some(7), "f").await.is_err());
+    }
+}
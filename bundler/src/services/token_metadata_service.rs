@@ -1,6 +1,6 @@
 use sqlx::{Pool, Postgres};
 
-use crate::db::dao::TokenMetadataDao;
+use crate::db::dao::{Tenant, TokenMetadataDao};
 use crate::errors::MetadataError;
 use crate::models::admin::{MetadataResponse, MetadataResponseV2};
 use crate::CONFIG;
@@ -9,13 +9,19 @@ use crate::CONFIG;
 pub struct TokenMetadataService;
 
 impl TokenMetadataService {
-    pub async fn get_chain(pool: &Pool<Postgres>) -> Result<MetadataResponse, MetadataError> {
+    pub async fn get_chain(
+        pool: &Pool<Postgres>,
+        tenant: &Tenant,
+    ) -> Result<MetadataResponse, MetadataError> {
         let supported_currencies = TokenMetadataDao::get_metadata_for_chain(
             pool,
             CONFIG.run_config.current_chain.clone(),
             None,
         )
-        .await?;
+        .await?
+        .into_iter()
+        .filter(|metadata| tenant.supports_token(&metadata.symbol))
+        .collect();
 
         Ok(MetadataResponse::new().to(
             supported_currencies,
@@ -25,9 +31,16 @@ impl TokenMetadataService {
         ))
     }
 
-    pub async fn get_chain_v2(pool: &Pool<Postgres>) -> Result<MetadataResponseV2, MetadataError> {
+    pub async fn get_chain_v2(
+        pool: &Pool<Postgres>,
+        tenant: &Tenant,
+    ) -> Result<MetadataResponseV2, MetadataError> {
         Ok(MetadataResponseV2::from_token_metadata(
-            TokenMetadataDao::get_metadata(pool).await?,
+            TokenMetadataDao::get_metadata(pool)
+                .await?
+                .into_iter()
+                .filter(|metadata| tenant.supports_token(&metadata.symbol))
+                .collect(),
         ))
     }
 }
@@ -1,32 +1,40 @@
 use actix_web::rt::spawn;
-use bigdecimal::{BigDecimal, ToPrimitive};
-use ethers::abi::{encode, Tokenizable};
-use ethers::types::{Address, Bytes, U256};
+use bigdecimal::BigDecimal;
+use ethers::types::{Address, Bytes, Signature, U256};
+use ethers::utils::hash_message;
 use ethers_signers::Signer;
+use log::info;
 use sqlx::{Pool, Postgres};
 use std::str::FromStr;
 
 use crate::bundler::Bundler;
-use crate::contracts::entrypoint_provider::EntryPointProvider;
+use crate::constants::Constants;
+use crate::contracts::erc1271_provider::Erc1271Provider;
+use crate::contracts::passkey_account_factory_provider::PasskeyAccountFactoryProvider;
+use crate::contracts::rotatable_account_provider::RotatableAccountProvider;
 use crate::contracts::simple_account_factory_provider::SimpleAccountFactoryProvider;
 use crate::contracts::simple_account_provider::SimpleAccountProvider;
+use crate::contracts::token_paymaster_provider::TokenPaymasterProvider;
 use crate::contracts::usdc_provider::USDCProvider;
 use crate::contracts::verifying_paymaster_provider::VerifyingPaymasterProvider;
 use crate::db::dao::{
-    TokenMetadataDao, TransactionDao, TransactionMetadata, User, UserOperationDao, UserTransaction,
-    WalletDao,
+    Gas, PasskeyDao, SessionKeyDao, SponsorshipDao, Tenant, TokenMetadataDao, TransactionDao,
+    TransactionMetadata, User, UserOperationDao, UserTransaction,
 };
-use crate::errors::{ProviderError, TransactionError, TransferError};
+use crate::errors::{DatabaseError, TransactionError, TransferError};
 use crate::models::contract_interaction::UserOperation;
 use crate::models::transaction::Transaction;
 use crate::models::transfer::{
-    Status, TransactionResponse, TransferInitResponse, TransferResponse,
+    Status, TransactionResponse, TransferInitResponse, TransferRequest, TransferResponse,
 };
+use crate::models::wallet::PasskeyAssertion;
 use crate::models::Currency;
 use crate::models::TransactionType;
-use crate::provider::helpers::{generate_txn_id, get_explorer_url};
-use crate::provider::listeners::user_op_event_listener;
+use crate::provider::helpers::{contract_exists_at, generate_txn_id, get_explorer_url};
+use crate::provider::listeners::{bundle_listener, user_op_event_listener};
+use crate::provider::webauthn::{encode_signature, get_coordinates, parse_owner, verify_assertion};
 use crate::provider::Web3Client;
+use crate::services::{SessionKeyService, SponsorshipService, WalletService, WebhookService};
 use crate::CONFIG;
 
 #[derive(Clone)]
@@ -36,146 +44,324 @@ impl TransferService {
     pub async fn init(
         pool: &Pool<Postgres>,
         provider: &Web3Client,
-        to: String,
-        value: String,
-        currency: String,
+        tenant: &Tenant,
+        request: TransferRequest,
         user: User,
     ) -> Result<TransferInitResponse, TransferError> {
         if user.wallet_address.is_empty() {
             return Err(TransferError::NotFound);
         }
-        let user_txn =
-            Self::get_user_transaction(&to, &value, &currency, user.wallet_address.clone());
+        let to = request.get_receiver();
+        let value = request.get_value();
+        let currency = request.metadata.get_currency();
+        if !tenant.supports_token(&currency) {
+            return Err(TransferError::InvalidCurrency);
+        }
+        let session_id = request.session_id.clone();
+        let use_token_paymaster = match request.get_paymaster().as_str() {
+            Constants::VERIFYING_PAYMASTER => false,
+            Constants::TOKEN_PAYMASTER => true,
+            _ => return Err(TransferError::InvalidPaymaster),
+        };
+        // the validator only allows the transfer itself, not the paymaster approval
+        if session_id.is_some() && use_token_paymaster {
+            return Err(TransferError::InvalidPaymaster);
+        }
+        let mut user_txn =
+            Self::get_user_transaction(tenant, &to, &value, &currency, user.wallet_address.clone());
+        if let Some(session_id) = session_id {
+            SessionKeyService::reserve(
+                pool,
+                user_txn.transaction_id.clone(),
+                session_id,
+                &user,
+                &to,
+                &user_txn.amount,
+                &currency,
+            )
+            .await?;
+        }
         let mut user_op0 = UserOperation::new();
-        user_op0.calldata(Self::get_call_data(pool, provider, to, value, currency).await?);
-        if !user.deployed {
-            user_op0.init_code(
-                SimpleAccountFactoryProvider::get_factory_address(provider),
-                SimpleAccountFactoryProvider::create_account(
-                    provider,
-                    user.owner_address.parse().unwrap(),
-                    U256::from(user.salt.to_u64().unwrap()),
-                )?,
-            );
+        if !Self::is_wallet_deployed(pool, &user).await? {
+            Self::set_init_code(provider, &mut user_op0, &user)?;
         }
 
         let wallet_address: Address = user.wallet_address.parse().unwrap();
-        let valid_until: u64 = 3735928559;
-        let valid_after: u64 = 4660;
-        let data = encode(&vec![valid_until.into_token(), valid_after.into_token()]);
         user_op0
-            .paymaster_and_data(data.clone(), wallet_address.clone(), None)
             .nonce(
-                EntryPointProvider::get_nonce(provider, wallet_address)
+                Bundler::get_nonce(provider, wallet_address)
                     .await?
                     .low_u64(),
             )
-            .sender(wallet_address.clone());
-
-        user_op0.signature(Bytes::from(
-            Web3Client::get_verifying_paymaster_wallet()
-                .sign_typed_data(&user_op0)
-                .await
-                .map_err(|err| TransferError::Provider(err.to_string()))?
-                .to_vec(),
-        ));
+            .sender(wallet_address);
 
-        let singed_hash =
-            Self::get_signed_hash(provider, user_op0.clone(), valid_until, valid_after).await?;
-        user_op0.paymaster_and_data(
-            data,
-            CONFIG.get_chain().verifying_paymaster_address,
-            Some(singed_hash),
-        );
+        let mut approval = None;
+        if use_token_paymaster {
+            let token_paymaster = TokenPaymasterProvider::get_address()?;
+            let price = TokenPaymasterProvider::get_price_with_markup(provider).await?;
+            let token_cost = TokenPaymasterProvider::get_token_cost(&user_op0, price)?;
+            approval = Some((
+                token_paymaster,
+                token_cost * U256::from(TokenPaymasterProvider::get_config()?.approval_multiplier),
+            ));
+            // the paymaster pulls the tokens during validation, before the approval in this
+            // batch runs. Without enough allowance the op is sponsored and the approval sets it
+            // up, the response reports the verifying paymaster so callers see the fallback
+            let allowance =
+                USDCProvider::allowance(provider, wallet_address, token_paymaster).await?;
+            if allowance >= token_cost {
+                user_op0.token_paymaster_and_data(token_paymaster, price);
+                user_txn.metadata.gas_erc20 = Gas {
+                    currency: currency.clone(),
+                    value: u64::try_from(token_cost).map_err(|err| {
+                        TransferError::Provider(format!("Invalid token cost: {:?}", err))
+                    })?,
+                };
+            } else {
+                info!(
+                    "allowance too low for token paymaster, sponsoring {:?}",
+                    wallet_address
+                );
+            }
+        }
+        user_op0
+            .calldata(Self::get_call_data(pool, provider, to, value, currency, approval).await?);
+        let sponsored = user_op0.paymaster_and_data.is_empty();
+        if sponsored {
+            SponsorshipService::check_transfer(
+                tenant,
+                &user_txn.to_address,
+                &user_txn.amount,
+                &user_txn.currency,
+            )?;
+            SponsorshipService::reserve(
+                pool,
+                tenant,
+                user_txn.transaction_id.clone(),
+                user.wallet_address.clone(),
+                &user_op0,
+            )
+            .await?;
+            Self::set_verifying_paymaster_and_data(provider, &mut user_op0, wallet_address).await?;
+        }
 
-        let user_op_hash = user_op0.hash(
-            CONFIG.get_chain().entrypoint_address,
-            CONFIG.get_chain().chain_id,
-        );
+        let user_op_hash = Bundler::get_user_op_hash(&user_op0)?;
         TransactionDao::create_user_transaction(pool, user_txn.clone()).await?;
         UserOperationDao::create_user_operation(
             pool,
+            tenant.id,
             user_txn.transaction_id.clone(),
             user_op0.clone(),
             Status::INITIATED.to_string(),
         )
         .await?;
+        let paymaster = if sponsored {
+            Constants::VERIFYING_PAYMASTER
+        } else {
+            Constants::TOKEN_PAYMASTER
+        };
 
         Ok(TransferInitResponse {
             msg_hash: user_op_hash,
             status: user_txn.status,
             transaction_id: user_txn.transaction_id,
+            paymaster: paymaster.to_string(),
         })
     }
 
+    // a no-op user operation that only carries the init code, signed and submitted like a transfer
+    pub async fn deploy_wallet(
+        pool: &Pool<Postgres>,
+        provider: &Web3Client,
+        tenant: &Tenant,
+        user: User,
+    ) -> Result<TransferInitResponse, TransferError> {
+        if user.wallet_address.is_empty() {
+            return Err(TransferError::NotFound);
+        }
+        if Self::is_wallet_deployed(pool, &user).await? {
+            return Err(TransferError::WalletDeployed);
+        }
+        let mut user_op0 = UserOperation::new();
+        Self::set_init_code(provider, &mut user_op0, &user)?;
+        let user_txn = Self::get_account_transaction(
+            tenant,
+            &user.wallet_address,
+            &user.wallet_address,
+            TransactionType::Deploy,
+        );
+        Self::create_sponsored_operation(pool, provider, tenant, user, user_op0, user_txn).await
+    }
+
+    // the current owner signs an op that upgrades the account to the rotatable implementation
+    // (if it is not already on it) and changes the owner. users.owner_address follows once the
+    // OwnerChanged event is seen. This is key hygiene only: it needs the current key, users who
+    // lost it go through the guardian recovery in RecoveryService
+    pub async fn rotate_owner(
+        pool: &Pool<Postgres>,
+        provider: &Web3Client,
+        tenant: &Tenant,
+        new_owner: Address,
+        user: User,
+    ) -> Result<TransferInitResponse, TransferError> {
+        if user.wallet_address.is_empty() {
+            return Err(TransferError::NotFound);
+        }
+        // the rotatable implementation is a SimpleAccount, it can't take over a passkey account
+        if user.is_passkey_owner()
+            || new_owner.is_zero()
+            || Some(new_owner) == user.owner_address.parse().ok()
+        {
+            return Err(TransferError::InvalidOwner);
+        }
+        let implementation =
+            CONFIG
+                .get_chain()
+                .rotatable_account_implementation
+                .ok_or(TransferError::Provider(String::from(
+                    "Rotatable account implementation is not configured for the current chain",
+                )))?;
+        let wallet_address: Address = user.wallet_address.parse().unwrap();
+        let mut user_op0 = UserOperation::new();
+        let mut change_owner = RotatableAccountProvider::change_owner(provider, new_owner)?;
+        if Self::is_wallet_deployed(pool, &user).await? {
+            if SimpleAccountProvider::get_implementation(wallet_address).await? != implementation {
+                change_owner = SimpleAccountProvider::upgrade_to_and_call(
+                    provider,
+                    implementation,
+                    change_owner,
+                )?;
+            }
+        } else {
+            Self::set_init_code(provider, &mut user_op0, &user)?;
+            change_owner =
+                SimpleAccountProvider::upgrade_to_and_call(provider, implementation, change_owner)?;
+        }
+        // upgrades are only authorized for the owner or the account itself, not the EntryPoint
+        user_op0.calldata(SimpleAccountProvider::execute(
+            provider,
+            wallet_address,
+            String::from("0"),
+            change_owner,
+        )?);
+        let user_txn = Self::get_account_transaction(
+            tenant,
+            &user.wallet_address,
+            &format!("{:?}", new_owner),
+            TransactionType::OwnerRotation,
+        );
+        Self::create_sponsored_operation(pool, provider, tenant, user, user_op0, user_txn).await
+    }
+
     pub async fn execute(
         pool: &Pool<Postgres>,
         provider: &Web3Client,
         transaction_id: String,
-        signature: Bytes,
+        mut signature: Bytes,
+        passkey: Option<PasskeyAssertion>,
         user: User,
     ) -> Result<TransferResponse, TransferError> {
         if user.wallet_address.is_empty() {
             return Err(TransferError::NotFound);
         }
-        let user_op = UserOperationDao::get_user_operation(pool, transaction_id.clone()).await?;
+        let user_op =
+            UserOperationDao::get_user_operation(pool, user.tenant_id, transaction_id.clone())
+                .await?;
 
         if user_op.transaction_id.is_empty() || user_op.status != Status::INITIATED.to_string() {
             return Err(TransferError::TxnNotFound);
         }
+        let mut user_operation = user_op.user_operation;
+        if let Some(session_signature) = SessionKeyService::get_signature(
+            pool,
+            transaction_id.clone(),
+            Bundler::get_user_op_hash(&user_operation)?,
+            signature.clone(),
+            &user,
+        )
+        .await?
+        {
+            signature = session_signature;
+        } else if user.is_passkey_owner() {
+            signature = Self::get_passkey_signature(pool, &user, &user_operation, passkey).await?;
+        } else {
+            Self::verify_owner_signature(
+                provider,
+                &user,
+                Bundler::get_user_op_hash(&user_operation)?,
+                &signature,
+            )
+            .await?;
+        }
+        SponsorshipService::hold(pool, transaction_id.clone()).await?;
+        SessionKeyService::hold(pool, user.tenant_id, transaction_id.clone()).await?;
         UserOperationDao::update_user_operation_status(
             pool,
+            user.tenant_id,
             transaction_id.clone(),
             Status::PENDING.to_string(),
         )
         .await?;
 
-        let mut user_operation = user_op.user_operation;
         user_operation.signature(signature);
 
-        let result = Bundler::submit(
-            provider,
-            user_operation.clone(),
-            CONFIG.run_config.account_owner,
-        )
-        .await;
+        let result =
+            Bundler::submit(provider, user_operation.clone(), CONFIG.get_beneficiary()).await;
         let txn_hash;
         match result {
             Ok(hash) => txn_hash = hash,
             Err(err) => {
                 TransactionDao::update_user_transaction(
                     pool,
-                    transaction_id,
+                    user.tenant_id,
+                    transaction_id.clone(),
                     None,
                     Status::FAILED.to_string(),
                 )
                 .await?;
+                // nothing was spent, release the reserved budget
+                SponsorshipDao::update_gas_cost(pool, transaction_id.clone(), BigDecimal::from(0))
+                    .await?;
+                SessionKeyDao::update_spend(
+                    pool,
+                    user.tenant_id,
+                    transaction_id.clone(),
+                    BigDecimal::from(0),
+                )
+                .await?;
+                spawn(WebhookService::notify_transaction(
+                    pool.clone(),
+                    transaction_id,
+                    Status::FAILED.to_string(),
+                    None,
+                ));
                 return Err(TransferError::from(err));
             }
         }
         TransactionDao::update_user_transaction(
             pool,
+            user.tenant_id,
             transaction_id.clone(),
             None,
             Status::PENDING.to_string(),
         )
         .await?;
 
-        if !user.deployed {
-            WalletDao::update_wallet_deployed(pool, user.external_user_id).await?;
-        }
-
+        spawn(bundle_listener(
+            pool.clone(),
+            provider.clone(),
+            txn_hash.clone(),
+        ));
         spawn(user_op_event_listener(
             pool.clone(),
             provider.clone(),
-            user_operation.hash(
-                CONFIG.get_chain().entrypoint_address,
-                CONFIG.get_chain().chain_id,
-            ),
+            user.tenant_id,
+            Bundler::get_user_op_hash(&user_operation)?,
             transaction_id.clone(),
         ));
         UserOperationDao::update_user_operation_status(
             pool,
+            user.tenant_id,
             transaction_id.clone(),
             Status::SUCCESS.to_string(),
         )
@@ -196,8 +382,13 @@ impl TransferService {
         txn_id: String,
         user: User,
     ) -> Result<Transaction, TransactionError> {
-        let transaction =
-            TransactionDao::get_transaction_by_id(pool, txn_id, user.wallet_address).await?;
+        let transaction = TransactionDao::get_transaction_by_id(
+            pool,
+            user.tenant_id,
+            txn_id,
+            user.wallet_address,
+        )
+        .await?;
 
         Ok(Transaction::from(transaction))
     }
@@ -209,6 +400,7 @@ impl TransferService {
     }
 
     fn get_user_transaction(
+        tenant: &Tenant,
         to: &String,
         value: &String,
         currency: &String,
@@ -216,8 +408,9 @@ impl TransferService {
     ) -> UserTransaction {
         let mut user_txn = UserTransaction::new();
         user_txn
+            .tenant_id(tenant.id)
             .user_address(wallet_address.clone())
-            .transaction_id(generate_txn_id())
+            .transaction_id(generate_txn_id(tenant.get_transaction_id_prefix()))
             .sender_address(wallet_address)
             .receiver_address(to.clone())
             .amount(BigDecimal::from_str(value).unwrap())
@@ -228,26 +421,227 @@ impl TransferService {
         user_txn
     }
 
-    async fn get_signed_hash(
+    pub fn get_account_transaction(
+        tenant: &Tenant,
+        wallet_address: &str,
+        to: &str,
+        transaction_type: TransactionType,
+    ) -> UserTransaction {
+        let mut user_txn = UserTransaction::new();
+        user_txn
+            .tenant_id(tenant.id)
+            .user_address(wallet_address.to_string())
+            .transaction_id(generate_txn_id(tenant.get_transaction_id_prefix()))
+            .sender_address(wallet_address.to_string())
+            .receiver_address(to.to_string())
+            .amount(BigDecimal::from(0))
+            .currency(CONFIG.get_chain().currency.clone())
+            .transaction_type(transaction_type.to_string())
+            .status(Status::INITIATED.to_string())
+            .metadata(Self::get_transaction_metadata());
+        user_txn
+    }
+
+    // account management ops are always sponsored by the verifying paymaster
+    pub async fn create_sponsored_operation(
+        pool: &Pool<Postgres>,
         provider: &Web3Client,
-        user_op0: UserOperation,
-        valid_until: u64,
-        valid_after: u64,
-    ) -> Result<Vec<u8>, ProviderError> {
-        let hash = VerifyingPaymasterProvider::get_hash(
+        tenant: &Tenant,
+        user: User,
+        mut user_op0: UserOperation,
+        user_txn: UserTransaction,
+    ) -> Result<TransferInitResponse, TransferError> {
+        let wallet_address: Address = user.wallet_address.parse().unwrap();
+        user_op0
+            .nonce(
+                Bundler::get_nonce(provider, wallet_address)
+                    .await?
+                    .low_u64(),
+            )
+            .sender(wallet_address);
+        SponsorshipService::reserve(
+            pool,
+            tenant,
+            user_txn.transaction_id.clone(),
+            user.wallet_address.clone(),
+            &user_op0,
+        )
+        .await?;
+        Self::set_verifying_paymaster_and_data(provider, &mut user_op0, wallet_address).await?;
+
+        let user_op_hash = Bundler::get_user_op_hash(&user_op0)?;
+        TransactionDao::create_user_transaction(pool, user_txn.clone()).await?;
+        UserOperationDao::create_user_operation(
+            pool,
+            tenant.id,
+            user_txn.transaction_id.clone(),
+            user_op0,
+            Status::INITIATED.to_string(),
+        )
+        .await?;
+
+        Ok(TransferInitResponse {
+            msg_hash: user_op_hash,
+            status: user_txn.status,
+            transaction_id: user_txn.transaction_id,
+            paymaster: Constants::VERIFYING_PAYMASTER.to_string(),
+        })
+    }
+
+    // users.deployed only flips once AccountDeployed is seen, the code check covers a deployment
+    // the listener missed
+    async fn is_wallet_deployed(pool: &Pool<Postgres>, user: &User) -> Result<bool, TransferError> {
+        if user.deployed {
+            return Ok(true);
+        }
+        if !contract_exists_at(user.wallet_address.clone()).await? {
+            return Ok(false);
+        }
+        WalletService::update_deployed(pool, user.tenant_id, user.wallet_address.clone(), true)
+            .await?;
+        Ok(true)
+    }
+
+    fn set_init_code(
+        provider: &Web3Client,
+        user_op0: &mut UserOperation,
+        user: &User,
+    ) -> Result<(), TransferError> {
+        let salt = user.get_salt().ok_or(TransferError::Provider(String::from(
+            "Unsupported account salt",
+        )))?;
+        if user.is_passkey_owner() {
+            let (x, y) = get_coordinates(
+                &parse_owner(&user.owner_address).map_err(TransferError::Provider)?,
+            );
+            user_op0.init_code(
+                PasskeyAccountFactoryProvider::get_factory_address()?,
+                PasskeyAccountFactoryProvider::create_account(provider, x, y, salt)?,
+            );
+            return Ok(());
+        }
+        user_op0.init_code(
+            SimpleAccountFactoryProvider::get_factory_address(provider),
+            SimpleAccountFactoryProvider::create_account(
+                provider,
+                user.owner_address.parse().unwrap(),
+                salt,
+            )?,
+        );
+        Ok(())
+    }
+
+    // the same check as SimpleAccount._validateSignature: ECDSA over the EIP-191 prefixed user op
+    // hash. Contract owners are asked over ERC-1271 with that prefixed hash
+    async fn verify_owner_signature(
+        provider: &Web3Client,
+        user: &User,
+        user_op_hash: [u8; 32],
+        signature: &Bytes,
+    ) -> Result<(), TransferError> {
+        let owner: Address = user.owner_address.parse().map_err(|_| {
+            TransferError::Provider(format!("Invalid owner address: {}", user.owner_address))
+        })?;
+        let recovered = Signature::try_from(signature.as_ref())
+            .and_then(|signature| signature.recover(&user_op_hash[..]));
+        if recovered.ok() == Some(owner) {
+            return Ok(());
+        }
+        if contract_exists_at(user.owner_address.clone()).await?
+            && Erc1271Provider::is_valid_signature(
+                provider,
+                owner,
+                hash_message(user_op_hash),
+                signature.clone(),
+            )
+            .await?
+        {
+            return Ok(());
+        }
+        Err(TransferError::InvalidSignature)
+    }
+
+    // the account checks the same assertion on chain, verifying it here keeps a bad one from
+    // failing the whole bundle
+    async fn get_passkey_signature(
+        pool: &Pool<Postgres>,
+        user: &User,
+        user_operation: &UserOperation,
+        passkey: Option<PasskeyAssertion>,
+    ) -> Result<Bytes, TransferError> {
+        let assertion = passkey.ok_or(TransferError::InvalidSignature)?;
+        let public_key = PasskeyDao::get_public_key(
+            pool,
+            user.tenant_id,
+            user.wallet_address.clone(),
+            assertion.credential_id.clone(),
+        )
+        .await
+        .map_err(|err| match err {
+            DatabaseError::NotFound => TransferError::InvalidSignature,
+            err => TransferError::from(err),
+        })?;
+        // only the key the account was deployed with can sign for it
+        if public_key != user.owner_address {
+            return Err(TransferError::InvalidSignature);
+        }
+        let public_key = parse_owner(&public_key).map_err(TransferError::Provider)?;
+        verify_assertion(
+            &public_key,
+            &assertion.authenticator_data,
+            &assertion.client_data_json,
+            &assertion.signature,
+            &Bundler::get_user_op_hash(user_operation)?,
+        )
+        .and_then(|signature| {
+            encode_signature(
+                &assertion.authenticator_data,
+                &assertion.client_data_json,
+                &signature,
+            )
+        })
+        .map_err(|err| {
+            info!(
+                "passkey assertion rejected for {}: {}",
+                user.wallet_address, err
+            );
+            TransferError::InvalidSignature
+        })
+    }
+
+    pub async fn set_verifying_paymaster_and_data(
+        provider: &Web3Client,
+        user_op0: &mut UserOperation,
+        wallet_address: Address,
+    ) -> Result<(), TransferError> {
+        let valid_until = VerifyingPaymasterProvider::VALID_UNTIL;
+        let valid_after = VerifyingPaymasterProvider::VALID_AFTER;
+        let data = VerifyingPaymasterProvider::get_validity_data(valid_until, valid_after);
+        user_op0.paymaster_and_data(data.clone(), wallet_address, None);
+
+        user_op0.signature(Bytes::from(
+            Web3Client::get_verifying_paymaster_wallet()
+                .sign_typed_data(&*user_op0)
+                .await
+                .map_err(|err| TransferError::Provider(err.to_string()))?
+                .to_vec(),
+        ));
+
+        let version = CONFIG.get_chain().get_entrypoint_version();
+        let singed_hash = VerifyingPaymasterProvider::get_signed_hash(
             provider,
-            VerifyingPaymasterProvider::get_verifying_paymaster_user_operation_payload(user_op0),
+            version,
+            user_op0.clone(),
             valid_until,
             valid_after,
         )
         .await?;
-        let result = Web3Client::get_verifying_paymaster_wallet()
-            .sign_message(hash)
-            .await;
-        match result {
-            Ok(signature) => Ok(signature.to_vec()),
-            Err(err) => Err(ProviderError(format!("Signing failed: {:?}", err))),
-        }
+        user_op0.paymaster_and_data(
+            data,
+            VerifyingPaymasterProvider::get_address(version)?,
+            Some(singed_hash),
+        );
+        Ok(())
     }
 
     async fn get_call_data(
@@ -256,6 +650,7 @@ impl TransferService {
         to: String,
         value: String,
         currency: String,
+        approval: Option<(Address, U256)>,
     ) -> Result<Bytes, TransferError> {
         let metadata = TokenMetadataDao::get_metadata_for_chain(
             pool,
@@ -264,12 +659,27 @@ impl TransferService {
         )
         .await?;
         match Currency::from_str(metadata[0].token_type.clone()) {
-            Some(Currency::Erc20) => Ok(SimpleAccountProvider::execute(
-                provider,
-                CONFIG.get_chain().usdc_address,
-                0.to_string(),
-                USDCProvider::transfer(provider, to.parse().unwrap(), value)?,
-            )?),
+            Some(Currency::Erc20) => {
+                let transfer = USDCProvider::transfer(provider, to.parse().unwrap(), value)?;
+                match approval {
+                    Some((spender, amount)) => Ok(SimpleAccountProvider::execute_batch(
+                        provider,
+                        vec![
+                            CONFIG.get_chain().usdc_address,
+                            CONFIG.get_chain().usdc_address,
+                        ],
+                        vec![USDCProvider::approve(provider, spender, amount)?, transfer],
+                    )?),
+                    None => Ok(SimpleAccountProvider::execute(
+                        provider,
+                        CONFIG.get_chain().usdc_address,
+                        0.to_string(),
+                        transfer,
+                    )?),
+                }
+            }
+            // executeBatch cannot carry value, so native transfers can't pay gas in tokens
+            Some(Currency::Native) if approval.is_some() => Err(TransferError::InvalidPaymaster),
             Some(Currency::Native) => Ok(SimpleAccountProvider::execute(
                 provider,
                 to.parse().unwrap(),
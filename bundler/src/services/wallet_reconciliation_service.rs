@@ -0,0 +1,82 @@
+use actix_web::rt::time::interval;
+use log::{error, info};
+use sqlx::{Pool, Postgres};
+use std::time::Duration;
+
+use crate::db::dao::{TenantDao, WalletDao};
+use crate::models::config::settings::WalletReconciliation;
+use crate::provider::helpers::contract_exists_at;
+use crate::services::WalletService;
+
+#[derive(Clone)]
+pub struct WalletReconciliationService;
+
+impl WalletReconciliationService {
+    // rows deployed before the flag followed AccountDeployed can be wrong either way
+    pub async fn run(pool: Pool<Postgres>, config: WalletReconciliation) {
+        info!(
+            "Wallet reconciliation: checking every {}s",
+            config.interval_secs
+        );
+        let mut ticker = interval(Duration::from_secs(config.interval_secs));
+        loop {
+            ticker.tick().await;
+            if let Err(err) = Self::reconcile(&pool).await {
+                error!("Wallet reconciliation: {}", err);
+            }
+        }
+    }
+
+    async fn reconcile(pool: &Pool<Postgres>) -> Result<(), String> {
+        let tenants = TenantDao::get_tenants(pool)
+            .await
+            .map_err(|err| format!("{:?}", err))?;
+        let mut fixed = 0;
+        for tenant in tenants {
+            // one wallet or tenant failing does not hold up the others, it is retried on the next
+            // pass
+            let wallets = match WalletDao::get_wallets(pool, tenant.id).await {
+                Ok(wallets) => wallets,
+                Err(err) => {
+                    error!("Wallet reconciliation: tenant {}: {:?}", tenant.id, err);
+                    continue;
+                }
+            };
+            for wallet in wallets {
+                let deployed = match contract_exists_at(wallet.wallet_address.clone()).await {
+                    Ok(deployed) => deployed,
+                    Err(err) => {
+                        error!(
+                            "Wallet reconciliation: {} of tenant {}: {:?}",
+                            wallet.wallet_address, tenant.id, err
+                        );
+                        continue;
+                    }
+                };
+                if deployed == wallet.deployed {
+                    continue;
+                }
+                info!(
+                    "Wallet reconciliation: {} of tenant {} deployed {} -> {}",
+                    wallet.wallet_address, tenant.id, wallet.deployed, deployed
+                );
+                let result = WalletService::update_deployed(
+                    pool,
+                    tenant.id,
+                    wallet.wallet_address.clone(),
+                    deployed,
+                )
+                .await;
+                match result {
+                    Ok(_) => fixed += 1,
+                    Err(err) => error!(
+                        "Wallet reconciliation: {} of tenant {}: {:?}",
+                        wallet.wallet_address, tenant.id, err
+                    ),
+                }
+            }
+        }
+        info!("Wallet reconciliation: fixed {} wallets", fixed);
+        Ok(())
+    }
+}
@@ -1,21 +1,27 @@
 use actix_web::rt::spawn;
 use bigdecimal::{BigDecimal, Zero};
-use ethers::types::Address;
+use ethers::types::{Address, Bytes};
 use log::info;
 use sqlx::{Pool, Postgres};
-use std::time::SystemTime;
 
+use crate::constants::Constants;
+use crate::contracts::passkey_account_factory_provider::PasskeyAccountFactoryProvider;
 use crate::contracts::simple_account_factory_provider::SimpleAccountFactoryProvider;
 use crate::contracts::simple_account_provider::SimpleAccountProvider;
-use crate::db::dao::{TransactionDao, User, WalletDao};
-use crate::errors::{ProviderError, TransactionError, WalletError};
+use crate::db::dao::{PasskeyDao, TransactionDao, User, WalletDao};
+use crate::errors::{DatabaseError, ProviderError, TransactionError, WalletError};
+use crate::middleware::auth_cache::AUTH_CACHE;
 use crate::models::transaction::Transaction;
 use crate::models::wallet::AddressResponse;
-use crate::provider::helpers::{contract_exists_at, get_hash};
+use crate::provider::helpers::{contract_exists_at, get_account_salt, to_decimal};
+use crate::provider::webauthn::{encode_public_key, get_coordinates, parse_public_key};
 use crate::provider::Web3Client;
 use crate::services::MintService;
 use crate::CONFIG;
 
+// the auth middleware caches user rows. Writes to them drop the cached row before they start and
+// again once they are visible, a request that read the old row in between could cache it for the
+// whole ttl otherwise
 #[derive(Clone)]
 pub struct WalletService;
 
@@ -30,22 +36,30 @@ impl WalletService {
         if user.wallet_address.is_empty() {
             result = Self::get_address(
                 &provider.clone(),
+                user.tenant_id,
                 user.external_user_id.as_str(),
                 user_wallet.parse().unwrap(),
             )
             .await?;
             info!("salt -> {}", result.salt);
+            let tenant_id = user.tenant_id;
+            let external_user_id = user.external_user_id.clone();
+            AUTH_CACHE.invalidate_user(tenant_id, &external_user_id);
             WalletDao::create_wallet(
                 pool,
+                user.tenant_id,
                 user.email,
                 user.name,
                 format!("{:?}", result.address),
                 user_wallet,
                 user.external_user_id,
                 result.salt,
+                Constants::ACCOUNT_SALT_VERSION,
+                Constants::EOA_OWNER,
                 result.deployed,
             )
             .await?;
+            AUTH_CACHE.invalidate_user(tenant_id, &external_user_id);
             // spawn a thread to mint for user
             spawn(MintService::mint(provider.clone(), result.address.clone()));
         } else {
@@ -61,21 +75,107 @@ impl WalletService {
         })
     }
 
+    // the wallet is created around the passkey, so it has to be registered before the first
+    // address lookup
+    pub async fn register_passkey(
+        pool: &Pool<Postgres>,
+        provider: &Web3Client,
+        user: User,
+        credential_id: String,
+        public_key: Bytes,
+    ) -> Result<AddressResponse, WalletError> {
+        if !user.wallet_address.is_empty() {
+            return Err(WalletError::WalletExists);
+        }
+        let public_key = parse_public_key(&public_key).map_err(WalletError::InvalidPasskey)?;
+        let (x, y) = get_coordinates(&public_key);
+        // the address depends on the key, so there is nothing to collide with
+        let salt = get_account_salt(user.tenant_id, user.external_user_id.as_str(), 0);
+        let address = PasskeyAccountFactoryProvider::get_address(provider, x, y, salt).await?;
+        let wallet_address = format!("{:?}", address);
+        let deployed = contract_exists_at(wallet_address.clone()).await?;
+        let tenant_id = user.tenant_id;
+        let external_user_id = user.external_user_id.clone();
+        AUTH_CACHE.invalidate_user(tenant_id, &external_user_id);
+        // a wallet without its credential could never sign
+        let mut txn = pool
+            .begin()
+            .await
+            .map_err(|err| WalletError::Database(err.to_string()))?;
+        WalletDao::create_wallet(
+            &mut *txn,
+            user.tenant_id,
+            user.email,
+            user.name,
+            wallet_address.clone(),
+            encode_public_key(&public_key),
+            user.external_user_id,
+            to_decimal(salt),
+            Constants::ACCOUNT_SALT_VERSION,
+            Constants::PASSKEY_OWNER,
+            deployed,
+        )
+        .await?;
+        PasskeyDao::create_credential(
+            &mut *txn,
+            tenant_id,
+            wallet_address,
+            credential_id,
+            encode_public_key(&public_key),
+        )
+        .await?;
+        txn.commit()
+            .await
+            .map_err(|err| WalletError::Database(err.to_string()))?;
+        AUTH_CACHE.invalidate_user(tenant_id, &external_user_id);
+        spawn(MintService::mint(provider.clone(), address));
+
+        Ok(AddressResponse { address })
+    }
+
+    pub async fn update_deployed(
+        pool: &Pool<Postgres>,
+        tenant_id: i32,
+        wallet_address: String,
+        deployed: bool,
+    ) -> Result<(), DatabaseError> {
+        AUTH_CACHE.invalidate_wallet(tenant_id, &wallet_address);
+        let result =
+            WalletDao::update_wallet_deployed(pool, tenant_id, wallet_address.clone(), deployed)
+                .await;
+        AUTH_CACHE.invalidate_wallet(tenant_id, &wallet_address);
+        result
+    }
+
+    pub async fn update_owner(
+        pool: &Pool<Postgres>,
+        tenant_id: i32,
+        wallet_address: String,
+        owner_address: String,
+    ) -> Result<(), DatabaseError> {
+        AUTH_CACHE.invalidate_wallet(tenant_id, &wallet_address);
+        let result =
+            WalletDao::update_owner_address(pool, tenant_id, wallet_address.clone(), owner_address)
+                .await;
+        AUTH_CACHE.invalidate_wallet(tenant_id, &wallet_address);
+        result
+    }
+
     async fn get_address(
         provider: &Web3Client,
+        tenant_id: i32,
         external_user_id: &str,
         user_wallet: Address,
     ) -> Result<Wallet, ProviderError> {
         let mut contract_address;
-        let mut suffix = "".to_string();
+        let mut index = 0;
         let mut salt;
         let mut deployed = false;
         loop {
-            let user = external_user_id.to_string().clone() + suffix.as_str();
-            salt = get_hash(user);
+            salt = get_account_salt(tenant_id, external_user_id, index);
             contract_address =
                 SimpleAccountFactoryProvider::get_address(provider, user_wallet, salt).await?;
-            if contract_exists_at(format!("{:?}", contract_address)).await {
+            if contract_exists_at(format!("{:?}", contract_address)).await? {
                 info!("contract exists at {:?}", contract_address);
                 if Self::is_deployed_by_us(provider, contract_address).await? {
                     deployed = true;
@@ -84,15 +184,11 @@ impl WalletService {
             } else {
                 break;
             }
-            suffix = SystemTime::now()
-                .duration_since(SystemTime::UNIX_EPOCH)
-                .map_err(|err| ProviderError(format!("Failed to create suffix: {:?}", err)))?
-                .as_nanos()
-                .to_string();
+            index += 1;
         }
         Ok(Wallet {
             address: contract_address,
-            salt: BigDecimal::from(salt),
+            salt: to_decimal(salt),
             deployed,
         })
     }
@@ -119,8 +215,14 @@ impl WalletService {
     ) -> Result<Vec<Transaction>, TransactionError> {
         let row_id = id.unwrap_or(i32::MAX);
 
-        let result =
-            TransactionDao::list_transactions(pool, page_size, row_id, user.wallet_address).await?;
+        let result = TransactionDao::list_transactions(
+            pool,
+            user.tenant_id,
+            page_size,
+            row_id,
+            user.wallet_address,
+        )
+        .await?;
 
         let transactions = result
             .iter()
@@ -0,0 +1,131 @@
+use hmac::{Hmac, Mac};
+use lazy_static::lazy_static;
+use log::error;
+use reqwest::header::CONTENT_TYPE;
+use serde_json::{json, Value};
+use sha2::Sha256;
+use sqlx::{Pool, Postgres};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::db::dao::TenantDao;
+
+const WEBHOOK_TIMEOUT_SECS: u64 = 10;
+const WEBHOOK_TIMESTAMP_HEADER: &str = "x-toad-timestamp";
+const WEBHOOK_SIGNATURE_HEADER: &str = "x-toad-signature";
+
+lazy_static! {
+    // one connection pool for every tenant, a slow endpoint gives up after the timeout
+    static ref WEBHOOK_CLIENT: reqwest::Client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(WEBHOOK_TIMEOUT_SECS))
+        .build()
+        .expect("Failed to build webhook client.");
+}
+
+// x-toad-signature is the hex HMAC-SHA256 of "{x-toad-timestamp}.{body}" with the tenant's
+// webhook secret
+#[derive(Clone)]
+pub struct WebhookService;
+
+impl WebhookService {
+    // tells the tenant a transaction reached a final status, delivery is best effort
+    pub async fn notify_transaction(
+        pool: Pool<Postgres>,
+        transaction_id: String,
+        status: String,
+        transaction_hash: Option<String>,
+    ) {
+        Self::notify(
+            &pool,
+            transaction_id.clone(),
+            json!({
+                "event": "transaction_status",
+                "transaction_id": transaction_id,
+                "status": status,
+                "transaction_hash": transaction_hash,
+            }),
+        )
+        .await;
+    }
+
+    // initiated transactions whose paymaster signature was replaced, the owner has to sign the
+    // new msg_hash before executing
+    pub async fn notify_resigned(pool: Pool<Postgres>, resigned: Vec<(String, [u8; 32])>) {
+        for (transaction_id, msg_hash) in resigned {
+            Self::notify(
+                &pool,
+                transaction_id.clone(),
+                json!({
+                    "event": "transaction_resigned",
+                    "transaction_id": transaction_id,
+                    "msg_hash": msg_hash,
+                }),
+            )
+            .await;
+        }
+    }
+
+    async fn notify(pool: &Pool<Postgres>, transaction_id: String, payload: Value) {
+        let tenant =
+            match TenantDao::get_tenant_by_transaction_id(pool, transaction_id.clone()).await {
+                Ok(tenant) => tenant,
+                Err(err) => {
+                    error!(
+                        "Webhook: failed to get tenant for {}: {:?}",
+                        transaction_id, err
+                    );
+                    return;
+                }
+            };
+        let webhook_url = match tenant.webhook_url {
+            Some(webhook_url) => webhook_url,
+            None => return,
+        };
+        let body = payload.to_string();
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let response = WEBHOOK_CLIENT
+            .post(&webhook_url)
+            .header(CONTENT_TYPE, "application/json")
+            .header(WEBHOOK_TIMESTAMP_HEADER, timestamp)
+            .header(
+                WEBHOOK_SIGNATURE_HEADER,
+                Self::sign(&tenant.webhook_secret, timestamp, &body),
+            )
+            .body(body)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        if let Err(err) = response {
+            error!(
+                "Webhook: failed to notify tenant {} of {}: {:?}",
+                tenant.id, transaction_id, err
+            );
+        }
+    }
+
+    fn sign(secret: &str, timestamp: u64, body: &str) -> String {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any size");
+        mac.update(format!("{}.{}", timestamp, body).as_bytes());
+        ethers::utils::hex::encode(mac.finalize().into_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signs_the_timestamp_and_body() {
+        // HMAC-SHA256("secret", "1700000000.{}")
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"secret").unwrap();
+        mac.update(b"1700000000.{}");
+        let expected = ethers::utils::hex::encode(mac.finalize().into_bytes());
+
+        assert_eq!(WebhookService::sign("secret", 1700000000, "{}"), expected);
+        assert_ne!(WebhookService::sign("other", 1700000000, "{}"), expected);
+        assert_ne!(WebhookService::sign("secret", 1700000001, "{}"), expected);
+    }
+}
@@ -0,0 +1,9 @@
+pub mod relayer_pool;
+pub mod remote_signer;
+pub mod signers;
+pub mod toad_signer;
+
+pub use relayer_pool::RelayerPool;
+pub use remote_signer::{RemoteSignRequest, RemoteSignResponse, RemoteSigner};
+pub use signers::Signers;
+pub use toad_signer::ToadSigner;
@@ -0,0 +1,168 @@
+use ethers::abi::Abi;
+use ethers::middleware::SignerMiddleware;
+use ethers::providers::{Http, Middleware, Provider};
+use ethers::types::{Address, BlockNumber, Bytes, U256};
+use ethers_signers::Signer;
+use futures::lock::{Mutex, MutexGuard};
+use std::sync::Arc;
+
+use crate::provider::web3_provider::Web3Provider;
+use crate::signer::ToadSigner;
+
+pub struct Relayer {
+    pub signer: ToadSigner,
+    // next nonce to use, None until it is read from chain or after a failed send
+    next_nonce: Mutex<Option<U256>>,
+}
+
+pub struct RelayerPool {
+    relayers: Vec<Relayer>,
+}
+
+impl RelayerPool {
+    pub fn new(signers: Vec<ToadSigner>) -> Self {
+        Self {
+            relayers: signers
+                .into_iter()
+                .map(|signer| Relayer {
+                    signer,
+                    next_nonce: Mutex::new(None),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn get_addresses(&self) -> Vec<Address> {
+        self.relayers
+            .iter()
+            .map(|relayer| relayer.signer.address())
+            .collect()
+    }
+
+    pub async fn execute(
+        &self,
+        client: Arc<Provider<Http>>,
+        to: Address,
+        value: String,
+        data: Bytes,
+        abi: &Abi,
+    ) -> Result<String, String> {
+        let (relayer, mut next_nonce) = self.lock_least_busy(&client).await?;
+        Self::send(
+            client,
+            &relayer.signer,
+            &mut next_nonce,
+            to,
+            value,
+            data,
+            abi,
+        )
+        .await
+    }
+
+    // for calls only a given key may make (eg: paymaster owner calls), its nonces still come from
+    // the pool so they don't collide with the bundles it sends
+    pub async fn execute_as(
+        &self,
+        client: Arc<Provider<Http>>,
+        address: Address,
+        to: Address,
+        value: String,
+        data: Bytes,
+        abi: &Abi,
+    ) -> Result<String, String> {
+        let relayer = self
+            .relayers
+            .iter()
+            .find(|relayer| relayer.signer.address() == address)
+            .ok_or(format!("{:?} is not in the relayer pool", address))?;
+        let mut next_nonce = relayer.next_nonce.lock().await;
+        Self::send(
+            client,
+            &relayer.signer,
+            &mut next_nonce,
+            to,
+            value,
+            data,
+            abi,
+        )
+        .await
+    }
+
+    // the nonce lock is held until the transaction is sent so nonces of a key are handed out in
+    // order
+    async fn send(
+        client: Arc<Provider<Http>>,
+        signer: &ToadSigner,
+        next_nonce: &mut Option<U256>,
+        to: Address,
+        value: String,
+        data: Bytes,
+        abi: &Abi,
+    ) -> Result<String, String> {
+        let nonce = match *next_nonce {
+            Some(nonce) => nonce,
+            None => Self::get_nonce(&client, signer.address(), BlockNumber::Pending).await?,
+        };
+        let result = Web3Provider::execute_with_nonce(
+            SignerMiddleware::new(client, signer.clone()),
+            to,
+            value,
+            data,
+            Some(nonce),
+            abi,
+        )
+        .await;
+        *next_nonce = match result {
+            Ok(_) => Some(nonce + 1),
+            Err(_) => None,
+        };
+        result
+    }
+
+    // busy = nonces handed out by this pool that are not mined yet. The relayer is picked and
+    // locked in one go, relayers that are sending right now are skipped
+    async fn lock_least_busy(
+        &self,
+        client: &Provider<Http>,
+    ) -> Result<(&Relayer, MutexGuard<'_, Option<U256>>), String> {
+        let mut least_busy: Option<(&Relayer, MutexGuard<'_, Option<U256>>, U256)> = None;
+        for relayer in &self.relayers {
+            let next_nonce = match relayer.next_nonce.try_lock() {
+                Some(next_nonce) => next_nonce,
+                None => continue,
+            };
+            let mined =
+                Self::get_nonce(client, relayer.signer.address(), BlockNumber::Latest).await?;
+            let pending = next_nonce.unwrap_or(mined).saturating_sub(mined);
+            if least_busy
+                .as_ref()
+                .map_or(true, |(_, _, least_pending)| pending < *least_pending)
+            {
+                least_busy = Some((relayer, next_nonce, pending));
+            }
+        }
+        match least_busy {
+            Some((relayer, next_nonce, _)) => Ok((relayer, next_nonce)),
+            // every relayer is sending, wait for the first one
+            None => {
+                let relayer = self
+                    .relayers
+                    .first()
+                    .ok_or(String::from("Relayer pool is empty"))?;
+                Ok((relayer, relayer.next_nonce.lock().await))
+            }
+        }
+    }
+
+    async fn get_nonce(
+        client: &Provider<Http>,
+        address: Address,
+        block: BlockNumber,
+    ) -> Result<U256, String> {
+        client
+            .get_transaction_count(address, Some(block.into()))
+            .await
+            .map_err(|err| format!("Failed to get relayer nonce: {:?}", err))
+    }
+}
@@ -0,0 +1,83 @@
+use ethers::types::{Address, Bytes, Signature, H256};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use zeroize::Zeroizing;
+
+use crate::errors::SignerError;
+
+#[derive(Serialize, Deserialize)]
+pub struct RemoteSignRequest {
+    pub address: Address,
+    pub hash: H256,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RemoteSignResponse {
+    pub signature: Bytes,
+}
+
+// the key never leaves the signing service, it signs the digests computed here
+#[derive(Clone)]
+pub struct RemoteSigner {
+    pub url: String,
+    pub address: Address,
+    pub chain_id: u64,
+    pub auth_token: Option<Zeroizing<String>>,
+    client: reqwest::Client,
+}
+
+impl fmt::Debug for RemoteSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RemoteSigner")
+            .field("url", &self.url)
+            .field("address", &self.address)
+            .field("chain_id", &self.chain_id)
+            .finish()
+    }
+}
+
+impl RemoteSigner {
+    pub fn new(url: String, address: Address, auth_token: Option<Zeroizing<String>>) -> Self {
+        Self {
+            url,
+            address,
+            chain_id: 1,
+            auth_token,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    pub async fn sign_hash(&self, hash: H256) -> Result<Signature, SignerError> {
+        let mut request = self
+            .client
+            .post(format!("{}/sign", self.url.trim_end_matches('/')))
+            .json(&RemoteSignRequest {
+                address: self.address,
+                hash,
+            });
+        if let Some(auth_token) = &self.auth_token {
+            request = request.bearer_auth(auth_token.as_str());
+        }
+        let response = request
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|err| SignerError::Remote(format!("Request failed: {:?}", err)))?
+            .json::<RemoteSignResponse>()
+            .await
+            .map_err(|err| SignerError::Remote(format!("Invalid response: {:?}", err)))?;
+
+        let mut signature = Signature::try_from(response.signature.as_ref())
+            .map_err(|err| SignerError::Remote(format!("Invalid signature: {:?}", err)))?;
+        if signature.v < 27 {
+            signature.v += 27;
+        }
+        if signature.recover(hash).ok() != Some(self.address) {
+            return Err(SignerError::Remote(format!(
+                "Signature is not from {:?}",
+                self.address
+            )));
+        }
+        Ok(signature)
+    }
+}
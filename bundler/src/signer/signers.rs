@@ -0,0 +1,111 @@
+use ethers_signers::Signer;
+
+use crate::models::config::settings::{SignerBackend, SignerSettings};
+use crate::signer::ToadSigner;
+
+// loaded once at startup, see SIGNERS in main
+pub struct Signers {
+    pub relayer: ToadSigner,
+    // relayer, bundler and the relayer pool keys, without duplicates
+    pub relayers: Vec<ToadSigner>,
+    // the configured paymaster signer followed by older keys kept for rotation
+    pub paymasters: Vec<ToadSigner>,
+    // only needed for automatic top ups
+    pub treasury: Option<ToadSigner>,
+    // backend held session key, signs for sessions created without a key of their own
+    pub session: Option<ToadSigner>,
+}
+
+impl Signers {
+    pub fn load(
+        settings: &SignerSettings,
+        relayer_pool: &[SignerBackend],
+        chain_id: u64,
+    ) -> Result<Signers, String> {
+        let relayer = ToadSigner::load(&settings.relayer, chain_id)?;
+        let bundler = ToadSigner::load(&settings.bundler, chain_id)?;
+        let mut relayers = vec![relayer.clone()];
+        for backend in relayer_pool {
+            Self::push_unique(&mut relayers, ToadSigner::load(backend, chain_id)?);
+        }
+        Self::push_unique(&mut relayers, bundler);
+
+        // older keys used to be read from this env var, which skipped the configured backends
+        if std::env::var("VERIFYING_PAYMASTER_PRIVATE_KEYS").is_ok() {
+            return Err(String::from(
+                "VERIFYING_PAYMASTER_PRIVATE_KEYS is no longer read, configure the older keys in \
+                signers.previous_paymasters",
+            ));
+        }
+        let mut paymasters = vec![ToadSigner::load(&settings.paymaster, chain_id)?];
+        for backend in &settings.previous_paymasters {
+            Self::push_unique(&mut paymasters, ToadSigner::load(backend, chain_id)?);
+        }
+
+        Ok(Signers {
+            relayer,
+            relayers,
+            paymasters,
+            treasury: Self::load_optional(&settings.treasury, chain_id)?,
+            session: Self::load_optional(&settings.session, chain_id)?,
+        })
+    }
+
+    fn push_unique(signers: &mut Vec<ToadSigner>, signer: ToadSigner) {
+        if !signers
+            .iter()
+            .any(|known| known.address() == signer.address())
+        {
+            signers.push(signer);
+        }
+    }
+
+    // an unset env var means the role is not configured, anything else is an error
+    fn load_optional(backend: &SignerBackend, chain_id: u64) -> Result<Option<ToadSigner>, String> {
+        if let SignerBackend::Env { env_var } = backend {
+            if std::env::var(env_var).is_err() {
+                return Ok(None);
+            }
+        }
+        ToadSigner::load(backend, chain_id).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env_signer(env_var: &str, signer: &ToadSigner) -> SignerBackend {
+        if let ToadSigner::Local(wallet) = signer {
+            std::env::set_var(
+                env_var,
+                ethers::utils::hex::encode(wallet.signer().to_bytes()),
+            );
+        }
+        SignerBackend::Env {
+            env_var: env_var.to_string(),
+        }
+    }
+
+    #[test]
+    fn loads_previous_paymasters_through_their_backends() {
+        let relayer = ToadSigner::mock(1);
+        let paymaster = ToadSigner::mock(2);
+        let previous = ToadSigner::mock(3);
+        let settings = SignerSettings {
+            relayer: env_signer("SIGNERS_TEST_RELAYER", &relayer),
+            bundler: env_signer("SIGNERS_TEST_RELAYER", &relayer),
+            paymaster: env_signer("SIGNERS_TEST_PAYMASTER", &paymaster),
+            previous_paymasters: vec![
+                env_signer("SIGNERS_TEST_PREVIOUS", &previous),
+                env_signer("SIGNERS_TEST_PAYMASTER", &paymaster),
+            ],
+            ..Default::default()
+        };
+
+        let signers = Signers::load(&settings, &[], 84532).unwrap();
+        let addresses: Vec<_> = signers.paymasters.iter().map(|s| s.address()).collect();
+        assert_eq!(addresses, vec![paymaster.address(), previous.address()]);
+        assert_eq!(signers.paymasters[1].chain_id(), 84532);
+    }
+}
@@ -0,0 +1,150 @@
+use async_trait::async_trait;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::transaction::eip712::Eip712;
+use ethers::types::{Address, Signature, H256};
+use ethers::utils::hash_message;
+use ethers_signers::{to_eip155_v, LocalWallet, Signer};
+use zeroize::Zeroizing;
+
+use crate::errors::SignerError;
+use crate::models::config::settings::SignerBackend;
+use crate::signer::RemoteSigner;
+
+// local keys are zeroized when the wallet is dropped
+#[derive(Clone, Debug)]
+pub enum ToadSigner {
+    Local(LocalWallet),
+    Remote(RemoteSigner),
+}
+
+impl ToadSigner {
+    pub fn load(backend: &SignerBackend, chain_id: u64) -> Result<ToadSigner, String> {
+        let signer = match backend {
+            SignerBackend::Env { env_var } => {
+                let key = Zeroizing::new(
+                    std::env::var(env_var).map_err(|_| format!("{} must be set", env_var))?,
+                );
+                ToadSigner::Local(
+                    key.parse::<LocalWallet>()
+                        .map_err(|_| format!("{} is not a valid private key", env_var))?,
+                )
+            }
+            SignerBackend::Keystore {
+                path,
+                password_env_var,
+            } => {
+                let password = Zeroizing::new(
+                    std::env::var(password_env_var)
+                        .map_err(|_| format!("{} must be set", password_env_var))?,
+                );
+                ToadSigner::Local(
+                    LocalWallet::decrypt_keystore(path, password.as_bytes())
+                        .map_err(|err| format!("Failed to decrypt {}: {:?}", path, err))?,
+                )
+            }
+            SignerBackend::Remote {
+                url,
+                address,
+                auth_token_env_var,
+            } => {
+                let auth_token = match auth_token_env_var {
+                    Some(env_var) => Some(Zeroizing::new(
+                        std::env::var(env_var).map_err(|_| format!("{} must be set", env_var))?,
+                    )),
+                    None => None,
+                };
+                ToadSigner::Remote(RemoteSigner::new(url.clone(), *address, auth_token))
+            }
+        };
+        Ok(signer.with_chain_id(chain_id))
+    }
+}
+
+#[async_trait]
+impl Signer for ToadSigner {
+    type Error = SignerError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(
+        &self,
+        message: S,
+    ) -> Result<Signature, Self::Error> {
+        match self {
+            ToadSigner::Local(wallet) => wallet
+                .sign_message(message)
+                .await
+                .map_err(|err| SignerError::Wallet(err.to_string())),
+            ToadSigner::Remote(signer) => signer.sign_hash(hash_message(message)).await,
+        }
+    }
+
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, Self::Error> {
+        match self {
+            ToadSigner::Local(wallet) => wallet
+                .sign_transaction(tx)
+                .await
+                .map_err(|err| SignerError::Wallet(err.to_string())),
+            // same as LocalWallet, the digest is signed remotely
+            ToadSigner::Remote(signer) => {
+                let chain_id = tx
+                    .chain_id()
+                    .map(|id| id.as_u64())
+                    .unwrap_or(signer.chain_id);
+                let mut tx = tx.clone();
+                tx.set_chain_id(chain_id);
+                let mut signature = signer.sign_hash(tx.sighash()).await?;
+                signature.v = to_eip155_v(signature.v as u8 - 27, chain_id);
+                Ok(signature)
+            }
+        }
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        payload: &T,
+    ) -> Result<Signature, Self::Error> {
+        match self {
+            ToadSigner::Local(wallet) => wallet
+                .sign_typed_data(payload)
+                .await
+                .map_err(|err| SignerError::Wallet(err.to_string())),
+            ToadSigner::Remote(signer) => {
+                let hash = payload
+                    .encode_eip712()
+                    .map_err(|err| SignerError::Remote(err.to_string()))?;
+                signer.sign_hash(H256::from(hash)).await
+            }
+        }
+    }
+
+    fn address(&self) -> Address {
+        match self {
+            ToadSigner::Local(wallet) => wallet.address(),
+            ToadSigner::Remote(signer) => signer.address,
+        }
+    }
+
+    fn chain_id(&self) -> u64 {
+        match self {
+            ToadSigner::Local(wallet) => wallet.chain_id(),
+            ToadSigner::Remote(signer) => signer.chain_id,
+        }
+    }
+
+    fn with_chain_id<T: Into<u64>>(self, chain_id: T) -> Self {
+        match self {
+            ToadSigner::Local(wallet) => ToadSigner::Local(wallet.with_chain_id(chain_id)),
+            ToadSigner::Remote(mut signer) => {
+                signer.chain_id = chain_id.into();
+                ToadSigner::Remote(signer)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+impl ToadSigner {
+    // a fixed local key per seed, so tests get distinct signers without any configuration
+    pub fn mock(seed: u8) -> ToadSigner {
+        ToadSigner::Local(LocalWallet::from_bytes(&[seed.max(1); 32]).unwrap())
+    }
+}
@@ -4,6 +4,9 @@ pragma solidity ^0.8.13;
 import "forge-std/Script.sol";
 import "../src/EntryPoint.sol";
 import "../src/SimpleAccountFactory.sol";
+import "../src/SessionKeyValidator.sol";
+import "../src/RotatableAccount.sol";
+import "../src/PasskeyAccountFactory.sol";
 import "../src/VerifyingPaymaster.sol";
 import "../src/tests/TestERC20.sol";
 
@@ -14,12 +17,18 @@ contract DeployLocal is Script {
     EntryPoint internal entryPoint;
     SimpleAccountFactory internal factory;
     VerifyingPaymaster internal paymaster;
+    SessionKeyValidator internal sessionKeyValidator;
+    RotatableAccount internal rotatableAccount;
+    PasskeyAccountFactory internal passkeyFactory;
     TestERC20 internal erc20;
 
     uint256 internal entryPointSalt;
     uint256 internal factorySalt;
     uint256 internal erc20Salt;
     uint256 internal paymasterSalt;
+    uint256 internal sessionKeyValidatorSalt;
+    uint256 internal rotatableAccountSalt;
+    uint256 internal passkeyFactorySalt;
 
     function setUp() public {
         deployerPrivateKey = vm.envUint("PRIVATE_KEY");
@@ -30,6 +39,9 @@ contract DeployLocal is Script {
         factorySalt = vm.envOr("SIMPLE_ACCOUNT_FACTORY_SALT", uint256(2));
         erc20Salt = vm.envOr("TEST_ERC20_SALT", uint256(3));
         paymasterSalt = vm.envOr("VERIFYING_PAYMASTER_SALT", uint256(4));
+        rotatableAccountSalt = vm.envOr("ROTATABLE_ACCOUNT_SALT", uint256(5));
+        sessionKeyValidatorSalt = vm.envOr("SESSION_KEY_VALIDATOR_SALT", uint256(7));
+        passkeyFactorySalt = vm.envOr("PASSKEY_ACCOUNT_FACTORY_SALT", uint256(6));
     }
 
     function run() public {
@@ -47,6 +59,17 @@ contract DeployLocal is Script {
         paymaster = new VerifyingPaymaster{salt: bytes32(paymasterSalt)}(entryPoint, owner);
         console.log("=VerifyingPaymaster addr==", address(paymaster));
 
+        sessionKeyValidator = new SessionKeyValidator{salt: bytes32(sessionKeyValidatorSalt)}();
+        console.log("=SessionKeyValidator addr==", address(sessionKeyValidator));
+
+        rotatableAccount = new RotatableAccount{salt: bytes32(rotatableAccountSalt)}(
+            entryPoint, sessionKeyValidator
+        );
+        console.log("=RotatableAccount addr==", address(rotatableAccount));
+
+        passkeyFactory = new PasskeyAccountFactory{salt: bytes32(passkeyFactorySalt)}(entryPoint);
+        console.log("=PasskeyAccountFactory addr==", address(passkeyFactory));
+
         vm.stopBroadcast();
     }
 }
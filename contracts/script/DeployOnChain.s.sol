@@ -4,6 +4,9 @@ pragma solidity ^0.8.13;
 import "forge-std/Script.sol";
 import "../src/EntryPoint.sol";
 import "../src/SimpleAccountFactory.sol";
+import "../src/SessionKeyValidator.sol";
+import "../src/RotatableAccount.sol";
+import "../src/PasskeyAccountFactory.sol";
 import "../src/VerifyingPaymaster.sol";
 import "../src/tests/TestERC20.sol";
 
@@ -15,9 +18,15 @@ contract DeployOnChain is Script {
 
     SimpleAccountFactory internal factory;
     VerifyingPaymaster internal paymaster;
+    SessionKeyValidator internal sessionKeyValidator;
+    RotatableAccount internal rotatableAccount;
+    PasskeyAccountFactory internal passkeyFactory;
 
     uint256 internal factorySalt;
     uint256 internal paymasterSalt;
+    uint256 internal sessionKeyValidatorSalt;
+    uint256 internal rotatableAccountSalt;
+    uint256 internal passkeyFactorySalt;
 
     function setUp() public {
         deployerPrivateKey = vm.envUint("PRIVATE_KEY");
@@ -32,6 +41,9 @@ contract DeployOnChain is Script {
 
         factorySalt = vm.envOr("SIMPLE_ACCOUNT_FACTORY_SALT", uint256(2));
         paymasterSalt = vm.envOr("VERIFYING_PAYMASTER_SALT", uint256(4));
+        rotatableAccountSalt = vm.envOr("ROTATABLE_ACCOUNT_SALT", uint256(5));
+        sessionKeyValidatorSalt = vm.envOr("SESSION_KEY_VALIDATOR_SALT", uint256(7));
+        passkeyFactorySalt = vm.envOr("PASSKEY_ACCOUNT_FACTORY_SALT", uint256(6));
     }
 
     function run() public {
@@ -43,6 +55,17 @@ contract DeployOnChain is Script {
         paymaster = new VerifyingPaymaster{salt: bytes32(paymasterSalt)}(IEntryPoint(entryPoint), verifyingSigner);
         console.log("=VerifyingPaymaster addr==", address(paymaster));
 
+        sessionKeyValidator = new SessionKeyValidator{salt: bytes32(sessionKeyValidatorSalt)}();
+        console.log("=SessionKeyValidator addr==", address(sessionKeyValidator));
+
+        rotatableAccount = new RotatableAccount{salt: bytes32(rotatableAccountSalt)}(
+            IEntryPoint(entryPoint), sessionKeyValidator
+        );
+        console.log("=RotatableAccount addr==", address(rotatableAccount));
+
+        passkeyFactory = new PasskeyAccountFactory{salt: bytes32(passkeyFactorySalt)}(IEntryPoint(entryPoint));
+        console.log("=PasskeyAccountFactory addr==", address(passkeyFactory));
+
         vm.stopBroadcast();
     }
 }
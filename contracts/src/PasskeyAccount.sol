@@ -0,0 +1,119 @@
+// SPDX-License-Identifier: GPL-3.0
+pragma solidity ^0.8.12;
+
+/* solhint-disable avoid-low-level-calls */
+/* solhint-disable no-inline-assembly */
+/* solhint-disable reason-string */
+
+import "@openzeppelin/contracts/proxy/utils/Initializable.sol";
+import "@openzeppelin/contracts/proxy/utils/UUPSUpgradeable.sol";
+
+import "./core/BaseAccount.sol";
+import "./TokenCallbackHandler.sol";
+import "./utils/WebAuthn.sol";
+
+/**
+  * account owned by a P-256 public key (a passkey).
+  *  the user operation signature is an abi encoded WebAuthn.WebAuthnAuth whose challenge is the
+  *  user operation hash. there is no EOA owner, calls come through the entryPoint only.
+  */
+contract PasskeyAccount is BaseAccount, TokenCallbackHandler, UUPSUpgradeable, Initializable {
+
+    uint256 public x;
+    uint256 public y;
+
+    IEntryPoint private immutable _entryPoint;
+
+    event PasskeyAccountInitialized(IEntryPoint indexed entryPoint, uint256 x, uint256 y);
+
+    /// @inheritdoc BaseAccount
+    function entryPoint() public view virtual override returns (IEntryPoint) {
+        return _entryPoint;
+    }
+
+    // solhint-disable-next-line no-empty-blocks
+    receive() external payable {}
+
+    constructor(IEntryPoint anEntryPoint) {
+        _entryPoint = anEntryPoint;
+        _disableInitializers();
+    }
+
+    function _onlySelf() internal view {
+        // through execute, which only the entryPoint can call
+        require(msg.sender == address(this), "only self");
+    }
+
+    /**
+     * execute a transaction (called by entryPoint)
+     */
+    function execute(address dest, uint256 value, bytes calldata func) external {
+        _requireFromEntryPoint();
+        _call(dest, value, func);
+    }
+
+    /**
+     * execute a sequence of transactions
+     */
+    function executeBatch(address[] calldata dest, bytes[] calldata func) external {
+        _requireFromEntryPoint();
+        require(dest.length == func.length, "wrong array lengths");
+        for (uint256 i = 0; i < dest.length; i++) {
+            _call(dest[i], 0, func[i]);
+        }
+    }
+
+    function initialize(uint256 anX, uint256 anY) public virtual initializer {
+        x = anX;
+        y = anY;
+        emit PasskeyAccountInitialized(_entryPoint, anX, anY);
+    }
+
+    /// implement template method of BaseAccount
+    /// user verification is the bundler's policy, on chain only user presence is required
+    function _validateSignature(UserOperation calldata userOp, bytes32 userOpHash)
+    internal override virtual returns (uint256 validationData) {
+        WebAuthn.WebAuthnAuth memory auth = abi.decode(userOp.signature, (WebAuthn.WebAuthnAuth));
+        if (!WebAuthn.verify(abi.encodePacked(userOpHash), false, auth, x, y))
+            return SIG_VALIDATION_FAILED;
+        return 0;
+    }
+
+    function _call(address target, uint256 value, bytes memory data) internal {
+        (bool success, bytes memory result) = target.call{value : value}(data);
+        if (!success) {
+            assembly {
+                revert(add(result, 32), mload(result))
+            }
+        }
+    }
+
+    /**
+     * check current account deposit in the entryPoint
+     */
+    function getDeposit() public view returns (uint256) {
+        return entryPoint().balanceOf(address(this));
+    }
+
+    /**
+     * deposit more funds for this account in the entryPoint
+     */
+    function addDeposit() public payable {
+        entryPoint().depositTo{value : msg.value}(address(this));
+    }
+
+    /**
+     * withdraw value from the account's deposit
+     * @param withdrawAddress target to send to
+     * @param amount to withdraw
+     */
+    function withdrawDepositTo(address payable withdrawAddress, uint256 amount) public {
+        _onlySelf();
+        entryPoint().withdrawTo(withdrawAddress, amount);
+    }
+
+    function _authorizeUpgrade(address newImplementation) internal view override {
+        (newImplementation);
+        _onlySelf();
+    }
+}
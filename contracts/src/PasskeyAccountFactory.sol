@@ -0,0 +1,49 @@
+// SPDX-License-Identifier: GPL-3.0
+pragma solidity ^0.8.12;
+
+import "@openzeppelin/contracts/utils/Create2.sol";
+import "@openzeppelin/contracts/proxy/ERC1967/ERC1967Proxy.sol";
+
+import "./PasskeyAccount.sol";
+
+/**
+ * A factory contract for PasskeyAccount, the counterpart of SimpleAccountFactory for a P-256 owner
+ * (x, y) instead of an owner address.
+ * The factory's createAccount returns the target account address even if it is already installed.
+ */
+contract PasskeyAccountFactory {
+    PasskeyAccount public immutable accountImplementation;
+
+    constructor(IEntryPoint _entryPoint) {
+        accountImplementation = new PasskeyAccount(_entryPoint);
+    }
+
+    /**
+     * create an account, and return its address.
+     * returns the address even if the account is already deployed.
+     */
+    function createAccount(uint256 x, uint256 y, uint256 salt) public returns (PasskeyAccount ret) {
+        address addr = getAddress(x, y, salt);
+        uint codeSize = addr.code.length;
+        if (codeSize > 0) {
+            return PasskeyAccount(payable(addr));
+        }
+        ret = PasskeyAccount(payable(new ERC1967Proxy{salt : bytes32(salt)}(
+                address(accountImplementation),
+                abi.encodeCall(PasskeyAccount.initialize, (x, y))
+            )));
+    }
+
+    /**
+     * calculate the counterfactual address of this account as it would be returned by createAccount()
+     */
+    function getAddress(uint256 x, uint256 y, uint256 salt) public view returns (address) {
+        return Create2.computeAddress(bytes32(salt), keccak256(abi.encodePacked(
+                type(ERC1967Proxy).creationCode,
+                abi.encode(
+                    address(accountImplementation),
+                    abi.encodeCall(PasskeyAccount.initialize, (x, y))
+                )
+            )));
+    }
+}
@@ -0,0 +1,166 @@
+// SPDX-License-Identifier: GPL-3.0
+pragma solidity ^0.8.12;
+
+/* solhint-disable reason-string */
+
+import "@openzeppelin/contracts/interfaces/IERC1271.sol";
+import "@openzeppelin/contracts/utils/cryptography/EIP712.sol";
+
+import "./SimpleAccount.sol";
+import "./SessionKeyValidator.sol";
+
+/**
+  * SimpleAccount whose owner can be rotated.
+  *  deployed accounts are upgraded to it over UUPS (upgradeToAndCall with changeOwner) the first
+  *  time their owner changes, so it only appends storage to SimpleAccount.
+  *  implements ERC-1271 over an EIP-712 AccountMessage, so a signature for one account can't be
+  *  replayed against another account of the same owner.
+  *  the owner can set guardians, a majority of them can move the account to a new owner with
+  *  recoverOwner. a user operation calling only recoverOwner needs no owner signature, it is
+  *  validated by the guardian signatures it carries.
+  *  any other signature that is not a plain 65 byte owner signature is abi.encode(validator, data)
+  *  and is handed to the session key validator the implementation was deployed with.
+  */
+contract RotatableAccount is SimpleAccount, EIP712, IERC1271 {
+    using ECDSA for bytes32;
+
+    bytes32 private constant ACCOUNT_MESSAGE_TYPEHASH = keccak256("AccountMessage(bytes32 hash)");
+
+    // bumped by every recovery, so guardian signatures for one recovery can't be replayed
+    uint256 public recoveryNonce;
+
+    SessionKeyValidator public immutable sessionKeyValidator;
+
+    address[] private _guardians;
+
+    event OwnerChanged(address indexed previousOwner, address indexed newOwner);
+
+    event GuardiansChanged(address[] guardians);
+
+    constructor(IEntryPoint anEntryPoint, SessionKeyValidator aSessionKeyValidator)
+    SimpleAccount(anEntryPoint) EIP712("SimpleAccount", "1") {
+        sessionKeyValidator = aSessionKeyValidator;
+    }
+
+    /**
+     * change the owner of the account (called by the owner, or through execute)
+     * @param newOwner the new signer of the account
+     */
+    function changeOwner(address newOwner) external onlyOwner {
+        _changeOwner(newOwner);
+    }
+
+    /**
+     * replace the guardians of the account (called by the owner, or through execute)
+     * @param guardians the new guardians, in any order, an empty list disables recovery
+     */
+    function setGuardians(address[] calldata guardians) external onlyOwner {
+        for (uint256 i = 0; i < guardians.length; i++) {
+            require(guardians[i] != address(0) && guardians[i] != address(this), "account: invalid guardian");
+            require(guardians[i] != owner, "account: owner can not be a guardian");
+            for (uint256 j = 0; j < i; j++) {
+                require(guardians[i] != guardians[j], "account: duplicate guardian");
+            }
+        }
+        _guardians = guardians;
+        emit GuardiansChanged(guardians);
+    }
+
+    function getGuardians() external view returns (address[] memory) {
+        return _guardians;
+    }
+
+    /**
+     * move the account to newOwner, signed by a majority of the guardians
+     * @param newOwner the new signer of the account
+     * @param signatures 65 byte guardian signatures of getRecoveryHash(newOwner), concatenated in
+     *  ascending guardian address order
+     */
+    function recoverOwner(address newOwner, bytes calldata signatures) external {
+        require(_checkGuardianSignatures(getRecoveryHash(newOwner), signatures), "account: invalid recovery");
+        recoveryNonce++;
+        _changeOwner(newOwner);
+    }
+
+    /**
+     * the hash the guardians sign (as an EIP-191 message) to move the account to newOwner
+     */
+    function getRecoveryHash(address newOwner) public view returns (bytes32) {
+        return keccak256(abi.encode(address(this), block.chainid, newOwner, recoveryNonce));
+    }
+
+    /// @inheritdoc IERC1271
+    function isValidSignature(bytes32 hash, bytes memory signature) public view override returns (bytes4) {
+        bytes32 digest = _hashTypedDataV4(keccak256(abi.encode(ACCOUNT_MESSAGE_TYPEHASH, hash)));
+        (address signer, ECDSA.RecoverError error) = digest.tryRecover(signature);
+        if (error != ECDSA.RecoverError.NoError || signer != owner)
+            return 0xffffffff;
+        return IERC1271.isValidSignature.selector;
+    }
+
+    /**
+     * a strict majority of the current guardians
+     */
+    function _getThreshold() internal view returns (uint256) {
+        return _guardians.length / 2 + 1;
+    }
+
+    /// recoverOwner is validated by the guardian signatures instead of the owner's, session key
+    /// signatures by the session key validator
+    function _validateSignature(UserOperation calldata userOp, bytes32 userOpHash)
+    internal override virtual returns (uint256 validationData) {
+        if (userOp.callData.length >= 4 && bytes4(userOp.callData[: 4]) == this.recoverOwner.selector) {
+            (address newOwner, bytes memory signatures) = abi.decode(userOp.callData[4 :], (address, bytes));
+            if (!_checkGuardianSignatures(getRecoveryHash(newOwner), signatures))
+                return SIG_VALIDATION_FAILED;
+            return 0;
+        }
+        if (userOp.signature.length != 65) {
+            (address validator, bytes memory data) = abi.decode(userOp.signature, (address, bytes));
+            if (validator != address(sessionKeyValidator))
+                return SIG_VALIDATION_FAILED;
+            return sessionKeyValidator.validateUserOp(userOp, userOpHash, data);
+        }
+        return super._validateSignature(userOp, userOpHash);
+    }
+
+    // ascending signers make every guardian count at most once
+    function _checkGuardianSignatures(bytes32 recoveryHash, bytes memory signatures) internal view returns (bool) {
+        uint256 count = signatures.length / 65;
+        if (_guardians.length == 0 || signatures.length % 65 != 0 || count < _getThreshold())
+            return false;
+        bytes32 hash = recoveryHash.toEthSignedMessageHash();
+        address previous = address(0);
+        for (uint256 i = 0; i < count; i++) {
+            bytes32 r;
+            bytes32 s;
+            uint8 v;
+            // solhint-disable-next-line no-inline-assembly
+            assembly {
+                let offset := add(add(signatures, 0x20), mul(i, 65))
+                r := mload(offset)
+                s := mload(add(offset, 0x20))
+                v := byte(0, mload(add(offset, 0x40)))
+            }
+            (address signer, ECDSA.RecoverError error) = hash.tryRecover(v, r, s);
+            if (error != ECDSA.RecoverError.NoError || signer <= previous || !_isGuardian(signer))
+                return false;
+            previous = signer;
+        }
+        return true;
+    }
+
+    function _isGuardian(address guardian) internal view returns (bool) {
+        for (uint256 i = 0; i < _guardians.length; i++) {
+            if (_guardians[i] == guardian)
+                return true;
+        }
+        return false;
+    }
+
+    function _changeOwner(address newOwner) internal {
+        require(newOwner != address(0) && newOwner != address(this), "account: invalid owner");
+        emit OwnerChanged(owner, newOwner);
+        owner = newOwner;
+    }
+}
@@ -0,0 +1,150 @@
+// SPDX-License-Identifier: GPL-3.0
+pragma solidity ^0.8.12;
+
+/* solhint-disable reason-string */
+
+import "@openzeppelin/contracts/utils/cryptography/ECDSA.sol";
+
+import "./core/Helpers.sol";
+import "./interfaces/UserOperation.sol";
+
+interface IOwnedAccount {
+    function owner() external view returns (address);
+}
+
+/**
+ * validates user operations signed by a session key.
+ *  the owner signs (as an EIP-191 message) the hash of a SessionPermission once, the session key
+ *  then signs user operations that only transfer native currency or an ERC-20 (execute with an
+ *  empty call, or execute with transfer(address,uint256)) to an allowed target, within the spend
+ *  limit of the token and the validity window of the permission.
+ *  called by the account from its validation, so msg.sender is the account and all state is kept
+ *  per account.
+ */
+contract SessionKeyValidator {
+    using ECDSA for bytes32;
+
+    // native currency is the zero address in tokens
+    struct SessionPermission {
+        address sessionKey;
+        uint256 validAfter;
+        uint256 validUntil;
+        address[] allowedTargets;
+        address[] tokens;
+        uint256[] spendLimits;
+        bytes32 salt;
+    }
+
+    uint256 internal constant SIG_VALIDATION_FAILED = 1;
+
+    bytes4 internal constant EXECUTE_SELECTOR = bytes4(keccak256("execute(address,uint256,bytes)"));
+    bytes4 internal constant TRANSFER_SELECTOR = bytes4(keccak256("transfer(address,uint256)"));
+
+    // the account is the last key, so the slots are associated with it as ERC-4337 validation requires
+    mapping(bytes32 => mapping(address => bool)) private _revoked;
+    mapping(bytes32 => mapping(address => mapping(address => uint256))) private _spent;
+
+    event SessionRevoked(address indexed account, bytes32 indexed permissionHash);
+
+    /**
+     * revoke a permission of the calling account
+     */
+    function revokeSession(bytes32 permissionHash) external {
+        _revoked[permissionHash][msg.sender] = true;
+        emit SessionRevoked(msg.sender, permissionHash);
+    }
+
+    function isRevoked(address account, bytes32 permissionHash) public view returns (bool) {
+        return _revoked[permissionHash][account];
+    }
+
+    function getSpent(address account, bytes32 permissionHash, address token) public view returns (uint256) {
+        return _spent[permissionHash][token][account];
+    }
+
+    function getPermissionHash(address account, SessionPermission memory permission) public view returns (bytes32) {
+        return keccak256(abi.encode(block.chainid, account, address(this), permission));
+    }
+
+    /**
+     * validate a user operation of the calling account, the spend is counted right away
+     * @param data abi.encode(permission, ownerSignature, sessionSignature)
+     * @return validationData as IAccount.validateUserOp, bounded by the permission's window
+     */
+    function validateUserOp(UserOperation calldata userOp, bytes32 userOpHash, bytes calldata data)
+    external returns (uint256 validationData) {
+        (SessionPermission memory permission, bytes memory ownerSignature, bytes memory sessionSignature) =
+            abi.decode(data, (SessionPermission, bytes, bytes));
+        if (userOp.sender != msg.sender || permission.tokens.length != permission.spendLimits.length)
+            return SIG_VALIDATION_FAILED;
+
+        bytes32 permissionHash = getPermissionHash(msg.sender, permission);
+        if (_revoked[permissionHash][msg.sender])
+            return SIG_VALIDATION_FAILED;
+        if (!_isSignedBy(permissionHash, ownerSignature, IOwnedAccount(msg.sender).owner()))
+            return SIG_VALIDATION_FAILED;
+        if (!_isSignedBy(userOpHash, sessionSignature, permission.sessionKey))
+            return SIG_VALIDATION_FAILED;
+        if (!_spend(permissionHash, permission, userOp.callData))
+            return SIG_VALIDATION_FAILED;
+
+        return _packValidationData(false, uint48(permission.validUntil), uint48(permission.validAfter));
+    }
+
+    function _isSignedBy(bytes32 hash, bytes memory signature, address signer) internal pure returns (bool) {
+        (address recovered, ECDSA.RecoverError error) = hash.toEthSignedMessageHash().tryRecover(signature);
+        return error == ECDSA.RecoverError.NoError && recovered == signer;
+    }
+
+    // the call has to be a single transfer in scope, its amount is added to the spent total
+    function _spend(bytes32 permissionHash, SessionPermission memory permission, bytes calldata callData)
+    internal returns (bool) {
+        if (callData.length < 4 || bytes4(callData[: 4]) != EXECUTE_SELECTOR)
+            return false;
+        (address dest, uint256 value, bytes memory func) = abi.decode(callData[4 :], (address, uint256, bytes));
+
+        address token;
+        address target;
+        uint256 amount;
+        if (func.length == 0) {
+            (token, target, amount) = (address(0), dest, value);
+        } else if (value == 0 && func.length == 68 && bytes4(func) == TRANSFER_SELECTOR) {
+            (target, amount) = _decodeTransfer(func);
+            token = dest;
+        } else {
+            return false;
+        }
+
+        if (!_isAllowedTarget(permission.allowedTargets, target))
+            return false;
+        for (uint256 i = 0; i < permission.tokens.length; i++) {
+            if (permission.tokens[i] == token) {
+                uint256 spent = _spent[permissionHash][token][msg.sender] + amount;
+                if (spent > permission.spendLimits[i])
+                    return false;
+                _spent[permissionHash][token][msg.sender] = spent;
+                return true;
+            }
+        }
+        return false;
+    }
+
+    // an empty list allows every target
+    function _isAllowedTarget(address[] memory allowedTargets, address target) internal pure returns (bool) {
+        if (allowedTargets.length == 0)
+            return true;
+        for (uint256 i = 0; i < allowedTargets.length; i++) {
+            if (allowedTargets[i] == target)
+                return true;
+        }
+        return false;
+    }
+
+    function _decodeTransfer(bytes memory func) internal pure returns (address target, uint256 amount) {
+        bytes memory args = new bytes(64);
+        for (uint256 i = 0; i < 64; i++) {
+            args[i] = func[i + 4];
+        }
+        (target, amount) = abi.decode(args, (address, uint256));
+    }
+}
@@ -0,0 +1,102 @@
+// SPDX-License-Identifier: GPL-3.0
+pragma solidity ^0.8.12;
+
+/* solhint-disable no-inline-assembly */
+
+import "@openzeppelin/contracts/utils/Base64.sol";
+
+/**
+ * verifies WebAuthn assertions of a P-256 key.
+ *  the indexes point at "type" and "challenge" in clientDataJSON, so the JSON is never parsed: the
+ *  signed client data only has to contain the expected type and challenge at those offsets.
+ *  the relying party is not checked here, the signature is only worth something to the rp that
+ *  registered the key.
+ */
+library WebAuthn {
+    struct WebAuthnAuth {
+        bytes authenticatorData;
+        string clientDataJSON;
+        uint256 challengeIndex;
+        uint256 typeIndex;
+        uint256 r;
+        uint256 s;
+    }
+
+    // RIP-7212 P256VERIFY
+    address internal constant P256_VERIFIER = address(0x100);
+
+    uint256 internal constant P256_N_DIV_2 = 0x7fffffff800000007fffffffffffffffde737d56d38bcf4279dce5617e3192a8;
+
+    bytes1 internal constant FLAG_USER_PRESENT = 0x01;
+    bytes1 internal constant FLAG_USER_VERIFIED = 0x04;
+
+    bytes32 internal constant EXPECTED_TYPE_HASH = keccak256('"type":"webauthn.get"');
+
+    function verify(bytes memory challenge, bool requireUserVerification, WebAuthnAuth memory auth, uint256 x, uint256 y)
+    internal view returns (bool) {
+        // the malleable high s form is refused
+        if (auth.s > P256_N_DIV_2) {
+            return false;
+        }
+
+        bytes memory clientDataJSON = bytes(auth.clientDataJSON);
+        if (keccak256(_slice(clientDataJSON, auth.typeIndex, 21)) != EXPECTED_TYPE_HASH) {
+            return false;
+        }
+        bytes memory expectedChallenge = abi.encodePacked('"challenge":"', encodeURL(challenge), '"');
+        if (keccak256(_slice(clientDataJSON, auth.challengeIndex, expectedChallenge.length))
+            != keccak256(expectedChallenge)) {
+            return false;
+        }
+
+        // rpIdHash (32) || flags (1) || signCount (4)
+        if (auth.authenticatorData.length < 37) {
+            return false;
+        }
+        bytes1 flags = auth.authenticatorData[32];
+        if (flags & FLAG_USER_PRESENT == 0) {
+            return false;
+        }
+        if (requireUserVerification && flags & FLAG_USER_VERIFIED == 0) {
+            return false;
+        }
+
+        bytes32 messageHash = sha256(abi.encodePacked(auth.authenticatorData, sha256(clientDataJSON)));
+        (bool success, bytes memory result) =
+            P256_VERIFIER.staticcall(abi.encode(messageHash, auth.r, auth.s, x, y));
+        return success && result.length == 32 && abi.decode(result, (uint256)) == 1;
+    }
+
+    /**
+     * base64url without padding, as the challenge appears in clientDataJSON
+     */
+    function encodeURL(bytes memory data) internal pure returns (string memory) {
+        bytes memory encoded = bytes(Base64.encode(data));
+        uint256 length = encoded.length;
+        while (length > 0 && encoded[length - 1] == "=") {
+            length--;
+        }
+        for (uint256 i = 0; i < length; i++) {
+            if (encoded[i] == "+") {
+                encoded[i] = "-";
+            } else if (encoded[i] == "/") {
+                encoded[i] = "_";
+            }
+        }
+        assembly {
+            mstore(encoded, length)
+        }
+        return string(encoded);
+    }
+
+    // an out of range slice comes back empty, so it never matches
+    function _slice(bytes memory data, uint256 start, uint256 length) private pure returns (bytes memory result) {
+        if (start + length > data.length) {
+            return result;
+        }
+        result = new bytes(length);
+        for (uint256 i = 0; i < length; i++) {
+            result[i] = data[start + i];
+        }
+    }
+}
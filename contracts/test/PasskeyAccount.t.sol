@@ -0,0 +1,128 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import "./TestHelper.sol";
+import "../src/PasskeyAccount.sol";
+import "../src/PasskeyAccountFactory.sol";
+//Utils
+import {Utilities} from "./Utilities.sol";
+
+contract PasskeyAccountTest is TestHelper {
+    Utilities internal utils;
+    PasskeyAccountFactory internal passkeyFactory;
+
+    // the fixed vector of bundler/src/provider/webauthn.rs: an assertion by the RFC 6979 A.2.5
+    // P-256 key over the challenge 0x0101..01
+    uint256 internal constant X = 0x60fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb6;
+    uint256 internal constant Y = 0x7903fe1008b8bc99a41ae9e95628bc64f2f1b20c2d7e9f5177a3c294d4462299;
+    bytes32 internal constant CHALLENGE = 0x0101010101010101010101010101010101010101010101010101010101010101;
+    bytes32 internal constant MESSAGE_HASH = 0x80e5ba8f55bd05d99aa0ece6360c5e5e6a9e8beda0dc4e56f71beda64cce3d88;
+    uint256 internal constant R = 0xe664d72f4116f8cbe9fcf5b8354407311fa0baff07a27dcc3d3f7d95b03489cf;
+    uint256 internal constant S = 0x10fb12ea831633b034ddaad50829b462cea47c3153108c166e18bdc0b4324dab;
+    uint256 internal constant P256_N = 0xffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551;
+    bytes internal constant AUTHENTICATOR_DATA =
+        hex"ca3a15f92c27b3fcf1b1026ad7781230b6a0f046798b1bff6c34879077ff81370500000001";
+    string internal constant CLIENT_DATA_JSON =
+        '{"type":"webauthn.get","challenge":"AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE","origin":"https://toad.cash","crossOrigin":false}';
+    // provider::webauthn::encode_signature of the vector
+    bytes internal constant ENCODED = hex"000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000000c0000000000000000000000000000000000000000000000000000000000000012000000000000000000000000000000000000000000000000000000000000000170000000000000000000000000000000000000000000000000000000000000001e664d72f4116f8cbe9fcf5b8354407311fa0baff07a27dcc3d3f7d95b03489cf10fb12ea831633b034ddaad50829b462cea47c3153108c166e18bdc0b4324dab0000000000000000000000000000000000000000000000000000000000000025ca3a15f92c27b3fcf1b1026ad7781230b6a0f046798b1bff6c34879077ff8137050000000100000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000827b2274797065223a22776562617574686e2e676574222c226368616c6c656e6765223a2241514542415145424151454241514542415145424151454241514542415145424151454241514542415145222c226f726967696e223a2268747470733a2f2f746f61642e63617368222c2263726f73734f726967696e223a66616c73657d000000000000000000000000000000000000000000000000000000000000";
+
+    function setUp() public {
+        utils = new Utilities();
+        deployEntryPoint(1501);
+        passkeyFactory = new PasskeyAccountFactory{salt: bytes32(uint256(1502))}(entryPoint);
+        // the EVM here has no RIP-7212 precompile: it answers 1 only for the P-256 verification of
+        // the vector, so every other input, including the vector's key with any other message,
+        // fails like it would on chain
+        vm.etch(WebAuthn.P256_VERIFIER, hex"00");
+        vm.mockCall(WebAuthn.P256_VERIFIER, abi.encode(MESSAGE_HASH, R, S, X, Y), abi.encode(uint256(1)));
+    }
+
+    // PasskeyAccountFactory
+    function test_Deployer() public {
+        address testAccount = passkeyFactory.getAddress(X, Y, 1503);
+        assertEq(utils.isContract(testAccount), false);
+        PasskeyAccount created = passkeyFactory.createAccount(X, Y, 1503);
+        assertEq(address(created), testAccount);
+        assertEq(created.x(), X);
+        assertEq(created.y(), Y);
+        // already deployed, the same account comes back
+        assertEq(address(passkeyFactory.createAccount(X, Y, 1503)), testAccount);
+    }
+
+    // The encoding of the bundler is the WebAuthnAuth the account decodes
+    function test_DecodeVector() public {
+        WebAuthn.WebAuthnAuth memory auth = abi.decode(ENCODED, (WebAuthn.WebAuthnAuth));
+        assertEq(auth.authenticatorData, AUTHENTICATOR_DATA);
+        assertEq(auth.clientDataJSON, CLIENT_DATA_JSON);
+        assertEq(auth.challengeIndex, 23);
+        assertEq(auth.typeIndex, 1);
+        assertEq(auth.r, R);
+        assertEq(auth.s, S);
+        assertEq(sha256(abi.encodePacked(AUTHENTICATOR_DATA, sha256(bytes(CLIENT_DATA_JSON)))), MESSAGE_HASH);
+    }
+
+    function test_EncodeURL() public {
+        assertEq(WebAuthn.encodeURL(abi.encodePacked(CHALLENGE)), "AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE");
+        assertEq(WebAuthn.encodeURL(hex"fbff"), "-_8");
+    }
+
+    // #validateUserOp
+    function test_ValidSignature() public {
+        assertEq(_validate(ENCODED, CHALLENGE, X), 0);
+    }
+
+    // The challenge is the user operation hash
+    function test_WrongChallenge() public {
+        assertEq(_validate(ENCODED, keccak256("other"), X), 1);
+    }
+
+    function test_OtherKey() public {
+        assertEq(_validate(ENCODED, CHALLENGE, X - 1), 1);
+    }
+
+    // The bundler normalizes s, the account refuses the high form
+    function test_HighS() public {
+        WebAuthn.WebAuthnAuth memory auth = abi.decode(ENCODED, (WebAuthn.WebAuthnAuth));
+        auth.s = P256_N - S;
+        vm.mockCall(WebAuthn.P256_VERIFIER, abi.encode(MESSAGE_HASH, R, auth.s, X, Y), abi.encode(uint256(1)));
+        assertEq(_validate(abi.encode(auth), CHALLENGE, X), 1);
+    }
+
+    function test_WrongIndexes() public {
+        WebAuthn.WebAuthnAuth memory auth = abi.decode(ENCODED, (WebAuthn.WebAuthnAuth));
+        auth.typeIndex = 0;
+        assertEq(_validate(abi.encode(auth), CHALLENGE, X), 1);
+        auth = abi.decode(ENCODED, (WebAuthn.WebAuthnAuth));
+        auth.challengeIndex = 24;
+        assertEq(_validate(abi.encode(auth), CHALLENGE, X), 1);
+        auth.challengeIndex = 1000;
+        assertEq(_validate(abi.encode(auth), CHALLENGE, X), 1);
+    }
+
+    function test_UserNotPresent() public {
+        WebAuthn.WebAuthnAuth memory auth = abi.decode(ENCODED, (WebAuthn.WebAuthnAuth));
+        auth.authenticatorData[32] = 0x04;
+        bytes32 messageHash = sha256(abi.encodePacked(auth.authenticatorData, sha256(bytes(CLIENT_DATA_JSON))));
+        vm.mockCall(WebAuthn.P256_VERIFIER, abi.encode(messageHash, R, S, X, Y), abi.encode(uint256(1)));
+        assertEq(_validate(abi.encode(auth), CHALLENGE, X), 1);
+    }
+
+    // Calls come through the entryPoint only
+    function test_ExecuteByNonEntryPoint(address caller) public {
+        vm.assume(caller != entryPointAddress);
+        PasskeyAccount passkeyAccount = passkeyFactory.createAccount(X, Y, 1504);
+        vm.prank(caller);
+        vm.expectRevert("account: not from EntryPoint");
+        passkeyAccount.execute(caller, 0, defaultBytes);
+    }
+
+    function _validate(bytes memory signature, bytes32 userOpHash, uint256 x) internal returns (uint256) {
+        PasskeyAccount passkeyAccount = passkeyFactory.createAccount(x, Y, 1505);
+        UserOperation memory op = defaultOp;
+        op.sender = address(passkeyAccount);
+        op.signature = signature;
+        vm.prank(entryPointAddress);
+        return passkeyAccount.validateUserOp(op, userOpHash, 0);
+    }
+}
@@ -0,0 +1,350 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import "./TestHelper.sol";
+import "../src/RotatableAccount.sol";
+//Utils
+import {Utilities} from "./Utilities.sol";
+
+contract RotatableAccountTest is TestHelper {
+    Utilities internal utils;
+    RotatableAccount internal rotatableImplementation;
+
+    event OwnerChanged(address indexed previousOwner, address indexed newOwner);
+    event GuardiansChanged(address[] guardians);
+
+    function setUp() public {
+        utils = new Utilities();
+        accountOwner = utils.createAddress("rotatable_account_owner");
+        deployEntryPoint(1301);
+        createAccount(1302, 1303);
+        rotatableImplementation = new RotatableAccount(entryPoint, new SessionKeyValidator());
+    }
+
+    // Owner should be able to upgrade and rotate in one call, as the bundler does
+    function test_UpgradeAndChangeOwner() public {
+        Account memory newOwner = utils.createAddress("new_owner");
+        vm.expectEmit(true, true, false, false, accountAddress);
+        emit OwnerChanged(accountOwner.addr, newOwner.addr);
+        _upgrade(newOwner.addr);
+
+        RotatableAccount rotatable = RotatableAccount(payable(accountAddress));
+        assertEq(rotatable.owner(), newOwner.addr);
+        // SimpleAccount storage survives the upgrade
+        assertEq(rotatable.deployedBy(), "toad.cash");
+        assertEq(rotatable.recoveryNonce(), 0);
+    }
+
+    // Other account should not be able to rotate the owner
+    function test_ChangeOwnerByNonOwner(address caller) public {
+        vm.assume(caller != accountOwner.addr && caller != accountAddress);
+        RotatableAccount rotatable = _upgrade(accountOwner.addr);
+        vm.prank(caller);
+        vm.expectRevert("only owner");
+        rotatable.changeOwner(caller);
+    }
+
+    // The account itself, through execute, should be able to rotate the owner
+    function test_ChangeOwnerThroughExecute() public {
+        Account memory newOwner = utils.createAddress("new_owner");
+        RotatableAccount rotatable = _upgrade(accountOwner.addr);
+        vm.prank(entryPointAddress);
+        rotatable.execute(accountAddress, 0, abi.encodeCall(RotatableAccount.changeOwner, (newOwner.addr)));
+        assertEq(rotatable.owner(), newOwner.addr);
+    }
+
+    function test_ChangeOwnerToZero() public {
+        RotatableAccount rotatable = _upgrade(accountOwner.addr);
+        vm.prank(accountOwner.addr);
+        vm.expectRevert("account: invalid owner");
+        rotatable.changeOwner(address(0));
+    }
+
+    // The recovery hash commits to the account, the chain, the new owner and the recovery nonce
+    function test_RecoveryHash() public {
+        Account memory newOwner = utils.createAddress("new_owner");
+        RotatableAccount rotatable = _upgrade(accountOwner.addr);
+        assertEq(
+            rotatable.getRecoveryHash(newOwner.addr),
+            keccak256(abi.encode(accountAddress, block.chainid, newOwner.addr, uint256(0)))
+        );
+    }
+
+    // #isValidSignature
+    function test_ValidSignature() public {
+        RotatableAccount rotatable = _upgrade(accountOwner.addr);
+        bytes32 hash = keccak256("message");
+        bytes memory signature = _signAccountMessage(accountAddress, hash, accountOwner.key);
+        assertEq(rotatable.isValidSignature(hash, signature), IERC1271.isValidSignature.selector);
+    }
+
+    function test_SignatureOfOtherSigner() public {
+        Account memory other = utils.createAddress("other");
+        RotatableAccount rotatable = _upgrade(accountOwner.addr);
+        bytes32 hash = keccak256("message");
+        bytes memory signature = _signAccountMessage(accountAddress, hash, other.key);
+        assertEq(rotatable.isValidSignature(hash, signature), bytes4(0xffffffff));
+    }
+
+    // A signature for another account of the same owner must not be accepted
+    function test_SignatureForOtherAccount() public {
+        RotatableAccount rotatable = _upgrade(accountOwner.addr);
+        (SimpleAccount other,) = createAccountWithFactory(1304);
+        bytes32 hash = keccak256("message");
+        bytes memory signature = _signAccountMessage(address(other), hash, accountOwner.key);
+        assertEq(rotatable.isValidSignature(hash, signature), bytes4(0xffffffff));
+    }
+
+    // A bare personal signature of the hash is not an account message
+    function test_PersonalSignatureRejected() public {
+        RotatableAccount rotatable = _upgrade(accountOwner.addr);
+        bytes32 hash = keccak256("message");
+        bytes memory signature = utils.signMessage(hash, accountOwner.key);
+        assertEq(rotatable.isValidSignature(hash, signature), bytes4(0xffffffff));
+    }
+
+    // #setGuardians
+    function test_SetGuardians() public {
+        RotatableAccount rotatable = _upgrade(accountOwner.addr);
+        address[] memory guardians = _guardianAddresses(3);
+        vm.expectEmit(false, false, false, true, accountAddress);
+        emit GuardiansChanged(guardians);
+        vm.prank(accountOwner.addr);
+        rotatable.setGuardians(guardians);
+        assertEq(rotatable.getGuardians(), guardians);
+    }
+
+    function test_SetGuardiansByNonOwner(address caller) public {
+        vm.assume(caller != accountOwner.addr && caller != accountAddress);
+        RotatableAccount rotatable = _upgrade(accountOwner.addr);
+        address[] memory guardians = _guardianAddresses(1);
+        vm.prank(caller);
+        vm.expectRevert("only owner");
+        rotatable.setGuardians(guardians);
+    }
+
+    function test_SetDuplicateGuardian() public {
+        RotatableAccount rotatable = _upgrade(accountOwner.addr);
+        address[] memory guardians = _guardianAddresses(2);
+        guardians[1] = guardians[0];
+        vm.prank(accountOwner.addr);
+        vm.expectRevert("account: duplicate guardian");
+        rotatable.setGuardians(guardians);
+    }
+
+    function test_SetOwnerAsGuardian() public {
+        RotatableAccount rotatable = _upgrade(accountOwner.addr);
+        address[] memory guardians = _guardianAddresses(2);
+        guardians[1] = accountOwner.addr;
+        vm.prank(accountOwner.addr);
+        vm.expectRevert("account: owner can not be a guardian");
+        rotatable.setGuardians(guardians);
+    }
+
+    // #recoverOwner
+    // A strict majority recovers: 1 of 1, 2 of 2, 2 of 3, 3 of 4, 3 of 5
+    function test_RecoverWithMajority() public {
+        uint256[5] memory required = [uint256(1), 2, 2, 3, 3];
+        for (uint256 n = 1; n <= 5; n++) {
+            (RotatableAccount rotatable, uint256[] memory keys) = _setupGuardians(n, 1400 + n);
+            Account memory newOwner = utils.createAddress("new_owner");
+            bytes memory signatures = _packGuardianSignatures(rotatable, keys, required[n - 1], newOwner.addr);
+            rotatable.recoverOwner(newOwner.addr, signatures);
+            assertEq(rotatable.owner(), newOwner.addr);
+            assertEq(rotatable.recoveryNonce(), 1);
+        }
+    }
+
+    // One signature short of the majority is not enough: 0 of 1, 1 of 2, 1 of 3, 2 of 4, 2 of 5
+    function test_RecoverBelowMajority() public {
+        uint256[5] memory signers = [uint256(0), 1, 1, 2, 2];
+        for (uint256 n = 1; n <= 5; n++) {
+            (RotatableAccount rotatable, uint256[] memory keys) = _setupGuardians(n, 1410 + n);
+            Account memory newOwner = utils.createAddress("new_owner");
+            bytes memory signatures = _packGuardianSignatures(rotatable, keys, signers[n - 1], newOwner.addr);
+            vm.expectRevert("account: invalid recovery");
+            rotatable.recoverOwner(newOwner.addr, signatures);
+        }
+    }
+
+    // Signatures have to be in ascending guardian order, as RotatableAccountProvider packs them
+    function test_RecoverWithUnsortedSignatures() public {
+        (RotatableAccount rotatable, uint256[] memory keys) = _setupGuardians(3, 1420);
+        Account memory newOwner = utils.createAddress("new_owner");
+        bytes memory signatures = _packGuardianSignatures(rotatable, keys, 2, newOwner.addr);
+        bytes memory swapped = abi.encodePacked(_slice(signatures, 65, 65), _slice(signatures, 0, 65));
+        vm.expectRevert("account: invalid recovery");
+        rotatable.recoverOwner(newOwner.addr, swapped);
+    }
+
+    // A guardian can't be counted twice
+    function test_RecoverWithRepeatedSignature() public {
+        (RotatableAccount rotatable, uint256[] memory keys) = _setupGuardians(3, 1421);
+        Account memory newOwner = utils.createAddress("new_owner");
+        bytes memory signatures = _packGuardianSignatures(rotatable, keys, 1, newOwner.addr);
+        vm.expectRevert("account: invalid recovery");
+        rotatable.recoverOwner(newOwner.addr, abi.encodePacked(signatures, signatures));
+    }
+
+    // Every signature takes exactly 65 bytes
+    function test_RecoverWithTruncatedSignatures() public {
+        (RotatableAccount rotatable, uint256[] memory keys) = _setupGuardians(1, 1422);
+        Account memory newOwner = utils.createAddress("new_owner");
+        bytes memory signatures = _packGuardianSignatures(rotatable, keys, 1, newOwner.addr);
+        vm.expectRevert("account: invalid recovery");
+        rotatable.recoverOwner(newOwner.addr, _slice(signatures, 0, 64));
+    }
+
+    function test_RecoverSignedByNonGuardian() public {
+        (RotatableAccount rotatable,) = _setupGuardians(1, 1423);
+        Account memory newOwner = utils.createAddress("new_owner");
+        uint256[] memory keys = new uint256[](1);
+        keys[0] = utils.createAddress("not_a_guardian").key;
+        bytes memory signatures = _packGuardianSignatures(rotatable, keys, 1, newOwner.addr);
+        vm.expectRevert("account: invalid recovery");
+        rotatable.recoverOwner(newOwner.addr, signatures);
+    }
+
+    // Signatures are for one new owner only, and are spent by the recovery
+    function test_RecoverReplay() public {
+        (RotatableAccount rotatable, uint256[] memory keys) = _setupGuardians(1, 1424);
+        Account memory newOwner = utils.createAddress("new_owner");
+        Account memory otherOwner = utils.createAddress("other_owner");
+        bytes memory signatures = _packGuardianSignatures(rotatable, keys, 1, newOwner.addr);
+        vm.expectRevert("account: invalid recovery");
+        rotatable.recoverOwner(otherOwner.addr, signatures);
+
+        rotatable.recoverOwner(newOwner.addr, signatures);
+        vm.expectRevert("account: invalid recovery");
+        rotatable.recoverOwner(newOwner.addr, signatures);
+    }
+
+    function test_RecoverWithoutGuardians() public {
+        RotatableAccount rotatable = _upgrade(accountOwner.addr);
+        Account memory newOwner = utils.createAddress("new_owner");
+        vm.expectRevert("account: invalid recovery");
+        rotatable.recoverOwner(newOwner.addr, defaultBytes);
+    }
+
+    // A user operation that only calls recoverOwner is validated by the guardian signatures
+    function test_ValidateRecoveryOp() public {
+        (RotatableAccount rotatable, uint256[] memory keys) = _setupGuardians(3, 1425);
+        Account memory newOwner = utils.createAddress("new_owner");
+        UserOperation memory op = defaultOp;
+        op.sender = accountAddress;
+        op.callData = abi.encodeCall(
+            RotatableAccount.recoverOwner, (newOwner.addr, _packGuardianSignatures(rotatable, keys, 2, newOwner.addr))
+        );
+        bytes32 userOpHash = utils.getUserOpHash(op, entryPointAddress, chainId);
+        vm.prank(entryPointAddress);
+        assertEq(rotatable.validateUserOp(op, userOpHash, 0), 0);
+
+        op.callData = abi.encodeCall(
+            RotatableAccount.recoverOwner, (newOwner.addr, _packGuardianSignatures(rotatable, keys, 1, newOwner.addr))
+        );
+        userOpHash = utils.getUserOpHash(op, entryPointAddress, chainId);
+        vm.prank(entryPointAddress);
+        assertEq(rotatable.validateUserOp(op, userOpHash, 0), 1);
+    }
+
+    // Any other call still needs the owner's signature
+    function test_ValidateUnsignedOp() public {
+        RotatableAccount rotatable = _upgrade(accountOwner.addr);
+        UserOperation memory op = defaultOp;
+        op.sender = accountAddress;
+        op.callData = abi.encodeCall(SimpleAccount.execute, (accountOwner.addr, 0, defaultBytes));
+        bytes32 userOpHash = utils.getUserOpHash(op, entryPointAddress, chainId);
+        op.signature = utils.signMessage(userOpHash, utils.createAddress("not_the_owner").key);
+        vm.prank(entryPointAddress);
+        assertEq(rotatable.validateUserOp(op, userOpHash, 0), 1);
+    }
+
+    function _upgrade(address newOwner) internal returns (RotatableAccount) {
+        vm.prank(accountOwner.addr);
+        account.upgradeToAndCall(
+            address(rotatableImplementation), abi.encodeCall(RotatableAccount.changeOwner, (newOwner))
+        );
+        return RotatableAccount(payable(accountAddress));
+    }
+
+    // the digest MessageService builds, spelled out so a change on either side breaks this test
+    function _signAccountMessage(address verifyingContract, bytes32 hash, uint256 key)
+        internal
+        view
+        returns (bytes memory)
+    {
+        bytes32 domainSeparator = keccak256(
+            abi.encode(
+                keccak256("EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)"),
+                keccak256("SimpleAccount"),
+                keccak256("1"),
+                block.chainid,
+                verifyingContract
+            )
+        );
+        bytes32 structHash = keccak256(abi.encode(keccak256("AccountMessage(bytes32 hash)"), hash));
+        bytes32 digest = keccak256(abi.encodePacked("\x19\x01", domainSeparator, structHash));
+        (uint8 v, bytes32 r, bytes32 s) = vm.sign(key, digest);
+        return abi.encodePacked(r, s, v);
+    }
+
+    function _setupGuardians(uint256 count, uint256 accountSalt)
+        internal
+        returns (RotatableAccount rotatable, uint256[] memory keys)
+    {
+        (SimpleAccount simple, address simpleAddress) = createAccountWithFactory(accountSalt);
+        vm.prank(accountOwner.addr);
+        simple.upgradeToAndCall(
+            address(rotatableImplementation), abi.encodeCall(RotatableAccount.changeOwner, (accountOwner.addr))
+        );
+        rotatable = RotatableAccount(payable(simpleAddress));
+        keys = new uint256[](count);
+        address[] memory guardians = new address[](count);
+        for (uint256 i = 0; i < count; i++) {
+            Account memory guardian = utils.createAddress(string(abi.encodePacked("guardian", vm.toString(i))));
+            keys[i] = guardian.key;
+            guardians[i] = guardian.addr;
+        }
+        vm.prank(accountOwner.addr);
+        rotatable.setGuardians(guardians);
+    }
+
+    function _guardianAddresses(uint256 count) internal returns (address[] memory guardians) {
+        guardians = new address[](count);
+        for (uint256 i = 0; i < count; i++) {
+            guardians[i] = utils.createAddress(string(abi.encodePacked("guardian", vm.toString(i)))).addr;
+        }
+    }
+
+    // personal signatures of the recovery hash by the first signers keys, sorted by guardian address
+    // and concatenated like RotatableAccountProvider::pack_guardian_signatures
+    function _packGuardianSignatures(
+        RotatableAccount rotatable,
+        uint256[] memory keys,
+        uint256 signers,
+        address newOwner
+    ) internal view returns (bytes memory packed) {
+        uint256[] memory sorted = new uint256[](signers);
+        for (uint256 i = 0; i < signers; i++) {
+            sorted[i] = keys[i];
+        }
+        for (uint256 i = 0; i < signers; i++) {
+            for (uint256 j = i + 1; j < signers; j++) {
+                if (vm.addr(sorted[j]) < vm.addr(sorted[i])) {
+                    (sorted[i], sorted[j]) = (sorted[j], sorted[i]);
+                }
+            }
+        }
+        bytes32 recoveryHash = rotatable.getRecoveryHash(newOwner);
+        for (uint256 i = 0; i < signers; i++) {
+            packed = abi.encodePacked(packed, utils.signMessage(recoveryHash, sorted[i]));
+        }
+    }
+
+    function _slice(bytes memory data, uint256 start, uint256 length) internal pure returns (bytes memory result) {
+        result = new bytes(length);
+        for (uint256 i = 0; i < length; i++) {
+            result[i] = data[start + i];
+        }
+    }
+}
@@ -0,0 +1,214 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.13;
+
+import "./TestHelper.sol";
+import "../src/RotatableAccount.sol";
+import "../src/SessionKeyValidator.sol";
+//Utils
+import {Utilities} from "./Utilities.sol";
+
+contract OwnedAccount {
+    address public owner;
+
+    function setOwner(address anOwner) external {
+        owner = anOwner;
+    }
+}
+
+contract SessionKeyValidatorTest is TestHelper {
+    Utilities internal utils;
+
+    // the fixture of bundler/src/contracts/session_key_validator_provider.rs
+    uint256 internal constant CHAIN_ID = 84532;
+    address internal constant ACCOUNT = 0x1111111111111111111111111111111111111111;
+    address internal constant VALIDATOR = 0x2222222222222222222222222222222222222222;
+    uint256 internal constant SESSION_KEY = 0x3333333333333333333333333333333333333333333333333333333333333333;
+    uint256 internal constant OWNER_KEY = 0x4444444444444444444444444444444444444444444444444444444444444444;
+    address internal constant TARGET = 0x5555555555555555555555555555555555555555;
+    address internal constant TOKEN = 0x6666666666666666666666666666666666666666;
+    bytes32 internal constant USER_OP_HASH = 0x2222222222222222222222222222222222222222222222222222222222222222;
+    bytes32 internal constant PERMISSION_HASH = 0x1222d0619d990a36145f041c0633c2f546ad74f784d9db4e4f6ec625e11d9c3f;
+    // keccak256 of SessionKeyValidatorProvider::encode_signature
+    bytes32 internal constant SIGNATURE_HASH = 0xa3a41a4f703b06478ed25fac7d10588794e8ea1430bef26c5e94a7816e260c60;
+    bytes internal constant OWNER_SIGNATURE =
+        hex"3878c840182732c6c1aa6d5da2ef23d27b8833f26ff8fdb427d2280cba64830851bf96b11a14a922f333fc4482bc9f1f89bc530d7f7ebf27460f137fb15a348b1b";
+    bytes internal constant SESSION_SIGNATURE =
+        hex"30f6998363d013b7e2fea7ce491c92d0189e4235cee065902ba9e4cad5d35ab14a725a2ee335d776f0ceb11410cf333f511b592e066f4bdb0d696570c68323141c";
+
+    SessionKeyValidator internal validator;
+
+    event SessionRevoked(address indexed account, bytes32 indexed permissionHash);
+
+    function setUp() public {
+        utils = new Utilities();
+        vm.chainId(CHAIN_ID);
+        vm.etch(VALIDATOR, address(new SessionKeyValidator()).code);
+        validator = SessionKeyValidator(VALIDATOR);
+        vm.etch(ACCOUNT, address(new OwnedAccount()).code);
+        OwnedAccount(ACCOUNT).setOwner(vm.addr(OWNER_KEY));
+    }
+
+    // The permission hash of the bundler is the one the validator checks
+    function test_PermissionHash() public {
+        SessionKeyValidator.SessionPermission memory permission = _permission();
+        assertEq(permission.sessionKey, 0x5CbDd86a2FA8Dc4bDdd8a8f69dBa48572EeC07FB);
+        assertEq(validator.getPermissionHash(ACCOUNT, permission), PERMISSION_HASH);
+        assertEq(
+            PERMISSION_HASH,
+            keccak256(abi.encode(CHAIN_ID, ACCOUNT, VALIDATOR, permission))
+        );
+    }
+
+    // abi.encode(validator, abi.encode(permission, ownerSignature, sessionSignature))
+    function test_SignatureEncoding() public {
+        assertEq(keccak256(_signature()), SIGNATURE_HASH);
+        (address encodedValidator, bytes memory data) = abi.decode(_signature(), (address, bytes));
+        assertEq(encodedValidator, VALIDATOR);
+        (SessionKeyValidator.SessionPermission memory permission, bytes memory ownerSignature,
+            bytes memory sessionSignature) = abi.decode(data, (SessionKeyValidator.SessionPermission, bytes, bytes));
+        assertEq(validator.getPermissionHash(ACCOUNT, permission), PERMISSION_HASH);
+        assertEq(ownerSignature, OWNER_SIGNATURE);
+        assertEq(sessionSignature, SESSION_SIGNATURE);
+    }
+
+    // #validateUserOp
+    function test_NativeTransfer() public {
+        assertEq(_validate(_execute(TARGET, 1 ether, defaultBytes), USER_OP_HASH), _validWindow());
+        assertEq(validator.getSpent(ACCOUNT, PERMISSION_HASH, address(0)), 1 ether);
+        // the limit is spent
+        assertEq(_validate(_execute(TARGET, 1, defaultBytes), USER_OP_HASH), 1);
+    }
+
+    function test_TokenTransfer() public {
+        bytes memory transfer = abi.encodeWithSignature("transfer(address,uint256)", TARGET, 60_000_000);
+        assertEq(_validate(_execute(TOKEN, 0, transfer), USER_OP_HASH), _validWindow());
+        assertEq(validator.getSpent(ACCOUNT, PERMISSION_HASH, TOKEN), 60_000_000);
+        // 120 of 100 USDC
+        assertEq(_validate(_execute(TOKEN, 0, transfer), USER_OP_HASH), 1);
+    }
+
+    function test_TargetNotAllowed() public {
+        assertEq(_validate(_execute(address(0x7777), 1, defaultBytes), USER_OP_HASH), 1);
+        bytes memory transfer = abi.encodeWithSignature("transfer(address,uint256)", address(0x7777), 1);
+        assertEq(_validate(_execute(TOKEN, 0, transfer), USER_OP_HASH), 1);
+    }
+
+    function test_TokenNotAllowed() public {
+        bytes memory transfer = abi.encodeWithSignature("transfer(address,uint256)", TARGET, 1);
+        assertEq(_validate(_execute(address(0x7777), 0, transfer), USER_OP_HASH), 1);
+    }
+
+    // Only a single transfer is in scope
+    function test_OtherCalls() public {
+        bytes memory approve = abi.encodeWithSignature("approve(address,uint256)", TARGET, 1);
+        assertEq(_validate(_execute(TOKEN, 0, approve), USER_OP_HASH), 1);
+        bytes memory transfer = abi.encodeWithSignature("transfer(address,uint256)", TARGET, 1);
+        assertEq(_validate(_execute(TOKEN, 1, transfer), USER_OP_HASH), 1);
+        address[] memory dest = new address[](1);
+        bytes[] memory func = new bytes[](1);
+        dest[0] = TARGET;
+        assertEq(_validate(abi.encodeCall(SimpleAccount.executeBatch, (dest, func)), USER_OP_HASH), 1);
+    }
+
+    // The session key signs the user operation hash
+    function test_WrongUserOpHash() public {
+        assertEq(_validate(_execute(TARGET, 1, defaultBytes), keccak256("other")), 1);
+    }
+
+    // The permission is only worth the signature of the current owner
+    function test_OwnerChanged() public {
+        OwnedAccount(ACCOUNT).setOwner(utils.createAddress("new_owner").addr);
+        assertEq(_validate(_execute(TARGET, 1, defaultBytes), USER_OP_HASH), 1);
+    }
+
+    function test_OtherSender() public {
+        UserOperation memory op = defaultOp;
+        op.sender = ACCOUNT;
+        op.callData = _execute(TARGET, 1, defaultBytes);
+        (, bytes memory data) = abi.decode(_signature(), (address, bytes));
+        vm.prank(TARGET);
+        assertEq(validator.validateUserOp(op, USER_OP_HASH, data), 1);
+    }
+
+    // #revokeSession
+    function test_RevokeSession() public {
+        vm.expectEmit(true, true, false, false, VALIDATOR);
+        emit SessionRevoked(ACCOUNT, PERMISSION_HASH);
+        vm.prank(ACCOUNT);
+        validator.revokeSession(PERMISSION_HASH);
+        assertTrue(validator.isRevoked(ACCOUNT, PERMISSION_HASH));
+        assertEq(_validate(_execute(TARGET, 1, defaultBytes), USER_OP_HASH), 1);
+    }
+
+    // Revocations are per account
+    function test_RevokeOtherAccount() public {
+        vm.prank(TARGET);
+        validator.revokeSession(PERMISSION_HASH);
+        assertFalse(validator.isRevoked(ACCOUNT, PERMISSION_HASH));
+        assertEq(_validate(_execute(TARGET, 1, defaultBytes), USER_OP_HASH), _validWindow());
+    }
+
+    // RotatableAccount hands session signatures to its validator, and only to it
+    function test_RotatableAccountDelegates() public {
+        accountOwner = utils.createAddress("session_account_owner");
+        deployEntryPoint(1601);
+        createAccount(1602, 1603);
+        vm.prank(accountOwner.addr);
+        account.upgradeToAndCall(
+            address(new RotatableAccount(entryPoint, validator)),
+            abi.encodeCall(RotatableAccount.changeOwner, (accountOwner.addr))
+        );
+
+        SessionKeyValidator.SessionPermission memory permission = _permission();
+        bytes memory ownerSignature =
+            utils.signMessage(validator.getPermissionHash(accountAddress, permission), accountOwner.key);
+        UserOperation memory op = defaultOp;
+        op.sender = accountAddress;
+        op.callData = _execute(TARGET, 1, defaultBytes);
+        bytes32 userOpHash = utils.getUserOpHash(op, entryPointAddress, chainId);
+        bytes memory data = abi.encode(permission, ownerSignature, utils.signMessage(userOpHash, SESSION_KEY));
+
+        op.signature = abi.encode(address(validator), data);
+        vm.prank(entryPointAddress);
+        assertEq(account.validateUserOp(op, userOpHash, 0), _validWindow());
+
+        op.signature = abi.encode(address(new SessionKeyValidator()), data);
+        vm.prank(entryPointAddress);
+        assertEq(account.validateUserOp(op, userOpHash, 0), 1);
+    }
+
+    function _permission() internal pure returns (SessionKeyValidator.SessionPermission memory permission) {
+        permission.sessionKey = vm.addr(SESSION_KEY);
+        permission.validAfter = 1700000000;
+        permission.validUntil = 1800000000;
+        permission.allowedTargets = new address[](1);
+        permission.allowedTargets[0] = TARGET;
+        permission.tokens = new address[](2);
+        permission.tokens[1] = TOKEN;
+        permission.spendLimits = new uint256[](2);
+        permission.spendLimits[0] = 1 ether;
+        permission.spendLimits[1] = 100_000_000;
+        permission.salt = keccak256("session_test");
+    }
+
+    function _signature() internal pure returns (bytes memory) {
+        return abi.encode(VALIDATOR, abi.encode(_permission(), OWNER_SIGNATURE, SESSION_SIGNATURE));
+    }
+
+    function _execute(address dest, uint256 value, bytes memory func) internal pure returns (bytes memory) {
+        return abi.encodeCall(SimpleAccount.execute, (dest, value, func));
+    }
+
+    function _validWindow() internal pure returns (uint256) {
+        return _packValidationData(false, 1800000000, 1700000000);
+    }
+
+    function _validate(bytes memory callData, bytes32 userOpHash) internal returns (uint256) {
+        UserOperation memory op = defaultOp;
+        op.sender = ACCOUNT;
+        op.callData = callData;
+        (, bytes memory data) = abi.decode(_signature(), (address, bytes));
+        vm.prank(ACCOUNT);
+        return validator.validateUserOp(op, userOpHash, data);
+    }
+}